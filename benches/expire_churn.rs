@@ -0,0 +1,50 @@
+//! Measures setting many keys with short, ever shorter times to live, where each key expires
+//! before every key set so far.
+//!
+//! Run with `cargo bench --bench expire_churn`. With `expire-wakeup-threshold` set to 0, every
+//! key wakes up the task removing expired keys, which then competes for the lock with the next
+//! write. With the default threshold, keys expiring within it of the planned wake up don't.
+
+use std::time::Instant;
+
+use bytes::Bytes;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::store::{NewValue, Store};
+
+const KEYS: u64 = 1_000_000;
+
+#[tokio::main]
+async fn main() {
+    for threshold in [0, Config::default().expire_wakeup_threshold] {
+        let store = Store::with_config(Config {
+            expire_wakeup_threshold: threshold,
+            ..Config::default()
+        });
+
+        let start = Instant::now();
+        for i in 0..KEYS {
+            // Each time to live is 2µs shorter than the previous one, more than setting a key
+            // takes, so every key expires before the previous ones, within 1 to 3 seconds.
+            let value = NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(Duration::from_micros(3_000_000 - 2 * i)),
+            };
+            store.set2(format!("key:{}", i), value);
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "threshold {}ms: {} keys in {:?}, {:.0} keys/s, {} wakeups",
+            threshold,
+            KEYS,
+            elapsed,
+            KEYS as f64 / elapsed.as_secs_f64(),
+            store.stats.expire_wakeups()
+        );
+
+        // Let every key expire, so removing them doesn't compete with the next run.
+        tokio::time::sleep(Duration::from_secs(3)).await;
+    }
+}
@@ -0,0 +1,85 @@
+//! Measures throughput of 64-deep pipelines of SET and of PING commands.
+//!
+//! Run with `cargo bench --bench pipeline`. The server is started once per read buffer size, on
+//! its own runtime, and a single client keeps 64 commands in flight at a time. Both commands are
+//! replied to with shared replies, so the ratio DEBUG SHARED-REPLIES reports is printed too.
+
+use std::thread;
+use std::time::Instant;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run;
+
+const PIPELINE_DEPTH: usize = 64;
+const ROUNDS: usize = 20_000;
+
+#[tokio::main]
+async fn main() {
+    for (port, read_buffer_size) in [(6380, 8 * 1024), (6381, 64 * 1024)] {
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(run(Config {
+                port,
+                read_buffer_size,
+                ..Config::default()
+            }))
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        for (name, command, reply) in [
+            (
+                "SET",
+                "*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n",
+                "+OK\r\n",
+            ),
+            ("PING", "*1\r\n$4\r\nPING\r\n", "+PONG\r\n"),
+        ] {
+            let elapsed = bench(port, command, reply).await;
+            let commands = PIPELINE_DEPTH * ROUNDS;
+            println!(
+                "read buffer {:>3} KiB, {:<4}: {} commands in {:?}, {:.0} commands/s",
+                read_buffer_size / 1024,
+                name,
+                commands,
+                elapsed,
+                commands as f64 / elapsed.as_secs_f64()
+            );
+        }
+        println!("{}", shared_replies(port).await);
+    }
+}
+
+/// The stats DEBUG SHARED-REPLIES reports, on a single line.
+async fn shared_replies(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"*2\r\n$5\r\nDEBUG\r\n$14\r\nSHARED-REPLIES\r\n")
+        .await
+        .unwrap();
+
+    let mut buf = vec![0; 1024];
+    let read = stream.read(&mut buf).await.unwrap();
+    let reply = String::from_utf8_lossy(&buf[..read]).into_owned();
+    reply.lines().skip(1).collect::<Vec<_>>().join(" ")
+}
+
+async fn bench(port: u16, command: &str, reply: &str) -> Duration {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.set_nodelay(true).unwrap();
+
+    let batch = command.repeat(PIPELINE_DEPTH);
+
+    let expected = reply.len() * PIPELINE_DEPTH;
+    let mut buf = vec![0; expected];
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        stream.write_all(batch.as_bytes()).await.unwrap();
+        stream.read_exact(&mut buf).await.unwrap();
+    }
+    start.elapsed()
+}
@@ -0,0 +1,51 @@
+//! Measures setting many keys with a time to live, one at a time and as a single batch.
+//!
+//! Run with `cargo bench --bench set_many`. Keys are set with decreasing times to live, so each
+//! one becomes the next key to expire: set one at a time, every key wakes up the task removing
+//! expired keys, which then competes for the lock with the next write. A batch wakes it up once.
+
+use std::time::Instant;
+
+use bytes::Bytes;
+use tokio::time::Duration;
+
+use rustdis::store::{NewValue, Store};
+
+const KEYS: u64 = 200_000;
+
+fn entries() -> impl Iterator<Item = (String, NewValue)> {
+    (0..KEYS).map(|i| {
+        (
+            format!("key:{}", i),
+            NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(Duration::from_secs(3600 + KEYS - i)),
+            },
+        )
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let store = Store::new();
+    let start = Instant::now();
+    for (key, value) in entries() {
+        store.set2(key, value);
+    }
+    report("one at a time", start.elapsed());
+
+    let store = Store::new();
+    let start = Instant::now();
+    store.set_many(entries());
+    report("batched", start.elapsed());
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:>13}: {} keys in {:?}, {:.0} keys/s",
+        name,
+        KEYS,
+        elapsed,
+        KEYS as f64 / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,120 @@
+//! Parses RESP read from standard input with `Frame::parse` and pretty-prints every frame, to
+//! explore the protocol by hand.
+//!
+//! ```text
+//! $ printf '*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n' | cargo run --example resp_playground
+//! 1) array, 2 elements
+//!    1) bulk string, 3 bytes: "GET"
+//!    2) bulk string, 3 bytes: "key"
+//! ```
+//!
+//! Typing RESP in a terminal ends lines with a bare `\n`, so every `\n` not preceded by `\r` is
+//! read as `\r\n`. Bulk string lengths don't count the `\r` of the line endings either way.
+//!
+//! Parsing stops at the first invalid frame, printing the error and the offset it starts at, and
+//! exits with status 1. Input ending in the middle of a frame is reported as incomplete, which is
+//! what makes the server wait for more data from a client.
+//!
+//! Ref: <https://redis.io/docs/latest/develop/reference/protocol-spec/>
+
+use std::io::{self, Cursor, Read};
+use std::process;
+
+use rustdis::frame::Frame;
+use rustdis::RustdisError;
+
+fn main() {
+    let mut input = vec![];
+    if let Err(err) = io::stdin().read_to_end(&mut input) {
+        eprintln!("failed to read standard input: {}", err);
+        process::exit(2);
+    }
+    let input = normalize_line_endings(&input);
+
+    let mut cursor = Cursor::new(input.as_slice());
+    let mut count = 0;
+    while (cursor.position() as usize) < input.len() {
+        let start = cursor.position();
+        match Frame::parse(&mut cursor) {
+            Ok(frame) => {
+                count += 1;
+                print_frame(&frame, &format!("{}) ", count), 0);
+            }
+            Err(RustdisError::Incomplete) => {
+                eprintln!(
+                    "incomplete frame at byte {}: {:?}",
+                    start,
+                    String::from_utf8_lossy(&input[start as usize..])
+                );
+                process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("invalid frame at byte {}: {}", start, err);
+                process::exit(1);
+            }
+        }
+    }
+
+    if count == 0 {
+        eprintln!("no frames in the input");
+    }
+}
+
+/// Turns every `\n` not preceded by `\r` into `\r\n`.
+fn normalize_line_endings(input: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(input.len());
+    for (i, byte) in input.iter().enumerate() {
+        if *byte == b'\n' && (i == 0 || input[i - 1] != b'\r') {
+            normalized.push(b'\r');
+        }
+        normalized.push(*byte);
+    }
+    normalized
+}
+
+/// Prints `frame` after `label`, with its nested frames indented below it.
+fn print_frame(frame: &Frame, label: &str, indent: usize) {
+    let description = match frame {
+        Frame::Simple(string) => format!("simple string: {:?}", string),
+        Frame::Error(message) => format!("error: {:?}", message),
+        Frame::Integer(integer) => format!("integer: {}", integer),
+        Frame::Bulk(bytes) => format!(
+            "bulk string, {} bytes: {:?}",
+            bytes.len(),
+            String::from_utf8_lossy(bytes)
+        ),
+        Frame::Double(double) => format!("double: {}", double),
+        Frame::Boolean(boolean) => format!("boolean: {}", boolean),
+        Frame::BigNumber(number) => format!("big number: {}", number),
+        Frame::Verbatim { encoding, text } => format!(
+            "verbatim string, {} encoded: {:?}",
+            encoding,
+            String::from_utf8_lossy(text)
+        ),
+        Frame::Null => "null".to_string(),
+        Frame::NullBulkString => "null bulk string (RESP2)".to_string(),
+        Frame::NullArray => "null array (RESP2)".to_string(),
+        Frame::Array(frames) if frames.len() == 1 => "array, 1 element".to_string(),
+        Frame::Array(frames) => format!("array, {} elements", frames.len()),
+        Frame::Map(entries) if entries.len() == 1 => "map, 1 entry".to_string(),
+        Frame::Map(entries) => format!("map, {} entries", entries.len()),
+        Frame::Set(frames) if frames.len() == 1 => "set, 1 element".to_string(),
+        Frame::Set(frames) => format!("set, {} elements", frames.len()),
+    };
+    println!("{:indent$}{}{}", "", label, description, indent = indent);
+
+    match frame {
+        Frame::Array(frames) | Frame::Set(frames) => {
+            for (i, frame) in frames.iter().enumerate() {
+                print_frame(frame, &format!("{}) ", i + 1), indent + label.len());
+            }
+        }
+        Frame::Map(entries) => {
+            for (i, (key, value)) in entries.iter().enumerate() {
+                print_frame(key, &format!("{}) key: ", i + 1), indent + label.len());
+                print_frame(value, &format!("{}) value: ", i + 1), indent + label.len());
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,18 +1,261 @@
-use clap::Parser;
-use rustdis::{server, Error};
+use clap::{ArgAction, Parser};
+use std::path::{Path, PathBuf};
+use std::process;
+use tracing::warn;
 
-const PORT: u16 = 6379;
+use rustdis::config::{
+    parse_bool, parse_memory, parse_read_buffer_size, parse_value_compression_threshold,
+    AppendFsync, Config,
+};
+use rustdis::engine::EngineKind;
+use rustdis::store::Store;
+use rustdis::{record, self_test, server, RustdisError};
 
+/// A partial Redis server implementation intended purely for educational purposes.
+///
+/// Options given on the command line take precedence over the ones in the configuration file.
 #[derive(Parser, Debug)]
+#[command(version, about)]
 struct Args {
-    /// The port to listen on
-    #[arg(short, long, default_value_t = PORT)]
-    port: u16,
+    /// Path to a redis.conf style configuration file, reloaded on SIGHUP
+    config_file: Option<PathBuf>,
+
+    /// The port to listen on [default: 6379]
+    #[arg(short, long)]
+    port: Option<u16>,
+
+    /// The interface to listen on [default: 127.0.0.1]
+    #[arg(long)]
+    bind: Option<String>,
+
+    /// Interval in seconds between TCP keepalive probes sent to idle clients, 0 disables them
+    /// [default: 300]
+    #[arg(long)]
+    tcp_keepalive: Option<u64>,
+
+    /// Disable Nagle's algorithm on client sockets (yes/no) [default: yes]
+    #[arg(long, value_parser = parse_bool, action = ArgAction::Set)]
+    tcp_nodelay: Option<bool>,
+
+    /// Initial size of each client's read buffer, accepts units such as 64kb [default: 8kb]
+    #[arg(long, value_parser = parse_read_buffer_size)]
+    read_buffer_size: Option<usize>,
+
+    /// Abort long running commands after this many milliseconds, 0 disables the limit
+    /// [default: 0]
+    #[arg(long)]
+    command_time_limit: Option<u64>,
+
+    /// Reply to KEYS with at most this many keys, followed by an error telling the reply is
+    /// partial, 0 disables the limit [default: 0]
+    #[arg(long)]
+    keys_max_results: Option<usize>,
+
+    /// Store string values of at least this size compressed, accepts units such as 1kb, 0
+    /// disables compression (requires the compression feature) [default: 0]
+    #[arg(long, value_parser = parse_value_compression_threshold)]
+    value_compression_threshold: Option<usize>,
+
+    /// Data structure keys are stored in, hashmap or btreemap (ordered keys) [default: hashmap]
+    #[arg(long)]
+    storage_engine: Option<EngineKind>,
+
+    /// Number of keys to preallocate the keyspace for, avoiding rehashes until it grows past it
+    /// [default: 0]
+    #[arg(long)]
+    keyspace_initial_capacity: Option<usize>,
+
+    /// Keep deleted keys as tombstones for this many milliseconds, visible through DEBUG
+    /// TOMBSTONES, 0 deletes them right away [default: 0]
+    #[arg(long)]
+    tombstone_grace_period: Option<u64>,
+
+    /// Wake up the task removing expired keys for a new time to live only when it elapses more
+    /// than this many milliseconds before the task wakes up anyway [default: 1]
+    #[arg(long)]
+    expire_wakeup_threshold: Option<u64>,
+
+    /// Log at most this many rejected commands, unknown or with invalid arguments, per second, 0
+    /// disables the log [default: 10]
+    #[arg(long)]
+    rejected_commands_log_rate: Option<u64>,
+
+    /// Memory limit, accepts units such as 100mb or 1gb (not enforced) [default: 0]
+    #[arg(long, value_parser = parse_memory)]
+    maxmemory: Option<u64>,
+
+    /// Enable the append only file (yes/no, not supported) [default: no]
+    #[arg(long, value_parser = parse_bool, action = ArgAction::Set)]
+    appendonly: Option<bool>,
+
+    /// How often the append only file is fsynced, always, everysec or no (not supported)
+    /// [default: everysec]
+    #[arg(long)]
+    appendfsync: Option<AppendFsync>,
+
+    /// File to write logs to, standard output when missing
+    #[arg(long)]
+    logfile: Option<PathBuf>,
+
+    /// Run as a daemon (yes/no, not supported) [default: no]
+    #[arg(long, value_parser = parse_bool, action = ArgAction::Set)]
+    daemonize: Option<bool>,
+
+    /// File with RESP encoded commands to execute at startup, commands are rejected until done
+    #[arg(long)]
+    import: Option<PathBuf>,
+
+    /// File with RESP encoded or inline commands to execute before accepting connections
+    #[arg(long)]
+    preload: Option<PathBuf>,
+
+    /// File to export the keyspace to, as RESP encoded commands, when the server shuts down
+    #[arg(long)]
+    export: Option<PathBuf>,
+
+    /// Directory to record the commands received by each connection to, one file per connection
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    /// Execute the commands of a recording against an empty keyspace, print their replies and
+    /// exit
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    /// Run an in-process smoke suite and exit, with a nonzero status if any check fails
+    #[arg(long)]
+    self_test: bool,
+}
+
+impl Args {
+    fn apply(self, config: &mut Config) {
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(bind) = self.bind {
+            config.bind = bind;
+        }
+        if let Some(tcp_keepalive) = self.tcp_keepalive {
+            config.tcp_keepalive = tcp_keepalive;
+        }
+        if let Some(tcp_nodelay) = self.tcp_nodelay {
+            config.tcp_nodelay = tcp_nodelay;
+        }
+        if let Some(read_buffer_size) = self.read_buffer_size {
+            config.read_buffer_size = read_buffer_size;
+        }
+        if let Some(command_time_limit) = self.command_time_limit {
+            config.command_time_limit = command_time_limit;
+        }
+        if let Some(keys_max_results) = self.keys_max_results {
+            config.keys_max_results = keys_max_results;
+        }
+        if let Some(threshold) = self.value_compression_threshold {
+            config.value_compression_threshold = threshold;
+        }
+        if let Some(storage_engine) = self.storage_engine {
+            config.storage_engine = storage_engine;
+        }
+        if let Some(capacity) = self.keyspace_initial_capacity {
+            config.keyspace_initial_capacity = capacity;
+        }
+        if let Some(grace_period) = self.tombstone_grace_period {
+            config.tombstone_grace_period = grace_period;
+        }
+        if let Some(threshold) = self.expire_wakeup_threshold {
+            config.expire_wakeup_threshold = threshold;
+        }
+        if let Some(rate) = self.rejected_commands_log_rate {
+            config.rejected_commands_log_rate = rate;
+        }
+        if let Some(maxmemory) = self.maxmemory {
+            config.maxmemory = maxmemory;
+        }
+        if let Some(appendonly) = self.appendonly {
+            config.appendonly = appendonly;
+        }
+        if let Some(appendfsync) = self.appendfsync {
+            config.appendfsync = appendfsync;
+        }
+        if let Some(logfile) = self.logfile {
+            config.logfile = Some(logfile);
+        }
+        if let Some(daemonize) = self.daemonize {
+            config.daemonize = daemonize;
+        }
+        if let Some(import) = self.import {
+            config.import = Some(import);
+        }
+        if let Some(preload) = self.preload {
+            config.preload = Some(preload);
+        }
+        if let Some(export) = self.export {
+            config.export = Some(export);
+        }
+        if let Some(record) = self.record {
+            config.record = Some(record);
+        }
+    }
+}
+
+/// Runs the smoke suite of `rustdis::self_test`, printing the outcome of each check, and exits.
+async fn self_test() -> ! {
+    let mut failed = 0;
+    for check in self_test::run().await {
+        match check.result {
+            Ok(()) => println!("ok      {}", check.name),
+            Err(reason) => {
+                println!("FAILED  {}: {}", check.name, reason);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        println!("{} checks failed", failed);
+        process::exit(1);
+    }
+    println!("all checks passed");
+    process::exit(0);
+}
+
+/// Replays the recording at `path`, see the `record` module, printing the reply to each command.
+fn replay(path: &Path) -> Result<(), RustdisError> {
+    let store = Store::new();
+    for (i, reply) in record::replay_file(&store, path)?.into_iter().enumerate() {
+        println!("{}) {:?}", i + 1, reply);
+    }
+    Ok(())
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
-    let args = Args::parse();
+async fn main() -> Result<(), RustdisError> {
+    let mut args = Args::parse();
+    if args.self_test {
+        self_test().await;
+    }
+    if let Some(path) = args.replay.take() {
+        return replay(&path);
+    }
+
+    let mut config = Config::default();
+
+    let warnings = match args.config_file.take() {
+        Some(path) => {
+            let warnings = config
+                .load_file(&path)
+                .map_err(|e| RustdisError::Other(e.into()))?;
+            config.config_file = Some(path);
+            warnings
+        }
+        None => vec![],
+    };
+    args.apply(&mut config);
+
+    server::init_tracing(&config)?;
+    for warning in warnings {
+        warn!("Ignoring configuration directive: {}", warning);
+    }
 
-    server::run(args.port).await
+    server::run(config).await
 }
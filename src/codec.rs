@@ -4,10 +4,26 @@ use std::env;
 use std::io::Cursor;
 use tokio_util::codec::Decoder;
 
-use crate::frame::{self, Frame};
-use crate::Error;
+use crate::error::RustdisError;
+use crate::frame::{Frame, Parser};
 
-pub struct FrameCodec;
+pub struct FrameCodec {
+    /// Number of bytes consumed by the frames decoded so far.
+    pub bytes_read: u64,
+    max_frame_size: usize,
+    /// Keeps what was parsed of a frame received in part, so it isn't parsed again on every read.
+    parser: Parser,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self {
+            bytes_read: 0,
+            max_frame_size: FrameCodec::max_frame_size(),
+            parser: Parser::default(),
+        }
+    }
+}
 
 impl FrameCodec {
     fn max_frame_size() -> usize {
@@ -19,22 +35,24 @@ impl FrameCodec {
 
 impl Decoder for FrameCodec {
     type Item = Frame;
-    type Error = Error;
+    type Error = RustdisError;
 
     // TODO:
     // * Use src.reserve. This is a more efficient way to allocate space in the buffer.
     // * Read more here: https://docs.rs/tokio-util/latest/tokio_util/codec/index.html
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         // Check if the frame size exceeds the limit to prevent DoS attacks.
-        if src.len() > FrameCodec::max_frame_size() {
-            return Err("frame size exceeds limit".into());
+        if src.len() > self.max_frame_size {
+            return Err(RustdisError::Protocol(
+                "frame size exceeds limit".to_string(),
+            ));
         }
 
         let mut cursor = Cursor::new(&src[..]);
-        let frame = match Frame::parse(&mut cursor) {
+        let frame = match self.parser.parse(&mut cursor) {
             Ok(frame) => frame,
-            Err(frame::Error::Incomplete) => return Ok(None), // Not enough data to parse a frame.
-            Err(err) => return Err(err.into()),
+            Err(RustdisError::Incomplete) => return Ok(None), // Not enough data to parse a frame.
+            Err(err) => return Err(err),
         };
 
         let position: usize = cursor
@@ -44,6 +62,7 @@ impl Decoder for FrameCodec {
 
         // Remove the parsed frame from the buffer.
         src.advance(position);
+        self.bytes_read += position as u64;
 
         Ok(Some(frame))
     }
@@ -2,9 +2,9 @@ use bytes::{Bytes, BytesMut};
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
 /// If key already exists and is a string, this command appends the value at the end of the string.
 /// If key does not exist it is created and set as an empty string, so APPEND will be similar to
@@ -18,9 +18,12 @@ pub struct Append {
 }
 
 impl Executable for Append {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let mut store = store.lock();
 
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let len = match store.get(&self.key) {
             Some(bytes) => {
                 let new_len = bytes.len() + self.value.len();
@@ -29,7 +32,7 @@ impl Executable for Append {
                 new_value.extend_from_slice(&bytes);
                 new_value.extend_from_slice(&self.value);
 
-                store.set(self.key, new_value.freeze());
+                store.update_value(self.key, new_value.freeze());
                 new_len
             }
             None => {
@@ -45,7 +48,7 @@ impl Executable for Append {
 }
 
 impl TryFrom<&mut CommandParser> for Append {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -59,8 +62,11 @@ impl TryFrom<&mut CommandParser> for Append {
 mod tests {
     use bytes::Bytes;
 
+    use tokio::time::Duration;
+
     use super::*;
     use crate::commands::Command;
+    use crate::store::NewValue;
 
     #[tokio::test]
     async fn when_key_does_not_exists() {
@@ -113,4 +119,33 @@ mod tests {
         assert_eq!(res, Frame::Integer(10));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("helloworld")));
     }
+
+    #[tokio::test]
+    async fn keeps_ttl() {
+        let store = Store::new();
+
+        store.set2(
+            String::from("key1"),
+            NewValue {
+                data: Bytes::from("hello"),
+                ttl: Some(Duration::from_secs(10)),
+            },
+        );
+        let expires_at = store.lock().get_value("key1").unwrap().expires_at;
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("APPEND")),
+            Frame::Bulk(Bytes::from("key1")),
+            Frame::Bulk(Bytes::from(" world")),
+        ]);
+        let res = Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+
+        assert_eq!(res, Frame::Integer(11));
+        let state = store.lock();
+        assert_eq!(state.get("key1"), Some(Bytes::from("hello world")));
+        assert_eq!(state.get_value("key1").unwrap().expires_at, expires_at);
+    }
 }
@@ -0,0 +1,182 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::{Store, STRING};
+
+/// Returns the value of `key` along with its version, the number RUSTDIS.CAS expects to set it.
+///
+/// ```text
+/// RUSTDIS.GETS key
+/// ```
+///
+/// Replies with an array holding the value, or nil when the key doesn't exist, and the version,
+/// which changes every time the key is written and is 0 when it doesn't exist, see
+/// `State::version`.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command. It is named
+/// after the `gets` command of memcached.
+#[derive(Debug, PartialEq)]
+pub struct Gets {
+    pub key: String,
+}
+
+/// Sets `key` to hold `value` only if its version is still `version`, as returned by
+/// RUSTDIS.GETS, which means nobody wrote the key in between.
+///
+/// ```text
+/// RUSTDIS.CAS key version value
+/// ```
+///
+/// Replies with the new version of the key when it is set, or nil when its version changed, in
+/// which case the client reads the key again and retries. A version of 0 only sets the key when it
+/// doesn't exist. The key keeps its time to live, like when APPEND modifies it.
+///
+/// This is optimistic concurrency in a single command, where WATCH and a transaction take four:
+/// the check and the write happen under the store lock, so no other write can happen in between.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command. It is named
+/// after the `cas` command of memcached.
+#[derive(Debug, PartialEq)]
+pub struct Cas {
+    pub key: String,
+    pub version: u64,
+    pub value: Bytes,
+}
+
+impl Executable for Gets {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        if let Err(err) = state.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
+        let value = match state.get(&self.key) {
+            Some(value) => Frame::Bulk(value),
+            None => Frame::NullBulkString,
+        };
+        let version = state.version(&self.key);
+
+        Ok(Frame::Array(vec![value, Frame::Integer(version as i64)]))
+    }
+}
+
+impl Executable for Cas {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        if let Err(err) = state.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
+        let res = match state.compare_and_set(self.key, self.version, self.value) {
+            Ok(version) => Frame::Integer(version as i64),
+            Err(_) => Frame::NullBulkString,
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Gets {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Cas {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let version = parser
+            .next_string()?
+            .parse::<u64>()
+            .map_err(|_| RustdisError::command(ErrorKind::Err, "invalid version"))?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self {
+            key,
+            version,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn run(store: &Store, args: &[&str]) -> Result<Frame, String> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(store.clone()).unwrap())
+    }
+
+    /// The version in the reply to RUSTDIS.GETS.
+    fn version(store: &Store, key: &str) -> String {
+        match run(store, &["RUSTDIS.GETS", key]).unwrap() {
+            Frame::Array(frames) => match frames[..] {
+                [_, Frame::Integer(version)] => version.to_string(),
+                _ => panic!("unexpected reply {:?}", frames),
+            },
+            frame => panic!("expected an array, got {:?}", frame),
+        }
+    }
+
+    #[tokio::test]
+    async fn compare_and_set() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.GETS", "counter"]),
+            Ok(Frame::Array(vec![Frame::NullBulkString, Frame::Integer(0)]))
+        );
+
+        let Ok(Frame::Integer(created)) = run(&store, &["RUSTDIS.CAS", "counter", "0", "1"]) else {
+            panic!("expected the key to be created");
+        };
+        assert_eq!(
+            run(&store, &["RUSTDIS.GETS", "counter"]),
+            Ok(Frame::Array(vec![
+                Frame::Bulk(Bytes::from("1")),
+                Frame::Integer(created)
+            ]))
+        );
+
+        // Another client writes the key in between.
+        let read = version(&store, "counter");
+        store.lock().set("counter".to_string(), Bytes::from("5"));
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.CAS", "counter", &read, "2"]),
+            Ok(Frame::NullBulkString)
+        );
+
+        let read = version(&store, "counter");
+        assert!(matches!(
+            run(&store, &["RUSTDIS.CAS", "counter", &read, "6"]),
+            Ok(Frame::Integer(version)) if version.to_string() != read
+        ));
+        assert_eq!(store.lock().get("counter"), Some(Bytes::from("6")));
+    }
+
+    #[tokio::test]
+    async fn invalid_version() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.CAS", "key", "-1", "value"]),
+            Err("ERR invalid version".to_string())
+        );
+    }
+}
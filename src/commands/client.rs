@@ -1,22 +1,296 @@
+use std::net::SocketAddr;
+
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
+use crate::reply::ReplyMode;
 use crate::store::Store;
-use crate::Error;
 
+/// The CLIENT command manages the connection of the client sending it.
+///
+/// Ref: <https://redis.io/docs/latest/commands/client/>
+#[derive(Debug, PartialEq)]
+pub enum Client {
+    Kill(Kill),
+    Reply(Reply),
+    /// Any other subcommand, like the CLIENT SETNAME clients send when connecting.
+    ///
+    /// **NOTE**: other subcommands are accepted for compatibility and do nothing.
+    Other,
+}
+
+/// Sets whether the server replies to the commands of the client: `ON` replies to every command,
+/// `OFF` to none, and `SKIP` skips the reply to the next command only. Commands are executed
+/// either way.
+///
+/// The mode is connection state, the server applies it when sending replies, see `ReplyFilter`.
+///
+/// Ref: <https://redis.io/docs/latest/commands/client-reply/>
 #[derive(Debug, PartialEq)]
-pub struct Client;
+pub struct Reply {
+    pub mode: ReplyMode,
+}
+
+/// Closes the connections of the clients matching every filter given, replying with how many
+/// were closed. The legacy form, `CLIENT KILL ip:port`, closes the client connected from that
+/// address and replies with OK, or with an error when there is none.
+///
+/// Unless `SKIPME no` is given, the client sending the command is never closed by the filter form.
+/// A client closing its own connection gets the replies to the commands it sent before, and the
+/// reply to CLIENT KILL, before the connection is closed. The commands it sent after are not
+/// executed.
+///
+/// **NOTE**: the server doesn't keep track of its connections, so a client can only kill its own
+/// connection, filters never match other clients. Clients have no numeric ids, so the `ID` filter
+/// matches no client either. Only the `ID`, `TYPE`, `ADDR` and `SKIPME` filters are supported.
+///
+/// Ref: <https://redis.io/docs/latest/commands/client-kill/>
+#[derive(Debug, PartialEq)]
+pub struct Kill {
+    /// Whether the legacy form was used, which is replied to with OK instead of a count.
+    pub legacy: bool,
+    pub id: Option<u64>,
+    pub type_: Option<String>,
+    pub addr: Option<String>,
+    pub skip_me: bool,
+}
+
+impl Kill {
+    /// Whether the client connected from `client_address`, the one sending the command, is killed.
+    pub fn matches(&self, client_address: SocketAddr) -> bool {
+        !self.skip_me
+            && self.id.is_none()
+            && self.type_.as_ref().is_none_or(|type_| type_ == "normal")
+            && self
+                .addr
+                .as_ref()
+                .is_none_or(|addr| *addr == client_address.to_string())
+    }
+
+    /// The reply once the matching client, if any, is killed.
+    pub fn reply(&self, killed: bool) -> Frame {
+        match (self.legacy, killed) {
+            (true, true) => Frame::Simple("OK".to_string()),
+            (true, false) => {
+                Frame::Error(RustdisError::command(ErrorKind::Err, "No such client").to_string())
+            }
+            (false, killed) => Frame::Integer(killed as i64),
+        }
+    }
+
+    fn parse(parser: &mut CommandParser) -> Result<Self, RustdisError> {
+        let mut kill = Self {
+            legacy: false,
+            id: None,
+            type_: None,
+            addr: None,
+            skip_me: true,
+        };
+
+        let mut filter = parser.next_string()?;
+        let mut value = match parser.next_string() {
+            Ok(value) => value,
+            // A single argument is the address of the legacy form.
+            Err(CommandParserError::EndOfStream) => {
+                return Ok(Self {
+                    legacy: true,
+                    addr: Some(filter),
+                    skip_me: false,
+                    ..kill
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        loop {
+            match filter.to_uppercase().as_str() {
+                "ID" => {
+                    let id = value
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|id| *id > 0)
+                        .ok_or_else(|| {
+                            RustdisError::command(
+                                ErrorKind::Err,
+                                "client-id should be greater than 0",
+                            )
+                        })?;
+                    kill.id = Some(id);
+                }
+                "TYPE" => {
+                    let type_ = value.to_lowercase();
+                    if !["normal", "master", "replica", "slave", "pubsub"].contains(&type_.as_str())
+                    {
+                        return Err(RustdisError::command(
+                            ErrorKind::Err,
+                            format!("Unknown client type '{}'", value),
+                        ));
+                    }
+                    kill.type_ = Some(type_);
+                }
+                "ADDR" => kill.addr = Some(value),
+                "SKIPME" => {
+                    kill.skip_me = match value.to_lowercase().as_str() {
+                        "yes" => true,
+                        "no" => false,
+                        _ => return Err(syntax_error()),
+                    }
+                }
+                _ => return Err(syntax_error()),
+            }
+
+            filter = match parser.next_string() {
+                Ok(filter) => filter,
+                Err(CommandParserError::EndOfStream) => return Ok(kill),
+                Err(err) => return Err(err.into()),
+            };
+            value = match parser.next_string() {
+                Ok(value) => value,
+                Err(CommandParserError::EndOfStream) => return Err(syntax_error()),
+                Err(err) => return Err(err.into()),
+            };
+        }
+    }
+}
 
 impl Executable for Client {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
-        Ok(Frame::Simple("OK".to_string()))
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let res = match self {
+            // Without a connection, there is no client to kill.
+            Client::Kill(kill) => kill.reply(false),
+            _ => Frame::Simple("OK".to_string()),
+        };
+        Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Client {
-    type Error = Error;
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let sub_command = parser.next_string()?.to_lowercase();
+
+        match sub_command.as_str() {
+            "kill" => return Ok(Self::Kill(Kill::parse(parser)?)),
+            "reply" => {}
+            _ => return Ok(Self::Other),
+        }
+
+        let mode = match parser.next_string()?.to_uppercase().as_str() {
+            "ON" => ReplyMode::On,
+            "OFF" => ReplyMode::Off,
+            "SKIP" => ReplyMode::Skip,
+            _ => return Err(syntax_error()),
+        };
+
+        Ok(Self::Reply(Reply { mode }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn parse(args: &[&str]) -> Result<Command, RustdisError> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        Command::try_from(frame)
+    }
+
+    #[test]
+    fn reply() {
+        for (arg, mode) in [
+            ("on", ReplyMode::On),
+            ("OFF", ReplyMode::Off),
+            ("Skip", ReplyMode::Skip),
+        ] {
+            assert_eq!(
+                parse(&["CLIENT", "REPLY", arg]).unwrap(),
+                Command::Client(Client::Reply(Reply { mode }))
+            );
+        }
+
+        assert_eq!(
+            parse(&["CLIENT", "REPLY", "LATER"])
+                .unwrap_err()
+                .to_string(),
+            "ERR syntax error"
+        );
+        assert!(parse(&["CLIENT", "REPLY"]).is_err());
+    }
+
+    #[test]
+    fn kill() {
+        let address: SocketAddr = "127.0.0.1:6000".parse().unwrap();
+
+        let legacy = match parse(&["CLIENT", "KILL", "127.0.0.1:6000"]).unwrap() {
+            Command::Client(Client::Kill(kill)) => kill,
+            cmd => panic!("unexpected command {:?}", cmd),
+        };
+        assert!(legacy.legacy);
+        assert!(legacy.matches(address));
+        assert!(!legacy.matches("127.0.0.1:6001".parse().unwrap()));
+        assert_eq!(legacy.reply(true), Frame::Simple("OK".to_string()));
+        assert_eq!(
+            legacy.reply(false),
+            Frame::Error("ERR No such client".to_string())
+        );
+
+        let kill = |args: &[&str]| match parse(&[&["CLIENT", "KILL"], args].concat()).unwrap() {
+            Command::Client(Client::Kill(kill)) => kill,
+            cmd => panic!("unexpected command {:?}", cmd),
+        };
+        // The client sending the command is skipped unless told otherwise.
+        assert!(!kill(&["ADDR", "127.0.0.1:6000"]).matches(address));
+        assert!(kill(&["ADDR", "127.0.0.1:6000", "SKIPME", "no"]).matches(address));
+        assert!(kill(&["skipme", "NO", "type", "Normal"]).matches(address));
+        assert!(!kill(&["SKIPME", "no", "TYPE", "pubsub"]).matches(address));
+        assert!(!kill(&["SKIPME", "no", "ID", "1"]).matches(address));
+        assert_eq!(kill(&["SKIPME", "no"]).reply(true), Frame::Integer(1));
+        assert_eq!(kill(&["SKIPME", "no"]).reply(false), Frame::Integer(0));
+
+        for (args, err) in [
+            (
+                &["ADDR", "127.0.0.1:6000", "SKIPME"][..],
+                "ERR syntax error",
+            ),
+            (&["SKIPME", "maybe"], "ERR syntax error"),
+            (&["MAXAGE", "10"], "ERR syntax error"),
+            (&["ID", "0"], "ERR client-id should be greater than 0"),
+            (&["TYPE", "robot"], "ERR Unknown client type 'robot'"),
+        ] {
+            let res = parse(&[&["CLIENT", "KILL"], args].concat());
+            assert_eq!(res.unwrap_err().to_string(), err, "{:?}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn kill_without_connection() {
+        let cmd = parse(&["CLIENT", "KILL", "127.0.0.1:6000"]).unwrap();
+        assert_eq!(
+            cmd.exec(Store::new()).unwrap(),
+            Frame::Error("ERR No such client".to_string())
+        );
+
+        let cmd = parse(&["CLIENT", "KILL", "SKIPME", "no"]).unwrap();
+        assert_eq!(cmd.exec(Store::new()).unwrap(), Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn other() {
+        let cmd = parse(&["CLIENT", "SETNAME", "foo"]).unwrap();
+
+        assert_eq!(cmd, Command::Client(Client::Other));
+
+        let res = cmd.exec(Store::new()).unwrap();
 
-    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
-        Ok(Self {})
+        assert_eq!(res, Frame::Simple("OK".to_string()));
     }
 }
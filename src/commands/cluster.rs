@@ -0,0 +1,239 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+use crate::utils::keyslot::{key_slot, SLOTS};
+
+/// Hash slot introspection, to explore how keys would be distributed across a Redis Cluster.
+///
+/// **NOTE**: this is a rustdis specific behavior. rustdis has no cluster support, Redis replies
+/// with an error to CLUSTER when cluster mode is disabled. Here the subcommands operate over the
+/// single-node keyspace as if it held every slot.
+///
+/// Ref: <https://redis.io/docs/latest/commands/cluster/>
+#[derive(Debug, PartialEq)]
+pub enum Cluster {
+    Keyslot(Keyslot),
+    Countkeysinslot(Countkeysinslot),
+    Getkeysinslot(Getkeysinslot),
+}
+
+/// Returns the hash slot of `key`.
+///
+/// Ref: <https://redis.io/docs/latest/commands/cluster-keyslot/>
+#[derive(Debug, PartialEq)]
+pub struct Keyslot {
+    pub key: String,
+}
+
+/// Returns the number of keys in `slot`.
+///
+/// Ref: <https://redis.io/docs/latest/commands/cluster-countkeysinslot/>
+#[derive(Debug, PartialEq)]
+pub struct Countkeysinslot {
+    pub slot: u16,
+}
+
+/// Returns up to `count` keys in `slot`.
+///
+/// Ref: <https://redis.io/docs/latest/commands/cluster-getkeysinslot/>
+#[derive(Debug, PartialEq)]
+pub struct Getkeysinslot {
+    pub slot: u16,
+    pub count: usize,
+}
+
+impl Executable for Cluster {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        match self {
+            Self::Keyslot(cmd) => cmd.exec(store),
+            Self::Countkeysinslot(cmd) => cmd.exec(store),
+            Self::Getkeysinslot(cmd) => cmd.exec(store),
+        }
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Cluster {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let sub_command = parser.next_string()?;
+        let sub_command = sub_command.to_lowercase();
+        let mut next_integer = || {
+            parser.next_integer().map_err(|_| {
+                RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+            })
+        };
+
+        match sub_command.as_str() {
+            "keyslot" => {
+                let key = parser.next_string()?;
+                Ok(Self::Keyslot(Keyslot { key }))
+            }
+            "countkeysinslot" => {
+                let slot = next_integer()?;
+                let slot = u16::try_from(slot)
+                    .ok()
+                    .filter(|slot| *slot < SLOTS)
+                    .ok_or_else(|| RustdisError::command(ErrorKind::Err, "Invalid slot"))?;
+                Ok(Self::Countkeysinslot(Countkeysinslot { slot }))
+            }
+            "getkeysinslot" => {
+                let slot = next_integer()?;
+                let count = next_integer()?;
+                let invalid =
+                    || RustdisError::command(ErrorKind::Err, "Invalid slot or number of keys");
+                let slot = u16::try_from(slot)
+                    .ok()
+                    .filter(|slot| *slot < SLOTS)
+                    .ok_or_else(invalid)?;
+                let count = usize::try_from(count).map_err(|_| invalid())?;
+                Ok(Self::Getkeysinslot(Getkeysinslot { slot, count }))
+            }
+            _ => Err(CommandParserError::UnknownCommand {
+                command: format!("CLUSTER {}", sub_command.to_uppercase()),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Executable for Keyslot {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Integer(key_slot(self.key.as_bytes()) as i64))
+    }
+}
+
+impl Executable for Countkeysinslot {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let count = state
+            .keys()
+            .filter(|key| key_slot(key.as_bytes()) == self.slot)
+            .count();
+
+        Ok(Frame::Integer(count as i64))
+    }
+}
+
+impl Executable for Getkeysinslot {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let keys = state
+            .keys()
+            .filter(|key| key_slot(key.as_bytes()) == self.slot)
+            .take(self.count)
+            .map(|key| Frame::Bulk(Bytes::from(key.to_string())))
+            .collect();
+
+        Ok(Frame::Array(keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn cluster(args: &[&str]) -> Result<Command, RustdisError> {
+        let mut frames = vec![Frame::Bulk(Bytes::from("CLUSTER"))];
+        frames.extend(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string()))),
+        );
+        Command::try_from(Frame::Array(frames))
+    }
+
+    #[tokio::test]
+    async fn keyslot() {
+        let store = Store::new();
+
+        let cmd = cluster(&["KEYSLOT", "somekey"]).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Cluster(Cluster::Keyslot(Keyslot {
+                key: String::from("somekey")
+            }))
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Integer(11058));
+    }
+
+    #[tokio::test]
+    async fn keys_in_slot() {
+        let store = Store::new();
+        for key in ["{user1}:name", "{user1}:email", "{user1}:age", "user2"] {
+            store.lock().set(key.to_string(), Bytes::from("value"));
+        }
+        let slot = key_slot(b"user1").to_string();
+
+        let res = cluster(&["COUNTKEYSINSLOT", &slot])
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        assert_eq!(res, Frame::Integer(3));
+
+        let res = cluster(&["GETKEYSINSLOT", &slot, "2"])
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        let Frame::Array(keys) = res else {
+            panic!("expected an array, got {:?}", res);
+        };
+        assert_eq!(keys.len(), 2);
+        for key in keys {
+            let Frame::Bulk(key) = key else {
+                panic!("expected a bulk string, got {:?}", key);
+            };
+            assert!(key.starts_with(b"{user1}:"));
+        }
+
+        let res = cluster(&["GETKEYSINSLOT", "0", "10"])
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        assert_eq!(res, Frame::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments() {
+        let error = |args: &[&str]| cluster(args).unwrap_err().to_string();
+
+        assert_eq!(error(&["COUNTKEYSINSLOT", "16384"]), "ERR Invalid slot");
+        assert_eq!(error(&["COUNTKEYSINSLOT", "-1"]), "ERR Invalid slot");
+        assert_eq!(
+            error(&["GETKEYSINSLOT", "16384", "1"]),
+            "ERR Invalid slot or number of keys"
+        );
+        assert_eq!(
+            error(&["GETKEYSINSLOT", "0", "-1"]),
+            "ERR Invalid slot or number of keys"
+        );
+        for args in [
+            &["COUNTKEYSINSLOT", "abc"][..],
+            &["COUNTKEYSINSLOT", "99999999999999999999"],
+            &["GETKEYSINSLOT", "abc", "1"],
+            &["GETKEYSINSLOT", "1", "abc"],
+        ] {
+            assert_eq!(
+                error(args),
+                "ERR value is not an integer or out of range",
+                "{:?}",
+                args
+            );
+        }
+
+        let err = cluster(&["NODES"]).unwrap_err();
+        assert!(matches!(err, RustdisError::Command { .. }));
+        assert_eq!(
+            err.to_string(),
+            "ERR protocol error; unknown command CLUSTER NODES"
+        );
+    }
+}
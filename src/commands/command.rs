@@ -1,16 +1,17 @@
-use strum::VariantNames;
+use bytes::Bytes;
 
 use crate::commands::executable::Executable;
-use crate::commands::{Command as RootCommand, CommandParser, CommandParserError};
+use crate::commands::{Command as RootCommand, CommandInfo, CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 #[derive(Debug, PartialEq)]
 pub enum Command {
     /// Return an array with details about every Redis command.
     ///
-    /// **NOTE**: only lists names of the implemented commands.
+    /// **NOTE**: only lists the implemented commands, without ACL categories, tips, key
+    /// specifications nor subcommands.
     ///
     /// Ref: <https://redis.io/docs/latest/commands/command/>
     Root(Root),
@@ -23,7 +24,7 @@ pub enum Command {
 }
 
 impl Executable for Command {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         match self {
             Self::Root(root) => root.exec(store),
             Self::Docs(docs) => docs.exec(store),
@@ -32,7 +33,7 @@ impl Executable for Command {
 }
 
 impl TryFrom<&mut CommandParser> for Command {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let sub = parser.next_string().map(|sub| sub.to_lowercase());
@@ -53,22 +54,84 @@ impl TryFrom<&mut CommandParser> for Command {
 pub struct Root;
 
 impl Executable for Root {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
-        // TODO: list subcommands
-        let cmds = RootCommand::VARIANTS
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let cmds = RootCommand::TABLE
             .iter()
-            .map(|s| Frame::Simple(s.to_uppercase().to_string()))
+            .map(CommandInfo::to_frame)
             .collect();
-
         Ok(Frame::Array(cmds))
     }
 }
 
+impl CommandInfo {
+    /// Replies with the command like Redis does, a 10 element array. The trailing ACL categories,
+    /// tips, key specifications and subcommands are left empty.
+    fn to_frame(&self) -> Frame {
+        let flags = self
+            .flags
+            .iter()
+            .map(|flag| Frame::Simple(flag.to_string()))
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from(self.name)),
+            Frame::Integer(self.arity),
+            Frame::Array(flags),
+            Frame::Integer(self.first_key),
+            Frame::Integer(self.last_key),
+            Frame::Integer(self.step),
+            Frame::Array(vec![]),
+            Frame::Array(vec![]),
+            Frame::Array(vec![]),
+            Frame::Array(vec![]),
+        ])
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Docs;
 
 impl Executable for Docs {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
         Ok(Frame::Simple("OK".to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_every_command() {
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("COMMAND"))]);
+        let cmd = RootCommand::try_from(frame).unwrap();
+
+        let Frame::Array(cmds) = cmd.exec(Store::new()).unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(cmds.len(), RootCommand::TABLE.len());
+
+        let get = cmds
+            .iter()
+            .find(|cmd| matches!(cmd, Frame::Array(parts) if parts[0] == Frame::Bulk(Bytes::from("get"))))
+            .unwrap();
+        assert_eq!(
+            *get,
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("get")),
+                Frame::Integer(2),
+                Frame::Array(vec![
+                    Frame::Simple(String::from("readonly")),
+                    Frame::Simple(String::from("fast")),
+                ]),
+                Frame::Integer(1),
+                Frame::Integer(1),
+                Frame::Integer(1),
+                Frame::Array(vec![]),
+                Frame::Array(vec![]),
+                Frame::Array(vec![]),
+                Frame::Array(vec![]),
+            ])
+        );
+    }
+}
@@ -1,22 +1,326 @@
+use bytes::Bytes;
+use std::env;
+
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::config::{ConfigError, DIRECTIVES};
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
+use crate::pattern::Pattern;
 use crate::store::Store;
-use crate::Error;
 
+/// Parameters rustdis has no directive for, that clients and frameworks read with CONFIG GET when
+/// they connect, and fail or warn on when missing. Each one is reported with the value matching
+/// how rustdis behaves, which is the Redis default unless noted.
+const COMPATIBILITY_PARAMETERS: &[(&str, &str)] = &[
+    // There is no persistence.
+    ("save", ""),
+    ("dbfilename", "dump.rdb"),
+    ("stop-writes-on-bgsave-error", "yes"),
+    // The number of databases SELECT accepts, see `select::DATABASES`.
+    ("databases", "16"),
+    // There is no eviction, writes are never rejected either, see `Config::maxmemory`.
+    ("maxmemory-policy", "noeviction"),
+    ("maxmemory-samples", "5"),
+    // There is no pub/sub, so no keyspace notifications.
+    ("notify-keyspace-events", ""),
+    ("timeout", "0"),
+    ("maxclients", "10000"),
+    ("requirepass", ""),
+    ("masterauth", ""),
+    ("protected-mode", "yes"),
+    ("loglevel", "notice"),
+    ("hz", "10"),
+    ("slowlog-log-slower-than", "10000"),
+    ("slowlog-max-len", "128"),
+    ("busy-reply-threshold", "5000"),
+    ("lua-time-limit", "5000"),
+    ("cluster-enabled", "no"),
+    ("replica-read-only", "yes"),
+    ("slave-read-only", "yes"),
+    ("min-replicas-to-write", "0"),
+    ("io-threads", "1"),
+    ("proto-max-bulk-len", "536870912"),
+    (
+        "client-output-buffer-limit",
+        "normal 0 0 0 slave 268435456 67108864 60 pubsub 33554432 8388608 60",
+    ),
+];
+
+/// The CONFIG command reads the configuration of the server.
+///
+/// Ref: <https://redis.io/docs/latest/commands/config/>
 #[derive(Debug, PartialEq)]
-pub struct Config;
+pub enum Config {
+    Get(Get),
+    Rewrite(Rewrite),
+    /// Any other subcommand, like CONFIG SET.
+    ///
+    /// **NOTE**: other subcommands are accepted for compatibility and do nothing, the
+    /// configuration can't be changed once the server is running.
+    Other,
+}
+
+/// Returns the parameters matching any of the glob-style `patterns`, case insensitively, as a
+/// map of names to values, which RESP2 clients receive as a flat array of names and values. A parameter matching several patterns is returned once.
+///
+/// The parameters are the directives of `config::Config`, with the value the server runs with, and
+/// the parameters clients commonly read at startup, like `maxmemory-policy`, with the value that
+/// matches how rustdis behaves, see `COMPATIBILITY_PARAMETERS`. The `dir` parameter is the working
+/// directory of the server.
+///
+/// **NOTE**: Redis returns every other parameter it has, rustdis returns nothing for them, like
+/// Redis does for unknown parameters.
+///
+/// Ref: <https://redis.io/docs/latest/commands/config-get/>
+#[derive(Debug, PartialEq)]
+pub struct Get {
+    pub patterns: Vec<String>,
+}
+
+/// Writes the configuration the server runs with back to the configuration file it was started
+/// with, keeping its comments and the directives rustdis doesn't know about, see
+/// `config::Config::rewrite`.
+///
+/// Replies with an error when the server was started without a configuration file.
+///
+/// Ref: <https://redis.io/docs/latest/commands/config-rewrite/>
+#[derive(Debug, PartialEq)]
+pub struct Rewrite;
 
 impl Executable for Config {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
-        Ok(Frame::Simple("OK".to_string()))
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        match self {
+            Config::Get(get) => get.exec(store),
+            Config::Rewrite(rewrite) => rewrite.exec(store),
+            Config::Other => Ok(Frame::Simple("OK".to_string())),
+        }
+    }
+}
+
+impl Executable for Get {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let patterns: Vec<Pattern> = self
+            .patterns
+            .iter()
+            .map(|pattern| Pattern::compile(pattern.to_lowercase()))
+            .collect();
+
+        let dir = env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let config = store.config();
+        let parameters = DIRECTIVES
+            .iter()
+            .filter_map(|name| Some((*name, config.get(name)?)))
+            .chain(
+                COMPATIBILITY_PARAMETERS
+                    .iter()
+                    .map(|(name, value)| (*name, value.to_string())),
+            )
+            .chain([("dir", dir)]);
+
+        let mut res = vec![];
+        for (name, value) in parameters {
+            if patterns.iter().any(|pattern| pattern.matches(name)) {
+                res.push((
+                    Frame::Bulk(Bytes::from(name)),
+                    Frame::Bulk(Bytes::from(value)),
+                ));
+            }
+        }
+
+        Ok(Frame::Map(res))
+    }
+}
+
+impl Executable for Rewrite {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let res = match store.config().rewrite() {
+            Ok(()) => Frame::Simple("OK".to_string()),
+            Err(err @ ConfigError::NoConfigFile) => Frame::Error(format!("ERR {}", err)),
+            Err(err) => Frame::Error(format!("ERR Rewriting config file: {}", err)),
+        };
+
+        Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Config {
-    type Error = Error;
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let sub_command = parser.next_string()?;
+
+        match sub_command.to_lowercase().as_str() {
+            "get" => {
+                let mut patterns = vec![];
+                loop {
+                    match parser.next_string() {
+                        Ok(pattern) => patterns.push(pattern),
+                        Err(CommandParserError::EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+
+                if patterns.is_empty() {
+                    return Err(RustdisError::command(
+                        ErrorKind::Err,
+                        "wrong number of arguments for 'config|get' command",
+                    ));
+                }
+
+                Ok(Config::Get(Get { patterns }))
+            }
+            "rewrite" => Ok(Config::Rewrite(Rewrite)),
+            _ => Ok(Config::Other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::select::DATABASES;
+    use crate::commands::Command;
+    use std::fs;
+    use std::process;
+
+    fn config(store: &Store, args: &[&str]) -> Result<Frame, String> {
+        let args = ["CONFIG"].iter().chain(args);
+        let frame = Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(store.clone()).unwrap())
+    }
+
+    fn parameters(parameters: &[(&str, &str)]) -> Frame {
+        Frame::Map(
+            parameters
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        Frame::Bulk(Bytes::copy_from_slice(name.as_bytes())),
+                        Frame::Bulk(Bytes::copy_from_slice(value.as_bytes())),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn get() {
+        let store = Store::with_config(crate::config::Config {
+            maxmemory: 1024,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            config(&store, &["GET", "maxmemory"]),
+            Ok(parameters(&[("maxmemory", "1024")]))
+        );
+        assert_eq!(
+            config(&store, &["get", "MAXMEMORY*"]),
+            Ok(parameters(&[
+                ("maxmemory", "1024"),
+                ("maxmemory-policy", "noeviction"),
+                ("maxmemory-samples", "5"),
+            ]))
+        );
+        assert_eq!(
+            config(&store, &["GET", "save", "appendonly", "save"]),
+            Ok(parameters(&[("appendonly", "no"), ("save", "")]))
+        );
+        assert_eq!(config(&store, &["GET", "nope"]), Ok(parameters(&[])));
+
+        let Ok(Frame::Map(every)) = config(&store, &["GET", "*"]) else {
+            panic!("expected a map");
+        };
+        assert_eq!(
+            every.len(),
+            DIRECTIVES.len() + COMPATIBILITY_PARAMETERS.len() + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn get_without_parameters() {
+        let store = Store::new();
+
+        assert_eq!(
+            config(&store, &["GET"]),
+            Err("ERR wrong number of arguments for 'config|get' command".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn other_subcommands() {
+        let store = Store::new();
+
+        assert_eq!(
+            config(&store, &["SET", "maxmemory", "0"]),
+            Ok(Frame::Simple("OK".to_string()))
+        );
+        assert_eq!(
+            config(&store, &["RESETSTAT"]),
+            Ok(Frame::Simple("OK".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn rewrite() {
+        let path = env::temp_dir().join(format!("rustdis-{}-config-rewrite.conf", process::id()));
+        fs::write(&path, "# Limits\nmaxmemory 1kb\n").unwrap();
+        let store = Store::with_config(crate::config::Config {
+            maxmemory: 2048,
+            config_file: Some(path.clone()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            config(&store, &["REWRITE"]),
+            Ok(Frame::Simple("OK".to_string()))
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Limits\nmaxmemory 2048\n"
+        );
+
+        fs::remove_file(&path).unwrap();
+        let Ok(Frame::Error(err)) = config(&store, &["REWRITE"]) else {
+            panic!("expected an error");
+        };
+        assert!(err.starts_with("ERR Rewriting config file: "), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rewrite_without_config_file() {
+        let store = Store::new();
+
+        assert_eq!(
+            config(&store, &["REWRITE"]),
+            Ok(Frame::Error(
+                "ERR the server is running without a config file".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn databases_matches_select() {
+        let databases = COMPATIBILITY_PARAMETERS
+            .iter()
+            .find(|(name, _)| *name == "databases")
+            .map(|(_, value)| *value);
+
+        assert_eq!(databases, Some(DATABASES.to_string().as_str()));
+    }
 
-    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
-        Ok(Self {})
+    #[test]
+    fn compatibility_parameters_are_not_directives() {
+        for (name, _) in COMPATIBILITY_PARAMETERS {
+            assert!(!DIRECTIVES.contains(name), "{}", name);
+            assert_ne!(*name, "dir");
+        }
     }
 }
@@ -0,0 +1,274 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{Command, CommandInfo, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// The commands of Redis 7.2, the version rustdis targets, by the group Redis documents them in,
+/// separated by spaces.
+const REDIS_COMMANDS: &[(&str, &str)] = &[
+    (
+        "string",
+        "append decr decrby get getdel getex getrange getset incr incrby incrbyfloat lcs mget \
+         mset msetnx psetex set setex setnx setrange strlen substr",
+    ),
+    (
+        "generic",
+        "copy del dump exists expire expireat expiretime keys migrate move object persist \
+         pexpire pexpireat pexpiretime pttl randomkey rename renamenx restore scan sort \
+         sort_ro touch ttl type unlink wait waitaof",
+    ),
+    (
+        "server",
+        "acl bgrewriteaof bgsave command config dbsize debug failover flushall flushdb info \
+         lastsave latency lolwut memory module monitor psync replconf replicaof restore-asking \
+         role save shutdown slaveof slowlog swapdb sync time",
+    ),
+    (
+        "connection",
+        "auth client echo hello ping quit reset select",
+    ),
+    ("transactions", "discard exec multi unwatch watch"),
+    ("cluster", "asking cluster readonly readwrite"),
+    ("sentinel", "sentinel"),
+    (
+        "list",
+        "blmove blmpop blpop brpop brpoplpush lindex linsert llen lmove lmpop lpop lpos lpush \
+         lpushx lrange lrem lset ltrim rpop rpoplpush rpush rpushx",
+    ),
+    (
+        "hash",
+        "hdel hexists hget hgetall hincrby hincrbyfloat hkeys hlen hmget hmset hrandfield \
+         hscan hset hsetnx hstrlen hvals",
+    ),
+    (
+        "set",
+        "sadd scard sdiff sdiffstore sinter sintercard sinterstore sismember smembers \
+         smismember smove spop srandmember srem sscan sunion sunionstore",
+    ),
+    (
+        "sorted-set",
+        "bzmpop bzpopmax bzpopmin zadd zcard zcount zdiff zdiffstore zincrby zinter zintercard \
+         zinterstore zlexcount zmpop zmscore zpopmax zpopmin zrandmember zrange zrangebylex \
+         zrangebyscore zrangestore zrank zrem zremrangebylex zremrangebyrank zremrangebyscore \
+         zrevrange zrevrangebylex zrevrangebyscore zrevrank zscan zscore zunion zunionstore",
+    ),
+    (
+        "stream",
+        "xack xadd xautoclaim xclaim xdel xgroup xinfo xlen xpending xrange xread xreadgroup \
+         xrevrange xsetid xtrim",
+    ),
+    (
+        "pubsub",
+        "psubscribe publish pubsub punsubscribe spublish ssubscribe subscribe sunsubscribe \
+         unsubscribe",
+    ),
+    (
+        "scripting",
+        "eval eval_ro evalsha evalsha_ro fcall fcall_ro function script",
+    ),
+    (
+        "geo",
+        "geoadd geodist geohash geopos georadius georadius_ro georadiusbymember \
+         georadiusbymember_ro geosearch geosearchstore",
+    ),
+    ("hyperloglog", "pfadd pfcount pfdebug pfmerge pfselftest"),
+    (
+        "bitmap",
+        "bitcount bitfield bitfield_ro bitop bitpos getbit setbit",
+    ),
+];
+
+/// Reports which Redis commands rustdis implements, by command group.
+///
+/// ```text
+/// RUSTDIS.COVERAGE [group]
+/// ```
+///
+/// Replies with an array holding, for each group, or only for `group` when given:
+///
+/// ```text
+/// 1) "string"
+/// 2) "implemented"
+/// 3) 1) "append" ...
+/// 4) "partial"
+/// 5) 1) 1) "lcs"
+///       2) 1) "IDX" ...
+/// 6) "missing"
+/// 7) 1) "getset" ...
+/// ```
+///
+/// Commands are `partial` when the registry lists options or subcommands they don't implement,
+/// see `commands!`, and `missing` when Redis has them but rustdis doesn't. Extension commands,
+/// like RUSTDIS.MSETEX, are reported within the group they are declared in.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command.
+#[derive(Debug, PartialEq)]
+pub struct Coverage {
+    pub group: Option<String>,
+}
+
+impl Executable for Coverage {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let groups = REDIS_COMMANDS
+            .iter()
+            .filter(|(group, _)| self.group.as_deref().is_none_or(|name| name == *group))
+            .map(|(group, commands)| group_coverage(group, commands.split_whitespace()))
+            .collect();
+
+        Ok(Frame::Array(groups))
+    }
+}
+
+/// The coverage of `group`, whose Redis commands are `commands`.
+fn group_coverage<'a>(group: &str, commands: impl Iterator<Item = &'a str>) -> Frame {
+    let declared: Vec<&CommandInfo> = Command::TABLE
+        .iter()
+        .filter(|info| info.group == group)
+        .collect();
+
+    let implemented = declared
+        .iter()
+        .filter(|info| info.unsupported.is_empty())
+        .map(|info| bulk(info.name))
+        .collect();
+    let partial = declared
+        .iter()
+        .filter(|info| !info.unsupported.is_empty())
+        .map(|info| {
+            let unsupported = info.unsupported.iter().map(|option| bulk(option)).collect();
+            Frame::Array(vec![bulk(info.name), Frame::Array(unsupported)])
+        })
+        .collect();
+    let missing = commands
+        .filter(|name| CommandInfo::lookup(name).is_none())
+        .map(bulk)
+        .collect();
+
+    Frame::Array(vec![
+        bulk(group),
+        bulk("implemented"),
+        Frame::Array(implemented),
+        bulk("partial"),
+        Frame::Array(partial),
+        bulk("missing"),
+        Frame::Array(missing),
+    ])
+}
+
+fn bulk(s: &str) -> Frame {
+    Frame::Bulk(Bytes::copy_from_slice(s.as_bytes()))
+}
+
+impl TryFrom<&mut CommandParser> for Coverage {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let group = match parser.next_string() {
+            Ok(group) => group.to_lowercase(),
+            Err(CommandParserError::EndOfStream) => return Ok(Self { group: None }),
+            Err(err) => return Err(err.into()),
+        };
+
+        if !REDIS_COMMANDS.iter().any(|(name, _)| *name == group) {
+            return Err(RustdisError::command(
+                ErrorKind::Err,
+                format!("Unknown command group '{}'", group),
+            ));
+        }
+
+        Ok(Self { group: Some(group) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(args: &[&str]) -> Result<Frame, String> {
+        let args = ["RUSTDIS.COVERAGE"].iter().chain(args);
+        let frame = Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(Store::new()).unwrap())
+    }
+
+    #[test]
+    fn every_command_is_in_a_redis_group() {
+        for info in Command::TABLE {
+            let commands = REDIS_COMMANDS
+                .iter()
+                .find(|(group, _)| *group == info.group)
+                .map(|(_, commands)| commands)
+                .unwrap_or_else(|| panic!("unknown group {} of {}", info.group, info.name));
+
+            assert!(
+                info.name.starts_with("rustdis.")
+                    || commands.split_whitespace().any(|name| name == info.name),
+                "{} is not a Redis {} command",
+                info.name,
+                info.group
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn group() {
+        let res = coverage(&["Transactions"]).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Array(vec![Frame::Array(vec![
+                bulk("transactions"),
+                bulk("implemented"),
+                Frame::Array(vec![
+                    bulk("discard"),
+                    bulk("exec"),
+                    bulk("multi"),
+                    bulk("unwatch"),
+                    bulk("watch"),
+                ]),
+                bulk("partial"),
+                Frame::Array(vec![]),
+                bulk("missing"),
+                Frame::Array(vec![]),
+            ])])
+        );
+
+        let Frame::Array(groups) = coverage(&["string"]).unwrap() else {
+            panic!("expected an array");
+        };
+        let Frame::Array(string) = &groups[0] else {
+            panic!("expected an array");
+        };
+        assert!(
+            matches!(&string[2], Frame::Array(names) if names.contains(&bulk("rustdis.msetex")))
+        );
+        assert_eq!(
+            string[4],
+            Frame::Array(vec![Frame::Array(vec![
+                bulk("lcs"),
+                Frame::Array(vec![bulk("IDX"), bulk("MINMATCHLEN"), bulk("WITHMATCHLEN")]),
+            ])])
+        );
+        assert!(matches!(&string[6], Frame::Array(names) if names.contains(&bulk("getset"))));
+    }
+
+    #[tokio::test]
+    async fn every_group() {
+        let Frame::Array(groups) = coverage(&[]).unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(groups.len(), REDIS_COMMANDS.len());
+
+        assert_eq!(
+            coverage(&["strings"]),
+            Err("ERR Unknown command group 'strings'".to_string())
+        );
+    }
+}
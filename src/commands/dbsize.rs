@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Return the number of keys in the current database
 ///
@@ -11,13 +11,13 @@ use crate::Error;
 pub struct DBSize;
 
 impl Executable for DBSize {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         Ok(Frame::Integer(store.lock().size() as i64))
     }
 }
 
 impl TryFrom<&mut CommandParser> for DBSize {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
         Ok(Self)
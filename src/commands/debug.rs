@@ -0,0 +1,789 @@
+use bytes::Bytes;
+use tokio::time::Duration;
+
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::dump;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::{Store, Value, TTL_HISTOGRAM_BOUNDS};
+use crate::utils::digest;
+
+/// How many times the keys the keyspace holds DEBUG REHASH allocates room for at most.
+const REHASH_MAX_GROWTH: usize = 16;
+
+/// The keys DEBUG REHASH allocates room for at most is computed from at least this many, so small
+/// keyspaces can still be preallocated.
+const REHASH_MIN_KEYS: usize = 1024;
+
+/// The DEBUG command is an internal command meant to be used for developing and testing Redis.
+///
+/// Ref: <https://redis.io/docs/latest/commands/debug/>
+#[derive(Debug, PartialEq)]
+pub enum Debug {
+    Digest(Digest),
+    DigestValue(DigestValue),
+    DumpKeys(DumpKeys),
+    ErrorReply(ErrorReply),
+    Panic(Panic),
+    Rehash(Rehash),
+    SetActiveExpire(SetActiveExpire),
+    SharedReplies(SharedReplies),
+    Sleep(Sleep),
+    Tombstones(Tombstones),
+    TtlStats(TtlStats),
+}
+
+/// Returns a digest of the whole keyspace, which is the same for two servers holding the same keys
+/// and values. Handy to check that data is preserved exactly when it is moved around.
+///
+/// **NOTE**: unlike Redis, time to live of keys is not part of the digest, and the digest is a
+/// 64-bit FNV-1a hash instead of a SHA1 one.
+#[derive(Debug, PartialEq)]
+pub struct Digest;
+
+/// Returns a digest of the value of each of the given keys, or a digest of zeros for the ones that
+/// don't exist.
+#[derive(Debug, PartialEq)]
+pub struct DigestValue {
+    pub keys: Vec<String>,
+}
+
+/// Returns the whole keyspace as a stream of RESP encoded commands that recreate it, see the
+/// `dump` module.
+///
+/// **NOTE**: this is a rustdis specific subcommand.
+#[derive(Debug, PartialEq)]
+pub struct DumpKeys;
+
+/// Replies with `message` as an error, to test how clients handle error replies, including the
+/// ones they don't know, like `-MYERROR something went wrong`.
+///
+/// **NOTE**: Redis prefixes the message with `ERR ` when it doesn't start with an uppercase code,
+/// rustdis replies with it as is.
+#[derive(Debug, PartialEq)]
+pub struct ErrorReply {
+    pub message: String,
+}
+
+/// Panics while holding the store lock, like a bug in a command would. Used to test that a
+/// panicking command doesn't take the server down.
+///
+/// **NOTE**: Redis crashes, whereas rustdis catches the panic and replies with an error.
+#[derive(Debug, PartialEq)]
+pub struct Panic;
+
+/// Reallocates the keyspace to hold at least `capacity` keys, or just the keys it holds when not
+/// given, rehashing every key right away. Benchmarks use it to trigger a rehash on purpose, so
+/// its cost is not mistaken for the cost of the commands being measured. `INFO memory` reports the
+/// resulting capacity.
+///
+/// The capacity is at most `REHASH_MAX_GROWTH` times the keys held, or times `REHASH_MIN_KEYS` for
+/// smaller keyspaces, and an allocation that can't be made is replied to with an error.
+///
+/// **NOTE**: this is a rustdis specific subcommand, it does nothing with the `btreemap` storage
+/// engine.
+#[derive(Debug, PartialEq)]
+pub struct Rehash {
+    pub capacity: Option<usize>,
+}
+
+/// Enables or disables the active expiration of keys. Test frameworks disable it during setup to
+/// make expiration deterministic.
+///
+/// **NOTE**: keys are always actively expired, this subcommand is accepted for compatibility and
+/// does nothing.
+#[derive(Debug, PartialEq)]
+pub struct SetActiveExpire {
+    pub enabled: bool,
+}
+
+/// Describes how often replies are sent already serialized, as `field:value` lines:
+///
+/// - `replies`: replies sent to clients.
+/// - `shared_replies`: replies among them that were already serialized, like OK or `:1`.
+/// - `shared_replies_ratio`: the share of replies that were already serialized.
+///
+/// The most frequent replies are tiny, and formatting them costs about as much as writing them.
+/// Keeping them serialized spares that work, and the ratio shows how much of a workload benefits,
+/// see `reply::shared_reply`. The reply to this command is not counted yet.
+///
+/// **NOTE**: this is a rustdis specific subcommand.
+#[derive(Debug, PartialEq)]
+pub struct SharedReplies;
+
+/// Delays the reply for `duration`, given in seconds, possibly fractional. Used to test client
+/// timeouts.
+///
+/// **NOTE**: Redis blocks the whole server meanwhile, whereas rustdis only delays the connection
+/// sending it, the store isn't locked and other clients are served. The connection applies the
+/// delay itself, before replying, see `Connection::delay`.
+#[derive(Debug, PartialEq)]
+pub struct Sleep {
+    pub duration: Duration,
+}
+
+/// Lists the tombstones left by DEL, sorted by key, as arrays holding the key, its value when it
+/// was deleted, nil unless it was a string, and how many milliseconds ago it was deleted. Empty
+/// unless tombstones are enabled, see `Config::tombstone_grace_period`.
+///
+/// **NOTE**: this is a rustdis specific subcommand.
+#[derive(Debug, PartialEq)]
+pub struct Tombstones;
+
+/// Describes the expirations scheduled by the task removing expired keys, as `field:value` lines:
+///
+/// - `scheduled`: keys with a time to live.
+/// - `overdue`: keys whose time to live has elapsed but haven't been removed yet, always 0 as
+///   they are removed before any command runs, see `InnerStore::lock`.
+/// - `next_expiration_in_ms` and `last_expiration_in_ms`: time left until the first and the last
+///   scheduled expirations, -1 when there are none.
+/// - `ttl_lt_<bound>` and `ttl_ge_<bound>`: a histogram of the time to live left of the keys.
+///
+/// Keys with a time to live are kept sorted by expiration time, so the task only ever looks at
+/// the first one to know how long to sleep.
+///
+/// **NOTE**: this is a rustdis specific subcommand.
+#[derive(Debug, PartialEq)]
+pub struct TtlStats;
+
+impl Executable for Debug {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        match self {
+            Self::Digest(cmd) => cmd.exec(store),
+            Self::DigestValue(cmd) => cmd.exec(store),
+            Self::DumpKeys(cmd) => cmd.exec(store),
+            Self::ErrorReply(cmd) => cmd.exec(store),
+            Self::Panic(cmd) => cmd.exec(store),
+            Self::Rehash(cmd) => cmd.exec(store),
+            Self::SetActiveExpire(cmd) => cmd.exec(store),
+            Self::SharedReplies(cmd) => cmd.exec(store),
+            Self::Sleep(cmd) => cmd.exec(store),
+            Self::Tombstones(cmd) => cmd.exec(store),
+            Self::TtlStats(cmd) => cmd.exec(store),
+        }
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Debug {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let sub_command = parser.next_string()?;
+        let sub_command = sub_command.to_lowercase();
+
+        match sub_command.as_str() {
+            "digest" => Ok(Self::Digest(Digest)),
+            "digest-value" => {
+                let mut keys = vec![];
+                loop {
+                    match parser.next_string() {
+                        Ok(key) => keys.push(key),
+                        Err(CommandParserError::EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Ok(Self::DigestValue(DigestValue { keys }))
+            }
+            "dump-keys" => Ok(Self::DumpKeys(DumpKeys)),
+            "error" => {
+                let message = parser.next_string()?;
+                Ok(Self::ErrorReply(ErrorReply { message }))
+            }
+            "panic" => Ok(Self::Panic(Panic)),
+            "rehash" => {
+                let capacity =
+                    match parser.next_integer() {
+                        Ok(capacity) => Some(usize::try_from(capacity).map_err(|_| {
+                            RustdisError::command(ErrorKind::Err, "invalid capacity")
+                        })?),
+                        Err(CommandParserError::EndOfStream) => None,
+                        Err(_) => {
+                            return Err(RustdisError::command(
+                                ErrorKind::Err,
+                                "value is not an integer or out of range",
+                            ))
+                        }
+                    };
+                Ok(Self::Rehash(Rehash { capacity }))
+            }
+            "set-active-expire" => {
+                let enabled = parser.next_integer().map_err(|_| {
+                    RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+                })? != 0;
+                Ok(Self::SetActiveExpire(SetActiveExpire { enabled }))
+            }
+            "shared-replies" => Ok(Self::SharedReplies(SharedReplies)),
+            "sleep" => {
+                let duration = parser
+                    .next_string()?
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .ok_or_else(|| {
+                        RustdisError::command(ErrorKind::Err, "value is not a valid float")
+                    })?;
+                Ok(Self::Sleep(Sleep { duration }))
+            }
+            "tombstones" => Ok(Self::Tombstones(Tombstones)),
+            "ttl-stats" => Ok(Self::TtlStats(TtlStats)),
+            _ => Err(CommandParserError::UnknownCommand {
+                command: format!("DEBUG {}", sub_command.to_uppercase()),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Executable for Digest {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let values = state
+            .iter_values()
+            .map(|(key, value)| (key, digested(value)));
+        let res = digest::to_hex(digest::digest_keyspace(values));
+
+        Ok(Frame::Simple(res))
+    }
+}
+
+impl Executable for DigestValue {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let res = self
+            .keys
+            .iter()
+            .map(|key| {
+                let digest = state
+                    .get_value(key)
+                    .map(|value| digest::digest_value(&digested(value)))
+                    .unwrap_or(0);
+                Frame::Simple(digest::to_hex(digest))
+            })
+            .collect();
+
+        Ok(Frame::Array(res))
+    }
+}
+
+/// The bytes DIGEST and DIGEST-VALUE digest for `value`: the data of a string, or the digest of
+/// the fields and values of a hash, which doesn't depend on their order.
+fn digested(value: &Value) -> Bytes {
+    match (value.string(), value.hash()) {
+        (Some(data), _) => data,
+        (None, Some(hash)) => Bytes::copy_from_slice(&digest::digest_hash(hash).to_le_bytes()),
+        (None, None) => unreachable!("every value is a string or a hash"),
+    }
+}
+
+impl Executable for DumpKeys {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Bulk(Bytes::from(dump::export(&store))))
+    }
+}
+
+impl Executable for ErrorReply {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Error(self.message))
+    }
+}
+
+impl Executable for Panic {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let _state = store.lock();
+        panic!("DEBUG PANIC");
+    }
+}
+
+impl Executable for Rehash {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        let capacity = self.capacity.unwrap_or_else(|| state.size());
+        let max_capacity = state
+            .size()
+            .max(REHASH_MIN_KEYS)
+            .saturating_mul(REHASH_MAX_GROWTH);
+        if capacity > max_capacity {
+            return Err(RustdisError::command(
+                ErrorKind::Err,
+                format!("invalid capacity, at most {max_capacity}"),
+            ));
+        }
+        state.rehash(capacity).map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "not enough memory to rehash the keyspace")
+        })?;
+
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl Executable for SetActiveExpire {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl Executable for Sleep {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl Executable for Tombstones {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let mut tombstones: Vec<_> = state.tombstones().collect();
+        tombstones.sort_by_key(|(key, _)| *key);
+
+        let res = tombstones
+            .into_iter()
+            .map(|(key, tombstone)| {
+                Frame::Array(vec![
+                    Frame::Bulk(Bytes::from(key.clone())),
+                    tombstone
+                        .value
+                        .string()
+                        .map_or(Frame::NullBulkString, Frame::Bulk),
+                    Frame::Integer(tombstone.deleted_at.elapsed().as_millis() as i64),
+                ])
+            })
+            .collect();
+
+        Ok(Frame::Array(res))
+    }
+}
+
+impl Executable for SharedReplies {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let replies = store.stats.replies_sent();
+        let shared_replies = store.stats.shared_replies_sent();
+        let ratio = match replies {
+            0 => 0.0,
+            replies => shared_replies as f64 / replies as f64,
+        };
+
+        let res = format!(
+            "replies:{}\nshared_replies:{}\nshared_replies_ratio:{:.4}\n",
+            replies, shared_replies, ratio
+        );
+        Ok(Frame::Bulk(Bytes::from(res)))
+    }
+}
+
+impl Executable for TtlStats {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let stats = store.lock().ttl_stats();
+        let millis = |time_left: Option<Duration>| match time_left {
+            Some(time_left) => time_left.as_millis() as i64,
+            None => -1,
+        };
+
+        let mut res = format!(
+            "scheduled:{}\noverdue:{}\nnext_expiration_in_ms:{}\nlast_expiration_in_ms:{}\n",
+            stats.scheduled,
+            stats.overdue,
+            millis(stats.next_expiration),
+            millis(stats.last_expiration),
+        );
+        for ((name, _), count) in TTL_HISTOGRAM_BOUNDS.iter().zip(stats.histogram) {
+            res.push_str(&format!("ttl_lt_{}:{}\n", name, count));
+        }
+        if let Some((name, _)) = TTL_HISTOGRAM_BOUNDS.last() {
+            let count = stats.histogram[TTL_HISTOGRAM_BOUNDS.len()];
+            res.push_str(&format!("ttl_ge_{}:{}\n", name, count));
+        }
+
+        Ok(Frame::Bulk(Bytes::from(res)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+    use crate::config::Config;
+    use crate::store::NewValue;
+
+    #[tokio::test]
+    async fn dump_keys() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("DUMP-KEYS")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(cmd, Command::Debug(Debug::DumpKeys(DumpKeys)));
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Bulk(Bytes::from("*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$1\r\n1\r\n"))
+        );
+    }
+
+    #[tokio::test]
+    async fn digest() {
+        let store = Store::new();
+        let other = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("DIGEST")),
+        ]);
+        let cmd = Command::try_from(frame.clone()).unwrap();
+
+        assert_eq!(cmd, Command::Debug(Debug::Digest(Digest)));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Simple("0000000000000000".to_string()));
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        store.lock().set(String::from("key2"), Bytes::from("2"));
+        other.lock().set(String::from("key2"), Bytes::from("2"));
+        other.lock().set(String::from("key1"), Bytes::from("1"));
+
+        let res = Command::try_from(frame.clone())
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        let other_res = Command::try_from(frame.clone())
+            .unwrap()
+            .exec(other.clone())
+            .unwrap();
+
+        assert_ne!(res, Frame::Simple("0000000000000000".to_string()));
+        assert_eq!(res, other_res);
+
+        other.lock().set(String::from("key1"), Bytes::from("3"));
+
+        let other_res = Command::try_from(frame).unwrap().exec(other).unwrap();
+
+        assert_ne!(res, other_res);
+    }
+
+    #[tokio::test]
+    async fn digest_value() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("DIGEST-VALUE")),
+            Frame::Bulk(Bytes::from("key1")),
+            Frame::Bulk(Bytes::from("key2")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Debug(Debug::DigestValue(DigestValue {
+                keys: vec![String::from("key1"), String::from("key2")]
+            }))
+        );
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Array(vec![
+                Frame::Simple(digest::to_hex(digest::digest_value(b"1"))),
+                Frame::Simple("0000000000000000".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn rehash() {
+        let store = Store::with_config(Config {
+            keyspace_initial_capacity: 1000,
+            ..Config::default()
+        });
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        assert!(store.lock().capacity().unwrap() >= 1000);
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("REHASH")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Debug(Debug::Rehash(Rehash { capacity: None }))
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Simple("OK".to_string()));
+        assert!(store.lock().capacity().unwrap() < 1000);
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("1")));
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("REHASH")),
+            Frame::Bulk(Bytes::from("5000")),
+        ]);
+        Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+
+        assert!(store.lock().capacity().unwrap() >= 5000);
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("REHASH")),
+            Frame::Bulk(Bytes::from("100000000000")),
+        ]);
+        let err = Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "ERR invalid capacity, at most 16384");
+        assert!(store.lock().capacity().unwrap() >= 5000);
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("REHASH")),
+            Frame::Bulk(Bytes::from("-1")),
+        ]);
+        let err = Command::try_from(frame).unwrap_err();
+
+        assert_eq!(err.to_string(), "ERR invalid capacity");
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("REHASH")),
+            Frame::Bulk(Bytes::from("abc")),
+        ]);
+        let err = Command::try_from(frame).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[tokio::test]
+    async fn set_active_expire() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("SET-ACTIVE-EXPIRE")),
+            Frame::Bulk(Bytes::from("0")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Debug(Debug::SetActiveExpire(SetActiveExpire { enabled: false }))
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Simple("OK".to_string()));
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("SET-ACTIVE-EXPIRE")),
+            Frame::Bulk(Bytes::from("abc")),
+        ]);
+        let err = Command::try_from(frame).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tombstones() {
+        let store = Store::with_config(Config {
+            tombstone_grace_period: 1000,
+            ..Config::default()
+        });
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("TOMBSTONES")),
+        ]);
+        let cmd = Command::try_from(frame.clone()).unwrap();
+
+        assert_eq!(cmd, Command::Debug(Debug::Tombstones(Tombstones)));
+
+        store.lock().set(String::from("key2"), Bytes::from("2"));
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        store.lock().delete("key2");
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        store.lock().delete("key1");
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Array(vec![
+                Frame::Array(vec![
+                    Frame::Bulk(Bytes::from("key1")),
+                    Frame::Bulk(Bytes::from("1")),
+                    Frame::Integer(0),
+                ]),
+                Frame::Array(vec![
+                    Frame::Bulk(Bytes::from("key2")),
+                    Frame::Bulk(Bytes::from("2")),
+                    Frame::Integer(10),
+                ]),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn error() {
+        let store = Store::new();
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("ERROR")),
+            Frame::Bulk(Bytes::from("MYERROR something went wrong")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd.exec(store).unwrap(),
+            Frame::Error("MYERROR something went wrong".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn sleep() {
+        let parse = |seconds: &str| {
+            let frame = Frame::Array(vec![
+                Frame::Bulk(Bytes::from("DEBUG")),
+                Frame::Bulk(Bytes::from("SLEEP")),
+                Frame::Bulk(Bytes::from(seconds.to_string())),
+            ]);
+            Command::try_from(frame).map_err(|err| err.to_string())
+        };
+
+        assert_eq!(
+            parse("0.25"),
+            Ok(Command::Debug(Debug::Sleep(Sleep {
+                duration: Duration::from_millis(250)
+            })))
+        );
+        assert_eq!(
+            parse("2"),
+            Ok(Command::Debug(Debug::Sleep(Sleep {
+                duration: Duration::from_secs(2)
+            })))
+        );
+        for invalid in ["-1", "soon", "inf"] {
+            assert_eq!(
+                parse(invalid),
+                Err("ERR value is not a valid float".to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_subcommand() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("foo")),
+        ]);
+
+        let err = Command::try_from(frame).err().unwrap();
+        assert!(matches!(err, RustdisError::Command { .. }));
+        assert_eq!(
+            err.to_string(),
+            "ERR protocol error; unknown command DEBUG FOO"
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_replies() {
+        let store = Store::new();
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("SHARED-REPLIES")),
+        ]);
+        let cmd = Command::try_from(frame.clone()).unwrap();
+
+        assert_eq!(cmd, Command::Debug(Debug::SharedReplies(SharedReplies)));
+        assert_eq!(
+            cmd.exec(store.clone()).unwrap(),
+            Frame::Bulk(Bytes::from(
+                "replies:0\nshared_replies:0\nshared_replies_ratio:0.0000\n"
+            ))
+        );
+
+        store.stats.incr_replies_sent(true);
+        store.stats.incr_replies_sent(true);
+        store.stats.incr_replies_sent(false);
+
+        let cmd = Command::try_from(frame).unwrap();
+        assert_eq!(
+            cmd.exec(store.clone()).unwrap(),
+            Frame::Bulk(Bytes::from(
+                "replies:3\nshared_replies:2\nshared_replies_ratio:0.6667\n"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn ttl_stats() {
+        tokio::time::pause();
+
+        let store = Store::new();
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("DEBUG")),
+            Frame::Bulk(Bytes::from("TTL-STATS")),
+        ]);
+        let cmd = Command::try_from(frame.clone()).unwrap();
+
+        assert_eq!(cmd, Command::Debug(Debug::TtlStats(TtlStats)));
+
+        let res = cmd.exec(store.clone()).unwrap();
+        assert_eq!(
+            res,
+            Frame::Bulk(Bytes::from(
+                "scheduled:0\noverdue:0\nnext_expiration_in_ms:-1\nlast_expiration_in_ms:-1\n\
+                 ttl_lt_1s:0\nttl_lt_10s:0\nttl_lt_1m:0\nttl_lt_10m:0\nttl_lt_1h:0\nttl_lt_1d:0\n\
+                 ttl_ge_1d:0\n"
+            ))
+        );
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        store.set_many(
+            [2, 30, 30, 7200, 200_000]
+                .into_iter()
+                .enumerate()
+                .map(|(i, secs)| {
+                    (
+                        format!("ttl{}", i),
+                        NewValue {
+                            data: Bytes::from("value"),
+                            ttl: Some(Duration::from_secs(secs)),
+                        },
+                    )
+                }),
+        );
+
+        let res = Command::try_from(frame).unwrap().exec(store).unwrap();
+        assert_eq!(
+            res,
+            Frame::Bulk(Bytes::from(
+                "scheduled:5\noverdue:0\nnext_expiration_in_ms:2000\n\
+                 last_expiration_in_ms:200000000\nttl_lt_1s:0\nttl_lt_10s:1\nttl_lt_1m:2\n\
+                 ttl_lt_10m:0\nttl_lt_1h:0\nttl_lt_1d:1\nttl_ge_1d:1\n"
+            ))
+        );
+    }
+}
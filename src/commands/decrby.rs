@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{next_number, CommandParser};
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Decrements the number stored at key by `decrement`.
 ///
@@ -14,22 +14,27 @@ pub struct DecrBy {
 }
 
 impl Executable for DecrBy {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let res = store.incr_by(&self.key, -self.decrement);
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        // The decrement is negated into an increment, which the smallest integer has none of.
+        let Some(increment) = self.decrement.checked_neg() else {
+            let err = RustdisError::command(ErrorKind::Err, "decrement would overflow");
+            return Ok(Frame::Error(err.to_string()));
+        };
+        let res = store.incr_by(&self.key, increment);
 
         match res {
-            Ok(_) => Ok(Frame::Simple("OK".to_string())),
-            Err(msg) => Ok(Frame::Error(msg.to_string())),
+            Ok(value) => Ok(Frame::Integer(value)),
+            Err(err) => Ok(Frame::Error(err.to_string())),
         }
     }
 }
 
 impl TryFrom<&mut CommandParser> for DecrBy {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
-        let decrement = parser.next_integer()?;
+        let decrement = next_number(parser)?;
 
         Ok(Self { key, decrement })
     }
@@ -65,7 +70,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(10));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("10")));
     }
 
@@ -90,7 +95,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(-10));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("-10")));
     }
 
@@ -119,7 +124,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("value")));
     }
@@ -151,11 +156,36 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
         assert_eq!(
             store.lock().get("key1"),
             Some(Bytes::from("999223372036854775808"))
         );
     }
+
+    #[tokio::test]
+    async fn invalid_decrement() {
+        let store = Store::new();
+
+        let parse = |decrement: &str| {
+            Command::try_from(Frame::Array(vec![
+                Frame::Bulk(Bytes::from("DECRBY")),
+                Frame::Bulk(Bytes::from("key1")),
+                Frame::Bulk(Bytes::from(decrement.to_string())),
+            ]))
+        };
+
+        assert_eq!(
+            parse("ten").unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+
+        let cmd = parse(&i64::MIN.to_string()).unwrap();
+        assert_eq!(
+            cmd.exec(store.clone()).unwrap(),
+            Frame::Error("ERR decrement would overflow".to_string())
+        );
+        assert_eq!(store.lock().get("key1"), None);
+    }
 }
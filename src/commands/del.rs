@@ -1,13 +1,16 @@
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Removes the specified `keys`.
 ///
 /// Returns the number of keys that were removed.
 ///
+/// **NOTE**: when `Config::tombstone_grace_period` is set, deleted keys are kept as tombstones
+/// visible through DEBUG TOMBSTONES until their grace period elapses.
+///
 /// Ref: <https://redis.io/docs/latest/commands/del/>
 #[derive(Debug, PartialEq)]
 pub struct Del {
@@ -15,20 +18,26 @@ pub struct Del {
 }
 
 impl Executable for Del {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let mut count = 0;
-        let mut store = store.lock();
+        let mut state = store.lock();
         for key in self.keys {
-            if store.remove(&key).is_some() {
+            if state.delete(&key) {
                 count += 1;
             }
         }
+        drop(state);
+
+        if count > 0 && store.config().tombstone_grace_period().is_some() {
+            store.notify_tombstones_added();
+        }
+
         Ok(Frame::Integer(count))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Del {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let mut keys = vec![];
@@ -53,6 +62,7 @@ mod tests {
 
     use super::*;
     use crate::commands::Command;
+    use crate::config::Config;
 
     #[test]
     fn multiple_keys() {
@@ -90,9 +100,11 @@ mod tests {
     fn zero_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("DEL"))]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
 
-        assert_eq!(*err, CommandParserError::EndOfStream);
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'del' command"
+        );
     }
 
     #[test]
@@ -103,14 +115,68 @@ mod tests {
             Frame::Bulk(Bytes::from("foo")),
         ]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
-
+        assert!(matches!(err, RustdisError::Protocol(_)));
         assert_eq!(
-            *err,
-            CommandParserError::InvalidFrame {
-                expected: "simple or bulk string".to_string(),
-                actual: Frame::Integer(42)
-            }
+            err.to_string(),
+            "ERR protocol error; invalid frame, expected simple or bulk string, got :42"
         );
     }
+
+    #[tokio::test]
+    async fn exec() {
+        let store = Store::new();
+        store.lock().set(String::from("foo"), Bytes::from("1"));
+        store.lock().set(String::from("bar"), Bytes::from("2"));
+
+        let cmd = Del {
+            keys: vec!["foo".to_string(), "bar".to_string(), "baz".to_string()],
+        };
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Integer(2));
+        assert_eq!(store.lock().size(), 0);
+        assert_eq!(store.lock().tombstones().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn hash_key() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                String::from("foo"),
+                [(String::from("field"), Bytes::from("1"))],
+            )
+            .unwrap();
+
+        let cmd = Del {
+            keys: vec!["foo".to_string()],
+        };
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Integer(1));
+        assert!(!store.lock().exists("foo"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn leaves_tombstones() {
+        let store = Store::with_config(Config {
+            tombstone_grace_period: 1000,
+            ..Config::default()
+        });
+        store.lock().set(String::from("foo"), Bytes::from("1"));
+
+        let cmd = Del {
+            keys: vec!["foo".to_string()],
+        };
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Integer(1));
+        assert!(!store.lock().exists("foo"));
+        assert_eq!(store.lock().tombstones().count(), 1);
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(1001)).await;
+
+        assert_eq!(store.lock().tombstones().count(), 0);
+    }
 }
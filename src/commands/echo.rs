@@ -0,0 +1,70 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns `message`.
+///
+/// Ref: <https://redis.io/docs/latest/commands/echo/>
+#[derive(Debug, PartialEq)]
+pub struct Echo {
+    pub message: Bytes,
+}
+
+impl Executable for Echo {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Bulk(self.message))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Echo {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let message = parser.next_bytes()?;
+        Ok(Self { message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn with_message() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("ECHO")),
+            Frame::Bulk(Bytes::from("Hello World!")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Echo(Echo {
+                message: Bytes::from("Hello World!")
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("Hello World!")));
+    }
+
+    #[tokio::test]
+    async fn without_message() {
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("ECHO"))]);
+
+        let err = Command::try_from(frame).err().unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'echo' command"
+        );
+    }
+}
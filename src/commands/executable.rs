@@ -1,7 +1,7 @@
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 pub trait Executable {
-    fn exec(self, store: Store) -> Result<Frame, Error>;
+    fn exec(self, store: Store) -> Result<Frame, RustdisError>;
 }
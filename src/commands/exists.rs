@@ -1,9 +1,9 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
 use crate::commands::CommandParserError;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Returns if `key` exists.
 ///
@@ -16,7 +16,7 @@ pub struct Exists {
 }
 
 impl Executable for Exists {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
         let count = self.keys.iter().filter(|key| store.exists(key)).count();
         Ok(Frame::Integer(count as i64))
@@ -24,7 +24,7 @@ impl Executable for Exists {
 }
 
 impl TryFrom<&mut CommandParser> for Exists {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let mut keys = vec![];
@@ -86,9 +86,11 @@ mod tests {
     fn zero_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("EXISTS"))]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
 
-        assert_eq!(*err, CommandParserError::EndOfStream);
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'exists' command"
+        );
     }
 
     #[test]
@@ -99,14 +101,10 @@ mod tests {
             Frame::Bulk(Bytes::from("foo")),
         ]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
-
+        assert!(matches!(err, RustdisError::Protocol(_)));
         assert_eq!(
-            *err,
-            CommandParserError::InvalidFrame {
-                expected: "simple or bulk string".to_string(),
-                actual: Frame::Integer(42)
-            }
+            err.to_string(),
+            "ERR protocol error; invalid frame, expected simple or bulk string, got :42"
         );
     }
 }
@@ -0,0 +1,211 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::Duration;
+
+use crate::error::{ErrorKind, RustdisError};
+
+/// Expiration option shared by the commands setting the time to live of a key, like SET and
+/// GETEX.
+///
+/// Ref: <https://redis.io/docs/latest/commands/set/#options>
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expiration {
+    /// `EX seconds`: seconds from now.
+    Ex(u64),
+    /// `PX milliseconds`: milliseconds from now.
+    Px(u64),
+    /// `EXAT timestamp`: Unix time in seconds.
+    ExAt(u64),
+    /// `PXAT timestamp`: Unix time in milliseconds.
+    PxAt(u64),
+    /// `KEEPTTL`: retain the time to live of the key.
+    KeepTtl,
+    /// `PERSIST`: remove the time to live of the key.
+    Persist,
+}
+
+/// What an expiration option does to a key at a given time, see [`Expiration::resolve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    /// The key expires after the given time to live.
+    ExpiresIn(Duration),
+    /// The expiration time has already elapsed, the key must be deleted.
+    Expired,
+    /// The key keeps its current time to live, if any.
+    Keep,
+    /// The key no longer expires.
+    Persist,
+}
+
+impl Expiration {
+    /// Whether `option`, in uppercase, is one of the expiration options taking a time argument.
+    pub fn takes_time(option: &str) -> bool {
+        matches!(option, "EX" | "PX" | "EXAT" | "PXAT")
+    }
+
+    /// Parses the `time` argument of the `option` expiration option, in uppercase, of `command`.
+    ///
+    /// Like Redis, `time` must be a positive integer, times in seconds must not overflow once
+    /// converted to milliseconds, and relative times must not overflow once added to the current
+    /// Unix time in milliseconds.
+    pub fn parse(option: &str, time: &str, command: &str) -> Result<Expiration, RustdisError> {
+        let time = time.parse::<i64>().map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+        })?;
+
+        let invalid = || {
+            RustdisError::command(
+                ErrorKind::Err,
+                format!("invalid expire time in '{}' command", command),
+            )
+        };
+        if time <= 0 {
+            return Err(invalid());
+        }
+        let time = time as u64;
+
+        let in_millis = match option {
+            "EX" | "EXAT" => time.checked_mul(1000),
+            _ => Some(time),
+        };
+        let max_millis = match option {
+            "EX" | "PX" => {
+                let unix_now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                i64::MAX as u64 - unix_now
+            }
+            _ => i64::MAX as u64,
+        };
+        if in_millis.is_none_or(|millis| millis > max_millis) {
+            return Err(invalid());
+        }
+
+        let expiration = match option {
+            "EX" => Expiration::Ex(time),
+            "PX" => Expiration::Px(time),
+            "EXAT" => Expiration::ExAt(time),
+            "PXAT" => Expiration::PxAt(time),
+            _ => unreachable!("{} does not take a time argument", option),
+        };
+
+        Ok(expiration)
+    }
+
+    /// Resolves the option for a key updated at `now`. Relative and absolute times become the
+    /// time to live left, which is never zero.
+    pub fn resolve(self, now: SystemTime) -> Resolution {
+        let unix_now = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let ttl = match self {
+            Expiration::Ex(secs) => Some(Duration::from_secs(secs)),
+            Expiration::Px(millis) => Some(Duration::from_millis(millis)),
+            Expiration::ExAt(secs) => Duration::from_secs(secs).checked_sub(unix_now),
+            Expiration::PxAt(millis) => Duration::from_millis(millis).checked_sub(unix_now),
+            Expiration::KeepTtl => return Resolution::Keep,
+            Expiration::Persist => return Resolution::Persist,
+        };
+
+        match ttl {
+            Some(ttl) if !ttl.is_zero() => Resolution::ExpiresIn(ttl),
+            _ => Resolution::Expired,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(option: &str, time: &str) -> String {
+        Expiration::parse(option, time, "set")
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Expiration::parse("EX", "10", "set").unwrap(),
+            Expiration::Ex(10)
+        );
+        assert_eq!(
+            Expiration::parse("PX", "10", "set").unwrap(),
+            Expiration::Px(10)
+        );
+        assert_eq!(
+            Expiration::parse("EXAT", "10", "set").unwrap(),
+            Expiration::ExAt(10)
+        );
+        assert_eq!(
+            Expiration::parse("PXAT", "10", "set").unwrap(),
+            Expiration::PxAt(10)
+        );
+
+        assert_eq!(
+            error("EX", "ten"),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(error("EX", "0"), "ERR invalid expire time in 'set' command");
+        assert_eq!(
+            error("PX", "-1"),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            error("EXAT", "9223372036854776"),
+            "ERR invalid expire time in 'set' command"
+        );
+        // Relative times overflow once added to the current time, absolute ones don't.
+        assert_eq!(
+            error("PX", "9223372036854775807"),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            error("EX", "9223372036854775"),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            Expiration::parse("PXAT", "9223372036854775807", "set").unwrap(),
+            Expiration::PxAt(i64::MAX as u64)
+        );
+        assert_eq!(
+            Expiration::parse("EX", "0", "getex")
+                .unwrap_err()
+                .to_string(),
+            "ERR invalid expire time in 'getex' command"
+        );
+    }
+
+    #[test]
+    fn resolve() {
+        let now = UNIX_EPOCH + Duration::from_millis(1_000_500);
+
+        let resolve = |expiration: Expiration| expiration.resolve(now);
+
+        assert_eq!(
+            resolve(Expiration::Ex(10)),
+            Resolution::ExpiresIn(Duration::from_secs(10))
+        );
+        assert_eq!(
+            resolve(Expiration::Px(10)),
+            Resolution::ExpiresIn(Duration::from_millis(10))
+        );
+        assert_eq!(
+            resolve(Expiration::ExAt(1001)),
+            Resolution::ExpiresIn(Duration::from_millis(500))
+        );
+        assert_eq!(
+            resolve(Expiration::PxAt(1_000_501)),
+            Resolution::ExpiresIn(Duration::from_millis(1))
+        );
+        assert_eq!(resolve(Expiration::PxAt(1_000_500)), Resolution::Expired);
+        assert_eq!(resolve(Expiration::ExAt(1000)), Resolution::Expired);
+        assert_eq!(resolve(Expiration::ExAt(1)), Resolution::Expired);
+        assert_eq!(
+            resolve(Expiration::PxAt(i64::MAX as u64)),
+            Resolution::ExpiresIn(Duration::from_millis(i64::MAX as u64 - 1_000_500))
+        );
+        assert_eq!(resolve(Expiration::KeepTtl), Resolution::Keep);
+        assert_eq!(resolve(Expiration::Persist), Resolution::Persist);
+    }
+}
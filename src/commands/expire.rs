@@ -0,0 +1,480 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::Duration;
+
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Set a timeout on `key`, after which the key is deleted. A timeout in the past deletes the key
+/// right away.
+///
+/// EXPIRE, PEXPIRE, EXPIREAT and PEXPIREAT only differ in how the time is expressed: in seconds
+/// or milliseconds (`MILLIS`), relative to now or as a Unix time (`ABSOLUTE`). They share this
+/// implementation, so every option behaves the same in all of them.
+///
+/// Options:
+///
+/// - `NX`: only set the timeout if the key has none.
+/// - `XX`: only set the timeout if the key already has one.
+/// - `GT`: only set the timeout if it is later than the current one. A key without timeout never
+///   expires, so no timeout is later.
+/// - `LT`: only set the timeout if it is earlier than the current one. A key without timeout never
+///   expires, so any timeout is earlier.
+///
+/// Replies with 1 when the timeout was set, and 0 when the key doesn't exist or the options
+/// prevented it.
+///
+/// Ref: <https://redis.io/docs/latest/commands/expire/>
+#[derive(Debug, PartialEq)]
+pub struct ExpireCommand<const MILLIS: bool, const ABSOLUTE: bool> {
+    pub key: String,
+    pub time: i64,
+    pub conditions: Conditions,
+}
+
+/// Ref: <https://redis.io/docs/latest/commands/expire/>
+pub type Expire = ExpireCommand<false, false>;
+
+/// Ref: <https://redis.io/docs/latest/commands/pexpire/>
+pub type Pexpire = ExpireCommand<true, false>;
+
+/// Ref: <https://redis.io/docs/latest/commands/expireat/>
+pub type Expireat = ExpireCommand<false, true>;
+
+/// Ref: <https://redis.io/docs/latest/commands/pexpireat/>
+pub type Pexpireat = ExpireCommand<true, true>;
+
+/// The `NX`, `XX`, `GT` and `LT` options. Like Redis, `XX` can be combined with `GT` or `LT`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Conditions {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+}
+
+impl Conditions {
+    /// Whether a key whose timeout is `current`, in milliseconds from now, can be given a timeout
+    /// of `ttl` milliseconds from now.
+    fn allow(&self, current: Option<i64>, ttl: i64) -> bool {
+        if self.nx && current.is_some() {
+            return false;
+        }
+        if self.xx && current.is_none() {
+            return false;
+        }
+        if self.gt && current.is_none_or(|current| ttl <= current) {
+            return false;
+        }
+        if self.lt && current.is_some_and(|current| ttl >= current) {
+            return false;
+        }
+        true
+    }
+}
+
+impl<const MILLIS: bool, const ABSOLUTE: bool> ExpireCommand<MILLIS, ABSOLUTE> {
+    fn name() -> &'static str {
+        match (MILLIS, ABSOLUTE) {
+            (false, false) => "expire",
+            (true, false) => "pexpire",
+            (false, true) => "expireat",
+            (true, true) => "pexpireat",
+        }
+    }
+
+    /// The Unix time in milliseconds the key expires at, or `None` when it overflows.
+    fn unix_time_millis(&self, unix_now: i64) -> Option<i64> {
+        let time = if MILLIS {
+            self.time
+        } else {
+            self.time.checked_mul(1000)?
+        };
+
+        if ABSOLUTE {
+            Some(time)
+        } else {
+            time.checked_add(unix_now)
+        }
+    }
+}
+
+impl<const MILLIS: bool, const ABSOLUTE: bool> Executable for ExpireCommand<MILLIS, ABSOLUTE> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let unix_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+
+        let Some(unix_time) = self.unix_time_millis(unix_now) else {
+            return Ok(Frame::Error(format!(
+                "ERR invalid expire time in '{}' command",
+                Self::name()
+            )));
+        };
+
+        let mut state = store.lock();
+
+        let Some(value) = state.get_value(&self.key) else {
+            return Ok(Frame::Integer(0));
+        };
+
+        let ttl = unix_time.saturating_sub(unix_now);
+        let current = value.expires_at.map(|expires_at| {
+            expires_at
+                .saturating_duration_since(state.now())
+                .as_millis() as i64
+        });
+
+        if !self.conditions.allow(current, ttl) {
+            return Ok(Frame::Integer(0));
+        }
+
+        if ttl > 0 {
+            state.set_ttl(&self.key, Some(Duration::from_millis(ttl as u64)));
+            store.notify_if_expires_next(&mut state, &self.key);
+        } else if state.remove(&self.key).is_some() {
+            store.stats.incr_expired_keys(1);
+        }
+
+        Ok(Frame::Integer(1))
+    }
+}
+
+impl<const MILLIS: bool, const ABSOLUTE: bool> TryFrom<&mut CommandParser>
+    for ExpireCommand<MILLIS, ABSOLUTE>
+{
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let time = parser.next_string()?;
+
+        let mut conditions = Conditions::default();
+        loop {
+            let option = match parser.next_string() {
+                Ok(option) => option,
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.to_uppercase().as_str() {
+                "NX" => conditions.nx = true,
+                "XX" => conditions.xx = true,
+                "GT" => conditions.gt = true,
+                "LT" => conditions.lt = true,
+                _ => {
+                    return Err(RustdisError::command(
+                        ErrorKind::Err,
+                        format!("Unsupported option {}", option),
+                    ))
+                }
+            }
+        }
+
+        // Like Redis, the options are validated before the time.
+        if conditions.nx && (conditions.xx || conditions.gt || conditions.lt) {
+            return Err(RustdisError::command(
+                ErrorKind::Err,
+                "NX and XX, GT or LT options at the same time are not compatible",
+            ));
+        }
+        if conditions.gt && conditions.lt {
+            return Err(RustdisError::command(
+                ErrorKind::Err,
+                "GT and LT options at the same time are not compatible",
+            ));
+        }
+
+        let time = time.parse().map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+        })?;
+
+        Ok(Self {
+            key,
+            time,
+            conditions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+    use crate::store::NewValue;
+    use tokio::time::Instant;
+
+    fn parse(args: &[String]) -> Result<Command, RustdisError> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.clone())))
+                .collect(),
+        );
+        Command::try_from(frame)
+    }
+
+    fn unix_now_millis() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64
+    }
+
+    /// The time argument of `command` for a timeout `secs` seconds from now.
+    fn time_arg(command: &str, secs: i64) -> String {
+        match command {
+            "EXPIRE" => secs.to_string(),
+            "PEXPIRE" => (secs * 1000).to_string(),
+            "EXPIREAT" => (unix_now_millis() / 1000 + secs).to_string(),
+            "PEXPIREAT" => (unix_now_millis() + secs * 1000).to_string(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Runs `command` with a timeout `secs` seconds from now and `options` against a key holding
+    /// a timeout of `current` seconds, if any, returning the reply and the timeout left in
+    /// seconds, rounded to ten seconds as the Unix time of EXPIREAT is only precise to the second.
+    fn run(
+        command: &str,
+        secs: i64,
+        options: &[&str],
+        current: Option<u64>,
+    ) -> (Frame, Option<Option<u64>>) {
+        let store = Store::new();
+        store.set2(
+            String::from("key"),
+            NewValue {
+                data: Bytes::from("value"),
+                ttl: current.map(Duration::from_secs),
+            },
+        );
+
+        let mut args = vec![
+            command.to_string(),
+            "key".to_string(),
+            time_arg(command, secs),
+        ];
+        args.extend(options.iter().map(|option| option.to_string()));
+
+        let res = parse(&args).unwrap().exec(store.clone()).unwrap();
+
+        let ttl = store.lock().get_value("key").map(|value| {
+            value.expires_at.map(|expires_at| {
+                let left = expires_at.saturating_duration_since(Instant::now());
+                (left.as_secs_f64() / 10.0).round() as u64 * 10
+            })
+        });
+
+        (res, ttl)
+    }
+
+    const COMMANDS: [&str; 4] = ["EXPIRE", "PEXPIRE", "EXPIREAT", "PEXPIREAT"];
+
+    #[tokio::test]
+    async fn parse_variants() {
+        for command in COMMANDS {
+            let cmd = parse(&[
+                command.to_string(),
+                "key".to_string(),
+                "10".to_string(),
+                "xx".to_string(),
+                "GT".to_string(),
+            ])
+            .unwrap();
+
+            let key = String::from("key");
+            let conditions = Conditions {
+                xx: true,
+                gt: true,
+                ..Conditions::default()
+            };
+            let expected = match command {
+                "EXPIRE" => Command::Expire(ExpireCommand {
+                    key,
+                    time: 10,
+                    conditions,
+                }),
+                "PEXPIRE" => Command::Pexpire(ExpireCommand {
+                    key,
+                    time: 10,
+                    conditions,
+                }),
+                "EXPIREAT" => Command::Expireat(ExpireCommand {
+                    key,
+                    time: 10,
+                    conditions,
+                }),
+                _ => Command::Pexpireat(ExpireCommand {
+                    key,
+                    time: 10,
+                    conditions,
+                }),
+            };
+            assert_eq!(cmd, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn without_options() {
+        for command in COMMANDS {
+            assert_eq!(
+                run(command, 100, &[], None),
+                (Frame::Integer(1), Some(Some(100))),
+                "{}",
+                command
+            );
+            assert_eq!(
+                run(command, 100, &[], Some(10)),
+                (Frame::Integer(1), Some(Some(100))),
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn flag_matrix() {
+        let set = |ttl| (Frame::Integer(1), Some(Some(ttl)));
+        let kept = |ttl| (Frame::Integer(0), Some(ttl));
+
+        for command in COMMANDS {
+            let cases = [
+                (&["NX"][..], None, set(100)),
+                (&["NX"][..], Some(50), kept(Some(50))),
+                (&["XX"][..], None, kept(None)),
+                (&["XX"][..], Some(50), set(100)),
+                (&["GT"][..], None, kept(None)),
+                (&["GT"][..], Some(50), set(100)),
+                (&["GT"][..], Some(200), kept(Some(200))),
+                (&["LT"][..], None, set(100)),
+                (&["LT"][..], Some(50), kept(Some(50))),
+                (&["LT"][..], Some(200), set(100)),
+                (&["XX", "GT"][..], None, kept(None)),
+                (&["XX", "GT"][..], Some(50), set(100)),
+                (&["XX", "LT"][..], None, kept(None)),
+                (&["XX", "LT"][..], Some(200), set(100)),
+            ];
+
+            for (options, current, expected) in cases {
+                assert_eq!(
+                    run(command, 100, options, current),
+                    expected,
+                    "{} {:?} with ttl {:?}",
+                    command,
+                    options,
+                    current
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn time_in_the_past() {
+        for command in COMMANDS {
+            let store = Store::new();
+            store.lock().set(String::from("key"), Bytes::from("value"));
+
+            let args = [
+                command.to_string(),
+                "key".to_string(),
+                time_arg(command, -1),
+            ];
+            let res = parse(&args).unwrap().exec(store.clone()).unwrap();
+
+            assert_eq!(res, Frame::Integer(1));
+            assert!(!store.lock().exists("key"));
+            assert_eq!(store.stats.expired_keys(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn hash_key() {
+        for command in COMMANDS {
+            let store = Store::new();
+            store
+                .lock()
+                .hset(
+                    String::from("key"),
+                    [(String::from("field"), Bytes::from("1"))],
+                )
+                .unwrap();
+
+            let args = [
+                command.to_string(),
+                "key".to_string(),
+                time_arg(command, 100),
+            ];
+            let res = parse(&args).unwrap().exec(store.clone()).unwrap();
+
+            assert_eq!(res, Frame::Integer(1));
+            let state = store.lock();
+            let value = state.get_value("key").unwrap();
+            assert!(value.expires_at.is_some());
+            assert_eq!(value.type_name(), "hash");
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_key() {
+        for command in COMMANDS {
+            let args = [command.to_string(), "key".to_string(), "10".to_string()];
+            let res = parse(&args).unwrap().exec(Store::new()).unwrap();
+
+            assert_eq!(res, Frame::Integer(0));
+        }
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let error = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            parse(&args).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            error(&["EXPIRE", "key", "10", "NX", "XX"]),
+            "ERR NX and XX, GT or LT options at the same time are not compatible"
+        );
+        assert_eq!(
+            error(&["PEXPIREAT", "key", "10", "GT", "LT"]),
+            "ERR GT and LT options at the same time are not compatible"
+        );
+        assert_eq!(
+            error(&["EXPIREAT", "key", "10", "ZZ"]),
+            "ERR Unsupported option ZZ"
+        );
+        assert_eq!(
+            error(&["PEXPIRE", "key", "soon", "ZZ"]),
+            "ERR Unsupported option ZZ"
+        );
+        assert_eq!(
+            error(&["PEXPIRE", "key", "soon"]),
+            "ERR value is not an integer or out of range"
+        );
+
+        let store = Store::new();
+        store.lock().set(String::from("key"), Bytes::from("value"));
+        for (command, time) in [
+            ("EXPIRE", i64::MAX / 1000 + 1),
+            ("PEXPIRE", i64::MAX),
+            ("EXPIREAT", i64::MAX / 1000 + 1),
+        ] {
+            let args = [command.to_string(), "key".to_string(), time.to_string()];
+            let res = parse(&args).unwrap().exec(store.clone()).unwrap();
+
+            assert_eq!(
+                res,
+                Frame::Error(format!(
+                    "ERR invalid expire time in '{}' command",
+                    command.to_lowercase()
+                ))
+            );
+        }
+        assert!(store.lock().exists("key"));
+    }
+}
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
 /// Get the value of `key`. If the key does not exist the special value `nil` is returned.
 ///
@@ -13,8 +13,11 @@ pub struct Get {
 }
 
 impl Executable for Get {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let value = store.get(&self.key);
 
         match value {
@@ -25,7 +28,7 @@ impl Executable for Get {
 }
 
 impl TryFrom<&mut CommandParser> for Get {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -85,4 +88,28 @@ mod tests {
 
         assert_eq!(res, Frame::NullBulkString);
     }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                String::from("key1"),
+                [(String::from("field"), Bytes::from("1"))],
+            )
+            .unwrap();
+
+        let cmd = Get {
+            key: String::from("key1"),
+        };
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
 }
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
 /// Get the value of key and delete the key. This command is similar to GET, except for the fact
 /// that it also deletes the key on success (if and only if the key's value type is a string).
@@ -14,12 +14,15 @@ pub struct Getdel {
 }
 
 impl Executable for Getdel {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let mut store = store.lock();
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let removed_key = store.remove(&self.key);
-        let res = match removed_key {
-            Some(val) => Frame::Bulk(val.data),
-            None => Frame::Null,
+        let res = match removed_key.and_then(|val| val.string()) {
+            Some(val) => Frame::Bulk(val),
+            None => Frame::NullBulkString,
         };
 
         Ok(res)
@@ -27,7 +30,7 @@ impl Executable for Getdel {
 }
 
 impl TryFrom<&mut CommandParser> for Getdel {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -82,6 +85,6 @@ mod tests {
         );
 
         let res = cmd.exec(store.clone()).unwrap();
-        assert_eq!(res, Frame::Null);
+        assert_eq!(res, Frame::NullBulkString);
     }
 }
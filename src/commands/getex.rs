@@ -0,0 +1,230 @@
+use std::time::SystemTime;
+
+use crate::commands::executable::Executable;
+use crate::commands::expiration::{Expiration, Resolution};
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::{Store, STRING};
+
+/// Get the value of key and optionally set its expiration. GETEX is similar to GET, but is a write
+/// command with additional options.
+///
+/// Options:
+///
+/// - `EX seconds`, `PX milliseconds`: set the time to live of the key.
+/// - `EXAT timestamp`, `PXAT timestamp`: set the Unix time, in seconds or milliseconds, at which
+///   the key expires.
+/// - `PERSIST`: remove the time to live of the key.
+///
+/// Ref: <https://redis.io/docs/latest/commands/getex/>
+#[derive(Debug, PartialEq)]
+pub struct Getex {
+    pub key: String,
+    pub expiration: Option<Expiration>,
+}
+
+impl Executable for Getex {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+
+        if let Err(err) = state.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
+        let Some(value) = state.get(&self.key) else {
+            return Ok(Frame::NullBulkString);
+        };
+
+        match self.expiration.map(|e| e.resolve(SystemTime::now())) {
+            None | Some(Resolution::Keep) => {}
+            Some(Resolution::Persist) => {
+                state.set_ttl(&self.key, None);
+            }
+            Some(Resolution::ExpiresIn(ttl)) => {
+                state.set_ttl(&self.key, Some(ttl));
+                store.notify_if_expires_next(&mut state, &self.key);
+            }
+            // The value is still returned, but the key is gone.
+            Some(Resolution::Expired) => {
+                if state.remove(&self.key).is_some() {
+                    store.stats.incr_expired_keys(1);
+                }
+            }
+        }
+
+        Ok(Frame::Bulk(value))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Getex {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+
+        let mut expiration: Option<(String, Option<String>)> = None;
+
+        loop {
+            let option = match parser.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            // Repeating an option is allowed, combining different ones is not.
+            if expiration
+                .as_ref()
+                .is_some_and(|(other, _)| *other != option)
+            {
+                return Err(syntax_error());
+            }
+
+            match option.as_str() {
+                "PERSIST" => expiration = Some((option, None)),
+                _ if Expiration::takes_time(&option) => {
+                    let time = match parser.next_string() {
+                        Ok(time) => time,
+                        Err(CommandParserError::EndOfStream) => return Err(syntax_error()),
+                        Err(err) => return Err(err.into()),
+                    };
+                    expiration = Some((option, Some(time)));
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        // Like Redis, the expire time is only validated once the options are known to be valid.
+        let expiration = match expiration {
+            Some((option, Some(time))) => Some(Expiration::parse(&option, &time, "getex")?),
+            Some((_, None)) => Some(Expiration::Persist),
+            None => None,
+        };
+
+        Ok(Self { key, expiration })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use tokio::time::Duration;
+
+    use super::*;
+    use crate::commands::Command;
+    use crate::store::NewValue;
+
+    fn getex_frame(args: &[&str]) -> Frame {
+        let args = ["GETEX", "key"].iter().chain(args);
+        Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        )
+    }
+
+    fn store_with_ttl() -> Store {
+        let store = Store::new();
+        let value = NewValue {
+            data: Bytes::from("value"),
+            ttl: Some(Duration::from_secs(1000)),
+        };
+        store.set2(String::from("key"), value);
+        store
+    }
+
+    fn expires_at(store: &Store) -> Option<tokio::time::Instant> {
+        store.lock().get_value("key").and_then(|v| v.expires_at)
+    }
+
+    #[tokio::test]
+    async fn without_options() {
+        let store = store_with_ttl();
+        let before = expires_at(&store);
+
+        let cmd = Command::try_from(getex_frame(&[])).unwrap();
+        assert_eq!(
+            cmd,
+            Command::Getex(Getex {
+                key: String::from("key"),
+                expiration: None
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+        assert_eq!(res, Frame::Bulk(Bytes::from("value")));
+        assert_eq!(expires_at(&store), before);
+    }
+
+    #[tokio::test]
+    async fn missing_key() {
+        let store = Store::new();
+
+        let cmd = Command::try_from(getex_frame(&["EX", "10"])).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::NullBulkString);
+        assert!(!store.lock().exists("key"));
+    }
+
+    #[tokio::test]
+    async fn set_ttl() {
+        let store = store_with_ttl();
+        let before = expires_at(&store);
+
+        let cmd = Command::try_from(getex_frame(&["PX", "10"])).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("value")));
+        assert!(expires_at(&store).is_some_and(|at| Some(at) < before));
+    }
+
+    #[tokio::test]
+    async fn persist() {
+        let store = store_with_ttl();
+
+        let cmd = Command::try_from(getex_frame(&["PERSIST"])).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("value")));
+        assert_eq!(expires_at(&store), None);
+    }
+
+    #[tokio::test]
+    async fn expire_time_in_the_past() {
+        let store = store_with_ttl();
+
+        let cmd = Command::try_from(getex_frame(&["EXAT", "1"])).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("value")));
+        assert!(!store.lock().exists("key"));
+    }
+
+    #[tokio::test]
+    async fn invalid_options() {
+        let error = |args: &[&str]| {
+            Command::try_from(getex_frame(args))
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(error(&["KEEPTTL"]), "ERR syntax error");
+        assert_eq!(error(&["EX", "10", "PERSIST"]), "ERR syntax error");
+        assert_eq!(error(&["EX", "10", "PX", "10"]), "ERR syntax error");
+        assert_eq!(error(&["PX"]), "ERR syntax error");
+        assert_eq!(
+            error(&["EX", "0"]),
+            "ERR invalid expire time in 'getex' command"
+        );
+        assert_eq!(
+            error(&["PX", "9223372036854775807"]),
+            "ERR invalid expire time in 'getex' command"
+        );
+        assert_eq!(
+            Command::try_from(getex_frame(&["ex", "1", "EX", "10"])).unwrap(),
+            Command::Getex(Getex {
+                key: String::from("key"),
+                expiration: Some(Expiration::Ex(10))
+            })
+        );
+    }
+}
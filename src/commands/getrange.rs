@@ -2,9 +2,9 @@ use bytes::Bytes;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
 /// Returns the substring of the string value stored at key, determined by the offsets start and
 /// end (both are inclusive). Negative offsets can be used in order to provide an offset starting
@@ -21,29 +21,31 @@ pub struct Getrange {
 }
 
 impl Executable for Getrange {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let value = store.get(&self.key);
         let bytes = match value {
             Some(val) => val,
             None => return Ok(Frame::Bulk(Bytes::new())),
         };
 
-        let value = String::from_utf8(bytes.to_vec()).unwrap();
-        // TODO: Should we worry about this conversion?
-        let len = value.len() as i64;
+        let len = bytes.len() as i64;
 
-        let start = get_positive_index(len, self.start);
-        let end = get_positive_index(len, self.end);
+        let start = get_positive_index(len, self.start).max(0);
+        let end = get_positive_index(len, self.end).min(len - 1);
 
-        let subset: String = value
-            .chars()
-            // We don't care about out of range indexes, take and skip will handle it.
-            .take((end + 1) as usize)
-            .skip(start as usize)
-            .collect();
+        if start > end {
+            return Ok(Frame::Bulk(Bytes::new()));
+        }
 
-        Ok(Frame::Bulk(Bytes::from(subset)))
+        // Slicing shares the stored buffer instead of copying it, which matters for large values
+        // built with SETRANGE when only a few bytes are requested.
+        let subset = bytes.slice(start as usize..=end as usize);
+
+        Ok(Frame::Bulk(subset))
     }
 }
 
@@ -52,17 +54,24 @@ fn get_positive_index(str_len: i64, index: i64) -> i64 {
     if is_positive {
         index
     } else {
-        str_len - index.abs()
+        // Adding the negative index can't overflow, unlike subtracting its absolute value, which
+        // doesn't fit in an i64 for `i64::MIN`.
+        str_len + index
     }
 }
 
 impl TryFrom<&mut CommandParser> for Getrange {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
-        let start = parser.next_integer()?;
-        let end = parser.next_integer()?;
+        let mut next_integer = || {
+            parser.next_integer().map_err(|_| {
+                RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+            })
+        };
+        let start = next_integer()?;
+        let end = next_integer()?;
 
         Ok(Self { key, start, end })
     }
@@ -186,4 +195,93 @@ mod tests {
         let res = cmd.exec(store.clone()).unwrap();
         assert_eq!(res, Frame::Bulk(Bytes::from("string")));
     }
+
+    /// No combination of indexes panics, and the reply is always a substring of the value.
+    #[tokio::test]
+    async fn fuzz_indexes() {
+        let indexes = [
+            i64::MIN,
+            i64::MIN + 1,
+            -17,
+            -16,
+            -1,
+            0,
+            1,
+            15,
+            16,
+            i64::MAX - 1,
+            i64::MAX,
+        ];
+        let value = "This is a string";
+
+        for stored in ["", value] {
+            let store = Store::default();
+            store
+                .lock()
+                .set("mykey".to_string(), Bytes::from(stored.to_string()));
+
+            for start in indexes {
+                for end in indexes {
+                    let frame = Frame::Array(vec![
+                        Frame::Bulk(Bytes::from("GETRANGE")),
+                        Frame::Bulk(Bytes::from("mykey")),
+                        Frame::Bulk(Bytes::from(start.to_string())),
+                        Frame::Bulk(Bytes::from(end.to_string())),
+                    ]);
+                    let res = Command::try_from(frame)
+                        .unwrap()
+                        .exec(store.clone())
+                        .unwrap();
+
+                    let Frame::Bulk(substring) = res else {
+                        panic!("unexpected reply {:?}", res);
+                    };
+                    let substring = std::str::from_utf8(&substring).unwrap();
+                    assert!(stored.contains(substring));
+                }
+            }
+        }
+    }
+
+    /// Arguments that aren't integers are replied to with an error, which keeps the connection
+    /// open, instead of failing to parse the frame, which closes it.
+    #[tokio::test]
+    async fn fuzz_invalid_indexes() {
+        let valid = ["0", "-1", "9223372036854775807", "-9223372036854775808"];
+        let invalid = [
+            "",
+            "a",
+            "1.5",
+            " 1",
+            "0x10",
+            "9223372036854775808",
+            "-9223372036854775809",
+        ];
+
+        for bad in invalid {
+            for good in valid {
+                for (start, end) in [(bad, good), (good, bad), (bad, bad)] {
+                    let frame = Frame::Array(vec![
+                        Frame::Bulk(Bytes::from("GETRANGE")),
+                        Frame::Bulk(Bytes::from("mykey")),
+                        Frame::Bulk(Bytes::from(start.to_string())),
+                        Frame::Bulk(Bytes::from(end.to_string())),
+                    ]);
+                    let err = Command::try_from(frame).unwrap_err();
+
+                    assert!(
+                        matches!(err, RustdisError::Command { .. }),
+                        "GETRANGE mykey {:?} {:?}: {:?}",
+                        start,
+                        end,
+                        err
+                    );
+                    assert_eq!(
+                        Frame::Error(err.to_string()),
+                        Frame::Error("ERR value is not an integer or out of range".to_string())
+                    );
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Removes the specified fields from the hash stored at `key`. Fields that don't exist are
+/// ignored, and the key is removed once the hash has no field left.
+///
+/// ```text
+/// HDEL key field [field ...]
+/// ```
+///
+/// Replies with the number of fields that were removed, 0 when the key doesn't exist.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hdel/>
+#[derive(Debug, PartialEq)]
+pub struct Hdel {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+impl Executable for Hdel {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        let res = match state.hdel(&self.key, &self.fields) {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hdel {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+
+        let mut fields = vec![];
+        loop {
+            match parser.next_string() {
+                Ok(field) => fields.push(field),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { key, fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hdel(store: &Store, key: &str, fields: &[&str]) -> Frame {
+        let args = ["HDEL", key].into_iter().chain(fields.iter().copied());
+        let frame = Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn removes_fields_then_key() {
+        let store = Store::new();
+        let fields = ["name", "age"].map(|field| (field.to_string(), Bytes::from("value")));
+        store.lock().hset("user".to_string(), fields).unwrap();
+
+        assert_eq!(hdel(&store, "user", &["name", "city"]), Frame::Integer(1));
+        assert!(store.lock().exists("user"));
+
+        assert_eq!(hdel(&store, "user", &["age", "age"]), Frame::Integer(1));
+        assert!(!store.lock().exists("user"));
+
+        assert_eq!(hdel(&store, "user", &["age"]), Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn version_changes_only_when_removing() {
+        let store = Store::new();
+        let fields = ["name", "age"].map(|field| (field.to_string(), Bytes::from("value")));
+        store.lock().hset("user".to_string(), fields).unwrap();
+        let version = store.lock().version("user");
+
+        hdel(&store, "user", &["city"]);
+        assert_eq!(store.lock().version("user"), version);
+
+        hdel(&store, "user", &["name"]);
+        assert_ne!(store.lock().version("user"), version);
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hdel(&store, "key", &["field"]),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::{Frame, Protocol};
+use crate::store::Store;
+
+/// Switches the connection to the protocol version `protover`, and replies with a map describing
+/// the server, in the new version. Without `protover`, the version is left as it is.
+///
+/// ```text
+/// HELLO [protover [AUTH username password] [SETNAME clientname]]
+/// ```
+///
+/// Connections start with RESP2, clients send `HELLO 3` to get the RESP3 reply types, like maps
+/// and a single null type. The version is connection state, the server applies it when sending
+/// replies, see `ReplyWriter`.
+///
+/// **NOTE**: there are no users nor passwords, so `AUTH` only accepts the `default` user, with any
+/// password, like Redis does when no password is set. `SETNAME` is accepted and ignored, like
+/// CLIENT SETNAME. Clients have no numeric ids, the `id` in the reply is always 0.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hello/>
+#[derive(Debug, PartialEq)]
+pub struct Hello {
+    pub protocol: Option<Protocol>,
+    pub client_name: Option<String>,
+}
+
+impl Hello {
+    /// The reply once the connection speaks `protocol`.
+    pub fn reply(&self, protocol: Protocol) -> Frame {
+        let proto = match protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+        let field = |name: &'static str, value: Frame| (Frame::Bulk(Bytes::from(name)), value);
+
+        Frame::Map(vec![
+            field("server", Frame::Bulk(Bytes::from("redis"))),
+            // The version INFO reports, which clients check before using newer commands.
+            field("version", Frame::Bulk(Bytes::from("7.2.4"))),
+            field("proto", Frame::Integer(proto)),
+            field("id", Frame::Integer(0)),
+            field("mode", Frame::Bulk(Bytes::from("standalone"))),
+            field("role", Frame::Bulk(Bytes::from("master"))),
+            field("modules", Frame::Array(vec![])),
+        ])
+    }
+}
+
+impl Executable for Hello {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        // Without a connection, there is no protocol to switch.
+        Ok(self.reply(self.protocol.unwrap_or_default()))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hello {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let protocol = match parser.next_integer() {
+            Ok(2) => Protocol::Resp2,
+            Ok(3) => Protocol::Resp3,
+            Ok(_) => {
+                return Err(RustdisError::command(
+                    ErrorKind::NoProto,
+                    "unsupported protocol version",
+                ))
+            }
+            Err(CommandParserError::EndOfStream) => {
+                return Ok(Self {
+                    protocol: None,
+                    client_name: None,
+                })
+            }
+            Err(_) => {
+                return Err(RustdisError::command(
+                    ErrorKind::Err,
+                    "Protocol version is not an integer or out of range",
+                ))
+            }
+        };
+
+        let mut client_name = None;
+        loop {
+            let option = match parser.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "AUTH" => {
+                    let (Ok(username), Ok(_password)) = (parser.next_string(), parser.next_bytes())
+                    else {
+                        return Err(syntax_error());
+                    };
+                    if username != "default" {
+                        return Err(RustdisError::command(
+                            ErrorKind::WrongPass,
+                            "invalid username-password pair or user is disabled.",
+                        ));
+                    }
+                }
+                "SETNAME" => client_name = Some(parser.next_string().map_err(|_| syntax_error())?),
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        Ok(Self {
+            protocol: Some(protocol),
+            client_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        Command::try_from(frame).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn protocol_versions() {
+        assert_eq!(
+            parse(&["HELLO"]),
+            Ok(Command::Hello(Hello {
+                protocol: None,
+                client_name: None,
+            }))
+        );
+        assert_eq!(
+            parse(&["HELLO", "3", "auth", "default", "secret", "SETNAME", "app"]),
+            Ok(Command::Hello(Hello {
+                protocol: Some(Protocol::Resp3),
+                client_name: Some("app".to_string()),
+            }))
+        );
+        assert_eq!(
+            parse(&["HELLO", "2"]),
+            Ok(Command::Hello(Hello {
+                protocol: Some(Protocol::Resp2),
+                client_name: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert_eq!(
+            parse(&["HELLO", "4"]),
+            Err("NOPROTO unsupported protocol version".to_string())
+        );
+        assert_eq!(
+            parse(&["HELLO", "three"]),
+            Err("ERR Protocol version is not an integer or out of range".to_string())
+        );
+        assert_eq!(
+            parse(&["HELLO", "3", "AUTH", "admin", "secret"]),
+            Err("WRONGPASS invalid username-password pair or user is disabled.".to_string())
+        );
+        assert_eq!(
+            parse(&["HELLO", "3", "AUTH", "default"]),
+            Err("ERR syntax error".to_string())
+        );
+        assert_eq!(
+            parse(&["HELLO", "3", "VERBOSE"]),
+            Err("ERR syntax error".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn reply() {
+        let store = Store::new();
+
+        let res = parse(&["HELLO", "3"]).unwrap().exec(store).unwrap();
+
+        let Frame::Map(fields) = res else {
+            panic!("expected a map, got {:?}", res);
+        };
+        let names: Vec<Frame> = fields.iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(
+            names,
+            ["server", "version", "proto", "id", "mode", "role", "modules"]
+                .map(|name| Frame::Bulk(Bytes::from(name)))
+        );
+        assert_eq!(
+            fields[2],
+            (Frame::Bulk(Bytes::from("proto")), Frame::Integer(3))
+        );
+    }
+}
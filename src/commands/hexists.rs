@@ -0,0 +1,88 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns whether `field` exists in the hash stored at `key`, 1 when it does and 0 when the
+/// field or the key doesn't exist.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hexists/>
+#[derive(Debug, PartialEq)]
+pub struct Hexists {
+    pub key: String,
+    pub field: String,
+}
+
+impl Executable for Hexists {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let res = match state.get_hash(&self.key) {
+            Ok(hash) => {
+                let exists = hash.is_some_and(|hash| hash.contains_key(&self.field));
+                Frame::Integer(exists as i64)
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hexists {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let field = parser.next_string()?;
+
+        Ok(Self { key, field })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hexists(store: &Store, key: &str, field: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HEXISTS")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+            Frame::Bulk(Bytes::from(field.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn existing_and_missing() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                "user".to_string(),
+                [("name".to_string(), Bytes::from("ana"))],
+            )
+            .unwrap();
+
+        assert_eq!(hexists(&store, "user", "name"), Frame::Integer(1));
+        assert_eq!(hexists(&store, "user", "age"), Frame::Integer(0));
+        assert_eq!(hexists(&store, "missing", "name"), Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hexists(&store, "key", "field"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
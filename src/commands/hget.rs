@@ -0,0 +1,91 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the value associated with `field` in the hash stored at `key`, or nil when the field
+/// or the key doesn't exist.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hget/>
+#[derive(Debug, PartialEq)]
+pub struct Hget {
+    pub key: String,
+    pub field: String,
+}
+
+impl Executable for Hget {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let res = match state.get_hash(&self.key) {
+            Ok(hash) => match hash.and_then(|hash| hash.get(&self.field)) {
+                Some(value) => Frame::Bulk(value.clone()),
+                None => Frame::NullBulkString,
+            },
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hget {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let field = parser.next_string()?;
+
+        Ok(Self { key, field })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hget(store: &Store, key: &str, field: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HGET")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+            Frame::Bulk(Bytes::from(field.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn existing_and_missing() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                "user".to_string(),
+                [("name".to_string(), Bytes::from("ana"))],
+            )
+            .unwrap();
+
+        assert_eq!(
+            hget(&store, "user", "name"),
+            Frame::Bulk(Bytes::from("ana"))
+        );
+        assert_eq!(hget(&store, "user", "age"), Frame::NullBulkString);
+        assert_eq!(hget(&store, "missing", "name"), Frame::NullBulkString);
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hget(&store, "key", "field"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns every field and value of the hash stored at `key`, as a map of fields to values, which
+/// RESP2 clients receive as a flat array of fields and values. The map is empty when the key
+/// doesn't exist.
+///
+/// **NOTE**: the fields are sorted, Redis returns them in no particular order.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hgetall/>
+#[derive(Debug, PartialEq)]
+pub struct Hgetall {
+    pub key: String,
+}
+
+impl Executable for Hgetall {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let hash = match state.get_hash(&self.key) {
+            Ok(hash) => hash,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+
+        let mut fields: Vec<_> = hash.into_iter().flatten().collect();
+        fields.sort();
+        let res = fields
+            .into_iter()
+            .map(|(field, value)| {
+                (
+                    Frame::Bulk(Bytes::copy_from_slice(field.as_bytes())),
+                    Frame::Bulk(value.clone()),
+                )
+            })
+            .collect();
+
+        Ok(Frame::Map(res))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hgetall {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn hgetall(store: &Store, key: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HGETALL")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sorted_fields() {
+        let store = Store::new();
+        let fields = [("name", "ana"), ("age", "30")]
+            .map(|(field, value)| (field.to_string(), Bytes::from(value)));
+        store.lock().hset("user".to_string(), fields).unwrap();
+
+        assert_eq!(
+            hgetall(&store, "user"),
+            Frame::Map(vec![
+                (
+                    Frame::Bulk(Bytes::from("age")),
+                    Frame::Bulk(Bytes::from("30"))
+                ),
+                (
+                    Frame::Bulk(Bytes::from("name")),
+                    Frame::Bulk(Bytes::from("ana"))
+                ),
+            ])
+        );
+        assert_eq!(hgetall(&store, "missing"), Frame::Map(vec![]));
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hgetall(&store, "key"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
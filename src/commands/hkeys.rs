@@ -0,0 +1,89 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the fields of the hash stored at `key`, an empty array when the key doesn't exist.
+///
+/// **NOTE**: the fields are sorted, Redis returns them in no particular order.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hkeys/>
+#[derive(Debug, PartialEq)]
+pub struct Hkeys {
+    pub key: String,
+}
+
+impl Executable for Hkeys {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let hash = match state.get_hash(&self.key) {
+            Ok(hash) => hash,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+
+        let mut fields: Vec<_> = hash.into_iter().flat_map(|hash| hash.keys()).collect();
+        fields.sort();
+        let res = fields
+            .into_iter()
+            .map(|field| Frame::Bulk(Bytes::copy_from_slice(field.as_bytes())))
+            .collect();
+
+        Ok(Frame::Array(res))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hkeys {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn hkeys(store: &Store, key: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HKEYS")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sorted_fields() {
+        let store = Store::new();
+        let fields = ["name", "age"].map(|field| (field.to_string(), Bytes::from("value")));
+        store.lock().hset("user".to_string(), fields).unwrap();
+
+        assert_eq!(
+            hkeys(&store, "user"),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("age")),
+                Frame::Bulk(Bytes::from("name")),
+            ])
+        );
+        assert_eq!(hkeys(&store, "missing"), Frame::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hkeys(&store, "key"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
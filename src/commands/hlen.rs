@@ -0,0 +1,74 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the number of fields of the hash stored at `key`, 0 when the key doesn't exist.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hlen/>
+#[derive(Debug, PartialEq)]
+pub struct Hlen {
+    pub key: String,
+}
+
+impl Executable for Hlen {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let res = match state.get_hash(&self.key) {
+            Ok(hash) => Frame::Integer(hash.map_or(0, |hash| hash.len()) as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hlen {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hlen(store: &Store, key: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HLEN")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn existing_and_missing() {
+        let store = Store::new();
+        let fields = ["name", "age"].map(|field| (field.to_string(), Bytes::from("value")));
+        store.lock().hset("user".to_string(), fields).unwrap();
+
+        assert_eq!(hlen(&store, "user"), Frame::Integer(2));
+        assert_eq!(hlen(&store, "missing"), Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hlen(&store, "key"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
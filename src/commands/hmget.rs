@@ -0,0 +1,125 @@
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the values associated with the specified fields in the hash stored at `key`, in the
+/// order they are given.
+///
+/// ```text
+/// HMGET key field [field ...]
+/// ```
+///
+/// Replies with nil for every field that doesn't exist, and for every field when the key doesn't
+/// exist.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hmget/>
+#[derive(Debug, PartialEq)]
+pub struct Hmget {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+impl Executable for Hmget {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let hash = match state.get_hash(&self.key) {
+            Ok(hash) => hash,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+
+        let values = self
+            .fields
+            .iter()
+            .map(|field| match hash.and_then(|hash| hash.get(field)) {
+                Some(value) => Frame::Bulk(value.clone()),
+                None => Frame::NullBulkString,
+            })
+            .collect();
+
+        Ok(Frame::Array(values))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hmget {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+
+        let mut fields = vec![];
+        loop {
+            match parser.next_string() {
+                Ok(field) => fields.push(field),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { key, fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hmget(store: &Store, key: &str, fields: &[&str]) -> Frame {
+        let mut args = vec![
+            Frame::Bulk(Bytes::from("HMGET")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ];
+        args.extend(
+            fields
+                .iter()
+                .map(|field| Frame::Bulk(Bytes::from(field.to_string()))),
+        );
+        let cmd = Command::try_from(Frame::Array(args)).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn existing_and_missing() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                "user".to_string(),
+                [
+                    ("name".to_string(), Bytes::from("ana")),
+                    ("age".to_string(), Bytes::from("30")),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            hmget(&store, "user", &["age", "city", "name"]),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("30")),
+                Frame::NullBulkString,
+                Frame::Bulk(Bytes::from("ana")),
+            ])
+        );
+        assert_eq!(
+            hmget(&store, "missing", &["name", "age"]),
+            Frame::Array(vec![Frame::NullBulkString, Frame::NullBulkString])
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hmget(&store, "key", &["field"]),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Sets the specified fields to their respective values in the hash stored at `key`, overwriting
+/// the fields that exist in the hash. A key that doesn't exist is created holding a new hash.
+///
+/// ```text
+/// HSET key field value [field value ...]
+/// ```
+///
+/// Replies with the number of fields that were added. The key keeps its time to live.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hset/>
+#[derive(Debug, PartialEq)]
+pub struct Hset {
+    pub key: String,
+    pub fields: Vec<(String, Bytes)>,
+}
+
+impl Executable for Hset {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        let res = match state.hset(self.key, self.fields) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hset {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+
+        let mut fields = vec![];
+        loop {
+            let field = match parser.next_string() {
+                Ok(field) => field,
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            let value = match parser.next_bytes() {
+                Ok(value) => value,
+                // A field without a value.
+                Err(CommandParserError::EndOfStream) => {
+                    return Err(RustdisError::command(
+                        ErrorKind::Err,
+                        "wrong number of arguments for 'hset' command",
+                    ))
+                }
+                Err(err) => return Err(err.into()),
+            };
+            fields.push((field, value));
+        }
+
+        Ok(Self { key, fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn run(store: &Store, args: &[&str]) -> Result<Frame, String> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(store.clone()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn add_and_overwrite() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["HSET", "user", "name", "ana", "age", "30"]),
+            Ok(Frame::Integer(2))
+        );
+        assert_eq!(
+            run(&store, &["HSET", "user", "age", "31", "city", "rome"]),
+            Ok(Frame::Integer(1))
+        );
+
+        let state = store.lock();
+        let hash = state.get_hash("user").unwrap().unwrap();
+        assert_eq!(hash.len(), 3);
+        assert_eq!(hash["age"], Bytes::from("31"));
+    }
+
+    #[tokio::test]
+    async fn keeps_ttl() {
+        let store = Store::new();
+        run(&store, &["HSET", "user", "name", "ana"]).unwrap();
+        run(&store, &["EXPIRE", "user", "100"]).unwrap();
+
+        run(&store, &["HSET", "user", "age", "30"]).unwrap();
+
+        assert!(store.lock().get_value("user").unwrap().expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        run(&store, &["SET", "key", "value"]).unwrap();
+
+        assert_eq!(
+            run(&store, &["HSET", "key", "field", "value"]),
+            Ok(Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ))
+        );
+        assert_eq!(store.lock().get("key"), Some(Bytes::from("value")));
+    }
+
+    #[tokio::test]
+    async fn field_without_value() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["HSET", "key", "field", "value", "other"]),
+            Err("ERR wrong number of arguments for 'hset' command".to_string())
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Sets `field` to `value` in the hash stored at `key`, only if the field doesn't exist yet. A key
+/// that doesn't exist is created holding a new hash.
+///
+/// ```text
+/// HSETNX key field value
+/// ```
+///
+/// Replies with 1 when the field was set, and 0 when it already existed. The key keeps its time
+/// to live.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hsetnx/>
+#[derive(Debug, PartialEq)]
+pub struct Hsetnx {
+    pub key: String,
+    pub field: String,
+    pub value: Bytes,
+}
+
+impl Executable for Hsetnx {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        let exists = match state.get_hash(&self.key) {
+            Ok(hash) => hash.is_some_and(|hash| hash.contains_key(&self.field)),
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+        if exists {
+            return Ok(Frame::Integer(0));
+        }
+
+        let res = match state.hset(self.key, [(self.field, self.value)]) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hsetnx {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let field = parser.next_string()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, field, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn hsetnx(store: &Store, key: &str, field: &str, value: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HSETNX")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+            Frame::Bulk(Bytes::from(field.to_string())),
+            Frame::Bulk(Bytes::from(value.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn set_only_new_fields() {
+        let store = Store::new();
+
+        assert_eq!(hsetnx(&store, "user", "name", "ana"), Frame::Integer(1));
+        assert_eq!(hsetnx(&store, "user", "name", "bob"), Frame::Integer(0));
+        assert_eq!(hsetnx(&store, "user", "age", "30"), Frame::Integer(1));
+
+        let state = store.lock();
+        let hash = state.get_hash("user").unwrap().unwrap();
+        assert_eq!(hash.len(), 2);
+        assert_eq!(hash["name"], Bytes::from("ana"));
+    }
+
+    #[tokio::test]
+    async fn existing_field_is_not_a_write() {
+        let store = Store::new();
+        hsetnx(&store, "user", "name", "ana");
+        let version = store.lock().version("user");
+
+        hsetnx(&store, "user", "name", "bob");
+
+        assert_eq!(store.lock().version("user"), version);
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hsetnx(&store, "key", "field", "value"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+        assert_eq!(store.lock().get("key"), Some(Bytes::from("value")));
+    }
+}
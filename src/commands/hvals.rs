@@ -0,0 +1,91 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the values of the hash stored at `key`, an empty array when the key doesn't exist.
+///
+/// **NOTE**: the values are sorted by their field, in the order HKEYS returns the fields, Redis
+/// returns them in no particular order.
+///
+/// Ref: <https://redis.io/docs/latest/commands/hvals/>
+#[derive(Debug, PartialEq)]
+pub struct Hvals {
+    pub key: String,
+}
+
+impl Executable for Hvals {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let hash = match state.get_hash(&self.key) {
+            Ok(hash) => hash,
+            Err(err) => return Ok(Frame::Error(err.to_string())),
+        };
+
+        let mut fields: Vec<_> = hash.into_iter().flatten().collect();
+        fields.sort();
+        let res = fields
+            .into_iter()
+            .map(|(_, value)| Frame::Bulk(value.clone()))
+            .collect();
+
+        Ok(Frame::Array(res))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Hvals {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn hvals(store: &Store, key: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("HVALS")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sorted_by_field() {
+        let store = Store::new();
+        let fields = [("name", "ana"), ("age", "30")]
+            .map(|(field, value)| (field.to_string(), Bytes::from(value)));
+        store.lock().hset("user".to_string(), fields).unwrap();
+
+        assert_eq!(
+            hvals(&store, "user"),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("30")),
+                Frame::Bulk(Bytes::from("ana")),
+            ])
+        );
+        assert_eq!(hvals(&store, "missing"), Frame::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn wrong_type() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            hvals(&store, "key"),
+            Frame::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            )
+        );
+    }
+}
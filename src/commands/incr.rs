@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Increments the number stored at key by one.
 ///
@@ -13,17 +13,17 @@ pub struct Incr {
 }
 
 impl Executable for Incr {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let res = store.incr_by(&self.key, 1);
         match res {
-            Ok(_) => Ok(Frame::Simple("OK".to_string())),
-            Err(msg) => Ok(Frame::Error(msg.to_string())),
+            Ok(value) => Ok(Frame::Integer(value)),
+            Err(err) => Ok(Frame::Error(err.to_string())),
         }
     }
 }
 
 impl TryFrom<&mut CommandParser> for Incr {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -60,7 +60,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(2));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("2")));
     }
 
@@ -83,7 +83,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(1));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("1")));
     }
 
@@ -110,7 +110,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("value")));
     }
@@ -140,7 +140,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
         assert_eq!(
             store.lock().get("key1"),
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{next_number, CommandParser};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Increments the number stored at key by `increment`.
 ///
@@ -14,21 +14,21 @@ pub struct IncrBy {
 }
 
 impl Executable for IncrBy {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let res = store.incr_by(&self.key, self.increment);
         match res {
-            Ok(_) => Ok(Frame::Simple("OK".to_string())),
-            Err(msg) => Ok(Frame::Error(msg.to_string())),
+            Ok(value) => Ok(Frame::Integer(value)),
+            Err(err) => Ok(Frame::Error(err.to_string())),
         }
     }
 }
 
 impl TryFrom<&mut CommandParser> for IncrBy {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
-        let increment = parser.next_integer()?;
+        let increment = next_number(parser)?;
 
         Ok(Self { key, increment })
     }
@@ -64,7 +64,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(30));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("30")));
     }
 
@@ -89,7 +89,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("OK".to_string()));
+        assert_eq!(result, Frame::Integer(10));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("10")));
     }
 
@@ -118,7 +118,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("value")));
     }
@@ -150,7 +150,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not an integer or out of range".to_string())
         );
 
         assert_eq!(
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{next_number, CommandParser};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Increment the string representing a floating point number stored at key by the specified
 /// increment. By using a negative increment value, the result is that the value stored at the key
@@ -12,7 +12,6 @@ use crate::Error;
 /// Ref: <https://redis.io/docs/latest/commands/incrbyfloat/>
 ///
 /// TODO:
-/// * Handle overflow errors.
 /// * The precision of the output is fixed at 17 digits after the decimal point regardless of the
 ///   actual internal precision of the computation.
 /// * Both the value already contained in the string key and the increment argument can be
@@ -25,21 +24,21 @@ pub struct IncrByFloat {
 }
 
 impl Executable for IncrByFloat {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let res = store.incr_by(&self.key, self.increment);
         match res {
-            Ok(res) => Ok(Frame::Simple(res.to_string())),
-            Err(msg) => Ok(Frame::Error(msg.to_string())),
+            Ok(res) => Ok(Frame::Bulk(res.to_string().into())),
+            Err(err) => Ok(Frame::Error(err.to_string())),
         }
     }
 }
 
 impl TryFrom<&mut CommandParser> for IncrByFloat {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
-        let increment = parser.next_float()?;
+        let increment = next_number(parser)?;
 
         Ok(Self { key, increment })
     }
@@ -75,7 +74,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("10.6".to_string()));
+        assert_eq!(result, Frame::Bulk(Bytes::from("10.6")));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("10.6")));
     }
 
@@ -100,7 +99,7 @@ mod tests {
 
         let result = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(result, Frame::Simple("10".to_string()));
+        assert_eq!(result, Frame::Bulk(Bytes::from("10")));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("10")));
     }
 
@@ -129,8 +128,24 @@ mod tests {
 
         assert_eq!(
             result,
-            Frame::Error("value is not of the correct type or out of range".to_string())
+            Frame::Error("ERR value is not a valid float".to_string())
         );
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("value")));
     }
+
+    #[tokio::test]
+    async fn invalid_increment() {
+        for increment in ["abc", "nan", "1,5"] {
+            let frame = Frame::Array(vec![
+                Frame::Bulk(Bytes::from("INCRBYFLOAT")),
+                Frame::Bulk(Bytes::from("key1")),
+                Frame::Bulk(Bytes::from(increment)),
+            ]);
+
+            assert_eq!(
+                Command::try_from(frame).unwrap_err().to_string(),
+                "ERR value is not a valid float"
+            );
+        }
+    }
 }
@@ -1,10 +1,12 @@
 use bytes::Bytes;
+use tokio::time::Instant;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::stats::Stats;
+use crate::store::{KeyspaceStats, ServerState, Store};
 
 const INFO: &str = r#"
 # Server
@@ -26,53 +28,323 @@ used_memory_peak:68848456
 used_memory_peak_human:65.66M
 maxmemory:4294967296
 maxmemory_human:4.00G
+compressed_values:{compressed_values}
+compressed_values_bytes:{compressed_values_bytes}
+compressed_values_original_bytes:{compressed_values_original_bytes}
+keyspace_capacity:{keyspace_capacity}
+keyspace_load_factor:{keyspace_load_factor}
 
 # Persistence
-loading:0
+loading:{loading}
 rdb_changes_since_last_save:1050288
 aof_enabled:0
 
 # Stats
-total_connections_received:21
-total_commands_processed:1308336
+total_connections_received:{total_connections_received}
+total_commands_processed:{total_commands_processed}
 instantaneous_ops_per_sec:0
+total_net_input_bytes:{total_net_input_bytes}
+total_net_output_bytes:{total_net_output_bytes}
+expired_keys:{expired_keys}
+scheduled_expirations:{scheduled_expirations}
+next_expiration_in_ms:{next_expiration_in_ms}
+evicted_keys:{evicted_keys}
+total_error_replies:{total_error_replies}
 
 # Replication
 role:master
 connected_slaves:0
+master_failover_state:no-failover
+master_replid:8c1ed58ecb8d4cfd4b3e5a49bb5b4e3d9cbc4e86
+master_replid2:0000000000000000000000000000000000000000
+master_repl_offset:0
+second_repl_offset:-1
+repl_backlog_active:0
+repl_backlog_size:1048576
+repl_backlog_first_byte_offset:0
+repl_backlog_histlen:0
 
 # CPU
 used_cpu_sys:850.545934
 used_cpu_user:1777.532734
 
 # Errorstats
-errorstat_ERR:count:1189
-
+{errorstats}
 # Cluster
 cluster_enabled:0
 
 # Keyspace
-db0:keys=397255,expires=845,avg_ttl=1527956522210785
-"#;
+{keyspace}"#;
 
 /// Returns information and statistics about the server.
 ///
-/// **NOTE**: returns a mock response.
+/// The reply is a verbatim string, which RESP2 clients receive as a bulk string.
+///
+/// The `keyspace_capacity` and `keyspace_load_factor` fields of the `# Memory` section show how
+/// full the hash map holding the keys is, see `DEBUG REHASH`. Both are 0 with storage engines that
+/// don't allocate ahead.
+///
+/// The `scheduled_expirations` and `next_expiration_in_ms` fields of the `# Stats` section show
+/// how many keys the task removing expired keys has to remove, and when it wakes up next, -1 when
+/// no key expires. DEBUG TTL-STATS details them.
+///
+/// The `# Replication` section has the fields Redis reports for a master without replicas, since
+/// there is no replication, see ROLE. Replica only fields, like `master_host` and
+/// `slave_repl_offset`, are never reported, clients read them only once `role` is `slave`.
+///
+/// The `# Errorstats` section counts the error replies sent by their prefix, like
+/// `errorstat_ERR:count=3`, including the ones of commands rejected as unknown or for their
+/// arguments, and `total_error_replies` of the `# Stats` section counts them all.
+///
+/// The `loading` field of the `# Persistence` section is 1 while the dataset passed with
+/// `--import` is loaded at startup.
+///
+/// **NOTE**: apart from the `# Stats`, `# Errorstats` and `# Keyspace` sections, the value
+/// compression and keyspace fields of the `# Memory` section and the `loading` field, returns a
+/// mock response.
 ///
 /// Ref: <https://redis.io/docs/latest/commands/info/>
 #[derive(Debug, PartialEq)]
 pub struct Info;
 
 impl Executable for Info {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
-        Ok(Frame::Bulk(Bytes::from(INFO)))
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        let compression = state.compression_stats();
+        let keyspace = state.keyspace_stats();
+        let next_expiration = state.next_expiration();
+        let capacity = state.capacity().unwrap_or(0);
+        let load_factor = match capacity {
+            0 => 0.0,
+            capacity => state.size() as f64 / capacity as f64,
+        };
+        drop(state);
+
+        let stats = &store.stats;
+        let info = INFO
+            .replace("{compressed_values}", &compression.values.to_string())
+            .replace("{compressed_values_bytes}", &compression.bytes.to_string())
+            .replace(
+                "{compressed_values_original_bytes}",
+                &compression.original_bytes.to_string(),
+            )
+            .replace(
+                "{loading}",
+                match store.server_state() {
+                    ServerState::Loading => "1",
+                    ServerState::Ready => "0",
+                },
+            )
+            .replace("{keyspace_capacity}", &capacity.to_string())
+            .replace("{keyspace_load_factor}", &format!("{:.2}", load_factor))
+            .replace(
+                "{total_connections_received}",
+                &stats.total_connections_received().to_string(),
+            )
+            .replace(
+                "{total_commands_processed}",
+                &stats.total_commands_processed().to_string(),
+            )
+            .replace(
+                "{total_net_input_bytes}",
+                &stats.total_net_input_bytes().to_string(),
+            )
+            .replace(
+                "{total_net_output_bytes}",
+                &stats.total_net_output_bytes().to_string(),
+            )
+            .replace("{expired_keys}", &stats.expired_keys().to_string())
+            .replace("{scheduled_expirations}", &keyspace.expires.to_string())
+            .replace(
+                "{next_expiration_in_ms}",
+                &next_expiration
+                    .map(|next| next.saturating_duration_since(Instant::now()).as_millis() as i64)
+                    .unwrap_or(-1)
+                    .to_string(),
+            )
+            .replace("{evicted_keys}", &stats.evicted_keys().to_string())
+            .replace(
+                "{total_error_replies}",
+                &stats.total_error_replies().to_string(),
+            )
+            .replace("{errorstats}", &errorstats_section(stats))
+            .replace("{keyspace}", &keyspace_section(&keyspace));
+
+        Ok(Frame::Verbatim {
+            encoding: "txt".to_string(),
+            text: Bytes::from(info),
+        })
     }
 }
 
+/// The lines of the `# Keyspace` section. Like Redis, databases without keys are left out, and
+/// there is only `db0` as SELECT doesn't switch databases.
+fn keyspace_section(keyspace: &KeyspaceStats) -> String {
+    if keyspace.keys == 0 {
+        return String::new();
+    }
+
+    format!(
+        "db0:keys={},expires={},avg_ttl={}\n",
+        keyspace.keys, keyspace.expires, keyspace.avg_ttl
+    )
+}
+
+/// The lines of the `# Errorstats` section, one for each error prefix replied with.
+fn errorstats_section(stats: &Stats) -> String {
+    stats
+        .error_replies()
+        .iter()
+        .map(|(code, count)| format!("errorstat_{}:count={}\n", code, count))
+        .collect()
+}
+
 impl TryFrom<&mut CommandParser> for Info {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
         Ok(Self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+    use crate::config::Config;
+    use crate::store::NewValue;
+    use tokio::time::{self, Duration};
+
+    #[tokio::test]
+    async fn stats_section() {
+        let store = Store::new();
+
+        store.stats.incr_connections_received();
+        store.stats.incr_commands_processed();
+        store.stats.incr_net_input_bytes(14);
+        store.stats.incr_net_output_bytes(7);
+        store.stats.incr_expired_keys(3);
+
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("INFO"))]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(cmd, Command::Info(Info));
+
+        let res = cmd.exec(store.clone()).unwrap();
+        let info = match res {
+            Frame::Verbatim { encoding, text } if encoding == "txt" => {
+                String::from_utf8(text.to_vec()).unwrap()
+            }
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        assert!(info.contains("total_connections_received:1\n"));
+        assert!(info.contains("total_commands_processed:1\n"));
+        assert!(info.contains("total_net_input_bytes:14\n"));
+        assert!(info.contains("total_net_output_bytes:7\n"));
+        assert!(info.contains("expired_keys:3\n"));
+        assert!(info.contains("evicted_keys:0\n"));
+    }
+
+    #[tokio::test]
+    async fn errorstats_section() {
+        let store = Store::new();
+        let info = |store: &Store| match Info.exec(store.clone()).unwrap() {
+            Frame::Verbatim { text, .. } => String::from_utf8(text.to_vec()).unwrap(),
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        assert!(info(&store).contains("total_error_replies:0\n"));
+        assert!(info(&store).contains("# Errorstats\n\n"));
+
+        store
+            .stats
+            .incr_error_replies("ERR unknown command 'NOPE', with args beginning with: ");
+        store
+            .stats
+            .incr_error_replies("WRONGTYPE Operation against a key holding the wrong kind");
+        store.stats.incr_error_replies("ERR syntax error");
+
+        assert!(info(&store).contains("total_error_replies:3\n"));
+        assert!(info(&store)
+            .contains("# Errorstats\nerrorstat_ERR:count=2\nerrorstat_WRONGTYPE:count=1\n\n"));
+    }
+
+    #[tokio::test]
+    async fn keyspace_section() {
+        time::pause();
+
+        let store = Store::new();
+        let info = |store: &Store| match Info.exec(store.clone()).unwrap() {
+            Frame::Verbatim { text, .. } => String::from_utf8(text.to_vec()).unwrap(),
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        assert!(info(&store).ends_with("# Keyspace\n"));
+        assert!(info(&store).contains("scheduled_expirations:0\nnext_expiration_in_ms:-1\n"));
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        store.set2(
+            String::from("key2"),
+            NewValue {
+                data: Bytes::from("2"),
+                ttl: Some(Duration::from_secs(10)),
+            },
+        );
+
+        assert!(info(&store).ends_with("# Keyspace\ndb0:keys=2,expires=1,avg_ttl=10000\n"));
+        assert!(info(&store).contains("scheduled_expirations:1\nnext_expiration_in_ms:10000\n"));
+    }
+
+    #[tokio::test]
+    async fn replication_section() {
+        let res = Info.exec(Store::new()).unwrap();
+        let info = match res {
+            Frame::Verbatim { text, .. } => String::from_utf8(text.to_vec()).unwrap(),
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        assert!(info.contains("# Replication\nrole:master\nconnected_slaves:0\n"));
+        assert!(info.contains("master_repl_offset:0\n"));
+        assert!(!info.contains("master_host:"));
+    }
+
+    #[tokio::test]
+    async fn loading() {
+        let store = Store::new();
+        let info = |store: &Store| match Info.exec(store.clone()).unwrap() {
+            Frame::Verbatim { text, .. } => String::from_utf8(text.to_vec()).unwrap(),
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        assert!(info(&store).contains("# Persistence\nloading:0\n"));
+
+        store.set_server_state(ServerState::Loading);
+        assert!(info(&store).contains("# Persistence\nloading:1\n"));
+    }
+
+    #[tokio::test]
+    async fn keyspace_capacity() {
+        let store = Store::with_config(Config {
+            keyspace_initial_capacity: 100,
+            ..Config::default()
+        });
+        store.lock().rehash(8).unwrap();
+        for i in 0..7 {
+            store.lock().set(i.to_string(), Bytes::from("value"));
+        }
+
+        let res = Info.exec(store.clone()).unwrap();
+        let info = match res {
+            Frame::Verbatim { text, .. } => String::from_utf8(text.to_vec()).unwrap(),
+            frame => panic!("expected verbatim frame, got {:?}", frame),
+        };
+
+        let capacity = store.lock().capacity().unwrap();
+        assert!(info.contains(&format!("keyspace_capacity:{}\n", capacity)));
+        assert!(info.contains(&format!(
+            "keyspace_load_factor:{:.2}\n",
+            7.0 / capacity as f64
+        )));
+    }
+}
@@ -0,0 +1,231 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::{Store, STRING};
+use crate::utils::json::{self, Path};
+
+/// Returns the JSON text of the value at `path` in the JSON document stored at `key`, `$`, the
+/// whole document, by default.
+///
+/// ```text
+/// RUSTDIS.JSONGET key [path]
+/// ```
+///
+/// Replies with nil when the key doesn't exist or there is no value at `path`. See `utils::json`
+/// for the paths supported.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command. Documents are
+/// stored as their JSON text in string keys, GET returns them too. It requires the `json`
+/// feature, rustdis replies with an error otherwise.
+///
+/// Ref: <https://redis.io/docs/latest/commands/json.get/>
+#[derive(Debug, PartialEq)]
+pub struct JsonGet {
+    pub key: String,
+    pub path: Path,
+}
+
+/// Sets the value at `path` in the JSON document stored at `key` to the JSON text `value`.
+///
+/// ```text
+/// RUSTDIS.JSONSET key path value
+/// ```
+///
+/// A key that doesn't exist is created, with `$` as the path. A missing field of an object is
+/// added, and nil is replied when the object or array holding the value doesn't exist, or the
+/// index is past the end of the array. The key keeps its time to live.
+///
+/// **NOTE**: this is a rustdis specific command, see RUSTDIS.JSONGET.
+///
+/// Ref: <https://redis.io/docs/latest/commands/json.set/>
+#[derive(Debug, PartialEq)]
+pub struct JsonSet {
+    pub key: String,
+    pub path: Path,
+    pub value: Bytes,
+}
+
+impl Executable for JsonGet {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+        if let Err(err) = state.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
+        let Some(document) = state.get(&self.key) else {
+            return Ok(Frame::NullBulkString);
+        };
+
+        let res = match json::get(&document, &self.path) {
+            Ok(Some(value)) => Frame::Bulk(Bytes::from(value)),
+            Ok(None) => Frame::NullBulkString,
+            Err(msg) => Frame::Error(format!("ERR {}", msg)),
+        };
+
+        Ok(res)
+    }
+}
+
+impl Executable for JsonSet {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+        if let Err(err) = state.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
+        let document = state.get(&self.key);
+
+        let res = match json::set(document.as_deref(), &self.path, &self.value) {
+            Ok(Some(document)) => {
+                state.update_value(self.key, Bytes::from(document));
+                Frame::Simple("OK".to_string())
+            }
+            Ok(None) => Frame::NullBulkString,
+            Err(msg) => Frame::Error(format!("ERR {}", msg)),
+        };
+
+        Ok(res)
+    }
+}
+
+fn parse_path(path: &str) -> Result<Path, RustdisError> {
+    Path::parse(path).ok_or_else(|| RustdisError::command(ErrorKind::Err, "invalid path"))
+}
+
+impl TryFrom<&mut CommandParser> for JsonGet {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let path = match parser.next_string() {
+            Ok(path) => parse_path(&path)?,
+            Err(CommandParserError::EndOfStream) => Path::root(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self { key, path })
+    }
+}
+
+impl TryFrom<&mut CommandParser> for JsonSet {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        let path = parse_path(&parser.next_string()?)?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, path, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn run(store: &Store, args: &[&str]) -> Result<Frame, String> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(store.clone()).unwrap())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn get_and_set() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONSET", "doc", "$", r#"{"tags": []}"#]),
+            Ok(Frame::Simple("OK".to_string()))
+        );
+        assert_eq!(
+            run(
+                &store,
+                &["RUSTDIS.JSONSET", "doc", "$.name", r#""rustdis""#]
+            ),
+            Ok(Frame::Simple("OK".to_string()))
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONSET", "doc", "$.tags[0]", "1"]),
+            Ok(Frame::NullBulkString)
+        );
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "doc"]),
+            Ok(Frame::Bulk(Bytes::from(r#"{"name":"rustdis","tags":[]}"#)))
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "doc", "$.name"]),
+            Ok(Frame::Bulk(Bytes::from(r#""rustdis""#)))
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "doc", "$.version"]),
+            Ok(Frame::NullBulkString)
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "missing"]),
+            Ok(Frame::NullBulkString)
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn keeps_ttl() {
+        let store = Store::new();
+        run(&store, &["SET", "doc", "{}", "EX", "100"]).unwrap();
+
+        run(&store, &["RUSTDIS.JSONSET", "doc", "$.a", "1"]).unwrap();
+
+        assert!(store.lock().get_value("doc").unwrap().expires_at.is_some());
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn not_a_document() {
+        let store = Store::new();
+        run(&store, &["SET", "key", "plain text"]).unwrap();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "key"]),
+            Ok(Frame::Error(
+                "ERR existing value is not a JSON document".to_string()
+            ))
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONSET", "other", "$", "{"]),
+            Ok(Frame::Error("ERR invalid JSON value".to_string()))
+        );
+    }
+
+    #[cfg(not(feature = "json"))]
+    #[tokio::test]
+    async fn disabled() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONSET", "doc", "$", "{}"]),
+            Ok(Frame::Error(format!("ERR {}", json::DISABLED)))
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_path() {
+        let store = Store::new();
+
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONGET", "doc", "name"]),
+            Err("ERR invalid path".to_string())
+        );
+        assert_eq!(
+            run(&store, &["RUSTDIS.JSONSET", "doc", "$[x]", "1"]),
+            Err("ERR invalid path".to_string())
+        );
+    }
+}
@@ -1,40 +1,76 @@
 use bytes::Bytes;
-use glob_match::glob_match;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
+
+/// Keys walked between two checks of the deadline, reading the clock for every key would slow
+/// KEYS down.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
 
 /// Return all keys matching `pattern`.
 ///
-/// Uses [glob-match](https://github.com/devongovett/glob-match) to match the `pattern`.
+/// See `Pattern` for the syntax of `pattern`, which may hold any byte.
+///
+/// Walking a large keyspace holds the store lock for long, so KEYS stops after
+/// `keys-max-results` matching keys or once the `command-time-limit` elapses. The keys found so
+/// far are then replied, followed by a `BUSY` error element telling the reply is partial.
+///
+/// **NOTE**: the limits and the partial reply are rustdis specific, Redis always replies with
+/// every matching key.
 ///
 /// Ref: <https://redis.io/commands/keys>
 #[derive(Debug, PartialEq)]
 pub struct Keys {
-    pub pattern: String,
+    pub pattern: Bytes,
 }
 
 impl Executable for Keys {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let store = store.lock();
-        let matching_keys: Vec<Frame> = store
-            .keys()
-            .filter(|key| glob_match(self.pattern.as_str(), key))
-            .map(|key| Frame::Bulk(Bytes::from(key.to_string())))
-            .collect();
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let pattern = store.pattern(&self.pattern);
+        let max_results = store.config().keys_max_results();
+        let deadline = store.command_deadline();
+        let state = store.lock();
+
+        let mut matching_keys = vec![];
+        let mut truncated = None;
+        for (i, key) in state.keys().enumerate() {
+            if i % DEADLINE_CHECK_INTERVAL == 0 {
+                if let Err(err) = deadline.check() {
+                    truncated = Some(format!("KEYS reply truncated, {}", err));
+                    break;
+                }
+            }
+            if !pattern.matches(key) {
+                continue;
+            }
+            if max_results.is_some_and(|max| matching_keys.len() == max) {
+                truncated = Some(format!(
+                    "KEYS reply truncated to {} keys by keys-max-results",
+                    matching_keys.len()
+                ));
+                break;
+            }
+            matching_keys.push(Frame::Bulk(Bytes::from(key.to_string())));
+        }
+
+        if let Some(reason) = truncated {
+            let message = format!("{}, use SCAN to iterate the whole keyspace", reason);
+            let err = RustdisError::command(ErrorKind::Busy, message);
+            matching_keys.push(Frame::Error(err.to_string()));
+        }
 
         Ok(Frame::Array(matching_keys))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Keys {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
-        let pattern = parser.next_string()?;
+        let pattern = parser.next_bytes()?;
         Ok(Self { pattern })
     }
 }
@@ -44,7 +80,8 @@ mod tests {
     use bytes::Bytes;
 
     use super::*;
-    use crate::commands::{Command, CommandParserError};
+    use crate::commands::Command;
+    use crate::config::Config;
 
     #[tokio::test]
     async fn with_wildcard_pattern() {
@@ -59,7 +96,7 @@ mod tests {
         assert_eq!(
             cmd,
             Command::Keys(Keys {
-                pattern: String::from("*")
+                pattern: Bytes::from("*")
             })
         );
 
@@ -73,7 +110,7 @@ mod tests {
         let result = cmd.exec(store.clone()).unwrap();
         let result = match result {
             Frame::Array(mut vec) => {
-                vec.sort();
+                vec.sort_by_key(|frame| frame.to_string());
                 Frame::Array(vec)
             }
             f => f,
@@ -89,27 +126,95 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn with_max_results() {
+        let store = Store::with_config(Config {
+            keys_max_results: 2,
+            ..Config::default()
+        });
+        for key in ["key1", "key2", "key3", "other"] {
+            store.lock().set(key.to_string(), Bytes::from("value"));
+        }
+
+        let res = Keys {
+            pattern: Bytes::from("key*"),
+        }
+        .exec(store.clone())
+        .unwrap();
+        let Frame::Array(frames) = res else {
+            panic!("expected an array, got {:?}", res);
+        };
+        assert_eq!(frames.len(), 3);
+        assert!(frames[..2]
+            .iter()
+            .all(|frame| matches!(frame, Frame::Bulk(key) if key.starts_with(b"key"))));
+        assert_eq!(
+            frames[2],
+            Frame::Error(
+                "BUSY KEYS reply truncated to 2 keys by keys-max-results, use SCAN to iterate the \
+                 whole keyspace"
+                    .to_string()
+            )
+        );
+
+        // Exactly as many matching keys as the limit is a complete reply.
+        store.lock().remove("key3");
+        let res = Keys {
+            pattern: Bytes::from("key*"),
+        }
+        .exec(store.clone())
+        .unwrap();
+        assert!(matches!(res, Frame::Array(frames) if frames.len() == 2));
+    }
+
+    #[tokio::test]
+    async fn with_time_limit() {
+        let store = Store::with_config(Config {
+            command_time_limit: 1,
+            ..Config::default()
+        });
+        for i in 0..200_000 {
+            store.lock().set(i.to_string(), Bytes::from("value"));
+        }
+
+        let res = Keys {
+            pattern: Bytes::from("*"),
+        }
+        .exec(store.clone())
+        .unwrap();
+        let Frame::Array(mut frames) = res else {
+            panic!("expected an array, got {:?}", res);
+        };
+        assert!(frames.len() < 200_000);
+        assert_eq!(
+            frames.pop(),
+            Some(Frame::Error(
+                "BUSY KEYS reply truncated, command exceeded the time limit of 1 ms, use SCAN to \
+                 iterate the whole keyspace"
+                    .to_string()
+            ))
+        );
+    }
+
     #[test]
     fn zero_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("KEYS"))]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
 
-        assert_eq!(*err, CommandParserError::EndOfStream);
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'keys' command"
+        );
     }
 
     #[test]
     fn invalid_frame() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("KEYS")), Frame::Integer(42)]);
         let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
-
+        assert!(matches!(err, RustdisError::Protocol(_)));
         assert_eq!(
-            *err,
-            CommandParserError::InvalidFrame {
-                expected: "simple or bulk string".to_string(),
-                actual: Frame::Integer(42)
-            }
+            err.to_string(),
+            "ERR protocol error; invalid frame, expected simple or bulk string, got :42"
         );
     }
 }
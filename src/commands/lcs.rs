@@ -3,13 +3,17 @@ use std::str::from_utf8;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::utils::lcs::lcs;
-use crate::Error;
+use crate::store::{Store, STRING};
+use crate::utils::lcs::{lcs, lcs_len};
 
 use super::CommandParserError;
 
+/// Maximum size in bytes of the table LCS builds, the same as Redis' default
+/// `proto-max-bulk-len`.
+const MAX_TABLE_SIZE: u64 = 512 * 1024 * 1024;
+
 /// The LCS command implements the longest common subsequence algorithm.
 ///
 /// Note that this is different than the longest common string algorithm,
@@ -24,29 +28,57 @@ pub struct Lcs {
 }
 
 impl Executable for Lcs {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let store = store.lock();
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
 
-        let value1 = store.get(&self.key1).unwrap_or_default();
-        let value2 = store.get(&self.key2).unwrap_or_default();
+        for key in [&self.key1, &self.key2] {
+            if let Err(err) = state.check_type(key, STRING) {
+                return Ok(Frame::Error(err.to_string()));
+            }
+        }
+        let value1 = state.get(&self.key1).unwrap_or_default();
+        let value2 = state.get(&self.key2).unwrap_or_default();
+        // The values are reference counted, other clients don't need to wait for the computation.
+        drop(state);
 
         let str1 = from_utf8(&value1).unwrap_or_default();
         let str2 = from_utf8(&value2).unwrap_or_default();
 
-        let res = lcs(str1, str2);
+        // Redis guards against the size of the table built by the algorithm, one 32 bit cell per
+        // pair of characters, before allocating it.
+        let cells = (str1.chars().count() as u64 + 1) * (str2.chars().count() as u64 + 1);
+        if cells >= u32::MAX as u64 {
+            return Ok(error(ErrorKind::Err, "String too long for LCS"));
+        }
+        if cells * 4 > MAX_TABLE_SIZE {
+            return Ok(error(
+                ErrorKind::Err,
+                "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len",
+            ));
+        }
 
+        let deadline = store.command_deadline();
         let res = if self.len {
-            Frame::Integer(res.len() as i64)
+            lcs_len(str1, str2, &deadline).map(|len| Frame::Integer(len as i64))
         } else {
-            Frame::Bulk(Bytes::from(res))
+            lcs(str1, str2, &deadline).map(|res| Frame::Bulk(Bytes::from(res)))
+        };
+
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => error(ErrorKind::Busy, err.to_string()),
         };
 
         Ok(res)
     }
 }
 
+fn error(kind: ErrorKind, message: impl Into<String>) -> Frame {
+    Frame::Error(RustdisError::command(kind, message).to_string())
+}
+
 impl TryFrom<&mut CommandParser> for Lcs {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key1 = parser.next_string()?;
@@ -67,6 +99,7 @@ mod tests {
 
     use super::*;
     use crate::commands::Command;
+    use crate::config::Config;
 
     #[tokio::test]
     async fn no_match() {
@@ -344,4 +377,83 @@ mod tests {
 
         assert_eq!(res, Frame::Bulk(Bytes::from("")));
     }
+
+    #[tokio::test]
+    async fn exceeds_command_time_limit() {
+        let store = Store::with_config(Config {
+            command_time_limit: 1,
+            ..Config::default()
+        });
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LCS")),
+            Frame::Bulk(Bytes::from("foo")),
+            Frame::Bulk(Bytes::from("bar")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        {
+            let mut store = store.lock();
+            store.set(String::from("foo"), Bytes::from("ab".repeat(2000)));
+            store.set(String::from("bar"), Bytes::from("ba".repeat(2000)));
+        }
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Error("BUSY command exceeded the time limit of 1 ms".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn string_too_long() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LCS")),
+            Frame::Bulk(Bytes::from("foo")),
+            Frame::Bulk(Bytes::from("bar")),
+            Frame::Bulk(Bytes::from("LEN")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        {
+            let mut store = store.lock();
+            store.set(String::from("foo"), Bytes::from("a".repeat(100_000)));
+            store.set(String::from("bar"), Bytes::from("a".repeat(100_000)));
+        }
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Error("ERR String too long for LCS".to_string()));
+    }
+
+    #[tokio::test]
+    async fn table_exceeds_max_size() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LCS")),
+            Frame::Bulk(Bytes::from("foo")),
+            Frame::Bulk(Bytes::from("bar")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        {
+            let mut store = store.lock();
+            store.set(String::from("foo"), Bytes::from("a".repeat(20_000)));
+            store.set(String::from("bar"), Bytes::from("a".repeat(20_000)));
+        }
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Error(
+                "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                    .to_string()
+            )
+        );
+    }
 }
@@ -0,0 +1,112 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Displays some computer art and the server version.
+///
+/// **NOTE**: there is no art, only the version is displayed, which is what Redis does for versions
+/// without an art of their own.
+///
+/// Ref: <https://redis.io/docs/latest/commands/lolwut/>
+#[derive(Debug, PartialEq)]
+pub struct Lolwut {
+    pub version: Option<i64>,
+}
+
+impl Executable for Lolwut {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let text = format!("rustdis ver. {}\n", env!("CARGO_PKG_VERSION"));
+
+        Ok(Frame::Verbatim {
+            encoding: "txt".to_string(),
+            text: Bytes::from(text),
+        })
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Lolwut {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let version = match parser.next_string() {
+            Ok(option) if option.to_lowercase() == "version" => {
+                Some(parser.next_integer().map_err(|_| {
+                    RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+                })?)
+            }
+            Ok(_) => return Err(syntax_error()),
+            Err(CommandParserError::EndOfStream) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self { version })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn with_version() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LOLWUT")),
+            Frame::Bulk(Bytes::from("VERSION")),
+            Frame::Bulk(Bytes::from("5")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(cmd, Command::Lolwut(Lolwut { version: Some(5) }));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Verbatim {
+                encoding: "txt".to_string(),
+                text: Bytes::from(format!("rustdis ver. {}\n", env!("CARGO_PKG_VERSION"))),
+            }
+        );
+        assert_eq!(
+            res.into_resp2(),
+            Frame::Bulk(Bytes::from(format!(
+                "rustdis ver. {}\n",
+                env!("CARGO_PKG_VERSION")
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_version() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LOLWUT")),
+            Frame::Bulk(Bytes::from("VERSION")),
+            Frame::Bulk(Bytes::from("abc")),
+        ]);
+        let err = Command::try_from(frame).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_option() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("LOLWUT")),
+            Frame::Bulk(Bytes::from("foo")),
+        ]);
+        let err = Command::try_from(frame).unwrap_err();
+
+        assert!(matches!(err, RustdisError::Command { .. }));
+        assert_eq!(err.to_string(), "ERR syntax error");
+    }
+}
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 #[derive(Debug, PartialEq)]
 pub enum Memory {
@@ -13,13 +13,16 @@ pub enum Memory {
 ///
 /// The MEMORY USAGE command reports the number of bytes that a key and its value require to be
 /// stored in RAM.
+///
+/// **NOTE**: rustdis reports the length of the value, the sum of the lengths of its fields and
+/// their values for a hash, without the overhead Redis adds.
 #[derive(Debug, PartialEq)]
 pub struct Usage {
     pub key: String,
 }
 
 impl Executable for Memory {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         match self {
             Self::Usage(encoding) => encoding.exec(store),
         }
@@ -27,7 +30,7 @@ impl Executable for Memory {
 }
 
 impl TryFrom<&mut CommandParser> for Memory {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let sub_command = parser.next_string()?;
@@ -47,13 +50,46 @@ impl TryFrom<&mut CommandParser> for Memory {
 }
 
 impl Executable for Usage {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
-        let res = match store.get(&self.key) {
+        let res = match store.get_value(&self.key) {
             Some(value) => Frame::Integer(value.len() as i64),
-            None => Frame::Null,
+            None => Frame::NullBulkString,
         };
 
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn usage(store: &Store, key: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("MEMORY")),
+            Frame::Bulk(Bytes::from("USAGE")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn by_type() {
+        let store = Store::new();
+        store
+            .lock()
+            .set(String::from("string"), Bytes::from("value"));
+        let fields = [("name", "ana"), ("age", "30")]
+            .map(|(field, value)| (field.to_string(), Bytes::from(value)));
+        store.lock().hset(String::from("hash"), fields).unwrap();
+
+        assert_eq!(usage(&store, "string"), Frame::Integer(5));
+        assert_eq!(usage(&store, "hash"), Frame::Integer(12));
+        assert_eq!(usage(&store, "missing"), Frame::NullBulkString);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Returns the values of all specified keys.
 ///
@@ -13,7 +13,7 @@ pub struct Mget {
 }
 
 impl Executable for Mget {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         if self.keys.is_empty() {
             return Ok(Frame::Error(
                 "ERR wrong number of arguments for command".to_string(),
@@ -28,7 +28,7 @@ impl Executable for Mget {
             .map(|value| {
                 value
                     .map(|v| Frame::Bulk(v.clone()))
-                    .unwrap_or_else(|| Frame::Null)
+                    .unwrap_or(Frame::NullBulkString)
             })
             .collect::<Vec<_>>();
 
@@ -37,7 +37,7 @@ impl Executable for Mget {
 }
 
 impl TryFrom<&mut CommandParser> for Mget {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let mut keys = vec![];
@@ -151,7 +151,7 @@ mod tests {
 
         let res = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(res, Frame::Array(vec![Frame::Null]));
+        assert_eq!(res, Frame::Array(vec![Frame::NullBulkString]));
     }
 
     #[tokio::test]
@@ -189,26 +189,20 @@ mod tests {
             res,
             Frame::Array(vec![
                 Frame::Bulk(Bytes::from("1")),
-                Frame::Null,
+                Frame::NullBulkString,
                 Frame::Bulk(Bytes::from("3"))
             ])
         );
     }
 
-    #[tokio::test]
-    async fn no_keys() {
-        let store = Store::new();
-
+    #[test]
+    fn no_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("MGET"))]);
-        let cmd = Command::try_from(frame).unwrap();
-
-        assert_eq!(cmd, Command::Mget(Mget { keys: vec![] }));
-
-        let res = cmd.exec(store.clone()).unwrap();
+        let err = Command::try_from(frame).err().unwrap();
 
         assert_eq!(
-            res,
-            Frame::Error("ERR wrong number of arguments for command".to_string())
+            err.to_string(),
+            "ERR wrong number of arguments for 'mget' command"
         );
     }
 }
@@ -1,162 +1,616 @@
 pub mod append;
+pub mod cas;
 pub mod client;
+pub mod cluster;
 pub mod command;
 pub mod config;
+pub mod coverage;
 pub mod dbsize;
+pub mod debug;
 pub mod decr;
 pub mod decrby;
 pub mod del;
+pub mod echo;
 pub mod executable;
 pub mod exists;
+pub mod expiration;
+pub mod expire;
 pub mod get;
 pub mod getdel;
+pub mod getex;
 pub mod getrange;
+pub mod hdel;
+pub mod hello;
+pub mod hexists;
+pub mod hget;
+pub mod hgetall;
+pub mod hkeys;
+pub mod hlen;
+pub mod hmget;
+pub mod hset;
+pub mod hsetnx;
+pub mod hstrlen;
+pub mod hvals;
 pub mod incr;
 pub mod incrby;
 pub mod incrbyfloat;
 pub mod info;
+pub mod json;
 pub mod keys;
 pub mod lcs;
+pub mod lolwut;
 pub mod memory;
 pub mod mget;
 pub mod module;
 pub mod mset;
+pub mod msetex;
 pub mod msetnx;
+pub mod multi;
 pub mod object;
 pub mod ping;
+pub mod randomkey;
+pub mod role;
 pub mod scan;
 pub mod select;
+pub mod sentinel;
 pub mod set;
 pub mod setnx;
 pub mod setrange;
 pub mod strlen;
 pub mod ttl;
+pub mod ttlscan;
 pub mod type_;
+pub mod wait;
+pub mod waitaof;
 
 use bytes::Bytes;
 use std::{str, vec};
-use strum_macros::VariantNames;
 use thiserror::Error as ThisError;
 
 use crate::commands::executable::Executable;
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Number, Store};
 
 use append::Append;
+use cas::{Cas, Gets};
 use client::Client;
+use cluster::Cluster;
 use command::Command as Command_;
 use config::Config;
+use coverage::Coverage;
 use dbsize::DBSize;
+use debug::Debug;
 use decr::Decr;
 use decrby::DecrBy;
 use del::Del;
+use echo::Echo;
 use exists::Exists;
+use expire::{Expire, Expireat, Pexpire, Pexpireat};
 use get::Get;
 use getdel::Getdel;
+use getex::Getex;
 use getrange::Getrange;
+use hdel::Hdel;
+use hello::Hello;
+use hexists::Hexists;
+use hget::Hget;
+use hgetall::Hgetall;
+use hkeys::Hkeys;
+use hlen::Hlen;
+use hmget::Hmget;
+use hset::Hset;
+use hsetnx::Hsetnx;
+use hstrlen::Hstrlen;
+use hvals::Hvals;
 use incr::Incr;
 use incrby::IncrBy;
 use incrbyfloat::IncrByFloat;
 use info::Info;
+use json::{JsonGet, JsonSet};
 use keys::Keys;
 use lcs::Lcs;
+use lolwut::Lolwut;
 use memory::Memory;
 use mget::Mget;
 use module::Module;
 use mset::Mset;
+use msetex::Msetex;
 use msetnx::Msetnx;
+use multi::{Discard, Exec, Multi, Unwatch, Watch};
 use object::Object;
 use ping::Ping;
+use randomkey::Randomkey;
+use role::Role;
 use scan::Scan;
 use select::Select;
+use sentinel::Sentinel;
 use set::Set;
 use setnx::Setnx;
 use setrange::Setrange;
 use strlen::Strlen;
-use ttl::Ttl;
+use ttl::{Pttl, Ttl};
+use ttlscan::Ttlscan;
 use type_::Type;
+use wait::Wait;
+use waitaof::Waitaof;
+
+/// Declares every command rustdis implements. From the list below it generates the `Command`
+/// enum, the dispatch of `exec` and `TryFrom<Frame>`, and `Command::TABLE`, the metadata each
+/// command is described with in the COMMAND reply. Adding a command only takes a new entry here,
+/// besides its module.
+///
+/// The metadata follows Redis: `arity` counts the command name, a negative arity means at least
+/// that many arguments, and `keys` holds the position of the first key, the last key, where -1
+/// means the last argument, and the step between keys. Commands are declared within the group
+/// Redis documents them in. `unsupported` lists the options and subcommands Redis has that the
+/// command doesn't implement, see RUSTDIS.COVERAGE.
+///
+/// Ref: <https://redis.io/docs/latest/commands/command/>
+macro_rules! commands {
+    ($(
+        group $group:literal {
+            $(
+                #[command(
+                    name = $name:literal,
+                    arity = $arity:literal,
+                    flags = [$($flag:literal),*],
+                    keys = ($first_key:literal, $last_key:literal, $step:literal)
+                    $(, unsupported = [$($unsupported:literal),*])?
+                )]
+                $variant:ident($cmd:ty),
+            )*
+        }
+    )*) => {
+        #[derive(Debug, PartialEq)]
+        pub enum Command {
+            $($($variant($cmd),)*)*
+        }
+
+        impl Command {
+            /// Metadata of every command, in declaration order.
+            pub const TABLE: &'static [CommandInfo] = &[$($(
+                CommandInfo {
+                    name: $name,
+                    arity: $arity,
+                    flags: &[$($flag),*],
+                    first_key: $first_key,
+                    last_key: $last_key,
+                    step: $step,
+                    group: $group,
+                    unsupported: &[$($($unsupported),*)?],
+                },
+            )*)*];
+
+            /// The lowercase name of the command.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($(Command::$variant(_) => $name,)*)*
+                }
+            }
+
+            fn parse(name: &str, parser: &mut CommandParser) -> Result<Command, RustdisError> {
+                match name {
+                    $($($name => <$cmd>::try_from(parser).map(Command::$variant),)*)*
+                    _ => Err(CommandParserError::UnknownCommand {
+                        command: name.to_string(),
+                    }
+                    .into()),
+                }
+            }
+        }
 
-#[derive(Debug, PartialEq, VariantNames)]
-pub enum Command {
-    Append(Append),
-    DBsize(DBSize),
-    Decr(Decr),
-    DecrBy(DecrBy),
-    Del(Del),
-    Exists(Exists),
-    Get(Get),
-    Getdel(Getdel),
-    Getrange(Getrange),
-    Incr(Incr),
-    IncrBy(IncrBy),
-    IncrByFloat(IncrByFloat),
-    Keys(Keys),
-    Lcs(Lcs),
-    Memory(Memory),
-    Mget(Mget),
-    Mset(Mset),
-    Msetnx(Msetnx),
-    Object(Object),
-    Scan(Scan),
-    Set(Set),
-    Setnx(Setnx),
-    Setrange(Setrange),
-    Strlen(Strlen),
-    Ttl(Ttl),
-    Type(Type),
-
-    Client(Client),
-    Command(Command_),
-    Config(Config),
-    Info(Info),
-    Module(Module),
-    Ping(Ping),
-    Select(Select),
+        impl Executable for Command {
+            fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+                match self {
+                    $($(Command::$variant(cmd) => cmd.exec(store),)*)*
+                }
+            }
+        }
+    };
 }
 
-impl Executable for Command {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        match self {
-            Command::Append(cmd) => cmd.exec(store),
-            Command::Client(cmd) => cmd.exec(store),
-            Command::Command(cmd) => cmd.exec(store),
-            Command::Config(cmd) => cmd.exec(store),
-            Command::DBsize(cmd) => cmd.exec(store),
-            Command::Decr(cmd) => cmd.exec(store),
-            Command::DecrBy(cmd) => cmd.exec(store),
-            Command::Del(cmd) => cmd.exec(store),
-            Command::Exists(cmd) => cmd.exec(store),
-            Command::Get(cmd) => cmd.exec(store),
-            Command::Getdel(cmd) => cmd.exec(store),
-            Command::Getrange(cmd) => cmd.exec(store),
-            Command::Incr(cmd) => cmd.exec(store),
-            Command::IncrBy(cmd) => cmd.exec(store),
-            Command::IncrByFloat(cmd) => cmd.exec(store),
-            Command::Info(cmd) => cmd.exec(store),
-            Command::Keys(cmd) => cmd.exec(store),
-            Command::Lcs(cmd) => cmd.exec(store),
-            Command::Memory(cmd) => cmd.exec(store),
-            Command::Mget(cmd) => cmd.exec(store),
-            Command::Module(cmd) => cmd.exec(store),
-            Command::Mset(cmd) => cmd.exec(store),
-            Command::Msetnx(cmd) => cmd.exec(store),
-            Command::Object(cmd) => cmd.exec(store),
-            Command::Ping(cmd) => cmd.exec(store),
-            Command::Scan(cmd) => cmd.exec(store),
-            Command::Select(cmd) => cmd.exec(store),
-            Command::Set(cmd) => cmd.exec(store),
-            Command::Setnx(cmd) => cmd.exec(store),
-            Command::Setrange(cmd) => cmd.exec(store),
-            Command::Strlen(cmd) => cmd.exec(store),
-            Command::Ttl(cmd) => cmd.exec(store),
-            Command::Type(cmd) => cmd.exec(store),
+commands! {
+    group "string" {
+        #[command(
+            name = "append",
+            arity = 3,
+            flags = ["write", "denyoom", "fast"],
+            keys = (1, 1, 1)
+        )]
+        Append(Append),
+        #[command(name = "decr", arity = 2, flags = ["write", "denyoom", "fast"], keys = (1, 1, 1))]
+        Decr(Decr),
+        #[command(
+            name = "decrby",
+            arity = 3,
+            flags = ["write", "denyoom", "fast"],
+            keys = (1, 1, 1)
+        )]
+        DecrBy(DecrBy),
+        #[command(name = "get", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Get(Get),
+        #[command(name = "getdel", arity = 2, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Getdel(Getdel),
+        #[command(name = "getex", arity = -2, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Getex(Getex),
+        #[command(name = "getrange", arity = 4, flags = ["readonly"], keys = (1, 1, 1))]
+        Getrange(Getrange),
+        #[command(name = "incr", arity = 2, flags = ["write", "denyoom", "fast"], keys = (1, 1, 1))]
+        Incr(Incr),
+        #[command(
+            name = "incrby",
+            arity = 3,
+            flags = ["write", "denyoom", "fast"],
+            keys = (1, 1, 1)
+        )]
+        IncrBy(IncrBy),
+        #[command(
+            name = "incrbyfloat",
+            arity = 3,
+            flags = ["write", "denyoom", "fast"],
+            keys = (1, 1, 1)
+        )]
+        IncrByFloat(IncrByFloat),
+        #[command(
+            name = "lcs",
+            arity = -3,
+            flags = ["readonly"],
+            keys = (1, 2, 1),
+            unsupported = ["IDX", "MINMATCHLEN", "WITHMATCHLEN"]
+        )]
+        Lcs(Lcs),
+        #[command(name = "mget", arity = -2, flags = ["readonly", "fast"], keys = (1, -1, 1))]
+        Mget(Mget),
+        #[command(name = "mset", arity = -3, flags = ["write", "denyoom"], keys = (1, -1, 2))]
+        Mset(Mset),
+        #[command(name = "msetnx", arity = -3, flags = ["write", "denyoom"], keys = (1, -1, 2))]
+        Msetnx(Msetnx),
+        #[command(name = "set", arity = -3, flags = ["write", "denyoom"], keys = (1, 1, 1))]
+        Set(Set),
+        #[command(
+            name = "setnx",
+            arity = 3,
+            flags = ["write", "denyoom", "fast"],
+            keys = (1, 1, 1)
+        )]
+        Setnx(Setnx),
+        #[command(name = "setrange", arity = 4, flags = ["write", "denyoom"], keys = (1, 1, 1))]
+        Setrange(Setrange),
+        #[command(name = "strlen", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Strlen(Strlen),
+        // Extension commands, namespaced with the `rustdis.` prefix so they never clash with
+        // Redis.
+        #[command(name = "rustdis.cas", arity = 4, flags = ["write", "denyoom"], keys = (1, 1, 1))]
+        Cas(Cas),
+        #[command(name = "rustdis.gets", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Gets(Gets),
+        #[command(name = "rustdis.jsonget", arity = -2, flags = ["readonly"], keys = (1, 1, 1))]
+        JsonGet(JsonGet),
+        #[command(name = "rustdis.jsonset", arity = 4, flags = ["write", "denyoom"], keys = (1, 1, 1))]
+        JsonSet(JsonSet),
+        #[command(
+            name = "rustdis.msetex",
+            arity = -4,
+            flags = ["write", "denyoom"],
+            keys = (1, -1, 3)
+        )]
+        Msetex(Msetex),
+    }
+
+    group "generic" {
+        #[command(name = "del", arity = -2, flags = ["write"], keys = (1, -1, 1))]
+        Del(Del),
+        #[command(name = "exists", arity = -2, flags = ["readonly", "fast"], keys = (1, -1, 1))]
+        Exists(Exists),
+        #[command(name = "expire", arity = -3, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Expire(Expire),
+        #[command(name = "expireat", arity = -3, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Expireat(Expireat),
+        #[command(name = "keys", arity = 2, flags = ["readonly"], keys = (0, 0, 0))]
+        Keys(Keys),
+        #[command(
+            name = "object",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = ["FREQ", "IDLETIME", "REFCOUNT"]
+        )]
+        Object(Object),
+        #[command(name = "pexpire", arity = -3, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Pexpire(Pexpire),
+        #[command(name = "pexpireat", arity = -3, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Pexpireat(Pexpireat),
+        #[command(name = "pttl", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Pttl(Pttl),
+        #[command(name = "randomkey", arity = 1, flags = ["readonly"], keys = (0, 0, 0))]
+        Randomkey(Randomkey),
+        #[command(name = "scan", arity = -2, flags = ["readonly"], keys = (0, 0, 0))]
+        Scan(Scan),
+        #[command(name = "ttl", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Ttl(Ttl),
+        #[command(name = "type", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Type(Type),
+        #[command(name = "wait", arity = 3, flags = [], keys = (0, 0, 0))]
+        Wait(Wait),
+        #[command(name = "waitaof", arity = 4, flags = [], keys = (0, 0, 0))]
+        Waitaof(Waitaof),
+        // Extension commands, namespaced with the `rustdis.` prefix so they never clash with
+        // Redis.
+        #[command(name = "rustdis.ttlscan", arity = -3, flags = ["readonly"], keys = (0, 0, 0))]
+        Ttlscan(Ttlscan),
+    }
+
+    group "server" {
+        #[command(
+            name = "command",
+            arity = -1,
+            flags = ["loading", "stale"],
+            keys = (0, 0, 0),
+            unsupported = ["COUNT", "GETKEYS", "GETKEYSANDFLAGS", "INFO", "LIST"]
+        )]
+        Command(Command_),
+        #[command(
+            name = "config",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = ["RESETSTAT", "SET"]
+        )]
+        Config(Config),
+        #[command(name = "dbsize", arity = 1, flags = ["readonly", "fast"], keys = (0, 0, 0))]
+        DBsize(DBSize),
+        #[command(
+            name = "debug",
+            arity = -2,
+            flags = ["admin", "noscript", "loading", "stale"],
+            keys = (0, 0, 0),
+            unsupported = ["JMAP", "OBJECT", "POPULATE", "RELOAD"]
+        )]
+        Debug(Debug),
+        #[command(
+            name = "info",
+            arity = -1,
+            flags = ["loading", "stale"],
+            keys = (0, 0, 0),
+            unsupported = ["section"]
+        )]
+        Info(Info),
+        #[command(name = "lolwut", arity = -1, flags = ["readonly", "fast"], keys = (0, 0, 0))]
+        Lolwut(Lolwut),
+        #[command(
+            name = "memory",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = ["DOCTOR", "MALLOC-STATS", "PURGE", "STATS"]
+        )]
+        Memory(Memory),
+        #[command(
+            name = "module",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = ["LIST", "LOAD", "LOADEX", "UNLOAD"]
+        )]
+        Module(Module),
+        #[command(
+            name = "role",
+            arity = 1,
+            flags = ["noscript", "loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Role(Role),
+        // Extension commands, namespaced with the `rustdis.` prefix so they never clash with
+        // Redis.
+        #[command(
+            name = "rustdis.coverage",
+            arity = -1,
+            flags = ["loading", "stale"],
+            keys = (0, 0, 0)
+        )]
+        Coverage(Coverage),
+    }
+
+    group "connection" {
+        #[command(
+            name = "client",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = [
+                "GETNAME", "ID", "INFO", "LIST", "PAUSE", "SETINFO", "TRACKING", "UNPAUSE",
+                "KILL LADDR", "KILL MAXAGE", "KILL USER"
+            ]
+        )]
+        Client(Client),
+        #[command(name = "echo", arity = 2, flags = ["fast"], keys = (0, 0, 0))]
+        Echo(Echo),
+        #[command(
+            name = "hello",
+            arity = -1,
+            flags = ["noscript", "loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Hello(Hello),
+        #[command(name = "ping", arity = -1, flags = ["fast"], keys = (0, 0, 0))]
+        Ping(Ping),
+        #[command(
+            name = "select",
+            arity = 2,
+            flags = ["loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Select(Select),
+    }
+
+    group "transactions" {
+        #[command(
+            name = "discard",
+            arity = 1,
+            flags = ["noscript", "loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Discard(Discard),
+        #[command(
+            name = "exec",
+            arity = 1,
+            flags = ["noscript", "loading", "stale"],
+            keys = (0, 0, 0)
+        )]
+        Exec(Exec),
+        #[command(
+            name = "multi",
+            arity = 1,
+            flags = ["noscript", "loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Multi(Multi),
+        #[command(
+            name = "unwatch",
+            arity = 1,
+            flags = ["noscript", "loading", "stale", "fast"],
+            keys = (0, 0, 0)
+        )]
+        Unwatch(Unwatch),
+        #[command(
+            name = "watch",
+            arity = -2,
+            flags = ["noscript", "loading", "stale", "fast", "no_multi"],
+            keys = (1, -1, 1)
+        )]
+        Watch(Watch),
+    }
+
+    group "cluster" {
+        #[command(
+            name = "cluster",
+            arity = -2,
+            flags = [],
+            keys = (0, 0, 0),
+            unsupported = ["INFO", "MYID", "NODES", "SHARDS", "SLOTS"]
+        )]
+        Cluster(Cluster),
+    }
+
+    group "sentinel" {
+        #[command(
+            name = "sentinel",
+            arity = -2,
+            flags = ["admin"],
+            keys = (0, 0, 0),
+            unsupported = ["CKQUORUM", "FAILOVER", "MONITOR", "REMOVE", "RESET", "SET"]
+        )]
+        Sentinel(Sentinel),
+    }
+
+    group "hash" {
+        #[command(name = "hdel", arity = -3, flags = ["write", "fast"], keys = (1, 1, 1))]
+        Hdel(Hdel),
+        #[command(name = "hexists", arity = 3, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Hexists(Hexists),
+        #[command(name = "hget", arity = 3, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Hget(Hget),
+        #[command(name = "hgetall", arity = 2, flags = ["readonly"], keys = (1, 1, 1))]
+        Hgetall(Hgetall),
+        #[command(name = "hkeys", arity = 2, flags = ["readonly"], keys = (1, 1, 1))]
+        Hkeys(Hkeys),
+        #[command(name = "hlen", arity = 2, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Hlen(Hlen),
+        #[command(name = "hmget", arity = -3, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Hmget(Hmget),
+        #[command(name = "hset", arity = -4, flags = ["write", "denyoom", "fast"], keys = (1, 1, 1))]
+        Hset(Hset),
+        #[command(name = "hsetnx", arity = 4, flags = ["write", "denyoom", "fast"], keys = (1, 1, 1))]
+        Hsetnx(Hsetnx),
+        #[command(name = "hstrlen", arity = 3, flags = ["readonly", "fast"], keys = (1, 1, 1))]
+        Hstrlen(Hstrlen),
+        #[command(name = "hvals", arity = 2, flags = ["readonly"], keys = (1, 1, 1))]
+        Hvals(Hvals),
+    }
+}
+
+/// Describes a command like the COMMAND reply of Redis does, see `commands!`.
+#[derive(Debug, PartialEq)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+    /// The group Redis documents the command in, like `string` or `generic`.
+    pub group: &'static str,
+    /// The options and subcommands of the Redis command this one doesn't implement.
+    pub unsupported: &'static [&'static str],
+}
+
+impl CommandInfo {
+    pub fn lookup(name: &str) -> Option<&'static CommandInfo> {
+        Command::TABLE.iter().find(|info| info.name == name)
+    }
+
+    /// The command `frame` calls, when it is a known command sent with a number of arguments it
+    /// accepts. Transactions queue such commands even when their arguments are invalid.
+    pub fn of_frame(frame: &Frame) -> Option<&'static CommandInfo> {
+        let Frame::Array(frames) = frame else {
+            return None;
+        };
+        let name = match frames.first()? {
+            Frame::Simple(s) => s.to_lowercase(),
+            Frame::Bulk(bytes) => str::from_utf8(bytes).ok()?.to_lowercase(),
+            _ => return None,
+        };
+
+        CommandInfo::lookup(&name).filter(|info| info.accepts(frames.len()))
+    }
+
+    /// Whether the command may modify the keyspace. Features depending on what a command does to
+    /// the data, like serving reads from a replica or pausing writes, tell commands apart with it
+    /// and `is_readonly`.
+    pub fn is_write(&self) -> bool {
+        self.flags.contains(&"write")
+    }
+
+    /// Whether the command only reads keys. Commands that don't touch the keyspace, like PING,
+    /// are neither read-only nor write commands.
+    pub fn is_readonly(&self) -> bool {
+        self.flags.contains(&"readonly")
+    }
+
+    /// Whether the command is executed while the dataset is loaded at startup, like INFO. The
+    /// others are replied to with a LOADING error. PING is allowed too, so clients and health
+    /// checks can tell the server is up.
+    pub fn is_allowed_while_loading(&self) -> bool {
+        self.flags.contains(&"loading") || self.name == "ping"
+    }
+
+    /// Whether the command can be queued in a transaction. The others, like WATCH, are rejected
+    /// within one, which aborts it.
+    pub fn is_allowed_in_transaction(&self) -> bool {
+        !self.flags.contains(&"no_multi")
+    }
+
+    /// Whether the command accepts `argc` arguments, counting its name.
+    pub fn accepts(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity < 0 {
+            argc >= -self.arity
+        } else {
+            argc == self.arity
         }
     }
 }
 
+impl Command {
+    /// The metadata of the command, see `commands!`.
+    pub fn info(&self) -> &'static CommandInfo {
+        CommandInfo::lookup(self.name()).expect("every command is described in the table")
+    }
+}
+
 impl TryFrom<Frame> for Command {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(frame: Frame) -> Result<Self, Self::Error> {
         // Clients send commands to the Redis server as RESP arrays.
@@ -171,54 +625,46 @@ impl TryFrom<Frame> for Command {
             }
         };
 
+        let argc = frames.len();
         let parser = &mut CommandParser {
             parts: frames.into_iter(),
         };
 
         let command_name = parser.parse_command_name()?;
 
-        match &command_name[..] {
-            "append" => Append::try_from(parser).map(Command::Append),
-            "client" => Client::try_from(parser).map(Command::Client),
-            "command" => Command_::try_from(parser).map(Command::Command),
-            "config" => Config::try_from(parser).map(Command::Config),
-            "dbsize" => DBSize::try_from(parser).map(Command::DBsize),
-            "decr" => Decr::try_from(parser).map(Command::Decr),
-            "decrby" => DecrBy::try_from(parser).map(Command::DecrBy),
-            "del" => Del::try_from(parser).map(Command::Del),
-            "exists" => Exists::try_from(parser).map(Command::Exists),
-            "get" => Get::try_from(parser).map(Command::Get),
-            "getdel" => Getdel::try_from(parser).map(Command::Getdel),
-            "getrange" => Getrange::try_from(parser).map(Command::Getrange),
-            "incr" => Incr::try_from(parser).map(Command::Incr),
-            "incrby" => IncrBy::try_from(parser).map(Command::IncrBy),
-            "incrbyfloat" => IncrByFloat::try_from(parser).map(Command::IncrByFloat),
-            "info" => Info::try_from(parser).map(Command::Info),
-            "keys" => Keys::try_from(parser).map(Command::Keys),
-            "lcs" => Lcs::try_from(parser).map(Command::Lcs),
-            "memory" => Memory::try_from(parser).map(Command::Memory),
-            "mget" => Mget::try_from(parser).map(Command::Mget),
-            "module" => Module::try_from(parser).map(Command::Module),
-            "mset" => Mset::try_from(parser).map(Command::Mset),
-            "msetnx" => Msetnx::try_from(parser).map(Command::Msetnx),
-            "object" => Object::try_from(parser).map(Command::Object),
-            "ping" => Ping::try_from(parser).map(Command::Ping),
-            "scan" => Scan::try_from(parser).map(Command::Scan),
-            "select" => Select::try_from(parser).map(Command::Select),
-            "set" => Set::try_from(parser).map(Command::Set),
-            "setnx" => Setnx::try_from(parser).map(Command::Setnx),
-            "setrange" => Setrange::try_from(parser).map(Command::Setrange),
-            "strlen" => Strlen::try_from(parser).map(Command::Strlen),
-            "ttl" => Ttl::try_from(parser).map(Command::Ttl),
-            "type" => Type::try_from(parser).map(Command::Type),
-            _ => Err(CommandParserError::UnknownCommand {
-                command: command_name,
+        if let Some(info) = CommandInfo::lookup(&command_name) {
+            if !info.accepts(argc) {
+                return Err(RustdisError::command(
+                    ErrorKind::Err,
+                    format!("wrong number of arguments for '{}' command", info.name),
+                ));
             }
-            .into()),
         }
+
+        Command::parse(&command_name, parser)
     }
 }
 
+/// The error Redis replies with when the options of a command are not valid together.
+fn syntax_error() -> RustdisError {
+    RustdisError::command(ErrorKind::Err, "syntax error")
+}
+
+/// Parses the next argument as the increment of the INCR family of commands, with the error
+/// Redis replies with when it is not a number, see `Number`.
+fn next_number<T: Number>(parser: &mut CommandParser) -> Result<T, RustdisError> {
+    let arg = match parser.parts.as_slice().first() {
+        // Integer frames are accepted, like `CommandParser::next_integer` does.
+        Some(Frame::Integer(i)) => {
+            let arg = i.to_string();
+            parser.parts.next();
+            arg
+        }
+        _ => parser.next_string()?,
+    };
+    T::parse(&arg).ok_or_else(|| RustdisError::command(ErrorKind::Err, T::INVALID))
+}
+
 struct CommandParser {
     parts: vec::IntoIter<Frame>,
 }
@@ -292,36 +738,6 @@ impl CommandParser {
         }
     }
 
-    fn next_float(&mut self) -> Result<f64, CommandParserError> {
-        let frame = self
-            .parts
-            .next()
-            .ok_or_else(|| CommandParserError::EndOfStream)?;
-
-        match frame {
-            Frame::Integer(i) => Ok(i as f64),
-            Frame::Simple(string) => {
-                string
-                    .parse::<f64>()
-                    .map_err(|_| CommandParserError::InvalidFrame {
-                        expected: "parseable f64 frame".to_string(),
-                        actual: Frame::Simple(string),
-                    })
-            }
-            Frame::Bulk(bytes) => str::from_utf8(&bytes[..])
-                .map_err(CommandParserError::InvalidUTF8String)?
-                .parse::<f64>()
-                .map_err(|_| CommandParserError::InvalidFrame {
-                    expected: "parseable f64 frame".to_string(),
-                    actual: Frame::Bulk(bytes),
-                }),
-            frame => Err(CommandParserError::InvalidFrame {
-                expected: "float".to_string(),
-                actual: frame,
-            }),
-        }
-    }
-
     fn next_bytes(&mut self) -> Result<Bytes, CommandParserError> {
         let frame = self
             .parts
@@ -347,8 +763,6 @@ pub(crate) enum CommandParserError {
     InvalidFrame { expected: String, actual: Frame },
     #[error("protocol error; unknown command {command}")]
     UnknownCommand { command: String },
-    #[error("protocol error; invalid command argument {command} {argument}")]
-    InvalidCommandArgument { command: String, argument: String },
     #[error("protocol error; invalid UTF-8 string")]
     InvalidUTF8String(#[from] str::Utf8Error),
     #[error("protocol error; attempting to extract a value failed due to the frame being fully consumed")]
@@ -407,7 +821,10 @@ mod tests {
             set_command,
             Command::Set(Set {
                 key: String::from("foo"),
-                value: Bytes::from("baz")
+                value: Bytes::from("baz"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
 
@@ -423,7 +840,10 @@ mod tests {
             set_command,
             Command::Set(Set {
                 key: String::from("foo"),
-                value: Bytes::from("baz")
+                value: Bytes::from("baz"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
 
@@ -439,7 +859,10 @@ mod tests {
             set_command,
             Command::Set(Set {
                 key: String::from("foo"),
-                value: Bytes::from("baz")
+                value: Bytes::from("baz"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
 
@@ -455,8 +878,95 @@ mod tests {
             set_command,
             Command::Set(Set {
                 key: String::from("foo"),
-                value: Bytes::from("baz")
+                value: Bytes::from("baz"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
     }
+
+    fn command_frame(name: &str, argc: usize) -> Frame {
+        let mut args = vec![Frame::Bulk(Bytes::from(name.to_uppercase()))];
+        args.resize(argc, Frame::Bulk(Bytes::from("0")));
+        Frame::Array(args)
+    }
+
+    /// Every command of the table is dispatched to its own parser, and calls with a number of
+    /// arguments its arity doesn't allow are rejected before reaching it.
+    #[test]
+    fn every_command_follows_its_metadata() {
+        let wrong_arity =
+            |name: &str| format!("ERR wrong number of arguments for '{}' command", name);
+
+        for info in Command::TABLE {
+            assert_eq!(CommandInfo::lookup(info.name), Some(info));
+            assert_eq!(info.name, info.name.to_lowercase());
+
+            let argc = info.arity.unsigned_abs() as usize;
+            match Command::try_from(command_frame(info.name, argc)) {
+                Ok(cmd) => assert_eq!(cmd.name(), info.name),
+                // Placeholder arguments may not be valid, or name an unknown subcommand, as long
+                // as the command's own parser rejected them.
+                Err(err) => {
+                    let err = err.to_string();
+                    assert_ne!(
+                        err,
+                        format!("protocol error; unknown command {}", info.name)
+                    );
+                    assert_ne!(err, wrong_arity(info.name));
+                }
+            }
+
+            if argc > 1 {
+                let err = Command::try_from(command_frame(info.name, argc - 1)).unwrap_err();
+                assert_eq!(err.to_string(), wrong_arity(info.name));
+            }
+
+            if info.arity > 0 {
+                let err = Command::try_from(command_frame(info.name, argc + 1)).unwrap_err();
+                assert_eq!(err.to_string(), wrong_arity(info.name));
+            }
+        }
+    }
+
+    #[test]
+    fn commands_reading_or_writing_keys() {
+        for info in Command::TABLE {
+            assert!(!(info.is_write() && info.is_readonly()), "{}", info.name);
+            // WATCH only records the version of its keys, like in Redis it neither reads nor
+            // writes them.
+            if info.first_key > 0 && info.name != "watch" {
+                assert!(info.is_write() || info.is_readonly(), "{}", info.name);
+            }
+        }
+
+        let get = Command::try_from(command_frame("get", 2)).unwrap();
+        assert!(get.info().is_readonly());
+
+        let set = Command::try_from(command_frame("set", 3)).unwrap();
+        assert!(set.info().is_write());
+
+        let ping = Command::try_from(command_frame("ping", 1)).unwrap();
+        assert!(!ping.info().is_write() && !ping.info().is_readonly());
+    }
+
+    #[test]
+    fn commands_allowed_while_loading() {
+        for name in ["info", "ping", "command", "select"] {
+            let info = CommandInfo::lookup(name).unwrap();
+            assert!(info.is_allowed_while_loading(), "{}", name);
+        }
+        for name in ["get", "set", "keys", "client"] {
+            let info = CommandInfo::lookup(name).unwrap();
+            assert!(!info.is_allowed_while_loading(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn unknown_command() {
+        let err = Command::try_from(command_frame("nope", 1)).unwrap_err();
+        assert!(matches!(err, RustdisError::Command { .. }));
+        assert_eq!(err.to_string(), "ERR protocol error; unknown command nope");
+    }
 }
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Module management commands.
 ///
@@ -13,13 +13,13 @@ use crate::Error;
 pub struct Module;
 
 impl Executable for Module {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
         Ok(Frame::Simple("OK".to_string()))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Module {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
         Ok(Self)
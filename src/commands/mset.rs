@@ -2,9 +2,9 @@ use bytes::Bytes;
 
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{NewValue, Store};
 
 /// Sets the given keys to their respective values. Replaces existing values with new values.
 ///
@@ -15,25 +15,25 @@ pub struct Mset {
 }
 
 impl Executable for Mset {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         if self.pairs.is_empty() {
             return Ok(Frame::Error(
                 "ERR wrong number of arguments for command".to_string(),
             ));
         }
 
-        let mut store = store.lock();
-
-        for (key, value) in self.pairs.iter() {
-            store.set(key.to_string(), value.clone());
-        }
+        store.set_many(
+            self.pairs
+                .into_iter()
+                .map(|(key, data)| (key, NewValue { data, ttl: None })),
+        );
 
         Ok(Frame::Simple("OK".to_string()))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Mset {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let mut pairs = vec![];
@@ -150,20 +150,14 @@ mod tests {
         assert_eq!(store.lock().get("key1").unwrap(), Bytes::from("value1"));
     }
 
-    #[tokio::test]
-    async fn no_keys() {
-        let store = Store::new();
-
+    #[test]
+    fn no_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("MSET"))]);
-        let cmd = Command::try_from(frame).unwrap();
-
-        assert_eq!(cmd, Command::Mset(Mset { pairs: vec![] }));
-
-        let res = cmd.exec(store.clone()).unwrap();
+        let err = Command::try_from(frame).err().unwrap();
 
         assert_eq!(
-            res,
-            Frame::Error("ERR wrong number of arguments for command".to_string())
+            err.to_string(),
+            "ERR wrong number of arguments for 'mset' command"
         );
     }
 }
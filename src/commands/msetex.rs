@@ -0,0 +1,168 @@
+use bytes::Bytes;
+use tokio::time::Duration;
+
+use crate::commands::executable::Executable;
+use crate::commands::expiration::Expiration;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::{NewValue, Store};
+
+/// Sets the given keys to their respective values, each with its own time to live in seconds, all
+/// at once: no client ever sees some of the keys set and not the others. Handy to warm up a cache
+/// with a single command.
+///
+/// ```text
+/// RUSTDIS.MSETEX key value seconds [key value seconds ...]
+/// ```
+///
+/// Each time to live is validated like the `EX` option of SET, and no key is set when one of them
+/// is invalid.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command.
+#[derive(Debug, PartialEq)]
+pub struct Msetex {
+    pub entries: Vec<(String, Bytes, Duration)>,
+}
+
+impl Executable for Msetex {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        store.set_many(self.entries.into_iter().map(|(key, data, ttl)| {
+            (
+                key,
+                NewValue {
+                    data,
+                    ttl: Some(ttl),
+                },
+            )
+        }));
+
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Msetex {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let mut entries = vec![];
+
+        loop {
+            let key = match parser.next_string() {
+                Ok(key) => key,
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            let (value, seconds) = match (parser.next_bytes(), parser.next_string()) {
+                (Ok(value), Ok(seconds)) => (value, seconds),
+                (Err(CommandParserError::EndOfStream), _)
+                | (_, Err(CommandParserError::EndOfStream)) => {
+                    return Err(RustdisError::command(
+                        ErrorKind::Err,
+                        "wrong number of arguments for 'rustdis.msetex' command",
+                    ))
+                }
+                (Err(err), _) | (_, Err(err)) => return Err(err.into()),
+            };
+
+            let Expiration::Ex(seconds) = Expiration::parse("EX", &seconds, "rustdis.msetex")?
+            else {
+                unreachable!("EX is parsed as seconds");
+            };
+
+            entries.push((key, value, Duration::from_secs(seconds)));
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn parse(args: &[&str]) -> Result<Command, RustdisError> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        Command::try_from(frame)
+    }
+
+    #[tokio::test]
+    async fn multiple_keys() {
+        time::pause();
+
+        let store = Store::new();
+
+        let cmd = parse(&["RUSTDIS.MSETEX", "key1", "1", "10", "key2", "2", "20"]).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Msetex(Msetex {
+                entries: vec![
+                    (
+                        String::from("key1"),
+                        Bytes::from("1"),
+                        Duration::from_secs(10)
+                    ),
+                    (
+                        String::from("key2"),
+                        Bytes::from("2"),
+                        Duration::from_secs(20)
+                    ),
+                ]
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Simple("OK".to_string()));
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("1")));
+        assert_eq!(store.lock().get("key2"), Some(Bytes::from("2")));
+
+        time::advance(Duration::from_secs(10)).await;
+        time::sleep(Duration::from_millis(1)).await;
+
+        assert_eq!(store.lock().get("key1"), None);
+        assert_eq!(store.lock().get("key2"), Some(Bytes::from("2")));
+
+        time::advance(Duration::from_secs(10)).await;
+        time::sleep(Duration::from_millis(1)).await;
+
+        assert_eq!(store.lock().get("key2"), None);
+    }
+
+    #[test]
+    fn incomplete_entry() {
+        let err = parse(&["RUSTDIS.MSETEX", "key1", "1", "10", "key2", "2"]).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'rustdis.msetex' command"
+        );
+    }
+
+    #[test]
+    fn invalid_ttl() {
+        let err = parse(&["RUSTDIS.MSETEX", "key1", "1", "10", "key2", "2", "0"]).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR invalid expire time in 'rustdis.msetex' command"
+        );
+
+        let err = parse(&["RUSTDIS.MSETEX", "key1", "1", "soon"]).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+}
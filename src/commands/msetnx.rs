@@ -2,9 +2,9 @@ use bytes::Bytes;
 
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{NewValue, Store};
 
 /// Sets the given keys to their respective values.
 /// Will not perform any operation at all even if just a single key already exists.
@@ -20,7 +20,7 @@ pub struct Msetnx {
 }
 
 impl Executable for Msetnx {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         if self.pairs.is_empty() {
             return Ok(Frame::Error(
                 "ERR wrong number of arguments for command".to_string(),
@@ -39,16 +39,20 @@ impl Executable for Msetnx {
             }
         }
 
-        for (key, value) in self.pairs.iter() {
-            store.set(key.to_string(), value.clone());
-        }
+        // No key has a time to live, so there is no need to wake up the task removing expired
+        // keys like `InnerStore::set_many` does.
+        store.set_many(
+            self.pairs
+                .into_iter()
+                .map(|(key, data)| (key, NewValue { data, ttl: None })),
+        );
 
         Ok(Frame::Integer(1))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Msetnx {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let mut pairs = vec![];
@@ -165,20 +169,14 @@ mod tests {
         assert_eq!(store.lock().get("key1").unwrap(), Bytes::from("1"));
     }
 
-    #[tokio::test]
-    async fn no_keys() {
-        let store = Store::new();
-
+    #[test]
+    fn no_keys() {
         let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("MSETNX"))]);
-        let cmd = Command::try_from(frame).unwrap();
-
-        assert_eq!(cmd, Command::Msetnx(Msetnx { pairs: vec![] }));
-
-        let res = cmd.exec(store.clone()).unwrap();
+        let err = Command::try_from(frame).err().unwrap();
 
         assert_eq!(
-            res,
-            Frame::Error("ERR wrong number of arguments for command".to_string())
+            err.to_string(),
+            "ERR wrong number of arguments for 'msetnx' command"
         );
     }
 }
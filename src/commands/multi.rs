@@ -0,0 +1,187 @@
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+use crate::transaction::Transaction;
+
+/// Marks the start of a transaction block. Subsequent commands are queued for atomic execution
+/// using EXEC.
+///
+/// The transaction is connection state, the server keeps it and queues the commands, see
+/// `Transaction`. Executed on their own, MULTI, EXEC and DISCARD reply like outside of a
+/// transaction.
+///
+/// Ref: <https://redis.io/docs/latest/commands/multi/>
+#[derive(Debug, PartialEq)]
+pub struct Multi;
+
+/// Executes all previously queued commands in a transaction and restores the connection state to
+/// normal, replying with the reply of each command.
+///
+/// Ref: <https://redis.io/docs/latest/commands/exec/>
+#[derive(Debug, PartialEq)]
+pub struct Exec;
+
+/// Flushes all previously queued commands in a transaction and restores the connection state to
+/// normal.
+///
+/// Ref: <https://redis.io/docs/latest/commands/discard/>
+#[derive(Debug, PartialEq)]
+pub struct Discard;
+
+/// Marks the given keys to be watched for conditional execution of a transaction: EXEC discards
+/// the transaction when any of them is written before it, see `Watched`.
+///
+/// The watched keys are connection state, the server keeps them. Executed on their own, WATCH and
+/// UNWATCH do nothing.
+///
+/// Ref: <https://redis.io/docs/latest/commands/watch/>
+#[derive(Debug, PartialEq)]
+pub struct Watch {
+    pub keys: Vec<String>,
+}
+
+/// Flushes all the previously watched keys for a transaction.
+///
+/// Ref: <https://redis.io/docs/latest/commands/unwatch/>
+#[derive(Debug, PartialEq)]
+pub struct Unwatch;
+
+impl Executable for Multi {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Transaction::default().begin())
+    }
+}
+
+impl Executable for Exec {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let res = match Transaction::default().exec(false) {
+            Ok(_) => Frame::Array(vec![]),
+            Err(res) => res,
+        };
+        Ok(res)
+    }
+}
+
+impl Executable for Discard {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Transaction::default().discard())
+    }
+}
+
+impl Executable for Watch {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl Executable for Unwatch {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Simple("OK".to_string()))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Multi {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Exec {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Discard {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Watch {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let mut keys = vec![];
+        loop {
+            match parser.next_string() {
+                Ok(key) => keys.push(key),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { keys })
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Unwatch {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn parse(name: &str) -> Command {
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from(name.to_string()))]);
+        Command::try_from(frame).unwrap()
+    }
+
+    #[tokio::test]
+    async fn outside_of_a_transaction() {
+        let store = Store::new();
+
+        assert_eq!(parse("MULTI"), Command::Multi(Multi));
+        assert_eq!(parse("exec"), Command::Exec(Exec));
+        assert_eq!(parse("Discard"), Command::Discard(Discard));
+
+        assert_eq!(
+            parse("MULTI").exec(store.clone()).unwrap(),
+            Frame::Simple("OK".to_string())
+        );
+        assert_eq!(
+            parse("EXEC").exec(store.clone()).unwrap(),
+            Frame::Error("ERR EXEC without MULTI".to_string())
+        );
+        assert_eq!(
+            parse("DISCARD").exec(store.clone()).unwrap(),
+            Frame::Error("ERR DISCARD without MULTI".to_string())
+        );
+        assert_eq!(
+            parse("UNWATCH").exec(store.clone()).unwrap(),
+            Frame::Simple("OK".to_string())
+        );
+    }
+
+    #[test]
+    fn watch() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("WATCH")),
+            Frame::Bulk(Bytes::from("key1")),
+            Frame::Bulk(Bytes::from("key2")),
+        ]);
+
+        assert_eq!(
+            Command::try_from(frame).unwrap(),
+            Command::Watch(Watch {
+                keys: vec!["key1".to_string(), "key2".to_string()]
+            })
+        );
+    }
+}
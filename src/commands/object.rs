@@ -1,18 +1,44 @@
 use bytes::Bytes;
+use std::collections::HashMap;
 
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
+
+/// The most fields a hash reported as `listpack` holds, see `Encoding`.
+const LISTPACK_MAX_ENTRIES: usize = 128;
+
+/// The longest field or value, in bytes, a hash reported as `listpack` holds, see `Encoding`.
+const LISTPACK_MAX_VALUE: usize = 64;
 
 #[derive(Debug, PartialEq)]
 pub enum Object {
+    Age(Age),
     Encoding(Encoding),
 }
 
+/// Age returns the number of seconds since the key at <key> was created. Modifying a value in
+/// place, like APPEND does, keeps its age, replacing it, like SET does, resets it.
+///
+/// **NOTE**: this is a rustdis specific subcommand, meant to show how per-key metadata flows
+/// through the store.
+#[derive(Debug, PartialEq)]
+pub struct Age {
+    pub key: String,
+}
+
 /// Encoding returns the internal encoding for the Redis object stored at <key>.
 ///
+/// Hashes are reported as `listpack` while they are small enough for Redis to encode them so with
+/// its default `hash-max-listpack-entries` and `hash-max-listpack-value`, and as `hashtable`
+/// otherwise.
+///
+/// **NOTE**: strings are reported as `raw`, or `lz4` when the value is stored compressed, see
+/// `Config::value_compression_threshold`. Hashes are stored the same whatever their size, and a
+/// hash that shrinks is reported as `listpack` again, where Redis keeps it a `hashtable`.
+///
 /// Ref: <https://redis.io/docs/latest/commands/object-encoding>
 #[derive(Debug, PartialEq)]
 pub struct Encoding {
@@ -20,21 +46,26 @@ pub struct Encoding {
 }
 
 impl Executable for Object {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         match self {
+            Self::Age(age) => age.exec(store),
             Self::Encoding(encoding) => encoding.exec(store),
         }
     }
 }
 
 impl TryFrom<&mut CommandParser> for Object {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let sub_command = parser.next_string()?;
         let sub_command = sub_command.to_lowercase();
 
         match sub_command.as_str() {
+            "age" => {
+                let key = parser.next_string()?;
+                Ok(Self::Age(Age { key }))
+            }
             "encoding" => {
                 let key = parser.next_string()?;
                 Ok(Self::Encoding(Encoding { key }))
@@ -47,15 +78,131 @@ impl TryFrom<&mut CommandParser> for Object {
     }
 }
 
+impl Executable for Age {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let store = store.lock();
+        let res = match store.get_value(&self.key) {
+            Some(value) => Frame::Integer(value.age().as_secs() as i64),
+            None => Frame::NullBulkString,
+        };
+
+        Ok(res)
+    }
+}
+
 impl Executable for Encoding {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
-        let res = if store.exists(&self.key) {
-            Frame::Bulk(Bytes::from("raw"))
-        } else {
-            Frame::Null
+        let res = match store.get_value(&self.key) {
+            Some(value) if value.is_compressed() => Frame::Bulk(Bytes::from("lz4")),
+            Some(value) => match value.hash() {
+                Some(hash) if is_listpack(hash) => Frame::Bulk(Bytes::from("listpack")),
+                Some(_) => Frame::Bulk(Bytes::from("hashtable")),
+                None => Frame::Bulk(Bytes::from("raw")),
+            },
+            None => Frame::NullBulkString,
         };
 
         Ok(res)
     }
 }
+
+/// Whether Redis encodes `hash` as a listpack, see `Encoding`.
+fn is_listpack(hash: &HashMap<String, Bytes>) -> bool {
+    hash.len() <= LISTPACK_MAX_ENTRIES
+        && hash
+            .iter()
+            .all(|(field, value)| field.len().max(value.len()) <= LISTPACK_MAX_VALUE)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::{self, Duration};
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn object_age(key: &str) -> Command {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("OBJECT")),
+            Frame::Bulk(Bytes::from("AGE")),
+            Frame::Bulk(Bytes::from(key.to_string())),
+        ]);
+        Command::try_from(frame).unwrap()
+    }
+
+    #[tokio::test]
+    async fn age() {
+        time::pause();
+
+        let store = Store::new();
+
+        assert_eq!(
+            object_age("key1"),
+            Command::Object(Object::Age(Age {
+                key: String::from("key1")
+            }))
+        );
+        assert_eq!(
+            object_age("key1").exec(store.clone()).unwrap(),
+            Frame::NullBulkString
+        );
+
+        store.lock().set(String::from("key1"), Bytes::from("1"));
+        time::advance(Duration::from_secs(5)).await;
+
+        store
+            .lock()
+            .update_value(String::from("key1"), Bytes::from("2"));
+        assert_eq!(
+            object_age("key1").exec(store.clone()).unwrap(),
+            Frame::Integer(5)
+        );
+
+        store.lock().set(String::from("key1"), Bytes::from("3"));
+        assert_eq!(
+            object_age("key1").exec(store.clone()).unwrap(),
+            Frame::Integer(0)
+        );
+    }
+    #[tokio::test]
+    async fn encoding() {
+        let store = Store::new();
+        let encoding = |key: &str| {
+            let frame = Frame::Array(vec![
+                Frame::Bulk(Bytes::from("OBJECT")),
+                Frame::Bulk(Bytes::from("ENCODING")),
+                Frame::Bulk(Bytes::from(key.to_string())),
+            ]);
+            Command::try_from(frame)
+                .unwrap()
+                .exec(store.clone())
+                .unwrap()
+        };
+
+        assert_eq!(encoding("missing"), Frame::NullBulkString);
+
+        store.lock().set(String::from("string"), Bytes::from("1"));
+        assert_eq!(encoding("string"), Frame::Bulk(Bytes::from("raw")));
+
+        let field = |i: usize| (format!("field:{}", i), Bytes::from("1"));
+        store
+            .lock()
+            .hset(String::from("small"), (0..LISTPACK_MAX_ENTRIES).map(field))
+            .unwrap();
+        assert_eq!(encoding("small"), Frame::Bulk(Bytes::from("listpack")));
+
+        store
+            .lock()
+            .hset(String::from("small"), [field(LISTPACK_MAX_ENTRIES)])
+            .unwrap();
+        assert_eq!(encoding("small"), Frame::Bulk(Bytes::from("hashtable")));
+
+        let long_value = Bytes::from("x".repeat(LISTPACK_MAX_VALUE + 1));
+        store
+            .lock()
+            .hset(String::from("long"), [(String::from("field"), long_value)])
+            .unwrap();
+        assert_eq!(encoding("long"), Frame::Bulk(Bytes::from("hashtable")));
+    }
+}
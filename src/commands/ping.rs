@@ -2,12 +2,14 @@ use bytes::Bytes;
 
 use crate::commands::executable::Executable;
 use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Returns PONG if no argument is provided, otherwise return a copy of the argument as a bulk.
 ///
+/// **NOTE**: PING inside subscribe mode is not supported, since there is no pub/sub yet.
+///
 /// Ref: <https://redis.io/docs/latest/commands/ping>
 #[derive(Debug, PartialEq)]
 pub struct Ping {
@@ -15,17 +17,17 @@ pub struct Ping {
 }
 
 impl Executable for Ping {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
         let res = self
             .payload
-            .map_or(Frame::Bulk(Bytes::from("PONG")), Frame::Bulk);
+            .map_or(Frame::Simple("PONG".to_string()), Frame::Bulk);
 
         Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Ping {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let payload = match parser.next_bytes() {
@@ -37,3 +39,45 @@ impl TryFrom<&mut CommandParser> for Ping {
         Ok(Self { payload })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn without_payload() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("PING"))]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(cmd, Command::Ping(Ping { payload: None }));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Simple("PONG".to_string()));
+    }
+
+    #[tokio::test]
+    async fn with_payload() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("PING")),
+            Frame::Bulk(Bytes::from("hello world")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Ping(Ping {
+                payload: Some(Bytes::from("hello world"))
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("hello world")));
+    }
+}
@@ -0,0 +1,71 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns a random key from the currently selected database, or nil when the database is empty.
+///
+/// Ref: <https://redis.io/docs/latest/commands/randomkey/>
+#[derive(Debug, PartialEq)]
+pub struct Randomkey;
+
+impl Executable for Randomkey {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let res = match store.sample_keys(1, None).pop() {
+            Some(key) => Frame::Bulk(Bytes::from(key)),
+            None => Frame::NullBulkString,
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Randomkey {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn random_key() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("RANDOMKEY"))]);
+        let cmd = Command::try_from(frame.clone()).unwrap();
+
+        assert_eq!(cmd, Command::Randomkey(Randomkey));
+
+        let res = cmd.exec(store.clone()).unwrap();
+        assert_eq!(res, Frame::NullBulkString);
+
+        for key in ["key1", "key2", "key3"] {
+            store.lock().set(key.to_string(), Bytes::from("value"));
+        }
+
+        let mut seen = vec![];
+        for _ in 0..100 {
+            let res = Command::try_from(frame.clone())
+                .unwrap()
+                .exec(store.clone())
+                .unwrap();
+            match res {
+                Frame::Bulk(key) if !seen.contains(&key) => seen.push(key),
+                Frame::Bulk(_) => {}
+                frame => panic!("expected bulk frame, got {:?}", frame),
+            }
+        }
+        seen.sort();
+
+        assert_eq!(seen, ["key1", "key2", "key3"]);
+    }
+}
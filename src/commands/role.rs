@@ -0,0 +1,62 @@
+use bytes::Bytes;
+
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the replication role of the instance. A master replies with `master`, its replication
+/// offset and the list of its connected replicas, each as its IP, port and acknowledged offset.
+///
+/// **NOTE**: there is no replication, so the instance is always a master with an offset of 0 and
+/// no replicas, like the `# Replication` section of INFO shows.
+///
+/// Ref: <https://redis.io/docs/latest/commands/role/>
+#[derive(Debug, PartialEq)]
+pub struct Role;
+
+impl Executable for Role {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Array(vec![
+            Frame::Bulk(Bytes::from("master")),
+            Frame::Integer(0),
+            Frame::Array(vec![]),
+        ]))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Role {
+    type Error = RustdisError;
+
+    fn try_from(_parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn master() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![Frame::Bulk(Bytes::from("ROLE"))]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(cmd, Command::Role(Role));
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("master")),
+                Frame::Integer(0),
+                Frame::Array(vec![]),
+            ])
+        );
+    }
+}
@@ -1,45 +1,454 @@
 use bytes::Bytes;
-
-use std::{str, vec};
+use std::str;
 
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
+
+/// How many keys SCAN walks per call without `COUNT`, like Redis.
+const DEFAULT_COUNT: u64 = 10;
 
 /// The SCAN command is used in order to incrementally iterate over a collection of elements.
 ///
+/// See `ScanOptions` for the options it accepts.
+///
+/// Every key present during the whole iteration is returned, and the iteration ends, see
+/// `State::scan` for how keys are walked and how `COUNT` bounds the work of each call.
+///
 /// Ref: <https://redis.io/docs/latest/commands/scan>
 #[derive(Debug, PartialEq)]
 pub struct Scan {
-    pub cursor: i64,
+    pub cursor: u64,
+    pub options: ScanOptions,
+}
+
+/// The options of SCAN, and of the commands iterating over the elements of a key, HSCAN, SSCAN
+/// and ZSCAN, which share its syntax. Every scan command parses them with `ScanOptions::parse`, so
+/// a new option is added once, here.
+///
+/// - `MATCH pattern`: only return elements matching the glob-style `pattern`, which may hold any
+///   byte, see `Pattern`.
+/// - `COUNT count`: a hint of how many elements to walk per call.
+/// - `TYPE type`: only return keys holding values of the given type. SCAN only.
+/// - `NOVALUES`: only return the fields of a hash, without their values. HSCAN only.
+///
+/// **NOTE**: there are no sets or sorted sets yet, and no HSCAN, so only SCAN parses them.
+///
+/// Ref: <https://redis.io/docs/latest/commands/scan>
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanOptions {
+    pub pattern: Option<Bytes>,
+    pub count: Option<u64>,
+    pub type_: Option<String>,
+    pub novalues: bool,
+}
+
+/// What a scan command iterates over, which decides the options it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanTarget {
+    Keyspace,
+    Hash,
+}
+
+impl ScanOptions {
+    /// Parses the options following the cursor of a scan command iterating over `target`.
+    pub(super) fn parse(
+        parser: &mut CommandParser,
+        target: ScanTarget,
+    ) -> Result<ScanOptions, RustdisError> {
+        let mut options = ScanOptions::default();
+
+        loop {
+            let option = match parser.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            if option == "NOVALUES" {
+                if target != ScanTarget::Hash {
+                    return Err(RustdisError::command(
+                        ErrorKind::Err,
+                        "NOVALUES option can only be used in HSCAN",
+                    ));
+                }
+                options.novalues = true;
+                continue;
+            }
+
+            let value = match parser.next_bytes() {
+                Ok(value) => value,
+                Err(CommandParserError::EndOfStream) => return Err(syntax_error()),
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "MATCH" => options.pattern = Some(value),
+                "COUNT" => {
+                    let count = str::from_utf8(&value)
+                        .ok()
+                        .and_then(|count| count.parse::<i64>().ok())
+                        .ok_or_else(|| {
+                            RustdisError::command(
+                                ErrorKind::Err,
+                                "value is not an integer or out of range",
+                            )
+                        })?;
+                    if count < 1 {
+                        return Err(syntax_error());
+                    }
+                    options.count = Some(count as u64);
+                }
+                "TYPE" if target == ScanTarget::Keyspace => {
+                    options.type_ = Some(String::from_utf8_lossy(&value).into_owned())
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        Ok(options)
+    }
 }
 
 impl Executable for Scan {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let store = store.lock();
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let pattern = self.options.pattern.map(|pattern| store.pattern(&pattern));
+        let count = self.options.count.unwrap_or(DEFAULT_COUNT) as usize;
+        let state = store.lock();
 
-        let next_cursor = Frame::Bulk(Bytes::from("0"));
-        let keys: Vec<Frame> = store
-            .keys()
-            .map(|key| Frame::Bulk(Bytes::from(key.clone())))
+        let (next_cursor, keys) = state.scan(self.cursor, count, |key, value| {
+            let matches = pattern.as_ref().is_none_or(|pattern| pattern.matches(key));
+            let of_type = self
+                .options
+                .type_
+                .as_ref()
+                .is_none_or(|type_| type_.eq_ignore_ascii_case(value.type_name()));
+            matches && of_type
+        });
+        let keys = keys
+            .into_iter()
+            .map(|key| Frame::Bulk(Bytes::from(key)))
             .collect();
-        let keys = Frame::Array(keys);
 
-        let res = Frame::Array(vec![next_cursor, keys]);
+        let res = Frame::Array(vec![
+            Frame::Bulk(Bytes::from(next_cursor.to_string())),
+            Frame::Array(keys),
+        ]);
         Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Scan {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
-        let cursor = parser.next_bytes()?;
-        let cursor = str::from_utf8(&cursor[..]).unwrap();
-        let cursor = cursor.parse::<i64>().unwrap();
+        let cursor = parser
+            .next_string()?
+            .parse::<u64>()
+            .map_err(|_| RustdisError::command(ErrorKind::Err, "invalid cursor"))?;
+        let options = ScanOptions::parse(parser, ScanTarget::Keyspace)?;
+
+        Ok(Self { cursor, options })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+    use crate::config::Config;
+    use crate::engine::EngineKind;
+    use crate::store::NewValue;
+
+    fn scan_frame(args: &[&str]) -> Frame {
+        let args = ["SCAN"].iter().chain(args);
+        Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        )
+    }
+
+    fn scan_keys(store: &Store, args: &[&str]) -> Vec<Bytes> {
+        let cmd = Command::try_from(scan_frame(args)).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        let Frame::Array(parts) = res else {
+            panic!("expected an array, got {:?}", res);
+        };
+        assert_eq!(parts[0], Frame::Bulk(Bytes::from("0")));
+
+        let Frame::Array(keys) = &parts[1] else {
+            panic!("expected an array of keys, got {:?}", parts[1]);
+        };
+        let mut keys: Vec<Bytes> = keys
+            .iter()
+            .map(|key| match key {
+                Frame::Bulk(key) => key.clone(),
+                key => panic!("expected a bulk string, got {:?}", key),
+            })
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn options() {
+        let cmd = Command::try_from(scan_frame(&[
+            "0", "match", "a*", "COUNT", "10", "TYPE", "x",
+        ]));
+
+        assert_eq!(
+            cmd.unwrap(),
+            Command::Scan(Scan {
+                cursor: 0,
+                options: ScanOptions {
+                    pattern: Some(Bytes::from("a*")),
+                    count: Some(10),
+                    type_: Some(String::from("x")),
+                    novalues: false,
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn match_and_type() {
+        let store = Store::new();
+        for key in ["apple", "avocado", "banana"] {
+            store.lock().set(key.to_string(), Bytes::from("1"));
+        }
+
+        assert_eq!(scan_keys(&store, &["0"]).len(), 3);
+        assert_eq!(
+            scan_all(&store, &["MATCH", "a*", "COUNT", "1"], 4),
+            vec![Bytes::from("apple"), Bytes::from("avocado")]
+        );
+        assert_eq!(scan_keys(&store, &["0", "TYPE", "string"]).len(), 3);
+        assert!(scan_keys(&store, &["0", "TYPE", "list"]).is_empty());
+    }
+
+    /// Follows the cursor of SCAN from 0 until it is 0 again, panicking after `max_calls` calls,
+    /// or when a call returns more keys than `COUNT`. Returns every key returned, sorted.
+    fn scan_all(store: &Store, options: &[&str], max_calls: usize) -> Vec<Bytes> {
+        let count = options
+            .iter()
+            .position(|option| option.eq_ignore_ascii_case("COUNT"))
+            .map(|position| options[position + 1].parse::<usize>().unwrap());
+        let mut cursor = String::from("0");
+        let mut keys = vec![];
+
+        for _ in 0..max_calls {
+            let args: Vec<&str> = [cursor.as_str()]
+                .into_iter()
+                .chain(options.iter().copied())
+                .collect();
+            let cmd = Command::try_from(scan_frame(&args)).unwrap();
+            let Frame::Array(parts) = cmd.exec(store.clone()).unwrap() else {
+                panic!("expected an array");
+            };
+            let [Frame::Bulk(next), Frame::Array(batch)] = &parts[..] else {
+                panic!("unexpected reply {:?}", parts);
+            };
+            if let Some(count) = count {
+                assert!(
+                    batch.len() <= count,
+                    "{} keys, COUNT {}",
+                    batch.len(),
+                    count
+                );
+            }
+
+            keys.extend(batch.iter().map(|key| match key {
+                Frame::Bulk(key) => key.clone(),
+                key => panic!("expected a bulk string, got {:?}", key),
+            }));
+            if next == "0" {
+                keys.sort();
+                return keys;
+            }
+            cursor = String::from_utf8(next.to_vec()).unwrap();
+        }
+
+        panic!("the iteration didn't end after {} calls", max_calls);
+    }
+
+    /// Every iteration ends within as many calls as it takes to return the keys `COUNT` at a
+    /// time, plus one, whatever the storage engine, the keyspace size and how few keys match.
+    /// Sorted engines hold keys sharing long prefixes next to each other, the worst case for
+    /// a cursor walking them in order while skipping the ones that don't match.
+    #[tokio::test]
+    async fn iteration_terminates() {
+        for engine in [EngineKind::HashMap, EngineKind::BTreeMap] {
+            for size in [0, 1, 100, 10_000] {
+                let store = Store::with_config(Config {
+                    storage_engine: engine,
+                    ..Config::default()
+                });
+                store.set_many((0..size).map(|i| {
+                    (
+                        format!("{}:{}", "prefix".repeat(10), i),
+                        NewValue {
+                            data: Bytes::from("1"),
+                            ttl: None,
+                        },
+                    )
+                }));
+
+                for count in [1, 10, 1000] {
+                    let max_calls = size / count + 1;
+                    let keys = scan_all(&store, &["COUNT", &count.to_string()], max_calls);
+                    assert_eq!(keys.len(), size, "{} keys, COUNT {}", size, count);
+
+                    // A single key in the keyspace matches.
+                    let options = ["MATCH", "*:7", "COUNT", &count.to_string()];
+                    let keys = scan_all(&store, &options, max_calls);
+                    assert_eq!(keys.len(), usize::from(size > 7));
+                }
+            }
+        }
+    }
+
+    /// Keys present during the whole iteration are returned, whatever is written between calls.
+    #[tokio::test]
+    async fn interleaved_writes() {
+        let store = Store::new();
+        for i in 0..100 {
+            store.lock().set(format!("key:{}", i), Bytes::from("1"));
+        }
+
+        let mut cursor = String::from("0");
+        let mut keys = vec![];
+        let mut deleted = 0;
+        loop {
+            let cmd = Command::try_from(scan_frame(&[&cursor, "COUNT", "5"])).unwrap();
+            let Frame::Array(parts) = cmd.exec(store.clone()).unwrap() else {
+                panic!("expected an array");
+            };
+            let [Frame::Bulk(next), Frame::Array(batch)] = &parts[..] else {
+                panic!("unexpected reply {:?}", parts);
+            };
+            keys.extend(batch.iter().cloned());
+
+            // A key is added, and another deleted, in between.
+            store
+                .lock()
+                .set(format!("new:{}", deleted), Bytes::from("1"));
+            store.lock().remove(&format!("key:{}", 99 - deleted));
+            deleted += 1;
+
+            if next == "0" {
+                break;
+            }
+            cursor = String::from_utf8(next.to_vec()).unwrap();
+        }
+
+        for i in 0..100 - deleted {
+            let key = Frame::Bulk(Bytes::from(format!("key:{}", i)));
+            assert!(keys.contains(&key), "{:?} was not returned", key);
+        }
+    }
+
+    /// `COUNT` bounds the keys walked, not the keys returned: when few keys match, up to ten
+    /// times as many are walked, and the reply may hold none.
+    #[tokio::test]
+    async fn count_is_adaptive() {
+        let store = Store::new();
+        for i in 0..1000 {
+            store.lock().set(format!("key:{}", i), Bytes::from("1"));
+        }
+
+        let reply = |args: &[&str]| {
+            let cmd = Command::try_from(scan_frame(args)).unwrap();
+            let Frame::Array(parts) = cmd.exec(store.clone()).unwrap() else {
+                panic!("expected an array");
+            };
+            let [Frame::Bulk(next), Frame::Array(batch)] = &parts[..] else {
+                panic!("unexpected reply {:?}", parts);
+            };
+            (next.clone(), batch.len())
+        };
+
+        let (next, returned) = reply(&["0", "COUNT", "10"]);
+        assert_ne!(next, "0");
+        assert_eq!(returned, 10);
+
+        // No key matches, the call walks a hundred keys, ten times COUNT, and returns none.
+        let (next, returned) = reply(&["0", "MATCH", "nope", "COUNT", "10"]);
+        assert_eq!(returned, 0);
+        let (after_hundred, _) = store.lock().scan(0, 100, |_, _| true);
+        assert_eq!(next, after_hundred.to_string());
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments() {
+        let error = |args: &[&str]| Command::try_from(scan_frame(args)).unwrap_err().to_string();
+
+        assert_eq!(error(&["x"]), "ERR invalid cursor");
+        assert_eq!(error(&["-1"]), "ERR invalid cursor");
+        assert_eq!(error(&["0", "MATCH"]), "ERR syntax error");
+        assert_eq!(error(&["0", "COUNT", "0"]), "ERR syntax error");
+        assert_eq!(error(&["0", "LIMIT", "10"]), "ERR syntax error");
+        assert_eq!(
+            error(&["0", "COUNT", "ten"]),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(
+            error(&["0", "NOVALUES"]),
+            "ERR NOVALUES option can only be used in HSCAN"
+        );
+    }
+
+    #[tokio::test]
+    async fn binary_pattern() {
+        let store = Store::new();
+        store.lock().set(String::from("key"), Bytes::from("1"));
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SCAN")),
+            Frame::Bulk(Bytes::from("0")),
+            Frame::Bulk(Bytes::from("MATCH")),
+            Frame::Bulk(Bytes::from_static(b"k\xff*")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd.exec(store).unwrap(),
+            Frame::Array(vec![Frame::Bulk(Bytes::from("0")), Frame::Array(vec![])])
+        );
+    }
+
+    #[test]
+    fn options_of_target() {
+        let parse = |target: ScanTarget, args: &[&str]| {
+            let parts: Vec<Frame> = args
+                .iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect();
+            let mut parser = CommandParser {
+                parts: parts.into_iter(),
+            };
+            ScanOptions::parse(&mut parser, target).map_err(|err| err.to_string())
+        };
 
-        Ok(Self { cursor })
+        assert_eq!(
+            parse(ScanTarget::Hash, &["NOVALUES", "MATCH", "f*"]),
+            Ok(ScanOptions {
+                pattern: Some(Bytes::from("f*")),
+                novalues: true,
+                ..ScanOptions::default()
+            })
+        );
+        assert_eq!(
+            parse(ScanTarget::Hash, &["TYPE", "string"]),
+            Err(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            parse(ScanTarget::Keyspace, &["NOVALUES"]),
+            Err(String::from(
+                "ERR NOVALUES option can only be used in HSCAN"
+            ))
+        );
     }
 }
@@ -1,33 +1,91 @@
 use bytes::Bytes;
+use std::str;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Number, Store};
+
+/// How many logical databases there are, the `databases` parameter CONFIG GET reports.
+pub const DATABASES: i64 = 16;
 
 /// Select the Redis logical database having the specified zero-based numeric index. New
 /// connections always use the database 0.
 ///
+/// Like Redis, the index is checked when the command is executed, so a transaction selecting an
+/// invalid database is not aborted, only the reply to SELECT is an error.
+///
+/// **NOTE**: there is a single keyspace, selecting any database in range succeeds and keeps using
+/// it, like the `# Keyspace` section of INFO shows.
+///
 /// Ref: <https://redis.io/docs/latest/commands/select>
 #[derive(Debug, PartialEq)]
 pub struct Select {
-    /// The GUI clients we tested send this index value as bytes. Since we are not processing this
-    /// value, there is no need to convert it to a number for now.
+    /// The GUI clients we tested send this index value as bytes. It is only parsed when the
+    /// command is executed, see above.
     pub index: Bytes,
 }
 
 impl Executable for Select {
-    fn exec(self, _store: Store) -> Result<Frame, Error> {
-        Ok(Frame::Simple("OK".to_string()))
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        let index = str::from_utf8(&self.index).ok().and_then(i64::parse);
+
+        let res = match index {
+            Some(index) if (0..DATABASES).contains(&index) => Frame::Simple("OK".to_string()),
+            Some(_) => Frame::Error("ERR DB index is out of range".to_string()),
+            None => Frame::Error(format!("ERR {}", i64::INVALID)),
+        };
+
+        Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Select {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let index = parser.next_bytes()?;
         Ok(Self { index })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::Command;
+
+    fn select(store: &Store, index: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SELECT")),
+            Frame::Bulk(Bytes::from(index.to_string())),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+        cmd.exec(store.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bounds() {
+        let store = Store::new();
+
+        for index in ["0", "15"] {
+            assert_eq!(select(&store, index), Frame::Simple("OK".to_string()));
+        }
+        for index in ["16", "-1", "9223372036854775807"] {
+            assert_eq!(
+                select(&store, index),
+                Frame::Error("ERR DB index is out of range".to_string()),
+                "{}",
+                index
+            );
+        }
+        for index in ["one", "1.0", "+1", "01", "", "9223372036854775808"] {
+            assert_eq!(
+                select(&store, index),
+                Frame::Error("ERR value is not an integer or out of range".to_string()),
+                "{}",
+                index
+            );
+        }
+    }
+}
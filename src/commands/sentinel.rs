@@ -0,0 +1,162 @@
+use crate::commands::executable::Executable;
+use crate::commands::{CommandParser, CommandParserError};
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// The queries Sentinel-aware clients send to discover the master they should connect to.
+///
+/// **NOTE**: rustdis is not a Sentinel. It replies like a Sentinel monitoring no master, so a
+/// client pointed at it gets a well-formed "unknown master" answer instead of an unknown command
+/// error it may not handle.
+///
+/// Ref: <https://redis.io/docs/latest/operate/oss_and_stack/management/sentinel/>
+#[derive(Debug, PartialEq)]
+pub enum Sentinel {
+    GetMasterAddrByName(GetMasterAddrByName),
+    Masters(Masters),
+    Master(Master),
+}
+
+/// Returns the IP and port of the master monitored under `name`, nil when there is none.
+#[derive(Debug, PartialEq)]
+pub struct GetMasterAddrByName {
+    pub name: String,
+}
+
+/// Returns the state of every monitored master.
+#[derive(Debug, PartialEq)]
+pub struct Masters;
+
+/// Returns the state of the master monitored under `name`, or the sentinels and replicas
+/// monitoring it, as its SENTINEL MASTER, SENTINELS and REPLICAS (or SLAVES) subcommands do.
+#[derive(Debug, PartialEq)]
+pub struct Master {
+    pub name: String,
+}
+
+impl Executable for Sentinel {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        match self {
+            Self::GetMasterAddrByName(cmd) => cmd.exec(store),
+            Self::Masters(cmd) => cmd.exec(store),
+            Self::Master(cmd) => cmd.exec(store),
+        }
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Sentinel {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let sub_command = parser.next_string()?;
+        let sub_command = sub_command.to_lowercase();
+
+        match sub_command.as_str() {
+            "get-master-addr-by-name" => {
+                let name = parser.next_string()?;
+                Ok(Self::GetMasterAddrByName(GetMasterAddrByName { name }))
+            }
+            "masters" => Ok(Self::Masters(Masters)),
+            "master" | "replicas" | "slaves" | "sentinels" => {
+                let name = parser.next_string()?;
+                Ok(Self::Master(Master { name }))
+            }
+            _ => Err(CommandParserError::UnknownCommand {
+                command: format!("SENTINEL {}", sub_command.to_uppercase()),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Executable for GetMasterAddrByName {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::NullArray)
+    }
+}
+
+impl Executable for Masters {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Array(vec![]))
+    }
+}
+
+impl Executable for Master {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Error(
+            "ERR No such master with that name".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn sentinel(args: &[&str]) -> Result<Command, RustdisError> {
+        let mut frames = vec![Frame::Bulk(Bytes::from("SENTINEL"))];
+        frames.extend(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string()))),
+        );
+        Command::try_from(Frame::Array(frames))
+    }
+
+    #[tokio::test]
+    async fn get_master_addr_by_name() {
+        let store = Store::new();
+
+        let cmd = sentinel(&["get-master-addr-by-name", "mymaster"]).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Sentinel(Sentinel::GetMasterAddrByName(GetMasterAddrByName {
+                name: String::from("mymaster")
+            }))
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::NullArray);
+    }
+
+    #[tokio::test]
+    async fn masters() {
+        let store = Store::new();
+
+        let res = sentinel(&["MASTERS"]).unwrap().exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn unknown_master() {
+        let store = Store::new();
+
+        for sub_command in ["master", "replicas", "slaves", "sentinels"] {
+            let res = sentinel(&[sub_command, "mymaster"])
+                .unwrap()
+                .exec(store.clone())
+                .unwrap();
+
+            assert_eq!(
+                res,
+                Frame::Error("ERR No such master with that name".to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_subcommand() {
+        let err = sentinel(&["failover", "mymaster"]).unwrap_err();
+        assert!(matches!(err, RustdisError::Command { .. }));
+        assert_eq!(
+            err.to_string(),
+            "ERR protocol error; unknown command SENTINEL FAILOVER"
+        );
+    }
+}
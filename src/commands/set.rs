@@ -1,45 +1,163 @@
 use bytes::Bytes;
+use std::time::SystemTime;
 
 use crate::commands::executable::Executable;
-use crate::commands::CommandParser;
+use crate::commands::expiration::{Expiration, Resolution};
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{NewValue, Store, Value, STRING};
 
-/// Set `key` to hold the `string` value. If `key` already holds a value, it is overwritten.
+/// Set `key` to hold the `string` value. If `key` already holds a value, it is overwritten, and
+/// any previous time to live is discarded unless `KEEPTTL` is given.
+///
+/// Options:
+///
+/// - `NX`: only set the key if it does not already exist.
+/// - `XX`: only set the key if it already exists.
+/// - `GET`: reply with the old value stored at key, or nil when the key did not exist. The old
+///   value is returned even when `NX` or `XX` prevent the key from being set.
+/// - `EX seconds`, `PX milliseconds`: set the time to live of the key.
+/// - `EXAT timestamp`, `PXAT timestamp`: set the Unix time, in seconds or milliseconds, at which
+///   the key expires.
+/// - `KEEPTTL`: retain the time to live of the key.
+///
+/// `NX` and `XX`, as well as the expiration options, are mutually exclusive. Repeating the same
+/// option is allowed, the last one wins.
 ///
 /// Ref: <https://redis.io/docs/latest/commands/set/>
 #[derive(Debug, PartialEq)]
 pub struct Set {
     pub key: String,
     pub value: Bytes,
+    pub condition: Option<Condition>,
+    pub expiration: Option<Expiration>,
+    pub get: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    /// Only set the key if it does not already exist.
+    Nx,
+    /// Only set the key if it already exists.
+    Xx,
 }
 
 impl Executable for Set {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let mut store = store.lock();
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let mut state = store.lock();
+
+        // Any value is replaced, but GET only returns strings.
+        if self.get {
+            if let Err(err) = state.check_type(&self.key, STRING) {
+                return Ok(Frame::Error(err.to_string()));
+            }
+        }
+        let old = state.get_value(&self.key);
+        let exists = old.is_some();
+        let old = old.filter(|_| self.get).and_then(Value::string);
+
+        let skip = match self.condition {
+            Some(Condition::Nx) => exists,
+            Some(Condition::Xx) => !exists,
+            None => false,
+        };
+
+        if !skip {
+            let resolution = self.expiration.map(|e| e.resolve(SystemTime::now()));
+            match resolution {
+                None | Some(Resolution::Persist) => state.set(self.key, self.value),
+                Some(Resolution::Keep) => state.update_value(self.key, self.value),
+                Some(Resolution::ExpiresIn(ttl)) => {
+                    let value = NewValue {
+                        data: self.value,
+                        ttl: Some(ttl),
+                    };
+                    state.set2(self.key.clone(), value);
+                    store.notify_if_expires_next(&mut state, &self.key);
+                }
+                // A time to live that has already elapsed deletes the key instead.
+                Some(Resolution::Expired) => {
+                    if state.remove(&self.key).is_some() {
+                        store.stats.incr_expired_keys(1);
+                    }
+                }
+            }
+        }
 
-        store.set(self.key, self.value);
+        let res = match (self.get, old) {
+            (true, Some(old)) => Frame::Bulk(old),
+            (true, None) => Frame::NullBulkString,
+            (false, _) if skip => Frame::NullBulkString,
+            (false, _) => Frame::Simple("OK".to_string()),
+        };
 
-        let res = Frame::Simple("OK".to_string());
         Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Set {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
         let value = parser.next_bytes()?;
 
-        Ok(Self { key, value })
+        let mut condition = None;
+        let mut expiration: Option<(String, String)> = None;
+        let mut keep_ttl = false;
+        let mut get = false;
+
+        loop {
+            let option = match parser.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "NX" if condition != Some(Condition::Xx) => condition = Some(Condition::Nx),
+                "XX" if condition != Some(Condition::Nx) => condition = Some(Condition::Xx),
+                "GET" => get = true,
+                "KEEPTTL" if expiration.is_none() => keep_ttl = true,
+                _ if Expiration::takes_time(&option)
+                    && !keep_ttl
+                    && expiration
+                        .as_ref()
+                        .is_none_or(|(other, _)| *other == option) =>
+                {
+                    let time = match parser.next_string() {
+                        Ok(time) => time,
+                        Err(CommandParserError::EndOfStream) => return Err(syntax_error()),
+                        Err(err) => return Err(err.into()),
+                    };
+                    expiration = Some((option, time));
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        // Like Redis, the expire time is only validated once the options are known to be valid.
+        let expiration = match expiration {
+            Some((option, time)) => Some(Expiration::parse(&option, &time, "set")?),
+            None if keep_ttl => Some(Expiration::KeepTtl),
+            None => None,
+        };
+
+        Ok(Self {
+            key,
+            value,
+            condition,
+            expiration,
+            get,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use tokio::time::Duration;
 
     use super::*;
     use crate::commands::Command;
@@ -59,7 +177,10 @@ mod tests {
             cmd,
             Command::Set(Set {
                 key: String::from("key1"),
-                value: Bytes::from("1")
+                value: Bytes::from("1"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
 
@@ -84,7 +205,10 @@ mod tests {
             cmd,
             Command::Set(Set {
                 key: String::from("key1"),
-                value: Bytes::from("2")
+                value: Bytes::from("2"),
+                condition: None,
+                expiration: None,
+                get: false,
             })
         );
 
@@ -97,4 +221,163 @@ mod tests {
         assert_eq!(res, Frame::Simple("OK".to_string()));
         assert_eq!(store.lock().get("key1"), Some(Bytes::from("2")));
     }
+
+    fn set_frame(args: &[&str]) -> Frame {
+        let args = ["SET", "key"].iter().chain(args);
+        Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        )
+    }
+
+    fn parse_error(args: &[&str]) -> String {
+        Command::try_from(set_frame(args)).unwrap_err().to_string()
+    }
+
+    /// Runs SET with every subset of its options, against a missing key and against a key with a
+    /// time to live, and compares the reply and the resulting key with what Redis does.
+    #[tokio::test]
+    async fn option_matrix() {
+        // 2100-01-01T00:00:00Z, in seconds and in milliseconds.
+        let options: [&[&str]; 8] = [
+            &["NX"],
+            &["XX"],
+            &["GET"],
+            &["EX", "100"],
+            &["PX", "100000"],
+            &["EXAT", "4102444800"],
+            &["PXAT", "4102444800000"],
+            &["KEEPTTL"],
+        ];
+
+        for subset in 0..1 << options.len() {
+            let chosen = |i: usize| subset & (1 << i) != 0;
+            let (nx, xx, get, keep_ttl) = (chosen(0), chosen(1), chosen(2), chosen(7));
+            let expirations = (3..8).filter(|&i| chosen(i)).count();
+
+            let mut args = vec!["new"];
+            for (i, option) in options.iter().enumerate() {
+                if chosen(i) {
+                    args.extend_from_slice(option);
+                }
+            }
+
+            if (nx && xx) || expirations > 1 {
+                assert_eq!(parse_error(&args), "ERR syntax error", "SET key {:?}", args);
+                continue;
+            }
+
+            for exists in [false, true] {
+                let store = Store::new();
+                if exists {
+                    let value = NewValue {
+                        data: Bytes::from("old"),
+                        ttl: Some(Duration::from_secs(1000)),
+                    };
+                    store.set2(String::from("key"), value);
+                }
+                let old_expires_at = store.lock().get_value("key").and_then(|v| v.expires_at);
+
+                let cmd = Command::try_from(set_frame(&args)).unwrap();
+                let res = cmd.exec(store.clone()).unwrap();
+
+                let skip = (nx && exists) || (xx && !exists);
+                let expected = match (get, exists) {
+                    (true, true) => Frame::Bulk(Bytes::from("old")),
+                    (true, false) => Frame::NullBulkString,
+                    (false, _) if skip => Frame::NullBulkString,
+                    (false, _) => Frame::Simple("OK".to_string()),
+                };
+                assert_eq!(res, expected, "SET key {:?} (exists: {})", args, exists);
+
+                let state = store.lock();
+                let value = state.get_value("key");
+                let expires_at = value.and_then(|v| v.expires_at);
+                if skip {
+                    assert_eq!(
+                        value.and_then(Value::string),
+                        exists.then(|| Bytes::from("old"))
+                    );
+                    assert_eq!(expires_at, old_expires_at);
+                } else if keep_ttl {
+                    assert_eq!(value.and_then(Value::string), Some(Bytes::from("new")));
+                    assert_eq!(expires_at, old_expires_at);
+                } else if expirations == 1 {
+                    assert_eq!(value.and_then(Value::string), Some(Bytes::from("new")));
+                    assert!(expires_at.is_some() && expires_at != old_expires_at);
+                } else {
+                    assert_eq!(value.and_then(Value::string), Some(Bytes::from("new")));
+                    assert_eq!(expires_at, None);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn options_are_case_insensitive_and_can_repeat() {
+        let cmd = Command::try_from(set_frame(&["v", "nx", "Get", "EX", "1", "ex", "10", "NX"]));
+
+        assert_eq!(
+            cmd.unwrap(),
+            Command::Set(Set {
+                key: String::from("key"),
+                value: Bytes::from("v"),
+                condition: Some(Condition::Nx),
+                expiration: Some(Expiration::Ex(10)),
+                get: true,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_options() {
+        assert_eq!(parse_error(&["v", "IDLE", "10"]), "ERR syntax error");
+        assert_eq!(
+            parse_error(&["v", "KEEPTTL", "EX", "10"]),
+            "ERR syntax error"
+        );
+        assert_eq!(
+            parse_error(&["v", "PX", "10", "KEEPTTL"]),
+            "ERR syntax error"
+        );
+        assert_eq!(parse_error(&["v", "EX"]), "ERR syntax error");
+        // Syntax errors are reported before invalid expire times.
+        assert_eq!(
+            parse_error(&["v", "EX", "0", "NX", "XX"]),
+            "ERR syntax error"
+        );
+        assert_eq!(
+            parse_error(&["v", "EX", "ten"]),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(
+            parse_error(&["v", "EX", "0"]),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            parse_error(&["v", "PXAT", "-1"]),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            parse_error(&["v", "EX", "9223372036854776"]),
+            "ERR invalid expire time in 'set' command"
+        );
+        assert_eq!(
+            parse_error(&["v", "PX", "9223372036854775807"]),
+            "ERR invalid expire time in 'set' command"
+        );
+    }
+
+    #[tokio::test]
+    async fn expire_time_in_the_past() {
+        let store = Store::new();
+        store.lock().set(String::from("key"), Bytes::from("old"));
+
+        let cmd = Command::try_from(set_frame(&["new", "EXAT", "1", "GET"])).unwrap();
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("old")));
+        assert!(!store.lock().exists("key"));
+        assert_eq!(store.stats.expired_keys(), 1);
+    }
 }
@@ -2,9 +2,9 @@ use bytes::Bytes;
 
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Set key to hold string value if key does not exist. In that case, it is equal to SET. When key
 /// already holds a value, no operation is performed. SETNX is short for "SET if Not eXists".
@@ -17,7 +17,7 @@ pub struct Setnx {
 }
 
 impl Executable for Setnx {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let mut store = store.lock();
 
         let res = match store.get(&self.key) {
@@ -33,7 +33,7 @@ impl Executable for Setnx {
 }
 
 impl TryFrom<&mut CommandParser> for Setnx {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
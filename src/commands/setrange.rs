@@ -1,12 +1,13 @@
 use bytes::Bytes;
 
 use crate::commands::executable::Executable;
-use crate::commands::{CommandParser, CommandParserError};
+use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
-const MAX_OFFSET: usize = 536_870_911;
+/// The maximum length of a string, 512 megabytes, like Redis' default `proto-max-bulk-len`.
+const MAX_STRING_LENGTH: usize = 512 * 1024 * 1024;
 
 /// Setrange overwrites part of the string stored at key, starting at the specified offset, for the
 /// entire length of value. If the offset is larger than the current length of the string at key,
@@ -26,37 +27,60 @@ pub struct Setrange {
 }
 
 impl Executable for Setrange {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let mut store = store.lock();
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let current_value = store.get(&self.key).unwrap_or_default();
 
-        let offset = self.offset as usize;
-        let new_len = offset + self.value.len();
-        let mut new_value = vec![b' '; usize::max(new_len, current_value.len())];
+        // Like Redis, setting nothing leaves the key untouched, even past the maximum length, and
+        // doesn't create missing keys.
+        if self.value.is_empty() {
+            return Ok(Frame::Integer(current_value.len() as i64));
+        }
+
+        // The offset is never negative, see `try_from`.
+        let offset = usize::try_from(self.offset).unwrap_or(usize::MAX);
+        let new_len = match offset.checked_add(self.value.len()) {
+            Some(new_len) if new_len <= MAX_STRING_LENGTH => new_len,
+            _ => {
+                return Ok(Frame::Error(
+                    "ERR string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
+                ))
+            }
+        };
+
+        // The padding is allocated zeroed instead of being filled afterwards. Zeroed allocations
+        // are served by pages the OS maps lazily, so a large offset only commits memory for the
+        // pages that are actually written to.
+        let mut new_value = vec![0; usize::max(new_len, current_value.len())];
 
         new_value[..current_value.len()].copy_from_slice(&current_value);
         new_value[offset..new_len].copy_from_slice(&self.value);
 
-        store.set(self.key.clone(), Bytes::from(new_value));
+        let len = new_value.len();
+        store.update_value(self.key.clone(), Bytes::from(new_value));
 
-        Ok(Frame::Integer(new_len as i64))
+        Ok(Frame::Integer(len as i64))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Setrange {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
-        let offset = parser.next_integer()?;
+        let offset = parser.next_integer().map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+        })?;
         let value = parser.next_bytes()?;
 
-        if offset as usize >= MAX_OFFSET {
-            return Err(CommandParserError::InvalidCommandArgument {
-                command: String::from("SETRANGE"),
-                argument: String::from("offset"),
-            }
-            .into());
+        if offset < 0 {
+            return Err(RustdisError::command(
+                ErrorKind::Err,
+                "offset is out of range",
+            ));
         }
 
         Ok(Self { key, offset, value })
@@ -121,7 +145,50 @@ mod tests {
         let res = cmd.exec(store.clone()).unwrap();
 
         assert_eq!(res, Frame::Integer(11));
-        assert_eq!(store.lock().get("key1"), Some(Bytes::from("      Redis")));
+        assert_eq!(
+            store.lock().get("key1"),
+            Some(Bytes::from("\0\0\0\0\0\0Redis"))
+        );
+    }
+
+    #[tokio::test]
+    async fn with_large_offset() {
+        let store = Store::new();
+        // The value ends exactly at the maximum length of a string.
+        let offset = MAX_STRING_LENGTH - 5;
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SETRANGE")),
+            Frame::Bulk(Bytes::from("key1")),
+            Frame::Bulk(Bytes::from(offset.to_string())),
+            Frame::Bulk(Bytes::from("Redis")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        let res = cmd.exec(store.clone()).unwrap();
+        assert_eq!(res, Frame::Integer(offset as i64 + 5));
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("STRLEN")),
+            Frame::Bulk(Bytes::from("key1")),
+        ]);
+        let res = Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        assert_eq!(res, Frame::Integer(offset as i64 + 5));
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("GETRANGE")),
+            Frame::Bulk(Bytes::from("key1")),
+            Frame::Bulk(Bytes::from("-7")),
+            Frame::Bulk(Bytes::from("-1")),
+        ]);
+        let res = Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap();
+        assert_eq!(res, Frame::Bulk(Bytes::from("\0\0Redis")));
     }
 
     #[tokio::test]
@@ -151,31 +218,112 @@ mod tests {
 
         let res = cmd.exec(store.clone()).unwrap();
 
-        assert_eq!(res, Frame::Integer(11));
+        assert_eq!(res, Frame::Integer(14));
         assert_eq!(
             store.lock().get("key1"),
             Some(Bytes::from("Hello Redis!!!"))
         );
     }
 
-    #[tokio::test]
-    async fn when_offset_is_to_big() {
+    fn setrange(store: &Store, offset: &str, value: &str) -> Result<Frame, RustdisError> {
         let frame = Frame::Array(vec![
             Frame::Bulk(Bytes::from("SETRANGE")),
             Frame::Bulk(Bytes::from("key1")),
-            Frame::Bulk(Bytes::from(format!("{}", MAX_OFFSET))),
-            Frame::Bulk(Bytes::from("value1")),
+            Frame::Bulk(Bytes::from(offset.to_string())),
+            Frame::Bulk(Bytes::from(value.to_string())),
         ]);
+        Command::try_from(frame).and_then(|cmd| cmd.exec(store.clone()))
+    }
 
-        let err = Command::try_from(frame).err().unwrap();
-        let err = err.downcast_ref::<CommandParserError>().unwrap();
+    #[tokio::test]
+    async fn with_invalid_offset() {
+        let store = Store::new();
 
         assert_eq!(
-            *err,
-            CommandParserError::InvalidCommandArgument {
-                command: String::from("SETRANGE"),
-                argument: "offset".to_string(),
-            }
+            setrange(&store, "-1", "value1").unwrap_err().to_string(),
+            "ERR offset is out of range"
+        );
+        assert_eq!(
+            setrange(&store, "one", "value1").unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(
+            setrange(&store, "9223372036854775808", "value1")
+                .unwrap_err()
+                .to_string(),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(store.lock().get("key1"), None);
+    }
+
+    #[tokio::test]
+    async fn when_string_exceeds_maximum_length() {
+        let store = Store::new();
+        let error = Frame::Error(
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
         );
+
+        let offset = MAX_STRING_LENGTH.to_string();
+        assert_eq!(setrange(&store, &offset, "a").unwrap(), error);
+        let offset = (MAX_STRING_LENGTH - 1).to_string();
+        assert_eq!(setrange(&store, &offset, "ab").unwrap(), error);
+        assert_eq!(setrange(&store, &i64::MAX.to_string(), "a").unwrap(), error);
+        assert_eq!(store.lock().get("key1"), None);
+    }
+
+    #[tokio::test]
+    async fn with_empty_value() {
+        let store = Store::new();
+
+        // Nothing is set, so neither the key is created nor the maximum length checked.
+        assert_eq!(
+            setrange(&store, &i64::MAX.to_string(), "").unwrap(),
+            Frame::Integer(0)
+        );
+        assert_eq!(store.lock().get("key1"), None);
+
+        store.lock().set(String::from("key1"), Bytes::from("Hello"));
+        assert_eq!(setrange(&store, "10", "").unwrap(), Frame::Integer(5));
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("Hello")));
+    }
+
+    /// No combination of offsets, values and stored strings panics, every command is replied to
+    /// with either the new length or an error.
+    #[tokio::test]
+    async fn fuzz_arguments() {
+        let offsets = [
+            i64::MIN,
+            i64::MIN + 1,
+            -(MAX_STRING_LENGTH as i64),
+            -1,
+            0,
+            1,
+            5,
+            MAX_STRING_LENGTH as i64 - 1,
+            MAX_STRING_LENGTH as i64,
+            u32::MAX as i64,
+            i64::MAX - 1,
+            i64::MAX,
+        ];
+
+        for stored in [None, Some(""), Some("Hello")] {
+            for offset in offsets {
+                for value in ["", "a", "Redis"] {
+                    let store = Store::new();
+                    if let Some(stored) = stored {
+                        store.lock().set(String::from("key1"), Bytes::from(stored));
+                    }
+
+                    match setrange(&store, &offset.to_string(), value) {
+                        Ok(Frame::Integer(len)) => {
+                            let stored = store.lock().get("key1").unwrap_or_default();
+                            assert_eq!(len, stored.len() as i64);
+                        }
+                        Ok(Frame::Error(_)) | Err(_) => {}
+                        res => panic!("unexpected reply {:?}", res),
+                    }
+                }
+            }
+        }
     }
 }
@@ -1,8 +1,8 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::store::Store;
-use crate::Error;
+use crate::store::{Store, STRING};
 
 /// Returns the length of the string value stored at key. An error is returned when key holds a
 /// non-string value.
@@ -14,8 +14,11 @@ pub struct Strlen {
 }
 
 impl Executable for Strlen {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let store = store.lock();
+        if let Err(err) = store.check_type(&self.key, STRING) {
+            return Ok(Frame::Error(err.to_string()));
+        }
         let value = store.get(&self.key);
 
         match value {
@@ -26,7 +29,7 @@ impl Executable for Strlen {
 }
 
 impl TryFrom<&mut CommandParser> for Strlen {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -1,32 +1,271 @@
+use tokio::time::Duration;
+
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// TTL returns the remaining time to live of a key that has a timeout. This introspection
 /// capability allows a Redis client to check how many seconds a given key will continue to be part
 /// of the dataset.
 ///
+/// Returns -2 if the key does not exist and -1 if it exists but has no associated expire. Like
+/// Redis, the time left is rounded to the closest second.
+///
 /// Ref: <https://redis.io/docs/latest/commands/ttl>
 #[derive(Debug, PartialEq)]
 pub struct Ttl {
     pub key: String,
 }
 
+/// Like TTL, but returns the remaining time to live in milliseconds.
+///
+/// Ref: <https://redis.io/docs/latest/commands/pttl>
+#[derive(Debug, PartialEq)]
+pub struct Pttl {
+    pub key: String,
+}
+
+/// The time to live left to `key`, `Err` with the reply of TTL and PTTL when it has none.
+///
+/// It is measured from `State::now`, the instant expired keys were removed at, so a key replied
+/// to as existing has time left, see `InnerStore::lock`.
+fn time_to_live(store: &Store, key: &str) -> Result<Duration, Frame> {
+    let state = store.lock();
+    match state.get_value(key) {
+        Some(value) => match value.expires_at {
+            Some(expires_at) => Ok(expires_at.saturating_duration_since(state.now())),
+            None => Err(Frame::Integer(-1)),
+        },
+        None => Err(Frame::Integer(-2)),
+    }
+}
+
 impl Executable for Ttl {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
-        let state = store.lock();
-        let ttl = if state.exists(&self.key) { -1 } else { -2 };
-        Ok(Frame::Integer(ttl))
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let res = match time_to_live(&store, &self.key) {
+            Ok(ttl) => Frame::Integer(((ttl.as_millis() + 500) / 1000) as i64),
+            Err(res) => res,
+        };
+        Ok(res)
+    }
+}
+
+impl Executable for Pttl {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let res = match time_to_live(&store, &self.key) {
+            Ok(ttl) => Frame::Integer(ttl.as_millis() as i64),
+            Err(res) => res,
+        };
+        Ok(res)
     }
 }
 
 impl TryFrom<&mut CommandParser> for Ttl {
-    type Error = Error;
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let key = parser.next_string()?;
+        Ok(Self { key })
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Pttl {
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
         Ok(Self { key })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio::time;
+
+    use super::*;
+    use crate::commands::Command;
+    use crate::store::NewValue;
+
+    fn exec(store: &Store, args: &[&str]) -> Frame {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        Command::try_from(frame)
+            .unwrap()
+            .exec(store.clone())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn without_expiration() {
+        let store = Store::new();
+
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(-2));
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(-2));
+
+        store.lock().set(String::from("key"), Bytes::from("value"));
+
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(-1));
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(-1));
+    }
+
+    #[tokio::test]
+    async fn with_expiration() {
+        time::pause();
+
+        let store = Store::new();
+        store.set2(
+            String::from("key"),
+            NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(Duration::from_millis(10_400)),
+            },
+        );
+
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(10));
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(10_400));
+
+        time::advance(Duration::from_millis(900)).await;
+
+        // Rounded to the closest second.
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(10));
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(9_500));
+    }
+
+    /// The time to live set by GETEX is the one TTL and PTTL report, whatever option set it.
+    #[tokio::test]
+    async fn consistent_with_getex() {
+        time::pause();
+
+        let store = Store::new();
+        store.lock().set(String::from("key"), Bytes::from("value"));
+
+        exec(&store, &["GETEX", "key", "PX", "2500"]);
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(2500));
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(3));
+
+        exec(&store, &["GETEX", "key", "EX", "100"]);
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(100_000));
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(100));
+
+        // Absolute times are relative to the wall clock, which advances while the command runs.
+        let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let at = (unix_now + Duration::from_secs(50)).as_millis().to_string();
+        exec(&store, &["GETEX", "key", "PXAT", &at]);
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(50));
+        let Frame::Integer(pttl) = exec(&store, &["PTTL", "key"]) else {
+            panic!("expected an integer");
+        };
+        assert!((49_000..=50_000).contains(&pttl), "{}", pttl);
+
+        exec(&store, &["GETEX", "key", "PERSIST"]);
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(-1));
+
+        exec(&store, &["GETEX", "key", "EXAT", "1"]);
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(-2));
+    }
+
+    #[tokio::test]
+    async fn far_future_expiration() {
+        let store = Store::new();
+        store.lock().set(String::from("key"), Bytes::from("value"));
+
+        let at = (i64::MAX / 1000).to_string();
+        let res = exec(&store, &["GETEX", "key", "EXAT", &at]);
+
+        assert_eq!(res, Frame::Bulk(Bytes::from("value")));
+        let Frame::Integer(ttl) = exec(&store, &["TTL", "key"]) else {
+            panic!("expected an integer");
+        };
+        assert!(ttl > 200_000_000 * 365 * 24 * 3600, "{}", ttl);
+    }
+
+    /// Sets `key` to expire in `ttl` without waking up the task removing expired keys, which is
+    /// left waiting for a key to expire, like it is when it runs late.
+    async fn set_unnoticed(store: &Store, key: &str, ttl: Duration) {
+        // The task waits once it found no key to expire.
+        tokio::task::yield_now().await;
+
+        store.lock().set2(
+            String::from(key),
+            NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(ttl),
+            },
+        );
+    }
+
+    /// A key expiring between a TTL and a GET sent in the same pipeline is gone for the GET, even
+    /// though the task removing expired keys didn't run in between.
+    #[tokio::test(start_paused = true)]
+    async fn expires_between_ttl_and_get() {
+        let store = Store::new();
+        set_unnoticed(&store, "key", Duration::from_millis(100)).await;
+
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(100));
+        assert_eq!(
+            exec(&store, &["GET", "key"]),
+            Frame::Bulk(Bytes::from("value"))
+        );
+
+        time::advance(Duration::from_millis(99)).await;
+
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(1));
+        assert_eq!(
+            exec(&store, &["GET", "key"]),
+            Frame::Bulk(Bytes::from("value"))
+        );
+
+        time::advance(Duration::from_millis(1)).await;
+
+        assert_eq!(exec(&store, &["GET", "key"]), Frame::NullBulkString);
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(-2));
+        assert_eq!(exec(&store, &["EXISTS", "key"]), Frame::Integer(0));
+        assert_eq!(store.stats.expired_keys(), 1);
+    }
+
+    /// A key expiring between a GET and a TTL is gone for the TTL, which never reports a key
+    /// without time left.
+    #[tokio::test(start_paused = true)]
+    async fn expires_between_get_and_ttl() {
+        let store = Store::new();
+        set_unnoticed(&store, "key", Duration::from_millis(100)).await;
+
+        assert_eq!(
+            exec(&store, &["GET", "key"]),
+            Frame::Bulk(Bytes::from("value"))
+        );
+
+        time::advance(Duration::from_millis(100)).await;
+
+        assert_eq!(exec(&store, &["TTL", "key"]), Frame::Integer(-2));
+        assert_eq!(exec(&store, &["PTTL", "key"]), Frame::Integer(-2));
+        assert_eq!(exec(&store, &["GET", "key"]), Frame::NullBulkString);
+    }
+
+    /// A command reading several keys sees them at the same instant, a key expiring while it runs
+    /// doesn't, see `InnerStore::lock`.
+    #[tokio::test(start_paused = true)]
+    async fn same_instant_for_every_key() {
+        let store = Store::new();
+        set_unnoticed(&store, "first", Duration::from_millis(100)).await;
+        set_unnoticed(&store, "second", Duration::from_millis(200)).await;
+
+        time::advance(Duration::from_millis(100)).await;
+
+        assert_eq!(
+            exec(&store, &["MGET", "first", "second"]),
+            Frame::Array(vec![
+                Frame::NullBulkString,
+                Frame::Bulk(Bytes::from("value"))
+            ])
+        );
+        assert_eq!(exec(&store, &["PTTL", "second"]), Frame::Integer(100));
+    }
+}
@@ -0,0 +1,206 @@
+use bytes::Bytes;
+use tokio::time::Duration;
+
+use crate::commands::executable::Executable;
+use crate::commands::{syntax_error, CommandParser, CommandParserError};
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Returns the keys with a time to live left between `min` and `max` milliseconds, both included,
+/// in the order they expire, each followed by the time it has left like PTTL replies it.
+///
+/// ```text
+/// RUSTDIS.TTLSCAN min max [COUNT count]
+/// ```
+///
+/// `COUNT` returns at most the `count` keys expiring first. Keys whose time to live has elapsed are
+/// never returned, they are removed before the command runs, see `InnerStore::lock`.
+///
+/// The scheduled expirations are kept sorted by deadline, so only the keys within the range are
+/// walked, however large the keyspace is.
+///
+/// **NOTE**: this is a rustdis specific command, namespaced with the `rustdis.` prefix like
+/// Redis modules namespace theirs, so it never clashes with a future Redis command.
+#[derive(Debug, PartialEq)]
+pub struct Ttlscan {
+    pub min: Duration,
+    pub max: Duration,
+    pub count: Option<usize>,
+}
+
+impl Executable for Ttlscan {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
+        let state = store.lock();
+
+        let keys = state
+            .expiring_between(self.min, self.max)
+            .take(self.count.unwrap_or(usize::MAX))
+            .flat_map(|(key, left)| {
+                [
+                    Frame::Bulk(Bytes::copy_from_slice(key.as_bytes())),
+                    Frame::Integer(left.as_millis() as i64),
+                ]
+            })
+            .collect();
+
+        Ok(Frame::Array(keys))
+    }
+}
+
+/// Parses a non negative number of milliseconds, or of keys for `COUNT`.
+fn parse_non_negative(arg: &str) -> Result<u64, RustdisError> {
+    let value = arg.parse::<i64>().map_err(|_| {
+        RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+    })?;
+
+    u64::try_from(value).map_err(|_| {
+        RustdisError::command(ErrorKind::Err, "value is out of range, must be positive")
+    })
+}
+
+impl TryFrom<&mut CommandParser> for Ttlscan {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let min = Duration::from_millis(parse_non_negative(&parser.next_string()?)?);
+        let max = Duration::from_millis(parse_non_negative(&parser.next_string()?)?);
+
+        let mut count = None;
+        loop {
+            match parser.next_string() {
+                Ok(option) if option.eq_ignore_ascii_case("COUNT") => {
+                    let arg = match parser.next_string() {
+                        Ok(arg) => arg,
+                        Err(CommandParserError::EndOfStream) => return Err(syntax_error()),
+                        Err(err) => return Err(err.into()),
+                    };
+                    count = Some(parse_non_negative(&arg)? as usize);
+                }
+                Ok(_) => return Err(syntax_error()),
+                Err(CommandParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { min, max, count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time;
+
+    use super::*;
+    use crate::commands::Command;
+    use crate::store::NewValue;
+
+    fn ttlscan(store: &Store, args: &[&str]) -> Result<Frame, String> {
+        let args = ["RUSTDIS.TTLSCAN"].iter().chain(args);
+        let frame = Frame::Array(
+            args.map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+        let cmd = Command::try_from(frame).map_err(|err| err.to_string())?;
+        Ok(cmd.exec(store.clone()).unwrap())
+    }
+
+    fn keys(keys: &[(&str, i64)]) -> Frame {
+        Frame::Array(
+            keys.iter()
+                .flat_map(|&(key, left)| {
+                    [
+                        Frame::Bulk(Bytes::copy_from_slice(key.as_bytes())),
+                        Frame::Integer(left),
+                    ]
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn range() {
+        time::pause();
+
+        let store = Store::new();
+        store.set_many([5, 1, 3, 10].map(|seconds| {
+            (
+                format!("key:{}", seconds),
+                NewValue {
+                    data: Bytes::from("value"),
+                    ttl: Some(Duration::from_secs(seconds)),
+                },
+            )
+        }));
+        store
+            .lock()
+            .set("persistent".to_string(), Bytes::from("value"));
+
+        assert_eq!(
+            ttlscan(&store, &["0", "5000"]),
+            Ok(keys(&[("key:1", 1000), ("key:3", 3000), ("key:5", 5000)]))
+        );
+        assert_eq!(
+            ttlscan(&store, &["2000", "60000"]),
+            Ok(keys(&[("key:3", 3000), ("key:5", 5000), ("key:10", 10000)]))
+        );
+        assert_eq!(
+            ttlscan(&store, &["0", "60000", "count", "2"]),
+            Ok(keys(&[("key:1", 1000), ("key:3", 3000)]))
+        );
+        assert_eq!(ttlscan(&store, &["4000", "2000"]), Ok(keys(&[])));
+
+        time::advance(Duration::from_millis(1500)).await;
+
+        // Excludes `key:1`, whether or not the task removing expired keys has run yet.
+        assert_eq!(
+            ttlscan(&store, &["1", "2000"]),
+            Ok(keys(&[("key:3", 1500)]))
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_before_the_command() {
+        time::pause();
+
+        let store = Store::new();
+        // Lets the task removing expired keys wait for a key to expire before setting one.
+        tokio::task::yield_now().await;
+        store.lock().set("key".to_string(), Bytes::from("value"));
+        store
+            .lock()
+            .set_ttl("key", Some(Duration::from_millis(100)));
+
+        // The task removing expired keys is never notified, the command removes the key itself.
+        time::advance(Duration::from_millis(200)).await;
+
+        assert_eq!(ttlscan(&store, &["0", "1000"]), Ok(keys(&[])));
+        assert_eq!(store.stats.expired_keys(), 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_arguments() {
+        let store = Store::default();
+
+        assert_eq!(
+            ttlscan(&store, &["0", "soon"]),
+            Err("ERR value is not an integer or out of range".to_string())
+        );
+        assert_eq!(
+            ttlscan(&store, &["-1", "1000"]),
+            Err("ERR value is out of range, must be positive".to_string())
+        );
+        assert_eq!(
+            ttlscan(&store, &["0", "1000", "LIMIT", "2"]),
+            Err("ERR syntax error".to_string())
+        );
+        assert_eq!(
+            ttlscan(&store, &["0", "1000", "COUNT"]),
+            Err("ERR syntax error".to_string())
+        );
+        assert_eq!(
+            ttlscan(&store, &["0", "1000", "COUNT", "1", "COUNT"]),
+            Err("ERR syntax error".to_string())
+        );
+    }
+}
@@ -1,15 +1,15 @@
 use crate::commands::executable::Executable;
 use crate::commands::CommandParser;
+use crate::error::RustdisError;
 use crate::frame::Frame;
 use crate::store::Store;
-use crate::Error;
 
 /// Returns the string representation of the type of the value stored at `key`.
 ///
 /// The different types that can be returned are: `string`, `list`, `set`, `zset`, `hash` and `stream`.
 /// If the key does not exist, `none` is returned.
 ///
-/// **NOTE**: This server implementation only supports `string` type.
+/// **NOTE**: This server implementation only supports the `string` and `hash` types.
 ///
 /// Ref: <https://redis.io/docs/latest/commands/type/>
 #[derive(Debug, PartialEq)]
@@ -18,19 +18,16 @@ pub struct Type {
 }
 
 impl Executable for Type {
-    fn exec(self, store: Store) -> Result<Frame, Error> {
+    fn exec(self, store: Store) -> Result<Frame, RustdisError> {
         let state = store.lock();
-        let type_ = state
-            .get(&self.key)
-            .map(|_| "string".to_string())
-            .unwrap_or_else(|| "none".to_string());
+        let type_ = state.type_of(&self.key).unwrap_or("none");
 
-        Ok(Frame::Simple(type_))
+        Ok(Frame::Simple(type_.to_string()))
     }
 }
 
 impl TryFrom<&mut CommandParser> for Type {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
         let key = parser.next_string()?;
@@ -69,6 +66,25 @@ mod tests {
         assert_eq!(result, Frame::Simple("string".to_string()));
     }
 
+    #[tokio::test]
+    async fn hash_key() {
+        let store = Store::new();
+        store
+            .lock()
+            .hset(
+                String::from("key1"),
+                [(String::from("field"), Bytes::from("1"))],
+            )
+            .unwrap();
+
+        let cmd = Type {
+            key: String::from("key1"),
+        };
+        let result = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(result, Frame::Simple("hash".to_string()));
+    }
+
     #[tokio::test]
     async fn missing_key() {
         let store = Store::new();
@@ -0,0 +1,99 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Blocks the current client until all the previous write commands are successfully transferred
+/// and acknowledged by at least the number of replicas specified, and returns the number of
+/// replicas that acknowledged them.
+///
+/// **NOTE**: there is no replication, so no replica ever acknowledges and 0 is returned right away.
+///
+/// Ref: <https://redis.io/docs/latest/commands/wait/>
+#[derive(Debug, PartialEq)]
+pub struct Wait {
+    pub num_replicas: i64,
+    pub timeout: i64,
+}
+
+impl Executable for Wait {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        Ok(Frame::Integer(0))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Wait {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let num_replicas = parser.next_integer().map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+        })?;
+        let timeout = parser.next_integer().map_err(|_| {
+            RustdisError::command(ErrorKind::Err, "timeout is not an integer or out of range")
+        })?;
+        if timeout < 0 {
+            return Err(RustdisError::command(ErrorKind::Err, "timeout is negative"));
+        }
+
+        Ok(Self {
+            num_replicas,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    #[tokio::test]
+    async fn no_replicas() {
+        let store = Store::new();
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("WAIT")),
+            Frame::Bulk(Bytes::from("0")),
+            Frame::Bulk(Bytes::from("0")),
+        ]);
+        let cmd = Command::try_from(frame).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Wait(Wait {
+                num_replicas: 0,
+                timeout: 0
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(res, Frame::Integer(0));
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let error = |num_replicas: &str, timeout: &str| {
+            let frame = Frame::Array(vec![
+                Frame::Bulk(Bytes::from("WAIT")),
+                Frame::Bulk(Bytes::from(num_replicas.to_string())),
+                Frame::Bulk(Bytes::from(timeout.to_string())),
+            ]);
+            Command::try_from(frame).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            error("a", "b"),
+            "ERR value is not an integer or out of range"
+        );
+        assert_eq!(
+            error("0", "soon"),
+            "ERR timeout is not an integer or out of range"
+        );
+        assert_eq!(error("0", "-1"), "ERR timeout is negative");
+    }
+}
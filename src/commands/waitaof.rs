@@ -0,0 +1,122 @@
+use crate::commands::executable::Executable;
+use crate::commands::CommandParser;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Blocks the current client until all the previous write commands are fsynced to the append only
+/// file of the local server and of at least the number of replicas specified. Returns an array of
+/// two integers: whether the local server fsynced them, and how many replicas did.
+///
+/// **NOTE**: there is no persistence, so, like Redis with `appendonly` disabled, waiting on the
+/// local server is an error whatever `appendonly` is set to. There is no replication either, so
+/// `[0, 0]` is returned right away.
+///
+/// Ref: <https://redis.io/docs/latest/commands/waitaof/>
+#[derive(Debug, PartialEq)]
+pub struct Waitaof {
+    pub num_local: i64,
+    pub num_replicas: i64,
+    pub timeout: i64,
+}
+
+impl Executable for Waitaof {
+    fn exec(self, _store: Store) -> Result<Frame, RustdisError> {
+        if self.num_local != 0 {
+            return Ok(Frame::Error(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled."
+                    .to_string(),
+            ));
+        }
+
+        Ok(Frame::Array(vec![Frame::Integer(0), Frame::Integer(0)]))
+    }
+}
+
+impl TryFrom<&mut CommandParser> for Waitaof {
+    type Error = RustdisError;
+
+    fn try_from(parser: &mut CommandParser) -> Result<Self, Self::Error> {
+        let mut next_integer = || {
+            parser.next_integer().map_err(|_| {
+                RustdisError::command(ErrorKind::Err, "value is not an integer or out of range")
+            })
+        };
+        let num_local = next_integer()?;
+        let num_replicas = next_integer()?;
+        let timeout = next_integer()?;
+
+        if timeout < 0 {
+            return Err(RustdisError::command(ErrorKind::Err, "timeout is negative"));
+        }
+
+        Ok(Self {
+            num_local,
+            num_replicas,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::Command;
+
+    fn parse(args: &[&str]) -> Result<Command, RustdisError> {
+        let mut frames = vec![Frame::Bulk(Bytes::from("WAITAOF"))];
+        frames.extend(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string()))),
+        );
+        Command::try_from(Frame::Array(frames))
+    }
+
+    #[tokio::test]
+    async fn no_replicas() {
+        let store = Store::new();
+
+        let cmd = parse(&["0", "1", "100"]).unwrap();
+
+        assert_eq!(
+            cmd,
+            Command::Waitaof(Waitaof {
+                num_local: 0,
+                num_replicas: 1,
+                timeout: 100,
+            })
+        );
+
+        let res = cmd.exec(store.clone()).unwrap();
+
+        assert_eq!(
+            res,
+            Frame::Array(vec![Frame::Integer(0), Frame::Integer(0)])
+        );
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let store = Store::new();
+
+        let res = parse(&["1", "0", "0"]).unwrap().exec(store).unwrap();
+        assert_eq!(
+            res,
+            Frame::Error(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled."
+                    .to_string()
+            )
+        );
+
+        assert_eq!(
+            parse(&["0", "0", "-1"]).unwrap_err().to_string(),
+            "ERR timeout is negative"
+        );
+        assert_eq!(
+            parse(&["zero", "0", "0"]).unwrap_err().to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+}
@@ -0,0 +1,1114 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error as ThisError;
+use tokio::time::Duration;
+
+use crate::connection::DEFAULT_READ_BUFFER_SIZE;
+use crate::engine::EngineKind;
+
+/// Default port Redis listens on.
+pub const PORT: u16 = 6379;
+
+/// How deep `include` directives can be nested, which also stops include cycles.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Server configuration.
+///
+/// Mirrors the subset of `redis.conf` directives rustdis understands. Every field defaults to the
+/// value Redis itself ships with.
+///
+/// Ref: <https://redis.io/docs/latest/operate/oss_and_stack/management/config-file/>
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The port to listen on.
+    pub port: u16,
+    /// The interface to listen on.
+    pub bind: String,
+    /// Send TCP keepalive probes to idle clients every `tcp_keepalive` seconds, which lets the
+    /// server detect dead peers. A value of 0 disables keepalive probes.
+    pub tcp_keepalive: u64,
+    /// Disable Nagle's algorithm on client sockets so small replies are sent immediately instead
+    /// of being buffered, trading bandwidth for latency.
+    pub tcp_nodelay: bool,
+    /// Initial size in bytes of each client's read buffer. Larger buffers let deep pipelines be
+    /// read with fewer syscalls, at the cost of memory per connection.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `read-buffer-size` in the configuration
+    /// file.
+    pub read_buffer_size: usize,
+    /// Commands running for longer than `command_time_limit` milliseconds are aborted with a
+    /// `BUSY` error instead of stalling every other client. Only long running commands, like LCS
+    /// and KEYS, check the limit. A value of 0 disables it.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `command-time-limit` in the configuration
+    /// file.
+    pub command_time_limit: u64,
+    /// KEYS replies with at most `keys_max_results` keys, followed by a `BUSY` error telling the
+    /// reply is partial, instead of holding the store lock while it walks a huge keyspace. KEYS
+    /// also stops at the `command_time_limit`. A value of 0 disables it.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `keys-max-results` in the configuration
+    /// file.
+    pub keys_max_results: usize,
+    /// String values of at least `value_compression_threshold` bytes are stored compressed with
+    /// LZ4, and decompressed when read. Trades CPU for memory on large, compressible values. A
+    /// value of 0 disables compression.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `value-compression-threshold` in the
+    /// configuration file. Only effective when built with the `compression` feature.
+    pub value_compression_threshold: usize,
+    /// The data structure keys are stored in, see the `engine` module.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `storage-engine` in the configuration file,
+    /// either `hashmap` or `btreemap`.
+    pub storage_engine: EngineKind,
+    /// Number of keys the keyspace is preallocated for, so the hash map holding them isn't
+    /// rehashed until it grows past it. Ignored by storage engines that don't preallocate.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `keyspace-initial-capacity` in the
+    /// configuration file.
+    pub keyspace_initial_capacity: usize,
+    /// When not 0, DEL leaves a tombstone in place of each key it deletes, which is purged
+    /// `tombstone_grace_period` milliseconds later. Tombstones are invisible to every command but
+    /// DEBUG TOMBSTONES, they exist to demonstrate how replicas resolve conflicting writes and how
+    /// deleted entries can be reclaimed lazily.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `tombstone-grace-period` in the
+    /// configuration file.
+    pub tombstone_grace_period: u64,
+    /// A write setting a time to live wakes up the task removing expired keys only when it
+    /// elapses more than `expire_wakeup_threshold` milliseconds before the task planned to wake
+    /// up. Otherwise the key is removed when the task wakes up, at most that late, which keeps a
+    /// stream of writes with ever shorter times to live from waking up the task for each of them.
+    /// A value of 0 wakes it up for every key that expires first.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `expire-wakeup-threshold` in the
+    /// configuration file.
+    pub expire_wakeup_threshold: u64,
+    /// Maximum number of rejected commands, unknown or sent with invalid arguments, logged per
+    /// second. The ones over the limit are only counted, and reported with the next one logged.
+    /// A value of 0 disables the log, the rejections are still counted in INFO errorstats.
+    ///
+    /// **NOTE**: this is a rustdis specific directive, `rejected-commands-log-rate` in the
+    /// configuration file.
+    pub rejected_commands_log_rate: u64,
+    /// Memory limit in bytes, 0 means no limit.
+    ///
+    /// **NOTE**: the limit is not enforced, there is no eviction.
+    pub maxmemory: u64,
+    /// Whether the append only file persistence is enabled.
+    ///
+    /// **NOTE**: there is no persistence, the value is only reported.
+    pub appendonly: bool,
+    /// How often the append only file is flushed to disk with fsync.
+    ///
+    /// **NOTE**: there is no persistence, the value is only validated.
+    pub appendfsync: AppendFsync,
+    /// File to write logs to, standard output when `None`.
+    pub logfile: Option<PathBuf>,
+    /// Whether to run as a daemon.
+    ///
+    /// **NOTE**: not supported, the server always runs in the foreground.
+    pub daemonize: bool,
+    /// File with RESP encoded commands executed at startup, see the `dump` module. Connections
+    /// are accepted meanwhile, but only commands allowed while loading are executed.
+    ///
+    /// **NOTE**: this is a rustdis specific option, it can't be set from a configuration file.
+    pub import: Option<PathBuf>,
+    /// File with RESP encoded or inline commands executed at startup, before any connection is
+    /// accepted, to start with a known dataset, see `dump::preload`. Unlike `import`, clients
+    /// connecting meanwhile wait rather than getting a LOADING error, and the server doesn't start
+    /// when a command fails.
+    ///
+    /// **NOTE**: this is a rustdis specific option, it can't be set from a configuration file.
+    pub preload: Option<PathBuf>,
+    /// File the keyspace is exported to when the server shuts down, see the `dump` module.
+    ///
+    /// **NOTE**: this is a rustdis specific option, it can't be set from a configuration file.
+    pub export: Option<PathBuf>,
+    /// Directory the commands received by each connection are recorded to, see the `record`
+    /// module.
+    ///
+    /// **NOTE**: this is a rustdis specific option, it can't be set from a configuration file.
+    pub record: Option<PathBuf>,
+    /// Whether the store keeps a journal of the commands it executes, see the `journal` module.
+    ///
+    /// **NOTE**: this is a rustdis specific option, meant for embedders, it can't be set from a
+    /// configuration file nor the command line.
+    pub journal: bool,
+    /// File the configuration was loaded from, loaded again when the process receives SIGHUP,
+    /// see `Config::reload`.
+    ///
+    /// **NOTE**: this is a rustdis specific option, set from the command line arguments.
+    pub config_file: Option<PathBuf>,
+}
+
+/// A directive whose value changed in the configuration file, see `Config::reload`.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub directive: &'static str,
+    pub from: String,
+    pub to: String,
+    /// Whether the new value is in effect, only the `RELOADABLE` directives are.
+    pub applied: bool,
+}
+
+impl Config {
+    /// The value of the directive `name`, as it would be written in `redis.conf`, or `None` when
+    /// the directive is not one of `DIRECTIVES`.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let value = match name.to_lowercase().as_str() {
+            "port" => self.port.to_string(),
+            "bind" => self.bind.clone(),
+            "tcp-keepalive" => self.tcp_keepalive.to_string(),
+            "tcp-nodelay" => format_bool(self.tcp_nodelay),
+            "read-buffer-size" => self.read_buffer_size.to_string(),
+            "command-time-limit" => self.command_time_limit.to_string(),
+            "keys-max-results" => self.keys_max_results.to_string(),
+            "value-compression-threshold" => self.value_compression_threshold.to_string(),
+            "storage-engine" => self.storage_engine.to_string(),
+            "keyspace-initial-capacity" => self.keyspace_initial_capacity.to_string(),
+            "tombstone-grace-period" => self.tombstone_grace_period.to_string(),
+            "expire-wakeup-threshold" => self.expire_wakeup_threshold.to_string(),
+            "rejected-commands-log-rate" => self.rejected_commands_log_rate.to_string(),
+            "maxmemory" => self.maxmemory.to_string(),
+            "appendonly" => format_bool(self.appendonly),
+            "appendfsync" => self.appendfsync.to_string(),
+            "logfile" => self
+                .logfile
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            "daemonize" => format_bool(self.daemonize),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Sets the directive `name` from its arguments, as they would be written in `redis.conf`.
+    pub fn set(&mut self, name: &str, args: &[String]) -> Result<(), ConfigError> {
+        let name = name.to_lowercase();
+        let invalid = |reason: String| ConfigError::InvalidArgument {
+            directive: name.clone(),
+            reason,
+        };
+
+        // `bind` is the only directive supported taking more than one argument.
+        let arg = match args {
+            [arg] => arg.as_str(),
+            [arg, ..] if name == "bind" => arg.as_str(),
+            _ if is_known(&name) => return Err(ConfigError::WrongNumberOfArguments(name)),
+            _ => return Err(ConfigError::Unknown(name)),
+        };
+
+        match name.as_str() {
+            "port" => self.port = arg.parse().map_err(|_| invalid(arg.to_string()))?,
+            // A leading `-` marks the address as optional, it is bound only if available.
+            "bind" => self.bind = arg.trim_start_matches('-').to_string(),
+            "tcp-keepalive" => {
+                self.tcp_keepalive = arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "tcp-nodelay" => self.tcp_nodelay = parse_bool(arg).map_err(invalid)?,
+            "read-buffer-size" => {
+                self.read_buffer_size = parse_read_buffer_size(arg).map_err(invalid)?
+            }
+            "command-time-limit" => {
+                self.command_time_limit = arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "keys-max-results" => {
+                self.keys_max_results = arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "value-compression-threshold" => {
+                self.value_compression_threshold =
+                    parse_value_compression_threshold(arg).map_err(invalid)?
+            }
+            "storage-engine" => self.storage_engine = arg.parse().map_err(invalid)?,
+            "keyspace-initial-capacity" => {
+                self.keyspace_initial_capacity =
+                    arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "tombstone-grace-period" => {
+                self.tombstone_grace_period = arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "expire-wakeup-threshold" => {
+                self.expire_wakeup_threshold = arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "rejected-commands-log-rate" => {
+                self.rejected_commands_log_rate =
+                    arg.parse().map_err(|_| invalid(arg.to_string()))?
+            }
+            "maxmemory" => self.maxmemory = parse_memory(arg).map_err(invalid)?,
+            "appendonly" => self.appendonly = parse_bool(arg).map_err(invalid)?,
+            "appendfsync" => self.appendfsync = arg.parse().map_err(invalid)?,
+            "logfile" if arg.is_empty() => self.logfile = None,
+            "logfile" => self.logfile = Some(PathBuf::from(arg)),
+            "daemonize" => self.daemonize = parse_bool(arg).map_err(invalid)?,
+            _ => return Err(ConfigError::Unknown(name)),
+        }
+
+        Ok(())
+    }
+
+    /// Applies the directives of a `redis.conf` style file on top of the current configuration.
+    ///
+    /// Each line holds a directive followed by its whitespace separated arguments, which can be
+    /// quoted. Lines starting with `#` are comments and `include <path>` loads another file in
+    /// place. Unknown directives are skipped and returned as warnings, so a complete `redis.conf`
+    /// can be used as is.
+    ///
+    /// Ref: <https://redis.io/docs/latest/operate/oss_and_stack/management/config-file/>
+    pub fn load_file(&mut self, path: &Path) -> Result<Vec<String>, ConfigError> {
+        let mut warnings = vec![];
+        self.load_file_at_depth(path, 0, &mut warnings)?;
+        Ok(warnings)
+    }
+
+    /// Loads `config_file` again on top of this configuration, returning the configuration to run
+    /// with and every directive whose value changed in the file. Only the changes to the
+    /// `RELOADABLE` directives are applied, the configuration is left untouched when the file is
+    /// invalid. The values in the file then take precedence over the ones given on the command
+    /// line.
+    ///
+    /// **NOTE**: Redis has no such thing, its configuration is changed with CONFIG SET.
+    pub fn reload(&self) -> Result<(Config, Vec<Change>), ConfigError> {
+        let Some(path) = &self.config_file else {
+            return Ok((self.clone(), vec![]));
+        };
+        let mut loaded = self.clone();
+        loaded.load_file(path)?;
+
+        let mut reloaded = self.clone();
+        let mut changes = vec![];
+        for directive in DIRECTIVES {
+            let (from, to) = (self.get(directive), loaded.get(directive));
+            let (Some(from), Some(to)) = (from, to) else {
+                continue;
+            };
+            if from == to {
+                continue;
+            }
+
+            let applied = RELOADABLE.contains(directive);
+            if applied {
+                reloaded.set(directive, std::slice::from_ref(&to))?;
+            }
+            changes.push(Change {
+                directive,
+                from,
+                to,
+                applied,
+            });
+        }
+
+        Ok((reloaded, changes))
+    }
+
+    /// Writes this configuration back to `config_file`, the way CONFIG REWRITE does.
+    ///
+    /// Comments, blank lines, `include` lines and unknown directives are kept as they are. The
+    /// first line of each directive is kept too if it still sets the current value, and rewritten
+    /// otherwise, while the lines repeating it are removed. Directives missing from the file are
+    /// appended when they differ from their default. The new file replaces the old one atomically,
+    /// so a failed rewrite leaves it untouched.
+    ///
+    /// **NOTE**: directives set in included files are written to `config_file` too, as Redis does.
+    ///
+    /// Ref: <https://redis.io/docs/latest/commands/config-rewrite/>
+    pub fn rewrite(&self) -> Result<(), ConfigError> {
+        let Some(path) = &self.config_file else {
+            return Err(ConfigError::NoConfigFile);
+        };
+        let content = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut lines = vec![];
+        let mut written = vec![];
+        for line in content.lines() {
+            // A line that doesn't parse is kept as is, like the comments, `load_file` reports it.
+            let args = split_args(line.trim()).unwrap_or_default();
+            let Some((name, args)) = args.split_first() else {
+                lines.push(line.to_string());
+                continue;
+            };
+            let name = name.to_lowercase();
+            let Some(value) = self.get(&name) else {
+                lines.push(line.to_string());
+                continue;
+            };
+            if written.contains(&name) {
+                continue;
+            }
+
+            let mut probe = Config::default();
+            let unchanged =
+                probe.set(&name, args).is_ok() && probe.get(&name) == Some(value.clone());
+            match unchanged {
+                true => lines.push(line.to_string()),
+                false => lines.push(format!("{} {}", name, quote_arg(&value))),
+            }
+            written.push(name);
+        }
+
+        let default = Config::default();
+        for directive in DIRECTIVES {
+            let value = self.get(directive);
+            if !written.iter().any(|name| name == directive) && value != default.get(directive) {
+                let value = value.expect("every directive has a value");
+                lines.push(format!("{} {}", directive, quote_arg(&value)));
+            }
+        }
+
+        let mut content = lines.join("\n");
+        content.push('\n');
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(format!(".rewrite-{}", std::process::id()));
+        let write_err = |source| ConfigError::Write {
+            path: path.to_path_buf(),
+            source,
+        };
+        fs::write(&tmp, content).map_err(write_err)?;
+        fs::rename(&tmp, path).map_err(|source| {
+            let _ = fs::remove_file(&tmp);
+            write_err(source)
+        })
+    }
+
+    fn load_file_at_depth(
+        &mut self,
+        path: &Path,
+        depth: usize,
+        warnings: &mut Vec<String>,
+    ) -> Result<(), ConfigError> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(ConfigError::IncludeDepth(path.to_path_buf()));
+        }
+
+        let content = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let at_line = |source: ConfigError| ConfigError::Line {
+                path: path.to_path_buf(),
+                line: index + 1,
+                source: Box::new(source),
+            };
+
+            let mut args = split_args(line).map_err(at_line)?;
+            let name = args.remove(0).to_lowercase();
+
+            let res = match (name.as_str(), args.as_slice()) {
+                ("include", [include]) => {
+                    self.load_file_at_depth(Path::new(include), depth + 1, warnings)
+                }
+                ("include", _) => Err(ConfigError::WrongNumberOfArguments(name)),
+                _ => self.set(&name, &args),
+            };
+
+            match res {
+                Ok(()) => {}
+                Err(err @ ConfigError::Unknown(_)) => warnings.push(at_line(err).to_string()),
+                Err(err @ ConfigError::Line { .. }) => return Err(err),
+                Err(err) => return Err(at_line(err)),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<Duration> {
+        match self.tcp_keepalive {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    pub fn command_time_limit(&self) -> Option<Duration> {
+        match self.command_time_limit {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        }
+    }
+
+    pub fn keys_max_results(&self) -> Option<usize> {
+        match self.keys_max_results {
+            0 => None,
+            max => Some(max),
+        }
+    }
+
+    /// How long deleted keys are kept as tombstones, `None` when DEL removes them right away.
+    pub fn tombstone_grace_period(&self) -> Option<Duration> {
+        match self.tombstone_grace_period {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        }
+    }
+
+    pub fn expire_wakeup_threshold(&self) -> Duration {
+        Duration::from_millis(self.expire_wakeup_threshold)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: PORT,
+            bind: String::from("127.0.0.1"),
+            tcp_keepalive: 300,
+            tcp_nodelay: true,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            command_time_limit: 0,
+            keys_max_results: 0,
+            value_compression_threshold: 0,
+            storage_engine: EngineKind::default(),
+            keyspace_initial_capacity: 0,
+            tombstone_grace_period: 0,
+            expire_wakeup_threshold: 1,
+            rejected_commands_log_rate: 10,
+            maxmemory: 0,
+            appendonly: false,
+            appendfsync: AppendFsync::default(),
+            logfile: None,
+            daemonize: false,
+            import: None,
+            preload: None,
+            export: None,
+            record: None,
+            journal: false,
+            config_file: None,
+        }
+    }
+}
+
+/// The fsync policies of the append only file, set with the `appendfsync` directive.
+///
+/// Ref: <https://redis.io/docs/latest/operate/oss_and_stack/management/persistence/#how-durable-is-the-append-only-file>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AppendFsync {
+    /// Fsync after every write, the slowest and safest policy.
+    Always,
+    /// Fsync once per second, losing at most a second of writes on a crash.
+    #[default]
+    EverySec,
+    /// Never fsync, leaving it to the operating system.
+    No,
+}
+
+impl FromStr for AppendFsync {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "always" => Ok(AppendFsync::Always),
+            "everysec" => Ok(AppendFsync::EverySec),
+            "no" => Ok(AppendFsync::No),
+            _ => Err(format!(
+                "argument must be 'always', 'everysec' or 'no', got '{}'",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AppendFsync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppendFsync::Always => write!(f, "always"),
+            AppendFsync::EverySec => write!(f, "everysec"),
+            AppendFsync::No => write!(f, "no"),
+        }
+    }
+}
+
+#[derive(Debug, ThisError)]
+pub enum ConfigError {
+    #[error("unknown directive '{0}'")]
+    Unknown(String),
+    #[error("wrong number of arguments for '{0}'")]
+    WrongNumberOfArguments(String),
+    #[error("invalid argument for '{directive}': {reason}")]
+    InvalidArgument { directive: String, reason: String },
+    #[error("unbalanced quotes")]
+    UnbalancedQuotes,
+    #[error("too many nested includes loading {0}")]
+    IncludeDepth(PathBuf),
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("failed to write {path}: {source}")]
+    Write { path: PathBuf, source: io::Error },
+    #[error("the server is running without a config file")]
+    NoConfigFile,
+    #[error("{path}:{line}: {source}")]
+    Line {
+        path: PathBuf,
+        line: usize,
+        source: Box<ConfigError>,
+    },
+}
+
+/// The directives `Config::set` and `Config::get` understand, in the order CONFIG GET lists
+/// them.
+pub const DIRECTIVES: &[&str] = &[
+    "port",
+    "bind",
+    "tcp-keepalive",
+    "tcp-nodelay",
+    "read-buffer-size",
+    "command-time-limit",
+    "keys-max-results",
+    "value-compression-threshold",
+    "storage-engine",
+    "keyspace-initial-capacity",
+    "tombstone-grace-period",
+    "expire-wakeup-threshold",
+    "rejected-commands-log-rate",
+    "maxmemory",
+    "appendonly",
+    "appendfsync",
+    "logfile",
+    "daemonize",
+];
+
+/// The directives `Config::reload` applies to a running server, the ones read each time they are
+/// used. The others are only read at startup, a restart is needed to change them.
+pub const RELOADABLE: &[&str] = &[
+    "command-time-limit",
+    "keys-max-results",
+    "expire-wakeup-threshold",
+    "maxmemory",
+];
+
+fn is_known(name: &str) -> bool {
+    DIRECTIVES.contains(&name)
+}
+
+/// Formats a boolean the way `redis.conf` spells them, see `parse_bool`.
+fn format_bool(value: bool) -> String {
+    match value {
+        true => "yes".to_string(),
+        false => "no".to_string(),
+    }
+}
+
+/// Quotes an argument for a `redis.conf` line when `split_args` would not read it back as is.
+fn quote_arg(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && !arg.starts_with('\'')
+        && !arg.chars().any(|c| c.is_whitespace() || c == '"');
+    if plain {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Splits a `redis.conf` line into its arguments. Arguments are separated by whitespace and can be
+/// wrapped in double or single quotes to include whitespace.
+fn split_args(line: &str) -> Result<Vec<String>, ConfigError> {
+    let mut args = vec![];
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(&first) = chars.peek() else {
+            return Ok(args);
+        };
+
+        let mut arg = String::new();
+        if first == '"' || first == '\'' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some(c) if c == first => break,
+                    Some('\\') if first == '"' => match chars.next() {
+                        Some('n') => arg.push('\n'),
+                        Some('t') => arg.push('\t'),
+                        Some(c) => arg.push(c),
+                        None => return Err(ConfigError::UnbalancedQuotes),
+                    },
+                    Some(c) => arg.push(c),
+                    None => return Err(ConfigError::UnbalancedQuotes),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                arg.push(c);
+            }
+        }
+
+        args.push(arg);
+    }
+}
+
+/// Parses a boolean the way `redis.conf` spells them: `yes` or `no`.
+pub fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("argument must be 'yes' or 'no', got '{}'", value)),
+    }
+}
+
+/// Parses a memory size, optionally followed by a unit, the way `redis.conf` does: `1k` is 1000
+/// bytes, `1kb` is 1024 bytes, and so on for `m`/`mb` and `g`/`gb`. Units are case insensitive.
+pub fn parse_memory(value: &str) -> Result<u64, String> {
+    let value = value.to_lowercase();
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+
+    let multiplier = match &value[digits.len()..] {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        unit => return Err(format!("invalid memory unit '{}'", unit)),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("argument must be a memory value, got '{}'", value))
+}
+
+/// Parses a read buffer size, a memory value that is greater than 0.
+pub fn parse_read_buffer_size(value: &str) -> Result<usize, String> {
+    match parse_memory(value)? {
+        0 => Err("read buffer size must be greater than 0".to_string()),
+        size => size
+            .try_into()
+            .map_err(|_| format!("read buffer size is too large, got '{}'", value)),
+    }
+}
+
+/// Parses a value compression threshold, a memory value where 0 disables compression.
+pub fn parse_value_compression_threshold(value: &str) -> Result<usize, String> {
+    parse_memory(value)?
+        .try_into()
+        .map_err(|_| format!("value compression threshold is too large, got '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn write_file(name: &str, content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rustdis-{}-{}", process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_file() {
+        let path = write_file(
+            "load_file.conf",
+            r#"
+# Network
+bind 0.0.0.0 -::1
+port 7000
+  tcp-keepalive 60
+
+MAXMEMORY 100mb
+appendonly yes
+logfile ""
+save 3600 1
+"#,
+        );
+
+        let mut config = Config::default();
+        let warnings = config.load_file(&path).unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                port: 7000,
+                bind: "0.0.0.0".to_string(),
+                tcp_keepalive: 60,
+                maxmemory: 100 * 1024 * 1024,
+                appendonly: true,
+                logfile: None,
+                ..Config::default()
+            }
+        );
+        assert_eq!(
+            warnings,
+            vec![format!("{}:10: unknown directive 'save'", path.display())]
+        );
+    }
+
+    #[test]
+    fn load_file_with_include() {
+        let included = write_file("included.conf", "port 7001\nlogfile \"/tmp/my log\"\n");
+        let path = write_file(
+            "include.conf",
+            &format!("port 7000\ninclude {}\ndaemonize yes\n", included.display()),
+        );
+
+        let mut config = Config::default();
+        config.load_file(&path).unwrap();
+
+        assert_eq!(config.port, 7001);
+        assert_eq!(config.logfile, Some(PathBuf::from("/tmp/my log")));
+        assert!(config.daemonize);
+    }
+
+    #[test]
+    fn load_file_with_include_cycle() {
+        let path = env::temp_dir().join(format!("rustdis-{}-cycle.conf", process::id()));
+        fs::write(&path, format!("include {}\n", path.display())).unwrap();
+
+        let err = Config::default().load_file(&path).unwrap_err();
+
+        assert!(matches!(err, ConfigError::Line { .. }));
+        assert!(err.to_string().contains("too many nested includes"));
+    }
+
+    #[test]
+    fn load_file_with_invalid_argument() {
+        let path = write_file("invalid.conf", "port 7000\nport foo\n");
+
+        let err = Config::default().load_file(&path).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!("{}:2: invalid argument for 'port': foo", path.display())
+        );
+    }
+
+    #[test]
+    fn reload() {
+        let path = write_file("reload.conf", "keys-max-results 10\nport 7000\n");
+        let config = Config {
+            config_file: Some(path.clone()),
+            ..Config::default()
+        };
+
+        fs::write(&path, "keys-max-results 20\nport 7001\nunknown yes\n").unwrap();
+        let (reloaded, changes) = config.reload().unwrap();
+
+        assert_eq!(reloaded.keys_max_results, 20);
+        assert_eq!(reloaded.port, config.port);
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    directive: "port",
+                    from: "6379".to_string(),
+                    to: "7001".to_string(),
+                    applied: false,
+                },
+                Change {
+                    directive: "keys-max-results",
+                    from: "0".to_string(),
+                    to: "20".to_string(),
+                    applied: true,
+                },
+            ]
+        );
+
+        fs::write(&path, "keys-max-results many\n").unwrap();
+        assert!(config.reload().is_err());
+    }
+
+    #[test]
+    fn rewrite() {
+        let path = write_file(
+            "rewrite.conf",
+            r#"# Network
+port 7000
+bind 0.0.0.0 -::1
+
+MAXMEMORY 100mb
+save 3600 1
+keys-max-results 10
+keys-max-results 20
+"#,
+        );
+        let mut config = Config::default();
+        config.load_file(&path).unwrap();
+        config.config_file = Some(path.clone());
+        config.port = 7001;
+        config.appendonly = true;
+        config.logfile = Some(PathBuf::from("/tmp/my \"log\""));
+
+        config.rewrite().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"# Network
+port 7001
+bind 0.0.0.0 -::1
+
+MAXMEMORY 100mb
+save 3600 1
+keys-max-results 20
+appendonly yes
+logfile "/tmp/my \"log\""
+"#
+        );
+
+        let mut rewritten = Config::default();
+        rewritten.load_file(&path).unwrap();
+        rewritten.config_file = Some(path.clone());
+        assert_eq!(rewritten, config);
+
+        // Rewriting the same configuration again leaves the file as it is.
+        let content = fs::read_to_string(&path).unwrap();
+        config.rewrite().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn rewrite_without_config_file() {
+        let err = Config::default().rewrite().unwrap_err();
+
+        assert!(matches!(err, ConfigError::NoConfigFile));
+    }
+
+    #[test]
+    fn reloadable_directives_are_directives() {
+        for directive in RELOADABLE {
+            assert!(DIRECTIVES.contains(directive), "{}", directive);
+        }
+    }
+
+    #[test]
+    fn set_wrong_number_of_arguments() {
+        let mut config = Config::default();
+
+        let err = config.set("port", &[]).unwrap_err();
+        assert!(matches!(err, ConfigError::WrongNumberOfArguments(_)));
+
+        let err = config
+            .set("port", &["1".to_string(), "2".to_string()])
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::WrongNumberOfArguments(_)));
+    }
+
+    #[test]
+    fn split_args_with_quotes() {
+        assert_eq!(
+            split_args(r#"logfile "/var/log/my redis.log""#).unwrap(),
+            vec!["logfile", "/var/log/my redis.log"]
+        );
+        assert_eq!(
+            split_args("requirepass 'foo bar'  ").unwrap(),
+            vec!["requirepass", "foo bar"]
+        );
+        assert!(matches!(
+            split_args(r#"logfile "/var/log"#),
+            Err(ConfigError::UnbalancedQuotes)
+        ));
+    }
+
+    #[test]
+    fn parse_bool_values() {
+        assert_eq!(parse_bool("yes"), Ok(true));
+        assert_eq!(parse_bool("NO"), Ok(false));
+        assert!(parse_bool("true").is_err());
+    }
+
+    #[test]
+    fn parse_memory_values() {
+        assert_eq!(parse_memory("0"), Ok(0));
+        assert_eq!(parse_memory("100"), Ok(100));
+        assert_eq!(parse_memory("1k"), Ok(1000));
+        assert_eq!(parse_memory("1kb"), Ok(1024));
+        assert_eq!(parse_memory("2m"), Ok(2_000_000));
+        assert_eq!(parse_memory("2MB"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_memory("4gb"), Ok(4 * 1024 * 1024 * 1024));
+
+        assert!(parse_memory("").is_err());
+        assert!(parse_memory("mb").is_err());
+        assert!(parse_memory("1tb").is_err());
+        assert!(parse_memory("-1").is_err());
+    }
+
+    #[test]
+    fn set_read_buffer_size() {
+        let mut config = Config::default();
+        assert_eq!(config.read_buffer_size, 8 * 1024);
+
+        config
+            .set("read-buffer-size", &["64kb".to_string()])
+            .unwrap();
+        assert_eq!(config.read_buffer_size, 64 * 1024);
+
+        let res = config.set("read-buffer-size", &["0".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_storage_engine() {
+        let mut config = Config::default();
+        assert_eq!(config.storage_engine, EngineKind::HashMap);
+
+        config
+            .set("storage-engine", &["btreemap".to_string()])
+            .unwrap();
+        assert_eq!(config.storage_engine, EngineKind::BTreeMap);
+
+        let res = config.set("storage-engine", &["sled".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_appendfsync() {
+        let mut config = Config::default();
+        assert_eq!(config.appendfsync, AppendFsync::EverySec);
+
+        config.set("appendfsync", &["Always".to_string()]).unwrap();
+        assert_eq!(config.appendfsync, AppendFsync::Always);
+        assert_eq!(config.appendfsync.to_string(), "always");
+
+        let res = config.set("appendfsync", &["sometimes".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_keyspace_initial_capacity() {
+        let mut config = Config::default();
+        assert_eq!(config.keyspace_initial_capacity, 0);
+
+        config
+            .set("keyspace-initial-capacity", &["100000".to_string()])
+            .unwrap();
+        assert_eq!(config.keyspace_initial_capacity, 100_000);
+
+        let res = config.set("keyspace-initial-capacity", &["-1".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_keys_max_results() {
+        let mut config = Config::default();
+        assert_eq!(config.keys_max_results(), None);
+
+        config
+            .set("keys-max-results", &["1000".to_string()])
+            .unwrap();
+        assert_eq!(config.keys_max_results(), Some(1000));
+
+        let res = config.set("keys-max-results", &["-1".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_tombstone_grace_period() {
+        let mut config = Config::default();
+        assert_eq!(config.tombstone_grace_period(), None);
+
+        config
+            .set("tombstone-grace-period", &["1500".to_string()])
+            .unwrap();
+        assert_eq!(
+            config.tombstone_grace_period(),
+            Some(Duration::from_millis(1500))
+        );
+
+        let res = config.set("tombstone-grace-period", &["soon".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn get_every_directive() {
+        let config = Config {
+            port: 7000,
+            bind: "0.0.0.0".to_string(),
+            tcp_nodelay: false,
+            read_buffer_size: 1024,
+            storage_engine: EngineKind::BTreeMap,
+            maxmemory: 1024 * 1024,
+            appendonly: true,
+            appendfsync: AppendFsync::Always,
+            logfile: Some(PathBuf::from("/var/log/rustdis.log")),
+            ..Config::default()
+        };
+
+        // Setting every directive to the value `get` returns gives back the same configuration.
+        let mut copy = Config::default();
+        for name in DIRECTIVES {
+            let value = config.get(name).unwrap();
+            copy.set(name, &[value]).unwrap();
+        }
+        assert_eq!(copy, config);
+
+        assert_eq!(config.get("TCP-NODELAY"), Some("no".to_string()));
+        assert_eq!(Config::default().get("logfile"), Some(String::new()));
+        assert_eq!(config.get("save"), None);
+    }
+
+    #[test]
+    fn set_expire_wakeup_threshold() {
+        let mut config = Config::default();
+        assert_eq!(config.expire_wakeup_threshold(), Duration::from_millis(1));
+
+        config
+            .set("expire-wakeup-threshold", &["0".to_string()])
+            .unwrap();
+        assert_eq!(config.expire_wakeup_threshold(), Duration::ZERO);
+
+        let res = config.set("expire-wakeup-threshold", &["-1".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_rejected_commands_log_rate() {
+        let mut config = Config::default();
+        assert_eq!(config.rejected_commands_log_rate, 10);
+
+        config
+            .set("rejected-commands-log-rate", &["0".to_string()])
+            .unwrap();
+        assert_eq!(config.rejected_commands_log_rate, 0);
+
+        let res = config.set("rejected-commands-log-rate", &["often".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn set_value_compression_threshold() {
+        let mut config = Config::default();
+        assert_eq!(config.value_compression_threshold, 0);
+
+        config
+            .set("value-compression-threshold", &["1kb".to_string()])
+            .unwrap();
+        assert_eq!(config.value_compression_threshold, 1024);
+
+        let res = config.set("value-compression-threshold", &["big".to_string()]);
+        assert!(matches!(res, Err(ConfigError::InvalidArgument { .. })));
+    }
+}
@@ -1,26 +1,85 @@
+use bytes::{Buf, Bytes, BytesMut};
 use futures::stream::StreamExt; // Use the correct StreamExt trait
+use futures::FutureExt;
+use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tokio_util::codec::FramedRead;
 use uuid::Uuid;
 
+use std::collections::VecDeque;
+use std::io::{self, IoSlice};
+use std::mem;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use crate::codec::FrameCodec;
+use crate::error::RustdisError;
 use crate::frame::Frame;
-use crate::Result;
+use crate::reply::{self, ReplyFilter, ReplyWriter};
+use crate::transaction::{Transaction, Watched};
+
+/// Initial capacity of the read buffer, the same `FramedRead` uses by default.
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Initial capacity of the buffer replies are serialized into. Larger replies get a buffer of
+/// their own.
+const REPLY_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Maximum number of queued replies handed to a single vectored write.
+const MAX_IO_SLICES: usize = 64;
+
+/// Queued replies are flushed right away once they add up to this many bytes, so a long pipeline
+/// doesn't keep every reply in memory.
+const MAX_QUEUED_BYTES: usize = 64 * 1024;
 
 pub struct Connection {
     pub id: Uuid,
     pub client_address: SocketAddr,
+    /// Serializes replies for the protocol version spoken with the client.
+    pub reply_writer: ReplyWriter,
+    /// Drops the replies the client turned off with CLIENT REPLY.
+    pub reply_filter: ReplyFilter,
+    /// The transaction the client started with MULTI, queuing its commands.
+    pub transaction: Transaction,
+    /// The keys the client watches with WATCH, checked by EXEC.
+    pub watched: Watched,
+    /// Set once the client killed its own connection with CLIENT KILL. The connection is closed
+    /// right after the reply to the command is sent.
+    pub killed: bool,
+    /// How long to wait before sending the reply to the command being executed, set by DEBUG
+    /// SLEEP. Only this connection waits, see `debug::Sleep`.
+    pub delay: Duration,
+    /// The id of the last frame received from the client, see `next_request_id`.
+    request_id: u64,
     pub writer: OwnedWriteHalf,
     reader: FramedRead<OwnedReadHalf, FrameCodec>,
+    /// Replies are queued and only flushed once no other frame is ready to be processed, so the
+    /// replies to pipelined commands are sent together with a single vectored write.
+    queue: VecDeque<Bytes>,
+    queued_bytes: usize,
+    /// Replies are serialized into this buffer and split off it to be queued, so they share its
+    /// allocation instead of allocating one each. Once the queued replies are written and
+    /// dropped, the buffer reuses the allocation for the next ones.
+    reply_buffer: BytesMut,
+    bytes_written: u64,
+    shared_replies_written: u64,
 }
 
 impl Connection {
     pub fn new(stream: TcpStream, client_address: SocketAddr) -> Connection {
+        Connection::with_read_buffer_size(stream, client_address, DEFAULT_READ_BUFFER_SIZE)
+    }
+
+    /// Creates a connection whose read buffer starts with `read_buffer_size` bytes of capacity.
+    /// A larger buffer lets more pipelined commands be read with a single syscall.
+    pub fn with_read_buffer_size(
+        stream: TcpStream,
+        client_address: SocketAddr,
+        read_buffer_size: usize,
+    ) -> Connection {
         let (reader, writer) = stream.into_split();
-        let reader = FramedRead::new(reader, FrameCodec);
+        let reader = FramedRead::with_capacity(reader, FrameCodec::default(), read_buffer_size);
         let id = Uuid::new_v4();
 
         Connection {
@@ -28,14 +87,143 @@ impl Connection {
             writer,
             reader,
             client_address,
+            reply_writer: ReplyWriter::default(),
+            reply_filter: ReplyFilter::default(),
+            transaction: Transaction::default(),
+            watched: Watched::default(),
+            killed: false,
+            delay: Duration::ZERO,
+            request_id: 0,
+            queue: VecDeque::new(),
+            queued_bytes: 0,
+            reply_buffer: BytesMut::with_capacity(REPLY_BUFFER_SIZE),
+            bytes_written: 0,
+            shared_replies_written: 0,
         }
     }
 
-    pub async fn read_frame(&mut self) -> Result<Option<Frame>> {
-        match self.reader.next().await {
+    /// Assigns an id to the frame just received from the client: 1 for the first one, then
+    /// increasing by one. The id is logged with every trace of the request and kept in the
+    /// journal, so the commands of pipelines executed at the same time by different connections
+    /// can be told apart.
+    pub fn next_request_id(&mut self) -> u64 {
+        self.request_id += 1;
+        self.request_id
+    }
+
+    /// Reads the next frame, flushing the queued replies first if it is not available yet.
+    pub async fn read_frame(&mut self) -> Result<Option<Frame>, RustdisError> {
+        // Polling the stream once either yields a frame that is already buffered (or readable
+        // right away), or registers interest in the socket. Reading the next frame is cancel safe,
+        // so the pending read can be dropped and awaited again after flushing.
+        let next = match self.reader.next().now_or_never() {
+            Some(next) => next,
+            None => {
+                self.flush().await?;
+                self.reader.next().await
+            }
+        };
+
+        match next {
             Some(Ok(frame)) => Ok(Some(frame)),
             Some(Err(e)) => Err(e),
-            None => Ok(None),
+            None => {
+                self.flush().await?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Writes the queued replies to the client.
+    pub async fn flush(&mut self) -> Result<(), RustdisError> {
+        while !self.queue.is_empty() {
+            let slices: Vec<IoSlice> = self
+                .queue
+                .iter()
+                .take(MAX_IO_SLICES)
+                .map(|reply| IoSlice::new(reply))
+                .collect();
+
+            let n = self.writer.write_vectored(&slices).await?;
+            if n == 0 {
+                return Err(io::Error::from(io::ErrorKind::WriteZero).into());
+            }
+            self.advance(n);
         }
+
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Drops the first `n` queued bytes, which have been written to the socket.
+    fn advance(&mut self, mut n: usize) {
+        self.queued_bytes -= n;
+
+        while n > 0 {
+            let front = self
+                .queue
+                .front_mut()
+                .expect("advanced past the queued replies");
+            if n < front.len() {
+                front.advance(n);
+                return;
+            }
+            n -= front.len();
+            self.queue.pop_front();
+        }
+    }
+
+    /// Serializes and queues `frame` to be written to the client, returning its size in bytes.
+    pub async fn write_frame(&mut self, frame: Frame) -> Result<usize, RustdisError> {
+        let frame = self.reply_writer.adapt(frame);
+
+        let bytes = match reply::shared_reply(&frame) {
+            Some(reply) => {
+                self.shared_replies_written += 1;
+                Bytes::from_static(reply)
+            }
+            None => {
+                frame.serialize_into(&mut self.reply_buffer);
+                if self.reply_buffer.len() > REPLY_BUFFER_SIZE {
+                    // A large reply takes the allocation it grew the buffer to with it, so the
+                    // allocation is freed once the reply is written instead of being kept by the
+                    // connection.
+                    mem::replace(
+                        &mut self.reply_buffer,
+                        BytesMut::with_capacity(REPLY_BUFFER_SIZE),
+                    )
+                } else {
+                    self.reply_buffer.split()
+                }
+                .freeze()
+            }
+        };
+        let len = bytes.len();
+
+        self.queue.push_back(bytes);
+        self.queued_bytes += len;
+        self.bytes_written += len as u64;
+
+        if self.queued_bytes >= MAX_QUEUED_BYTES {
+            self.flush().await?;
+        }
+
+        Ok(len)
+    }
+
+    /// Total number of bytes read from the client, counting only fully decoded frames.
+    pub fn bytes_read(&self) -> u64 {
+        self.reader.decoder().bytes_read
+    }
+
+    /// Total number of bytes written to the client.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Number of replies written to the client that were already serialized, see
+    /// `reply::shared_reply`.
+    pub fn shared_replies_written(&self) -> u64 {
+        self.shared_replies_written
     }
 }
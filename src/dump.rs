@@ -0,0 +1,374 @@
+//! Export and import of the keyspace as a stream of RESP encoded commands.
+//!
+//! Every key is written as a `SET key value` command, or an `HSET key field value ...` command
+//! for hashes, followed by a `PEXPIREAT key timestamp` command when the key has a time to live.
+//! The output can be replayed into rustdis with [`import`], or into any Redis server with
+//! `redis-cli --pipe`, which makes it handy to seed demo datasets and to move data in and out of
+//! rustdis without implementing RDB.
+//!
+//! Fixture files, executed with [`preload`] before the server accepts connections, may also hold
+//! inline commands, one per line, which are easier to write by hand.
+
+use bytes::Bytes;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+use crate::commands::executable::Executable;
+use crate::commands::Command;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Serializes every key of `store` as RESP encoded commands. Keys are sorted so the output is
+/// deterministic.
+pub fn export(store: &Store) -> Vec<u8> {
+    let state = store.lock();
+    let mut keys: Vec<_> = state.iter_values().collect();
+    keys.sort_by_key(|(key, _)| *key);
+
+    let now = Instant::now();
+    let unix_now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let mut bytes = vec![];
+    for (key, value) in keys {
+        let set = match (value.string(), value.hash()) {
+            (Some(data), _) => command(vec![Bytes::from("SET"), Bytes::from(key.clone()), data]),
+            (None, Some(hash)) => {
+                let mut fields: Vec<_> = hash.iter().collect();
+                fields.sort_by_key(|(field, _)| *field);
+                let mut parts = vec![Bytes::from("HSET"), Bytes::from(key.clone())];
+                for (field, value) in fields {
+                    parts.push(Bytes::from(field.clone()));
+                    parts.push(value.clone());
+                }
+                command(parts)
+            }
+            (None, None) => unreachable!("every value is a string or a hash"),
+        };
+        bytes.extend(set.serialize());
+
+        if let Some(expires_at) = value.expires_at {
+            let unix_expires_at = unix_now + expires_at.saturating_duration_since(now);
+            let pexpireat = command(vec![
+                Bytes::from("PEXPIREAT"),
+                Bytes::from(key.clone()),
+                Bytes::from(unix_expires_at.as_millis().to_string()),
+            ]);
+            bytes.extend(pexpireat.serialize());
+        }
+    }
+
+    bytes
+}
+
+/// Executes every RESP encoded command in `data` against `store`, returning how many were
+/// executed. Stops at the first command that fails.
+pub fn import(store: &Store, data: &[u8]) -> Result<usize, RustdisError> {
+    let mut cursor = Cursor::new(data);
+    let mut count = 0;
+
+    while (cursor.position() as usize) < data.len() {
+        let frame = match Frame::parse(&mut cursor) {
+            Ok(frame) => frame,
+            Err(RustdisError::Incomplete) => {
+                return Err(RustdisError::Protocol(
+                    "unexpected end of the import data".to_string(),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+
+        execute(store, frame)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Writes the output of [`export`] to the file at `path`.
+pub fn export_to_file(store: &Store, path: &Path) -> Result<(), RustdisError> {
+    fs::write(path, export(store))?;
+    Ok(())
+}
+
+/// Runs [`import`] over the content of the file at `path`.
+pub fn import_from_file(store: &Store, path: &Path) -> Result<usize, RustdisError> {
+    let data = fs::read(path)?;
+    import(store, &data)
+}
+
+/// Executes every command in `data` against `store`, returning how many were executed. Stops at
+/// the first command that fails.
+///
+/// Commands are either RESP encoded, like the ones [`export`] writes, or inline: a line holding
+/// the command and its arguments separated by spaces, like `SET greeting "hello world"`. Double
+/// quotes delimit an argument holding spaces, and blank lines and lines starting with `#` are
+/// skipped.
+pub fn preload(store: &Store, data: &[u8]) -> Result<usize, RustdisError> {
+    let mut cursor = Cursor::new(data);
+    let mut count = 0;
+
+    loop {
+        let rest = &data[cursor.position() as usize..];
+        let Some(start) = rest.iter().position(|byte| !byte.is_ascii_whitespace()) else {
+            break;
+        };
+        cursor.set_position(cursor.position() + start as u64);
+
+        let frame = if rest[start] == b'*' {
+            match Frame::parse(&mut cursor) {
+                Ok(frame) => frame,
+                Err(RustdisError::Incomplete) => {
+                    return Err(RustdisError::Protocol(
+                        "unexpected end of the preload data".to_string(),
+                    ))
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            let line = &rest[start..];
+            let end = line
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .unwrap_or(line.len());
+            cursor.set_position(cursor.position() + end as u64);
+
+            if line[0] == b'#' {
+                continue;
+            }
+            command(split_inline(&line[..end])?)
+        };
+
+        execute(store, frame)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Runs [`preload`] over the content of the file at `path`.
+pub fn preload_from_file(store: &Store, path: &Path) -> Result<usize, RustdisError> {
+    let data = fs::read(path)?;
+    preload(store, &data)
+}
+
+/// Splits an inline command into its arguments.
+fn split_inline(line: &[u8]) -> Result<Vec<Bytes>, RustdisError> {
+    let mut parts = vec![];
+    let mut bytes = line.iter().copied().peekable();
+
+    loop {
+        while bytes.next_if(u8::is_ascii_whitespace).is_some() {}
+        let Some(first) = bytes.next() else {
+            break;
+        };
+
+        let mut part = vec![];
+        if first == b'"' {
+            loop {
+                match bytes.next() {
+                    Some(b'"') => break,
+                    Some(byte) => part.push(byte),
+                    None => {
+                        return Err(RustdisError::Protocol(
+                            "unbalanced quotes in inline command".to_string(),
+                        ))
+                    }
+                }
+            }
+        } else {
+            part.push(first);
+            while let Some(byte) = bytes.next_if(|byte| !byte.is_ascii_whitespace()) {
+                part.push(byte);
+            }
+        }
+        parts.push(Bytes::from(part));
+    }
+
+    Ok(parts)
+}
+
+/// Executes `frame` against `store`, turning an error reply into an error.
+fn execute(store: &Store, frame: Frame) -> Result<(), RustdisError> {
+    let cmd = Command::try_from(frame)?;
+    if let Frame::Error(msg) = cmd.exec(store.clone())? {
+        return Err(RustdisError::command(ErrorKind::Err, msg));
+    }
+
+    Ok(())
+}
+
+fn command(parts: Vec<Bytes>) -> Frame {
+    Frame::Array(parts.into_iter().map(Frame::Bulk).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::NewValue;
+    use tokio::time::Duration;
+
+    #[tokio::test]
+    async fn export_keys() {
+        let store = Store::new();
+
+        {
+            let mut state = store.lock();
+            state.set(String::from("key2"), Bytes::from("value2"));
+            state.set(String::from("key1"), Bytes::from("value1"));
+        }
+
+        let res = export(&store);
+
+        assert_eq!(
+            res,
+            b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n\
+              *3\r\n$3\r\nSET\r\n$4\r\nkey2\r\n$6\r\nvalue2\r\n"
+                .to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn export_hash() {
+        let store = Store::new();
+        let fields = [("name", "ana"), ("age", "30")]
+            .map(|(field, value)| (field.to_string(), Bytes::from(value)));
+        store.lock().hset(String::from("user"), fields).unwrap();
+
+        let res = export(&store);
+
+        assert_eq!(
+            Frame::parse(&mut Cursor::new(&res[..])).unwrap(),
+            command(vec![
+                Bytes::from("HSET"),
+                Bytes::from("user"),
+                Bytes::from("age"),
+                Bytes::from("30"),
+                Bytes::from("name"),
+                Bytes::from("ana"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn export_keys_with_ttl() {
+        let store = Store::new();
+
+        store.set2(
+            String::from("key1"),
+            NewValue {
+                data: Bytes::from("value1"),
+                ttl: Some(Duration::from_secs(100)),
+            },
+        );
+
+        let res = export(&store);
+
+        let mut cursor = Cursor::new(&res[..]);
+        let set = Frame::parse(&mut cursor).unwrap();
+        let pexpireat = Frame::parse(&mut cursor).unwrap();
+
+        assert_eq!(
+            set,
+            command(vec![
+                Bytes::from("SET"),
+                Bytes::from("key1"),
+                Bytes::from("value1")
+            ])
+        );
+
+        let parts = match pexpireat {
+            Frame::Array(parts) => parts,
+            frame => panic!("expected array frame, got {:?}", frame),
+        };
+        assert_eq!(parts[0], Frame::Bulk(Bytes::from("PEXPIREAT")));
+        assert_eq!(parts[1], Frame::Bulk(Bytes::from("key1")));
+    }
+
+    #[tokio::test]
+    async fn import_keys() {
+        let store = Store::new();
+
+        let data = b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n\
+                     *3\r\n$3\r\nSET\r\n$4\r\nkey2\r\n$6\r\nvalue2\r\n";
+
+        let res = import(&store, data).unwrap();
+
+        assert_eq!(res, 2);
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("value1")));
+        assert_eq!(store.lock().get("key2"), Some(Bytes::from("value2")));
+    }
+
+    #[tokio::test]
+    async fn import_truncated_data() {
+        let store = Store::new();
+
+        let data = b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n*3\r\n$3\r\nSET\r\n";
+
+        let res = import(&store, data);
+
+        assert!(matches!(res, Err(RustdisError::Protocol(_))));
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("value1")));
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let store = Store::new();
+
+        {
+            let mut state = store.lock();
+            state.set(String::from("key1"), Bytes::from("value1"));
+            state.set(String::from("key2"), Bytes::from(""));
+            state.set(String::from("key3"), Bytes::from("multi\r\nline"));
+            let fields = [("name", "ana"), ("age", "30")]
+                .map(|(field, value)| (field.to_string(), Bytes::from(value)));
+            state.hset(String::from("key4"), fields).unwrap();
+        }
+
+        let other = Store::new();
+        import(&other, &export(&store)).unwrap();
+
+        assert_eq!(export(&other), export(&store));
+    }
+
+    #[tokio::test]
+    async fn preload_commands() {
+        let store = Store::new();
+
+        let data = b"# Demo dataset\n\
+                     SET greeting \"hello world\"\r\n\
+                     \n\
+                     *3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n\
+                     PEXPIRE key1 100000\n\
+                     set   empty \"\"";
+
+        let res = preload(&store, data).unwrap();
+
+        assert_eq!(res, 4);
+        assert_eq!(
+            store.lock().get("greeting"),
+            Some(Bytes::from("hello world"))
+        );
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("value1")));
+        assert!(store.lock().get_value("key1").unwrap().expires_at.is_some());
+        assert_eq!(store.lock().get("empty"), Some(Bytes::from("")));
+    }
+
+    #[tokio::test]
+    async fn preload_invalid_commands() {
+        let store = Store::new();
+
+        let res = preload(&store, b"SET key \"value\n");
+        assert!(matches!(res, Err(RustdisError::Protocol(_))));
+
+        let res = preload(&store, b"SET key1 value1\nNOPE key\nSET key2 value2\n");
+        assert!(matches!(res, Err(RustdisError::Command { .. })));
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("value1")));
+        assert_eq!(store.lock().get("key2"), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! Storage engines holding the keyspace of the store.
+//!
+//! The store keeps the logic shared by every engine, like expiration and compression, and delegates
+//! to a `KvEngine` the data structure keys and values live in. Which engine is used is chosen with
+//! the `storage-engine` configuration directive.
+
+use std::collections::{BTreeMap, HashMap, TryReserveError};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::store::Value;
+
+/// A map from keys to values, the data structure behind the store.
+pub trait KvEngine: Send {
+    fn get(&self, key: &str) -> Option<&Value>;
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value>;
+
+    /// Inserts `value` at `key`, returning the value it replaces, if any.
+    fn insert(&mut self, key: String, value: Value) -> Option<Value>;
+
+    fn remove(&mut self, key: &str) -> Option<Value>;
+
+    fn len(&self) -> usize;
+
+    /// Iterates over every key and value, in an order defined by the engine.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&String, &Value)> + '_>;
+
+    /// How many keys fit before the engine has to grow, `None` for engines that don't allocate
+    /// ahead, like B-trees.
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Reallocates the engine to hold at least `capacity` keys, moving every key to the new
+    /// allocation. Fails, keeping the current allocation, when the new one can't be made. Does
+    /// nothing for engines that don't allocate ahead.
+    fn rehash(&mut self, _capacity: usize) -> Result<(), TryReserveError> {
+        Ok(())
+    }
+
+    fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Keeps keys in a hash map, with constant time lookups and no particular key order. The default
+/// engine, like Redis does.
+///
+/// The map doubles its capacity once it is full, moving every key: a rehash, which makes the write
+/// that triggers it much slower than the others. Preallocating with `keyspace-initial-capacity`
+/// avoids them while the keyspace stays below that size.
+#[derive(Default)]
+pub struct HashMapEngine {
+    keys: HashMap<String, Value>,
+}
+
+impl HashMapEngine {
+    pub fn with_capacity(capacity: usize) -> HashMapEngine {
+        HashMapEngine {
+            keys: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl KvEngine for HashMapEngine {
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.keys.get(key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.keys.get_mut(key)
+    }
+
+    fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.keys.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Value> {
+        self.keys.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&String, &Value)> + '_> {
+        Box::new(self.keys.iter())
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.keys.capacity())
+    }
+
+    fn rehash(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        self.keys.shrink_to(capacity);
+        self.keys
+            .try_reserve(capacity.saturating_sub(self.keys.len()))
+    }
+}
+
+/// Keeps keys in a B-tree, with logarithmic time lookups and keys iterated in lexicographical
+/// order, so KEYS and SCAN reply with sorted keys.
+#[derive(Default)]
+pub struct BTreeMapEngine {
+    keys: BTreeMap<String, Value>,
+}
+
+impl KvEngine for BTreeMapEngine {
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.keys.get(key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.keys.get_mut(key)
+    }
+
+    fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.keys.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Value> {
+        self.keys.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&String, &Value)> + '_> {
+        Box::new(self.keys.iter())
+    }
+}
+
+/// The storage engines that can be selected with the `storage-engine` directive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EngineKind {
+    #[default]
+    HashMap,
+    BTreeMap,
+}
+
+impl EngineKind {
+    /// Creates an empty engine, preallocated for `capacity` keys when the engine supports it.
+    pub fn build(self, capacity: usize) -> Box<dyn KvEngine> {
+        match self {
+            EngineKind::HashMap => Box::new(HashMapEngine::with_capacity(capacity)),
+            EngineKind::BTreeMap => Box::<BTreeMapEngine>::default(),
+        }
+    }
+}
+
+impl FromStr for EngineKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "hashmap" => Ok(EngineKind::HashMap),
+            "btreemap" => Ok(EngineKind::BTreeMap),
+            _ => Err(format!(
+                "storage engine must be 'hashmap' or 'btreemap', got '{}'",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineKind::HashMap => write!(f, "hashmap"),
+            EngineKind::BTreeMap => write!(f, "btreemap"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    /// Runs the same operations against every engine.
+    fn engines() -> Vec<Box<dyn KvEngine>> {
+        vec![EngineKind::HashMap.build(0), EngineKind::BTreeMap.build(0)]
+    }
+
+    #[tokio::test]
+    async fn operations() {
+        for mut engine in engines() {
+            assert!(engine.is_empty());
+
+            assert!(engine
+                .insert("key1".to_string(), Value::new(Bytes::from("1")))
+                .is_none());
+            let replaced = engine.insert("key1".to_string(), Value::new(Bytes::from("2")));
+            assert_eq!(
+                replaced.and_then(|value| value.string()),
+                Some(Bytes::from("1"))
+            );
+
+            assert!(engine.contains_key("key1"));
+            assert_eq!(engine.len(), 1);
+            assert_eq!(
+                engine.get("key1").and_then(Value::string),
+                Some(Bytes::from("2"))
+            );
+
+            engine.get_mut("key1").unwrap().expires_at = None;
+
+            assert!(engine.remove("key1").is_some());
+            assert!(engine.remove("key1").is_none());
+            assert!(!engine.contains_key("key1"));
+        }
+    }
+
+    #[tokio::test]
+    async fn btreemap_iterates_in_order() {
+        let mut engine = EngineKind::BTreeMap.build(0);
+        for key in ["c", "a", "b"] {
+            engine.insert(key.to_string(), Value::new(Bytes::from(key)));
+        }
+
+        let keys: Vec<&String> = engine.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn capacity() {
+        let mut engine = EngineKind::HashMap.build(1000);
+        assert!(engine.capacity().unwrap() >= 1000);
+
+        for i in 0..10 {
+            engine.insert(i.to_string(), Value::new(Bytes::from("value")));
+        }
+        engine.rehash(0).unwrap();
+        let capacity = engine.capacity().unwrap();
+        assert!((10..1000).contains(&capacity));
+
+        engine.rehash(5000).unwrap();
+        assert!(engine.capacity().unwrap() >= 5000);
+        assert_eq!(engine.len(), 10);
+
+        // An allocation that can't be made fails instead of aborting, keeping every key.
+        assert!(engine.rehash(usize::MAX).is_err());
+        assert!(engine.capacity().unwrap() >= 5000);
+        assert_eq!(engine.len(), 10);
+
+        assert_eq!(EngineKind::BTreeMap.build(1000).capacity(), None);
+    }
+
+    #[test]
+    fn parse_kind() {
+        assert_eq!("hashmap".parse(), Ok(EngineKind::HashMap));
+        assert_eq!("BTreeMap".parse(), Ok(EngineKind::BTreeMap));
+        assert!("sled".parse::<EngineKind>().is_err());
+        assert_eq!(EngineKind::BTreeMap.to_string(), "btreemap");
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+use std::io;
+
+use thiserror::Error as ThisError;
+
+use crate::commands::CommandParserError;
+
+/// The prefix Redis puts in front of an error reply, which clients use to tell errors apart.
+///
+/// Ref: <https://redis.io/docs/latest/develop/reference/protocol-spec/#simple-errors>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Err,
+    WrongType,
+    Busy,
+    Loading,
+    ExecAbort,
+    NoProto,
+    WrongPass,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Err => write!(f, "ERR"),
+            ErrorKind::WrongType => write!(f, "WRONGTYPE"),
+            ErrorKind::Busy => write!(f, "BUSY"),
+            ErrorKind::Loading => write!(f, "LOADING"),
+            ErrorKind::ExecAbort => write!(f, "EXECABORT"),
+            ErrorKind::NoProto => write!(f, "NOPROTO"),
+            ErrorKind::WrongPass => write!(f, "WRONGPASS"),
+        }
+    }
+}
+
+/// Structured error type of the crate. It lets embedders tell protocol errors apart from IO
+/// errors and command errors, while its `Display` output matches the RESP error string a Redis
+/// client would receive.
+///
+/// The codec, the frame parser, the commands and the server all fail with it. The boxed
+/// `crate::Error` can still be converted into this type with `From`, which recovers the variant
+/// whenever the boxed error is one of ours.
+#[derive(Debug, ThisError)]
+pub enum RustdisError {
+    /// The client sent data that does not follow the RESP specification, or a command that could
+    /// not be parsed from it.
+    #[error("ERR {0}")]
+    Protocol(String),
+    /// The data received so far ends before the frame being parsed does, more has to be read
+    /// to parse it, see `Frame::parse`. It is never replied to a client.
+    #[error("ERR not enough data is available to parse an entire frame")]
+    Incomplete,
+    /// Reading from or writing to the socket failed.
+    #[error("ERR {0}")]
+    Io(#[from] io::Error),
+    /// A command was parsed correctly but could not be executed.
+    #[error("{kind} {message}")]
+    Command { kind: ErrorKind, message: String },
+    /// The store rejected an operation.
+    #[error("ERR {0}")]
+    Store(String),
+    /// Any other error, converted from the boxed `crate::Error`.
+    #[error("ERR {0}")]
+    Other(crate::Error),
+}
+
+impl RustdisError {
+    pub fn command(kind: ErrorKind, message: impl Into<String>) -> RustdisError {
+        RustdisError::Command {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<CommandParserError> for RustdisError {
+    fn from(err: CommandParserError) -> RustdisError {
+        match err {
+            CommandParserError::UnknownCommand { .. } => {
+                RustdisError::command(ErrorKind::Err, err.to_string())
+            }
+            err => RustdisError::Protocol(err.to_string()),
+        }
+    }
+}
+
+impl From<crate::Error> for RustdisError {
+    fn from(err: crate::Error) -> RustdisError {
+        let err = match err.downcast::<RustdisError>() {
+            Ok(err) => return *err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<CommandParserError>() {
+            Ok(err) => return (*err).into(),
+            Err(err) => err,
+        };
+        match err.downcast::<io::Error>() {
+            Ok(err) => RustdisError::Io(*err),
+            Err(err) => RustdisError::Other(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_matches_resp_error_strings() {
+        let err = RustdisError::command(
+            ErrorKind::WrongType,
+            "Operation against a key holding the wrong kind of value",
+        );
+        assert_eq!(
+            err.to_string(),
+            "WRONGTYPE Operation against a key holding the wrong kind of value"
+        );
+
+        let err = RustdisError::Store("value is not an integer or out of range".to_string());
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+
+    #[test]
+    fn from_boxed_error_recovers_the_variant() {
+        let err: crate::Error = CommandParserError::UnknownCommand {
+            command: "foo".to_string(),
+        }
+        .into();
+        let err = RustdisError::from(err);
+        assert!(matches!(
+            err,
+            RustdisError::Command {
+                kind: ErrorKind::Err,
+                ..
+            }
+        ));
+
+        let err: crate::Error = CommandParserError::EndOfStream.into();
+        assert!(matches!(RustdisError::from(err), RustdisError::Protocol(_)));
+
+        let err: crate::Error = RustdisError::Incomplete.into();
+        assert!(matches!(RustdisError::from(err), RustdisError::Incomplete));
+
+        let err: crate::Error = io::Error::new(io::ErrorKind::BrokenPipe, "closed").into();
+        assert!(matches!(RustdisError::from(err), RustdisError::Io(_)));
+
+        let err: crate::Error = "something else".into();
+        assert!(matches!(RustdisError::from(err), RustdisError::Other(_)));
+    }
+}
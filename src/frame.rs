@@ -3,208 +3,538 @@
 use std::fmt;
 
 use bytes::Buf;
+use bytes::BufMut;
 use bytes::Bytes;
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 use std::string::FromUtf8Error;
-use thiserror::Error as ThisError;
 use tracing::error;
 
+use crate::error::RustdisError;
+
 static CRLF: &[u8; 2] = b"\r\n";
 
-#[derive(Debug, ThisError)]
-pub enum Error {
-    #[error("not enough data is available to parse an entire frame")]
-    Incomplete,
-    #[error("invalid frame data type: {0}")]
-    InvalidDataType(u8),
-    /// Invalid message encoding.
-    #[error("{0}")]
-    Other(crate::Error),
+/// The most elements an array may declare. Larger lengths are a protocol error, like Redis
+/// rejects them, rather than a promise of data that may never come.
+pub const MAX_ARRAY_LEN: usize = 1024 * 1024;
+
+/// The most arrays that may be nested within each other. Requests are flat arrays, and replies
+/// nest a few levels at most.
+pub const MAX_NESTING_DEPTH: usize = 128;
+
+/// The length of the shortest frame, like `_\r\n` or `:1\r\n`.
+const MIN_FRAME_LEN: usize = 3;
+
+/// What `Frame::parse_element` parsed.
+enum Element {
+    Frame(Frame),
+    /// The header of an aggregate with that many elements, at least one. The keys and the values
+    /// of a map are counted as elements of their own.
+    Aggregate(Aggregate, usize),
+}
+
+/// The frames holding other frames.
+#[derive(Clone, Copy, PartialEq)]
+enum Aggregate {
+    Array,
+    Map,
+    Set,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+impl Aggregate {
+    /// Builds the frame holding `frames`, which come in key and value pairs for a map.
+    fn build(self, frames: Vec<Frame>) -> Frame {
+        match self {
+            Aggregate::Array => Frame::Array(frames),
+            Aggregate::Set => Frame::Set(frames),
+            Aggregate::Map => {
+                let mut frames = frames.into_iter();
+                let mut entries = Vec::with_capacity(frames.len() / 2);
+                while let (Some(key), Some(value)) = (frames.next(), frames.next()) {
+                    entries.push((key, value));
+                }
+                Frame::Map(entries)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     Simple(String),
     Error(String),
     Integer(i64),
     Bulk(Bytes),
     Array(Vec<Frame>),
+    // RESP3 scalar types. Big numbers are kept as their decimal representation, since they may
+    // be outside the range of any Rust integer type.
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    // A RESP3 bulk string meant to be shown to the user as is. `encoding` is a three letter hint
+    // of its format: `txt` for plain text or `mkd` for markdown.
+    Verbatim { encoding: String, text: Bytes },
     // Whereas RESP3 has a dedicated data type for null values, RESP2 has no such type. Instead,
     // due to historical reasons, the representation of null values in RESP2 is via predetermined
-    // forms of the bulk strings and arrays types.
+    // forms of the bulk strings and arrays types. Commands reply with the variant a RESP2 client
+    // expects in each context, and all of them are sent as the null type to RESP3 clients.
     Null,
     NullBulkString,
     NullArray,
+    // RESP3 aggregate types. A map keeps its entries in the order they were received, and neither
+    // a map nor a set checks its keys or elements are unique. RESP2 clients receive them as arrays,
+    // flattened into keys and values for a map.
+    Map(Vec<(Frame, Frame)>),
+    Set(Vec<Frame>),
+}
+
+/// Parses a frame that may arrive over several reads, keeping what it parsed of an incomplete
+/// frame, so every byte is parsed once however many reads the frame takes. The codec holds one per
+/// connection, see `FrameCodec`.
+#[derive(Default)]
+pub struct Parser {
+    /// Arrays, maps and sets are parsed without recursion, so deeply nested ones can't exhaust the
+    /// stack: the ones being parsed are kept here, innermost last, along with how many elements
+    /// each one is still missing.
+    aggregates: Vec<(Aggregate, Vec<Frame>, usize)>,
+    /// The bytes of the frame being parsed up to the end of its last complete element.
+    parsed: usize,
+    /// The bytes past `parsed` known not to hold a CRLF.
+    searched: usize,
+}
+
+impl Parser {
+    /// Parses the frame at the position of `src`, advancing it past the frame, like
+    /// `Frame::parse`. When `src` ends before the frame does, the elements parsed so far are kept
+    /// and the next call resumes after them, so it must be given the same frame from its start,
+    /// with more data received.
+    pub fn parse(&mut self, src: &mut Cursor<&[u8]>) -> Result<Frame, RustdisError> {
+        let start = src.position() as usize;
+        let res = self.resume(src, start);
+        if !matches!(res, Err(RustdisError::Incomplete)) {
+            *self = Parser::default();
+        }
+        res
+    }
+
+    fn resume(&mut self, src: &mut Cursor<&[u8]>, start: usize) -> Result<Frame, RustdisError> {
+        loop {
+            let element_start = start + self.parsed;
+            src.set_position(element_start as u64);
+
+            // Every element starts with a line. A line longer than the data received so far is
+            // only searched for its CRLF in the bytes received since the last call, the byte
+            // before them included, as the CRLF may be split between them.
+            let buf = *src.get_ref();
+            let Some(&first_byte) = buf.get(element_start) else {
+                return Err(RustdisError::Incomplete);
+            };
+            DataType::try_from(first_byte)?;
+            let search_start = (element_start + self.searched)
+                .saturating_sub(1)
+                .max(element_start);
+            let unsearched = buf.get(search_start..).unwrap_or_default();
+            if !unsearched.windows(2).any(|window| window == CRLF) {
+                self.searched = buf.len() - element_start;
+                return Err(RustdisError::Incomplete);
+            }
+
+            let element = Frame::parse_element(src)?;
+            self.parsed = src.position() as usize - start;
+            self.searched = 0;
+
+            let mut frame = match element {
+                Element::Frame(frame) => frame,
+                Element::Aggregate(aggregate, elements) => {
+                    if self.aggregates.len() == MAX_NESTING_DEPTH {
+                        return Err(RustdisError::Protocol(
+                            "protocol error; too many nested arrays".to_string(),
+                        ));
+                    }
+                    // The declared length is not trusted to preallocate the elements: every
+                    // element takes at least `MIN_FRAME_LEN` bytes, so there can't be more of
+                    // them than the data received so far holds.
+                    let capacity = elements.min(src.remaining() / MIN_FRAME_LEN);
+                    self.aggregates
+                        .push((aggregate, Vec::with_capacity(capacity), elements));
+                    continue;
+                }
+            };
+
+            // The frame completes the aggregates it is the last element of.
+            loop {
+                let Some((_, frames, missing)) = self.aggregates.last_mut() else {
+                    return Ok(frame);
+                };
+                frames.push(frame);
+                *missing -= 1;
+                if *missing > 0 {
+                    break;
+                }
+                let (aggregate, frames, _) = self
+                    .aggregates
+                    .pop()
+                    .expect("checked by the let-else above");
+                frame = aggregate.build(frames);
+            }
+        }
+    }
 }
 
 // Protocol specification: https://redis.io/docs/reference/protocol-spec/
 impl Frame {
-    pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Self, Error> {
+    /// Parses the frame at the position of `src`, advancing it past the frame. Fails with
+    /// `RustdisError::Incomplete` when `src` ends before the frame does, in which case the caller reads
+    /// more data and parses again from the same position. A `Parser` does the same without parsing
+    /// the elements received so far again.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use bytes::Bytes;
+    /// use rustdis::frame::Frame;
+    /// use rustdis::RustdisError;
+    ///
+    /// let data: &[u8] = b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n:42\r\n";
+    /// let mut src = Cursor::new(data);
+    ///
+    /// let frame = Frame::parse(&mut src).unwrap();
+    /// assert_eq!(
+    ///     frame,
+    ///     Frame::Array(vec![
+    ///         Frame::Bulk(Bytes::from("GET")),
+    ///         Frame::Bulk(Bytes::from("key")),
+    ///     ])
+    /// );
+    /// assert_eq!(Frame::parse(&mut src).unwrap(), Frame::Integer(42));
+    ///
+    /// // A bulk string announcing more bytes than available.
+    /// let mut src = Cursor::new(&b"$5\r\nhel"[..]);
+    /// assert!(matches!(Frame::parse(&mut src), Err(RustdisError::Incomplete)));
+    ///
+    /// // `?` is not a RESP type.
+    /// let mut src = Cursor::new(&b"?1\r\n"[..]);
+    /// assert!(matches!(Frame::parse(&mut src), Err(RustdisError::Protocol(_))));
+    /// ```
+    pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Self, RustdisError> {
+        Parser::default().parse(src)
+    }
+
+    /// Parses a frame that is not a non empty aggregate, or the header of a non empty aggregate,
+    /// whose elements follow.
+    fn parse_element(src: &mut Cursor<&[u8]>) -> Result<Element, RustdisError> {
         // The first byte in an RESP-serialized payload always identifies its type.
         // Subsequent bytes constitute the type's contents.
         let first_byte = get_byte(src)?;
         let data_type = DataType::try_from(first_byte)?;
 
+        // *<number-of-elements>\r\n<element-1>...<element-n>
+        // %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>
+        // ~<number-of-elements>\r\n<element-1>...<element-n>
+        let aggregate = match data_type {
+            DataType::Array => Some(Aggregate::Array),
+            DataType::Map => Some(Aggregate::Map),
+            DataType::Set => Some(Aggregate::Set),
+            _ => None,
+        };
+        if let Some(aggregate) = aggregate {
+            let length = get_frame_bytes(src)?;
+            let length = String::from_utf8(length.to_vec()).map_err(invalid_format)?;
+            let length = length
+                .parse::<isize>()
+                .map_err(|e| RustdisError::Protocol(e.to_string()))?;
+
+            return match length {
+                -1 if aggregate == Aggregate::Array => Ok(Element::Frame(Frame::NullArray)),
+                0 => Ok(Element::Frame(aggregate.build(vec![]))),
+                1.. if length as usize <= MAX_ARRAY_LEN => {
+                    let elements = match aggregate {
+                        Aggregate::Map => length as usize * 2,
+                        _ => length as usize,
+                    };
+                    Ok(Element::Aggregate(aggregate, elements))
+                }
+                _ => Err(RustdisError::Protocol(format!(
+                    "protocol error; invalid multibulk length {}",
+                    length
+                ))),
+            };
+        }
+
+        Self::parse_scalar(src, data_type).map(Element::Frame)
+    }
+
+    /// Parses a frame of `data_type`, any but an array, past its first byte.
+    fn parse_scalar(src: &mut Cursor<&[u8]>, data_type: DataType) -> Result<Frame, RustdisError> {
         match data_type {
             DataType::SimpleString => {
                 let bytes = get_frame_bytes(src)?.to_vec();
-                let string = String::from_utf8(bytes)?;
+                let string = String::from_utf8(bytes).map_err(invalid_format)?;
                 Ok(Frame::Simple(string))
             }
             DataType::SimpleError => {
                 let bytes = get_frame_bytes(src)?.to_vec();
-                let string = String::from_utf8(bytes)?;
+                let string = String::from_utf8(bytes).map_err(invalid_format)?;
                 Ok(Frame::Error(string))
             }
             DataType::Integer => {
                 let bytes = get_frame_bytes(src)?.to_vec();
-                let string = String::from_utf8(bytes)?;
+                let string = String::from_utf8(bytes).map_err(invalid_format)?;
                 let integer = string
                     .parse::<i64>()
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-                    .map_err(Error::Other)?;
+                    .map_err(|e| RustdisError::Protocol(e.to_string()))?;
 
                 Ok(Frame::Integer(integer))
             }
             // $<length>\r\n<data>\r\n
             DataType::BulkString => {
                 let length = get_frame_bytes(src)?;
-                let length = String::from_utf8(length.to_vec())?;
+                let length = String::from_utf8(length.to_vec()).map_err(invalid_format)?;
                 let length = length
                     .parse::<isize>()
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-                    .map_err(Error::Other)?;
+                    .map_err(|e| RustdisError::Protocol(e.to_string()))?;
 
                 if length == -1 {
-                    return Ok(Frame::Null);
+                    return Ok(Frame::NullBulkString);
                 }
 
-                let data = get_frame_bytes(src)?;
-                let data = Bytes::from(data.to_vec());
+                let data = get_sized_frame_bytes(src, length)?;
+                let data = Bytes::copy_from_slice(data);
 
                 Ok(Frame::Bulk(data))
             }
             // !<length>\r\n<error>\r\n
             DataType::BulkError => {
                 let length = get_frame_bytes(src)?;
-                let length = String::from_utf8(length.to_vec())?;
+                let length = String::from_utf8(length.to_vec()).map_err(invalid_format)?;
                 let length = length
                     .parse::<isize>()
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-                    .map_err(Error::Other)?;
+                    .map_err(|e| RustdisError::Protocol(e.to_string()))?;
 
                 // NOTE: the protocol does not specify a way to represent a null bulk error
                 if length == -1 {
                     return Ok(Frame::Null);
                 }
 
-                let msg = get_frame_bytes(src)?;
-                let msg = String::from_utf8(msg.to_vec())?;
+                let msg = get_sized_frame_bytes(src, length)?;
+                let msg = String::from_utf8(msg.to_vec()).map_err(invalid_format)?;
 
                 Ok(Frame::Error(msg))
             }
-            // *<number-of-elements>\r\n<element-1>...<element-n>
-            DataType::Array => {
-                let length = get_frame_bytes(src)?;
-                let length = String::from_utf8(length.to_vec())?;
-                let length = length
-                    .parse::<isize>()
-                    .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })
-                    .map_err(Error::Other)?;
+            DataType::Null => {
+                // Advance the cursor to the end of the frame.
+                let _ = get_frame_bytes(src)?.to_vec();
 
-                if length == -1 {
-                    return Ok(Frame::Null);
-                }
+                Ok(Frame::Null)
+            }
+            // ,[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n
+            DataType::Double => {
+                let bytes = get_frame_bytes(src)?.to_vec();
+                let string = String::from_utf8(bytes).map_err(invalid_format)?;
+                let double = parse_double(&string).ok_or_else(|| {
+                    RustdisError::Protocol(format!("protocol error; invalid double {}", string))
+                })?;
 
-                let mut frames = Vec::with_capacity(length as usize);
-                for _ in 0..length {
-                    let frame = Self::parse(src)?;
-                    frames.push(frame);
+                Ok(Frame::Double(double))
+            }
+            // #<t|f>\r\n
+            DataType::Boolean => match get_frame_bytes(src)? {
+                b"t" => Ok(Frame::Boolean(true)),
+                b"f" => Ok(Frame::Boolean(false)),
+                _ => Err(RustdisError::Protocol(
+                    "protocol error; invalid boolean".to_string(),
+                )),
+            },
+            // ([+|-]<number>\r\n
+            DataType::BigNumber => {
+                let bytes = get_frame_bytes(src)?.to_vec();
+                let string = String::from_utf8(bytes).map_err(invalid_format)?;
+                let digits = string.strip_prefix(['+', '-']).unwrap_or(&string);
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(RustdisError::Protocol(format!(
+                        "protocol error; invalid big number {}",
+                        string
+                    )));
                 }
 
-                Ok(Frame::Array(frames))
+                Ok(Frame::BigNumber(string))
             }
-            DataType::Null => {
-                // Advance the cursor to the end of the frame.
-                let _ = get_frame_bytes(src)?.to_vec();
+            // =<length>\r\n<encoding>:<data>\r\n
+            DataType::VerbatimString => {
+                let length = get_frame_bytes(src)?;
+                let length = String::from_utf8(length.to_vec()).map_err(invalid_format)?;
+                let length = length
+                    .parse::<isize>()
+                    .map_err(|e| RustdisError::Protocol(e.to_string()))?;
 
-                Ok(Frame::Null)
+                let data = get_sized_frame_bytes(src, length)?;
+                if data.len() < 4 || data[3] != b':' {
+                    return Err(RustdisError::Protocol(
+                        "protocol error; invalid verbatim string encoding".to_string(),
+                    ));
+                }
+                let encoding = String::from_utf8(data[..3].to_vec()).map_err(invalid_format)?;
+                let text = Bytes::copy_from_slice(&data[4..]);
+
+                Ok(Frame::Verbatim { encoding, text })
             }
             data_type => {
                 error!("Unsupported data type: {:?}", data_type);
-                todo!()
+                Err(RustdisError::Protocol(format!(
+                    "protocol error; unsupported data type {:?}",
+                    data_type
+                )))
             }
         }
     }
 
     pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_into(&mut bytes);
+        bytes
+    }
+
+    /// Appends the serialization of the frame to `buf`, without allocating anything but the room
+    /// `buf` needs to grow. Serializing many frames into the same buffer reuses its allocation.
+    pub fn serialize_into(&self, buf: &mut impl BufMut) {
         match self {
             Frame::Simple(s) => {
-                let mut bytes = Vec::with_capacity(1 + s.len() + CRLF.len());
-                bytes.push(u8::from(DataType::SimpleString));
-                bytes.extend_from_slice(s.as_bytes());
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::SimpleString));
+                buf.put_slice(s.as_bytes());
+                buf.put_slice(CRLF);
             }
             Frame::Error(s) => {
-                let mut bytes = Vec::with_capacity(1 + s.len() + CRLF.len());
-                bytes.push(u8::from(DataType::SimpleError));
-                bytes.extend_from_slice(s.as_bytes());
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::SimpleError));
+                buf.put_slice(s.as_bytes());
+                buf.put_slice(CRLF);
             }
             Frame::Integer(i) => {
-                let mut bytes = Vec::with_capacity(1 + i.to_string().len() + CRLF.len());
-                bytes.push(u8::from(DataType::Integer));
-                bytes.extend_from_slice(i.to_string().as_bytes());
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::Integer));
+                put_decimal(buf, *i);
+                buf.put_slice(CRLF);
             }
             Frame::Bulk(bytes) => {
-                let length_str = bytes.len().to_string();
-                let mut result = Vec::with_capacity(
-                    1 + length_str.len() + CRLF.len() + bytes.len() + CRLF.len(),
-                );
-                result.push(u8::from(DataType::BulkString));
-                result.extend_from_slice(length_str.as_bytes());
-                result.extend_from_slice(CRLF);
-                result.extend_from_slice(bytes);
-                result.extend_from_slice(CRLF);
-                result
+                buf.put_u8(u8::from(DataType::BulkString));
+                put_decimal(buf, bytes.len() as i64);
+                buf.put_slice(CRLF);
+                buf.put_slice(bytes);
+                buf.put_slice(CRLF);
             }
             Frame::Null => {
-                let mut bytes = Vec::with_capacity(3);
-                bytes.push(u8::from(DataType::Null));
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::Null));
+                buf.put_slice(CRLF);
             }
             Frame::NullBulkString => {
-                let mut bytes = Vec::with_capacity(4);
-                bytes.push(u8::from(DataType::BulkString));
-                bytes.extend_from_slice("-1".as_bytes());
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::BulkString));
+                buf.put_slice(b"-1");
+                buf.put_slice(CRLF);
             }
             Frame::NullArray => {
-                let mut bytes = Vec::with_capacity(4);
-                bytes.push(u8::from(DataType::Array));
-                bytes.extend_from_slice("-1".as_bytes());
-                bytes.extend_from_slice(CRLF);
-                bytes
+                buf.put_u8(u8::from(DataType::Array));
+                buf.put_slice(b"-1");
+                buf.put_slice(CRLF);
+            }
+            Frame::Double(double) => {
+                buf.put_u8(u8::from(DataType::Double));
+                buf.put_slice(serialize_double(*double).as_bytes());
+                buf.put_slice(CRLF);
+            }
+            Frame::Boolean(boolean) => {
+                buf.put_u8(u8::from(DataType::Boolean));
+                buf.put_u8(if *boolean { b't' } else { b'f' });
+                buf.put_slice(CRLF);
+            }
+            Frame::BigNumber(number) => {
+                buf.put_u8(u8::from(DataType::BigNumber));
+                buf.put_slice(number.as_bytes());
+                buf.put_slice(CRLF);
+            }
+            Frame::Verbatim { encoding, text } => {
+                buf.put_u8(u8::from(DataType::VerbatimString));
+                put_decimal(buf, (encoding.len() + 1 + text.len()) as i64);
+                buf.put_slice(CRLF);
+                buf.put_slice(encoding.as_bytes());
+                buf.put_u8(b':');
+                buf.put_slice(text);
+                buf.put_slice(CRLF);
             }
             Frame::Array(arr) => {
-                let length_str = arr.len().to_string();
-                let mut bytes = Vec::with_capacity(1 + length_str.len() + CRLF.len());
-                bytes.push(u8::from(DataType::Array));
-                bytes.extend_from_slice(length_str.as_bytes());
-                bytes.extend_from_slice(CRLF);
+                buf.put_u8(u8::from(DataType::Array));
+                put_decimal(buf, arr.len() as i64);
+                buf.put_slice(CRLF);
                 for frame in arr {
-                    bytes.extend(frame.serialize());
+                    frame.serialize_into(buf);
+                }
+            }
+            Frame::Map(entries) => {
+                buf.put_u8(u8::from(DataType::Map));
+                put_decimal(buf, entries.len() as i64);
+                buf.put_slice(CRLF);
+                for (key, value) in entries {
+                    key.serialize_into(buf);
+                    value.serialize_into(buf);
+                }
+            }
+            Frame::Set(set) => {
+                buf.put_u8(u8::from(DataType::Set));
+                put_decimal(buf, set.len() as i64);
+                buf.put_slice(CRLF);
+                for frame in set {
+                    frame.serialize_into(buf);
                 }
-                bytes
             }
         }
     }
+
+    /// Converts the RESP3 types that have no RESP2 counterpart into the RESP2 types Redis replies
+    /// with to clients that did not negotiate RESP3.
+    pub fn into_resp2(self) -> Frame {
+        match self {
+            Frame::Double(double) => Frame::Bulk(Bytes::from(serialize_double(double))),
+            Frame::Boolean(boolean) => Frame::Integer(boolean as i64),
+            Frame::BigNumber(number) => Frame::Bulk(Bytes::from(number)),
+            Frame::Verbatim { text, .. } => Frame::Bulk(text),
+            // Commands reply with the explicit RESP2 null variants, a bare null is treated as the
+            // null bulk string, which is what Redis replies with in most cases.
+            Frame::Null => Frame::NullBulkString,
+            Frame::Array(arr) | Frame::Set(arr) => {
+                Frame::Array(arr.into_iter().map(Frame::into_resp2).collect())
+            }
+            Frame::Map(entries) => Frame::Array(
+                entries
+                    .into_iter()
+                    .flat_map(|(key, value)| [key.into_resp2(), value.into_resp2()])
+                    .collect(),
+            ),
+            frame => frame,
+        }
+    }
+
+    /// Converts the RESP2 null variants into the single null type of RESP3.
+    pub fn into_resp3(self) -> Frame {
+        match self {
+            Frame::NullBulkString | Frame::NullArray => Frame::Null,
+            Frame::Array(arr) => Frame::Array(arr.into_iter().map(Frame::into_resp3).collect()),
+            Frame::Set(set) => Frame::Set(set.into_iter().map(Frame::into_resp3).collect()),
+            Frame::Map(entries) => Frame::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_resp3(), value.into_resp3()))
+                    .collect(),
+            ),
+            frame => frame,
+        }
+    }
+}
+
+/// Version of the protocol spoken with a client. Connections start with RESP2 and may switch to
+/// RESP3, which adds richer reply types.
+///
+/// Ref: <https://redis.io/docs/latest/develop/reference/protocol-spec/#resp-versions>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
 }
 
 impl From<Frame> for Vec<u8> {
@@ -222,9 +552,15 @@ impl fmt::Display for Frame {
             Frame::Error(s) => write!(f, "-{}", s),
             Frame::Integer(i) => write!(f, ":{}", i),
             Frame::Bulk(bytes) => write!(f, "${}", String::from_utf8_lossy(bytes)),
-            Frame::Null => write!(f, "$-1"),
+            Frame::Null => write!(f, "_"),
             Frame::NullBulkString => write!(f, "$-1"),
             Frame::NullArray => write!(f, "*-1"),
+            Frame::Double(double) => write!(f, ",{}", serialize_double(*double)),
+            Frame::Boolean(boolean) => write!(f, "#{}", if *boolean { 't' } else { 'f' }),
+            Frame::BigNumber(number) => write!(f, "({}", number),
+            Frame::Verbatim { encoding, text } => {
+                write!(f, "={}:{}", encoding, String::from_utf8_lossy(text))
+            }
             Frame::Array(arr) => {
                 write!(f, "*{}\r\n", arr.len())?;
                 for frame in arr {
@@ -232,11 +568,63 @@ impl fmt::Display for Frame {
                 }
                 Ok(())
             }
+            Frame::Map(entries) => {
+                write!(f, "%{}\r\n", entries.len())?;
+                for (key, value) in entries {
+                    write!(f, "{}\r\n{}\r\n", key, value)?;
+                }
+                Ok(())
+            }
+            Frame::Set(set) => {
+                write!(f, "~{}\r\n", set.len())?;
+                for frame in set {
+                    write!(f, "{}\r\n", frame)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-fn get_frame_bytes<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
+/// Parses a RESP3 double. Besides the usual decimal and exponent notations, the protocol spells
+/// infinities and NaN as `inf`, `-inf` and `nan`.
+fn parse_double(string: &str) -> Option<f64> {
+    match string {
+        "inf" | "+inf" => Some(f64::INFINITY),
+        "-inf" => Some(f64::NEG_INFINITY),
+        "nan" => Some(f64::NAN),
+        // Rust would also accept spellings such as `infinity` or `NaN`, which RESP does not.
+        s if s
+            .bytes()
+            .any(|b| b.is_ascii_alphabetic() && b != b'e' && b != b'E') =>
+        {
+            None
+        }
+        s => s.parse().ok(),
+    }
+}
+
+/// Writes the decimal representation of `n`, formatted on the stack instead of in a `String`.
+fn put_decimal(buf: &mut impl BufMut, n: i64) {
+    // The longest i64 is `-9223372036854775808`, 20 bytes.
+    let mut digits = [0; 20];
+    let mut cursor = Cursor::new(&mut digits[..]);
+    write!(cursor, "{}", n).expect("an i64 is at most 20 bytes long");
+    let len = cursor.position() as usize;
+    buf.put_slice(&digits[..len]);
+}
+
+fn serialize_double(double: f64) -> String {
+    if double.is_nan() {
+        "nan".to_string()
+    } else if double.is_infinite() {
+        if double > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        double.to_string()
+    }
+}
+
+fn get_frame_bytes<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], RustdisError> {
     let start = src.position() as usize;
     let end = src.get_ref().len();
 
@@ -244,17 +632,45 @@ fn get_frame_bytes<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
         .windows(2)
         .enumerate()
         .position(|(_, window)| window == CRLF)
-        .ok_or(Error::Incomplete)
+        .ok_or(RustdisError::Incomplete)
         .map(|index| start + index)?;
 
     src.set_position((frame_end_position + CRLF.len()) as u64);
 
-    return Ok(&src.get_ref()[start..frame_end_position]);
+    Ok(&src.get_ref()[start..frame_end_position])
+}
+
+/// Reads `length` bytes followed by a CRLF, as found in bulk strings and bulk errors. Relying on
+/// the declared length instead of looking for the CRLF keeps the data binary safe, and lets an
+/// incomplete frame be detected without scanning the bytes received so far.
+fn get_sized_frame_bytes<'a>(
+    src: &mut Cursor<&'a [u8]>,
+    length: isize,
+) -> Result<&'a [u8], RustdisError> {
+    let length = usize::try_from(length).map_err(|_| {
+        RustdisError::Protocol(format!("protocol error; invalid bulk length {}", length))
+    })?;
+
+    let start = src.position() as usize;
+    let end = start + length;
+
+    if src.get_ref().len() < end + CRLF.len() {
+        return Err(RustdisError::Incomplete);
+    }
+    if &src.get_ref()[end..end + CRLF.len()] != CRLF {
+        return Err(RustdisError::Protocol(
+            "protocol error; bulk data does not match its declared length".to_string(),
+        ));
+    }
+
+    src.set_position((end + CRLF.len()) as u64);
+
+    Ok(&src.get_ref()[start..end])
 }
 
-fn get_byte(src: &mut Cursor<&[u8]>) -> Result<u8, Error> {
+fn get_byte(src: &mut Cursor<&[u8]>) -> Result<u8, RustdisError> {
     if !src.has_remaining() {
-        return Err(Error::Incomplete);
+        return Err(RustdisError::Incomplete);
     }
     Ok(src.get_u8())
 }
@@ -282,7 +698,7 @@ enum DataType {
 }
 
 impl TryFrom<u8> for DataType {
-    type Error = Error;
+    type Error = RustdisError;
 
     fn try_from(byte: u8) -> Result<Self, Self::Error> {
         match byte {
@@ -300,7 +716,10 @@ impl TryFrom<u8> for DataType {
             b'%' => Ok(Self::Map),
             b'~' => Ok(Self::Set),
             b'>' => Ok(Self::Push),
-            _ => Err(Error::InvalidDataType(byte)),
+            _ => Err(RustdisError::Protocol(format!(
+                "invalid frame data type: {}",
+                byte
+            ))),
         }
     }
 }
@@ -326,26 +745,15 @@ impl From<DataType> for u8 {
     }
 }
 
-impl From<FromUtf8Error> for Error {
-    fn from(_src: FromUtf8Error) -> Error {
-        "protocol error; invalid frame format".into()
-    }
-}
-
-impl From<&str> for Error {
-    fn from(src: &str) -> Error {
-        src.to_string().into()
-    }
-}
-
-impl From<String> for Error {
-    fn from(src: String) -> Error {
-        Error::Other(src.into())
-    }
+/// The error of a frame whose text is not valid UTF-8.
+fn invalid_format(_err: FromUtf8Error) -> RustdisError {
+    RustdisError::Protocol("protocol error; invalid frame format".to_string())
 }
 
 #[cfg(test)]
 mod tests {
+    use bytes::BytesMut;
+
     use super::*;
 
     #[test]
@@ -372,7 +780,7 @@ mod tests {
     }
 
     fn parse_integer_frame(data: &[u8], expected: i64) {
-        let mut cursor = Cursor::new(&data[..]);
+        let mut cursor = Cursor::new(data);
 
         let frame = Frame::parse(&mut cursor);
 
@@ -432,7 +840,40 @@ mod tests {
 
         let frame = Frame::parse(&mut cursor);
 
-        assert!(matches!(frame, Ok(Frame::Null)));
+        assert!(matches!(frame, Ok(Frame::NullBulkString)));
+    }
+
+    #[test]
+    fn parse_bulk_string_frame_with_crlf() {
+        let data = b"$8\r\nfoo\r\nbar\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(
+            frame,
+            Ok(Frame::Bulk(ref b)) if b == &Bytes::from("foo\r\nbar")
+        ));
+    }
+
+    #[test]
+    fn parse_bulk_string_frame_incomplete() {
+        let data = b"$6\r\nfoo";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(frame, Err(RustdisError::Incomplete)));
+    }
+
+    #[test]
+    fn parse_bulk_string_frame_with_wrong_length() {
+        let data = b"$2\r\nfoobar\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(frame, Err(RustdisError::Protocol(_))));
     }
 
     #[test]
@@ -457,7 +898,7 @@ mod tests {
 
         assert!(matches!(
             frame,
-            Ok(Frame::Error(ref s)) if s == ""
+            Ok(Frame::Error(ref s)) if s.is_empty()
         ));
     }
 
@@ -541,7 +982,7 @@ mod tests {
 
         let frame = Frame::parse(&mut cursor);
 
-        assert!(matches!(frame, Ok(Frame::Null)));
+        assert!(matches!(frame, Ok(Frame::NullArray)));
     }
 
     #[test]
@@ -561,11 +1002,444 @@ mod tests {
             Ok(Frame::Array(ref a)) if a[0] == Frame::Bulk(Bytes::from("hello"))
         ));
 
-        assert!(matches!(frame, Ok(Frame::Array(ref a)) if a[1] == Frame::Null));
+        assert!(matches!(frame, Ok(Frame::Array(ref a)) if a[1] == Frame::NullBulkString));
 
         assert!(matches!(
             frame,
             Ok(Frame::Array(ref a)) if a[2] == Frame::Bulk(Bytes::from("world"))
         ));
     }
+
+    #[test]
+    fn parse_double_frame() {
+        let cases: [(&[u8], f64); 6] = [
+            (b",1.23\r\n", 1.23),
+            (b",-10\r\n", -10.0),
+            (b",1.5e3\r\n", 1500.0),
+            (b",+2E-1\r\n", 0.2),
+            (b",inf\r\n", f64::INFINITY),
+            (b",-inf\r\n", f64::NEG_INFINITY),
+        ];
+
+        for (data, expected) in cases {
+            let mut cursor = Cursor::new(data);
+            let frame = Frame::parse(&mut cursor);
+            assert!(matches!(frame, Ok(Frame::Double(d)) if d == expected));
+        }
+
+        let data = b",nan\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+        let frame = Frame::parse(&mut cursor);
+        assert!(matches!(frame, Ok(Frame::Double(d)) if d.is_nan()));
+    }
+
+    #[test]
+    fn parse_double_frame_invalid() {
+        for data in [&b",\r\n"[..], b",abc\r\n", b",infinity\r\n", b",NaN\r\n"] {
+            let mut cursor = Cursor::new(data);
+            let frame = Frame::parse(&mut cursor);
+            assert!(matches!(frame, Err(RustdisError::Protocol(_))));
+        }
+    }
+
+    #[test]
+    fn parse_boolean_frame() {
+        let mut cursor = Cursor::new(&b"#t\r\n#f\r\n#x\r\n"[..]);
+
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Ok(Frame::Boolean(true))
+        ));
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Ok(Frame::Boolean(false))
+        ));
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Err(RustdisError::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn parse_big_number_frame() {
+        let data = b"(-3492890328409238509324850943850943825024385\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(
+            frame,
+            Ok(Frame::BigNumber(ref n)) if n == "-3492890328409238509324850943850943825024385"
+        ));
+
+        let data = b"(12a\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Err(RustdisError::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn parse_unsupported_frame_does_not_panic() {
+        let data = b">1\r\n+message\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(frame, Err(RustdisError::Protocol(_))));
+    }
+
+    #[test]
+    fn serialize_into_appends() {
+        let mut buf = BytesMut::from("+OK\r\n");
+
+        Frame::Integer(i64::MIN).serialize_into(&mut buf);
+        Frame::Array(vec![Frame::Integer(0), Frame::Bulk(Bytes::from("hello"))])
+            .serialize_into(&mut buf);
+
+        assert_eq!(
+            buf,
+            "+OK\r\n:-9223372036854775808\r\n*2\r\n:0\r\n$5\r\nhello\r\n"
+        );
+    }
+
+    #[test]
+    fn serialize_resp3_scalars() {
+        let cases = [
+            (Frame::Double(1.23), &b",1.23\r\n"[..]),
+            (Frame::Double(10.0), b",10\r\n"),
+            (Frame::Double(f64::INFINITY), b",inf\r\n"),
+            (Frame::Double(f64::NEG_INFINITY), b",-inf\r\n"),
+            (Frame::Double(f64::NAN), b",nan\r\n"),
+            (Frame::Boolean(true), b"#t\r\n"),
+            (Frame::Boolean(false), b"#f\r\n"),
+            (
+                Frame::BigNumber("-12345678901234567890".to_string()),
+                b"(-12345678901234567890\r\n",
+            ),
+        ];
+
+        for (frame, expected) in cases {
+            let bytes = frame.serialize();
+            assert_eq!(bytes, expected);
+
+            let mut cursor = Cursor::new(&bytes[..]);
+            let parsed = Frame::parse(&mut cursor).unwrap();
+            assert_eq!(parsed.serialize(), bytes);
+        }
+    }
+
+    #[test]
+    fn parse_verbatim_string_frame() {
+        let data = b"=15\r\ntxt:Some string\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor).unwrap();
+
+        assert_eq!(
+            frame,
+            Frame::Verbatim {
+                encoding: "txt".to_string(),
+                text: Bytes::from("Some string"),
+            }
+        );
+        assert_eq!(frame.serialize(), data);
+    }
+
+    #[test]
+    fn parse_verbatim_string_frame_without_encoding() {
+        let data = b"=3\r\ntxt\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(frame, Err(RustdisError::Protocol(_))));
+    }
+
+    #[test]
+    fn into_resp2() {
+        let frame = Frame::Array(vec![
+            Frame::Verbatim {
+                encoding: "mkd".to_string(),
+                text: Bytes::from("# Title"),
+            },
+            Frame::Double(1.5),
+            Frame::Boolean(true),
+            Frame::BigNumber("123".to_string()),
+            Frame::Integer(1),
+        ]);
+
+        assert_eq!(
+            frame.into_resp2(),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("# Title")),
+                Frame::Bulk(Bytes::from("1.5")),
+                Frame::Integer(1),
+                Frame::Bulk(Bytes::from("123")),
+                Frame::Integer(1),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_map_frame() {
+        let data = b"%2\r\n+first\r\n:1\r\n+second\r\n*1\r\n#t\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor).unwrap();
+
+        assert_eq!(
+            frame,
+            Frame::Map(vec![
+                (Frame::Simple("first".to_string()), Frame::Integer(1)),
+                (
+                    Frame::Simple("second".to_string()),
+                    Frame::Array(vec![Frame::Boolean(true)])
+                ),
+            ])
+        );
+        assert_eq!(frame.serialize(), data);
+        assert_eq!(cursor.position() as usize, data.len());
+    }
+
+    #[test]
+    fn parse_map_frame_without_value() {
+        let data = b"%1\r\n+key\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Err(RustdisError::Incomplete)
+        ));
+    }
+
+    #[test]
+    fn parse_set_frame() {
+        let data = b"~3\r\n$1\r\na\r\n~0\r\n%0\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor).unwrap();
+
+        assert_eq!(
+            frame,
+            Frame::Set(vec![
+                Frame::Bulk(Bytes::from("a")),
+                Frame::Set(vec![]),
+                Frame::Map(vec![]),
+            ])
+        );
+        assert_eq!(frame.serialize(), data);
+    }
+
+    #[test]
+    fn parse_map_and_set_frames_invalid_length() {
+        for data in [&b"%-1\r\n"[..], b"~-1\r\n", b"%1000000000\r\n"] {
+            let mut cursor = Cursor::new(data);
+
+            let err = Frame::parse(&mut cursor).unwrap_err();
+
+            assert!(
+                err.to_string()
+                    .starts_with("ERR protocol error; invalid multibulk length"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn map_and_set_into_resp2() {
+        let frame = Frame::Map(vec![
+            (
+                Frame::Bulk(Bytes::from("members")),
+                Frame::Set(vec![Frame::Boolean(false)]),
+            ),
+            (Frame::Bulk(Bytes::from("score")), Frame::Double(2.5)),
+        ]);
+
+        assert_eq!(
+            frame.clone().into_resp3(),
+            frame,
+            "RESP3 keeps maps and sets"
+        );
+        assert_eq!(
+            frame.into_resp2(),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("members")),
+                Frame::Array(vec![Frame::Integer(0)]),
+                Frame::Bulk(Bytes::from("score")),
+                Frame::Bulk(Bytes::from("2.5")),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_array_frame_nested_then_scalar() {
+        let data = b"*3\r\n*1\r\n*1\r\n:1\r\n*0\r\n$1\r\na\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor).unwrap();
+
+        assert_eq!(
+            frame,
+            Frame::Array(vec![
+                Frame::Array(vec![Frame::Array(vec![Frame::Integer(1)])]),
+                Frame::Array(vec![]),
+                Frame::Bulk(Bytes::from("a")),
+            ])
+        );
+        assert_eq!(cursor.position() as usize, data.len());
+    }
+
+    #[test]
+    fn parse_array_frame_huge_length() {
+        let data = b"*1000000000\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let err = Frame::parse(&mut cursor).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR protocol error; invalid multibulk length 1000000000"
+        );
+    }
+
+    #[test]
+    fn parse_array_frame_negative_length() {
+        let data = b"*-2\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let err = Frame::parse(&mut cursor).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR protocol error; invalid multibulk length -2"
+        );
+    }
+
+    /// An array declaring as many elements as allowed, but sent without them, is incomplete, and
+    /// nothing is allocated for the elements it announces.
+    #[test]
+    fn parse_array_frame_max_length_without_elements() {
+        let data = format!("*{}\r\n:1\r\n", MAX_ARRAY_LEN);
+        let mut cursor = Cursor::new(data.as_bytes());
+
+        assert!(matches!(
+            Frame::parse(&mut cursor),
+            Err(RustdisError::Incomplete)
+        ));
+    }
+
+    #[test]
+    fn parse_array_frame_deeply_nested() {
+        let nested = |depth: usize| {
+            let mut data = b"*1\r\n".repeat(depth);
+            data.extend_from_slice(b":1\r\n");
+            data
+        };
+
+        let data = nested(MAX_NESTING_DEPTH);
+        let mut frame = Frame::parse(&mut Cursor::new(&data[..])).unwrap();
+        for _ in 0..MAX_NESTING_DEPTH {
+            let Frame::Array(mut frames) = frame else {
+                panic!("expected an array");
+            };
+            frame = frames.pop().unwrap();
+        }
+        assert_eq!(frame, Frame::Integer(1));
+
+        // Way past what a recursive parser would survive.
+        for depth in [MAX_NESTING_DEPTH + 1, 1_000_000] {
+            let data = nested(depth);
+            let err = Frame::parse(&mut Cursor::new(&data[..])).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "ERR protocol error; too many nested arrays"
+            );
+        }
+    }
+
+    #[test]
+    fn parser_resumes_incomplete_frames() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SET")),
+            Frame::Bulk(Bytes::from("hello\r\nworld")),
+            Frame::Map(vec![(
+                Frame::Simple("key".to_string()),
+                Frame::Integer(-42),
+            )]),
+            Frame::Array(vec![Frame::NullBulkString, Frame::Boolean(true)]),
+        ]);
+        let mut data = frame.serialize();
+        data.extend_from_slice(b":1\r\n");
+        let len = data.len() - 4;
+
+        // The frame arrives one byte at a time, its CRLFs split between reads.
+        let mut parser = Parser::default();
+        for received in 0..len {
+            let mut src = Cursor::new(&data[..received]);
+            assert!(matches!(
+                parser.parse(&mut src),
+                Err(RustdisError::Incomplete)
+            ));
+        }
+        let mut src = Cursor::new(&data[..]);
+        assert_eq!(parser.parse(&mut src).unwrap(), frame);
+        assert_eq!(src.position() as usize, len);
+
+        // The next frame is parsed from scratch.
+        assert_eq!(parser.parse(&mut src).unwrap(), Frame::Integer(1));
+    }
+
+    #[test]
+    fn parser_searches_long_lines_once() {
+        let mut data = b"*2\r\n+".to_vec();
+        data.extend_from_slice(&[b'a'; 1000]);
+
+        let mut parser = Parser::default();
+        let err = parser.parse(&mut Cursor::new(&data[..])).unwrap_err();
+        assert!(matches!(err, RustdisError::Incomplete));
+        assert_eq!(parser.parsed, 4);
+        assert_eq!(parser.searched, 1001);
+
+        data.push(b'\r');
+        let err = parser.parse(&mut Cursor::new(&data[..])).unwrap_err();
+        assert!(matches!(err, RustdisError::Incomplete));
+        assert_eq!(parser.searched, 1002);
+
+        data.extend_from_slice(b"\n:1\r\n");
+        assert_eq!(
+            parser.parse(&mut Cursor::new(&data[..])).unwrap(),
+            Frame::Array(vec![Frame::Simple("a".repeat(1000)), Frame::Integer(1)])
+        );
+    }
+
+    #[test]
+    fn parse_null_frame() {
+        let data = b"_\r\n";
+        let mut cursor = Cursor::new(&data[..]);
+
+        let frame = Frame::parse(&mut cursor);
+
+        assert!(matches!(frame, Ok(Frame::Null)));
+    }
+
+    #[test]
+    fn null_per_protocol() {
+        let frame = Frame::Array(vec![Frame::Null, Frame::NullBulkString, Frame::NullArray]);
+
+        assert_eq!(
+            frame.clone().into_resp2(),
+            Frame::Array(vec![
+                Frame::NullBulkString,
+                Frame::NullBulkString,
+                Frame::NullArray
+            ])
+        );
+        assert_eq!(
+            frame.into_resp3(),
+            Frame::Array(vec![Frame::Null, Frame::Null, Frame::Null])
+        );
+    }
 }
@@ -0,0 +1,144 @@
+//! An in-memory journal of the commands a store executes, for applications embedding rustdis in
+//! their tests.
+//!
+//! A server whose store is created with `Config::journal` appends every command it executes to
+//! the journal of the store, in execution order, as the command name and its arguments. Tests
+//! start the server with `server::serve`, keeping a handle on the store, and assert on the exact
+//! sequence of commands their code issued, like which keys a cache layer read and wrote.
+//!
+//! ```no_run
+//! use rustdis::config::Config;
+//! use rustdis::store::Store;
+//!
+//! # async fn example() {
+//! let store = Store::with_config(Config {
+//!     journal: true,
+//!     ..Config::default()
+//! });
+//! tokio::spawn(rustdis::server::serve(store.clone(), std::future::pending()));
+//!
+//! // ... exercise the code under test against the server ...
+//!
+//! let journal = store.journal().expect("the journal is enabled");
+//! for entry in journal.entries() {
+//!     println!("{} {:?}", entry.command, entry.args);
+//! }
+//! # }
+//! ```
+
+use bytes::Bytes;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use uuid::Uuid;
+
+use crate::frame::Frame;
+
+/// A command executed by the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// The connection the command was received from.
+    pub connection_id: Uuid,
+    /// The id of the request the command was received in, on its connection, see
+    /// `Connection::next_request_id`.
+    pub request_id: u64,
+    /// The lowercase name of the command, like `set`.
+    pub command: &'static str,
+    /// The arguments of the command, without its name.
+    pub args: Vec<Bytes>,
+}
+
+impl JournalEntry {
+    /// The entry of `command` sent as `frame`. Arguments that aren't strings, which commands
+    /// never accept, are left out.
+    pub fn new(
+        connection_id: Uuid,
+        request_id: u64,
+        command: &'static str,
+        frame: &Frame,
+    ) -> JournalEntry {
+        let args = match frame {
+            Frame::Array(frames) => frames
+                .iter()
+                .skip(1)
+                .filter_map(|frame| match frame {
+                    Frame::Bulk(arg) => Some(arg.clone()),
+                    Frame::Simple(arg) => Some(Bytes::from(arg.clone())),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        JournalEntry {
+            connection_id,
+            request_id,
+            command,
+            args,
+        }
+    }
+}
+
+/// The commands executed by a store, in execution order.
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Mutex<Vec<JournalEntry>>,
+}
+
+impl Journal {
+    fn lock(&self) -> MutexGuard<'_, Vec<JournalEntry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn append(&self, entry: JournalEntry) {
+        self.lock().push(entry);
+    }
+
+    /// A copy of every entry appended so far.
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.lock().clone()
+    }
+
+    /// Removes every entry, returning them, so a test can assert on the commands issued by each
+    /// of its steps separately.
+    pub fn take(&self) -> Vec<JournalEntry> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_take() {
+        let journal = Journal::default();
+        let id = Uuid::new_v4();
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SET")),
+            Frame::Bulk(Bytes::from("key")),
+            Frame::Bulk(Bytes::from("value")),
+        ]);
+
+        journal.append(JournalEntry::new(id, 1, "set", &frame));
+        assert_eq!(journal.len(), 1);
+        assert_eq!(
+            journal.entries(),
+            vec![JournalEntry {
+                connection_id: id,
+                request_id: 1,
+                command: "set",
+                args: vec![Bytes::from("key"), Bytes::from("value")],
+            }]
+        );
+
+        assert_eq!(journal.take().len(), 1);
+        assert!(journal.is_empty());
+    }
+}
@@ -2,31 +2,66 @@
 //!
 //! The primary goal of rustdis is to offer a straightforward and comprehensible implementation,
 //! with no optimization techniques to ensure the code remains accessible and easy to understand.
-//! As of now, rustdis focuses exclusively on implementing Redis' String data type and its
-//! associated methods. You can find more about Redis strings here:
-//! [Redis Strings](https://redis.io/docs/data-types/strings/).
+//! As of now, rustdis implements Redis' String and Hash data types and their associated methods.
+//! You can find more about them here: [Redis Strings](https://redis.io/docs/data-types/strings/)
+//! and [Redis Hashes](https://redis.io/docs/data-types/hashes/).
 //!
 //! # Architecture
 //!
 //! * `server`: Redis server module. Provides a run function that initiates the server, enabling it
-//! to begin handling incoming connections from Redis clients. It manages client requests, executes
-//! Redis commands, and handles connection lifecycles.
+//!   to begin handling incoming connections from Redis clients. It manages client requests, executes
+//!   Redis commands, and handles connection lifecycles.
+//!
+//! * `config`: This module defines `Config`, the server configuration. It mirrors the subset of
+//!   `redis.conf` directives rustdis understands, read from a configuration file or set directly.
 //!
 //! * `connection`: The Connection module manages a TCP connection for a Redis client. It separates
-//! the TCP stream into readable and writable components to facilitate data consumption and
-//! transmission. The server uses this connection module to read data from the TCP connection.
+//!   the TCP stream into readable and writable components to facilitate data consumption and
+//!   transmission. The server uses this connection module to read data from the TCP connection.
 //!
 //! * `codec`: This module is responsible for decoding raw TCP byte streams into `Frame` data
-//! structures. This is an essential component for translating incoming client requests into
-//! meaningful Redis commands.
+//!   structures. This is an essential component for translating incoming client requests into
+//!   meaningful Redis commands.
 //!
 //! * `frame`: This module defines the `Frame` enum, representing different types of Redis protocol
-//! messages, and provides parsing and serialization functionalities. It adheres to the RESP (Redis
-//! Serialization Protocol) specifications.
+//!   messages, and provides parsing and serialization functionalities. It adheres to the RESP (Redis
+//!   Serialization Protocol) specifications.
+//!
+//! * `reply`: This module defines `ReplyWriter`, which serializes the frames returned by commands
+//!   for the protocol version spoken with each client, RESP2 or RESP3, and `ReplyFilter`, which
+//!   drops the replies a client turned off with CLIENT REPLY.
+//!
+//! * `error`: This module defines `RustdisError`, the structured error type of the crate. It
+//!   classifies failures as protocol, IO, command or store errors, and renders them as the RESP
+//!   error strings Redis clients expect.
+//!
+//! * `stats`: This module defines `Stats`, the server wide counters, like connections received,
+//!   network bytes and error replies, that the INFO command reports in its stats section.
+//!
+//! * `transaction`: This module defines `Transaction`, the commands a client queues between MULTI
+//!   and EXEC, and how errors raised while queuing them abort the transaction.
+//!
+//! * `store`: This module provides a simple key-value store for managing Redis strings and
+//!   hashes. It supports basic operations such as setting, getting, removing, and incrementing
+//!   values associated with keys, and setting and removing the fields of hashes.
 //!
-//! * `store`: This module provides a simple key-value store for managing Redis string data types.
-//! It supports basic operations such as setting, getting, removing, and incrementing values
-//! associated with keys.
+//! * `dump`: This module exports the keyspace as a stream of RESP encoded commands, one per key
+//!   along with its time to live, and imports such a stream back into a store.
+//!
+//! * `engine`: This module defines the `KvEngine` trait, the data structure the store keeps its
+//!   keys in, along with a hash map and an ordered B-tree implementation.
+//!
+//! * `pattern`: This module compiles the glob-style patterns of KEYS and SCAN MATCH, with Redis'
+//!   syntax, and caches the recent compilations so a repeated pattern is parsed only once.
+//!
+//! * `journal`: This module keeps an in-memory journal of the commands a store executes, which
+//!   applications embedding rustdis in their tests assert on.
+//!
+//! * `record`: This module records the commands each connection receives to a file, with
+//!   `--record`, and replays such recordings against a store to reproduce bugs deterministically.
+//!
+//! * `self_test`: This module runs an in-process smoke suite over the codec, the store and a few
+//!   commands, which `rustdis --self-test` uses to check a build before relying on it.
 //!
 //! ```text
 //!
@@ -103,11 +138,25 @@
 
 pub mod codec;
 pub mod commands;
+pub mod config;
 pub mod connection;
+pub mod dump;
+pub mod engine;
+pub mod error;
 pub mod frame;
+pub mod journal;
+pub mod pattern;
+pub mod record;
+pub mod reply;
+pub mod self_test;
 pub mod server;
+pub mod stats;
 pub mod store;
+pub mod transaction;
 pub mod utils;
 
+pub use error::{ErrorKind, RustdisError};
+
+/// The boxed error the crate used before `RustdisError`, which can be converted from it.
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,277 @@
+//! Glob-style patterns, as used by KEYS and SCAN MATCH.
+//!
+//! A pattern is compiled once into a `Pattern`, which then matches any number of keys without
+//! parsing the pattern again. Commands get their patterns through `PatternCache`, so a pattern
+//! repeated across calls, like the one of a SCAN iteration, is compiled only once.
+//!
+//! Ref: <https://redis.io/docs/latest/commands/keys/>
+
+use bytes::Bytes;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Matches the given byte.
+    Literal(u8),
+    /// `?`: matches any byte.
+    Any,
+    /// `*`: matches any number of bytes, including none.
+    Star,
+    /// `[...]`: matches a byte within, or with `[^...]` outside of, the inclusive ranges.
+    Class {
+        negated: bool,
+        ranges: Vec<(u8, u8)>,
+    },
+}
+
+impl Token {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            Token::Literal(literal) => *literal == byte,
+            Token::Any => true,
+            Token::Star => false,
+            Token::Class { negated, ranges } => {
+                let within = ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&byte));
+                within != *negated
+            }
+        }
+    }
+}
+
+/// A compiled glob-style pattern, with the same syntax and semantics as Redis':
+///
+/// - `?` matches any character.
+/// - `*` matches any number of characters, including none.
+/// - `[ae]` matches `a` or `e`, `[^e]` any character but `e` and `[a-c]` any of `a`, `b` or `c`.
+/// - `\` escapes the next character, so `\*` matches `*`.
+///
+/// Patterns are matched byte by byte, so they may hold any byte, and no pattern is invalid: an
+/// unterminated `[` class ends with the pattern and a trailing `\` matches itself, like in Redis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    pub fn compile(pattern: impl AsRef<[u8]>) -> Pattern {
+        let pattern = pattern.as_ref();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < pattern.len() {
+            let token = match pattern[i] {
+                // Consecutive stars match the same as a single one.
+                b'*' if tokens.last() == Some(&Token::Star) => {
+                    i += 1;
+                    continue;
+                }
+                b'*' => Token::Star,
+                b'?' => Token::Any,
+                b'[' => {
+                    i += 1;
+                    let negated = pattern.get(i) == Some(&b'^');
+                    if negated {
+                        i += 1;
+                    }
+
+                    let mut ranges = Vec::new();
+                    while i < pattern.len() && pattern[i] != b']' {
+                        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+                            i += 1;
+                            ranges.push((pattern[i], pattern[i]));
+                        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' {
+                            let (start, end) = (pattern[i], pattern[i + 2]);
+                            ranges.push((start.min(end), start.max(end)));
+                            i += 2;
+                        } else {
+                            ranges.push((pattern[i], pattern[i]));
+                        }
+                        i += 1;
+                    }
+
+                    Token::Class { negated, ranges }
+                }
+                b'\\' if i + 1 < pattern.len() => {
+                    i += 1;
+                    Token::Literal(pattern[i])
+                }
+                byte => Token::Literal(byte),
+            };
+            tokens.push(token);
+            i += 1;
+        }
+
+        Pattern { tokens }
+    }
+
+    pub fn matches(&self, key: impl AsRef<[u8]>) -> bool {
+        let key = key.as_ref();
+        let (mut t, mut k) = (0, 0);
+        // Where to resume after a mismatch: the token following the last star, and the key byte
+        // that star swallows next. Backtracking to the last star only is enough, since a later
+        // match of an earlier star can't succeed where the last star failed, so matching is
+        // never exponential.
+        let mut backtrack = None;
+
+        while k < key.len() {
+            match self.tokens.get(t) {
+                Some(Token::Star) => {
+                    t += 1;
+                    backtrack = Some((t, k));
+                    continue;
+                }
+                Some(token) if token.matches(key[k]) => {
+                    t += 1;
+                    k += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            match backtrack {
+                Some((star_t, star_k)) => {
+                    t = star_t;
+                    k = star_k + 1;
+                    backtrack = Some((star_t, k));
+                }
+                None => return false,
+            }
+        }
+
+        self.tokens[t..].iter().all(|token| *token == Token::Star)
+    }
+}
+
+/// The most recently compiled patterns, evicted in the order they were compiled in.
+pub struct PatternCache {
+    capacity: usize,
+    patterns: HashMap<Bytes, Arc<Pattern>>,
+    order: VecDeque<Bytes>,
+}
+
+impl PatternCache {
+    pub fn new(capacity: usize) -> PatternCache {
+        PatternCache {
+            capacity,
+            patterns: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns `pattern` compiled, compiling and caching it unless it is already cached.
+    pub fn get(&mut self, pattern: impl AsRef<[u8]>) -> Arc<Pattern> {
+        let pattern = pattern.as_ref();
+        if let Some(compiled) = self.patterns.get(pattern) {
+            return compiled.clone();
+        }
+
+        let compiled = Arc::new(Pattern::compile(pattern));
+        if self.capacity == 0 {
+            return compiled;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.patterns.remove(&oldest);
+            }
+        }
+        let pattern = Bytes::copy_from_slice(pattern);
+        self.order.push_back(pattern.clone());
+        self.patterns.insert(pattern, compiled.clone());
+
+        compiled
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, key: &str) -> bool {
+        Pattern::compile(pattern).matches(key)
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*", ""));
+        assert!(matches("*", "a/b:c"));
+        assert!(matches("h?llo", "hello"));
+        assert!(!matches("h?llo", "hllo"));
+        assert!(matches("h*llo", "hllo"));
+        assert!(matches("h*llo", "heeeello"));
+        assert!(!matches("h*llo", "hello!"));
+        assert!(matches("user:*:name", "user:1:2:name"));
+        assert!(matches("**a**", "bab"));
+        assert!(!matches("", "a"));
+        assert!(matches("", ""));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("h[ae]llo", "hello"));
+        assert!(matches("h[ae]llo", "hallo"));
+        assert!(!matches("h[ae]llo", "hillo"));
+        assert!(matches("h[^e]llo", "hallo"));
+        assert!(!matches("h[^e]llo", "hello"));
+        assert!(matches("h[a-b]llo", "hbllo"));
+        assert!(!matches("h[a-b]llo", "hcllo"));
+        // Reversed ranges are swapped, like in Redis.
+        assert!(matches("h[b-a]llo", "hallo"));
+        assert!(matches("[\\]]", "]"));
+        assert!(!matches("[]", "a"));
+        // An unterminated class ends with the pattern.
+        assert!(matches("a[bc", "ac"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+        assert!(matches("\\?\\[", "?["));
+        assert!(matches("a\\", "a\\"));
+    }
+
+    #[test]
+    fn bytes() {
+        let pattern = Pattern::compile(b"k\xff[\x00-\x7f]*");
+        assert!(pattern.matches(b"k\xff\x01"));
+        assert!(!pattern.matches(b"k\xff\x80"));
+        assert!(!Pattern::compile(b"\xff*").matches("key"));
+    }
+
+    #[test]
+    fn many_stars_are_not_exponential() {
+        let key = "a".repeat(10_000);
+        let pattern = format!("{}b", "a*".repeat(100));
+        assert!(!matches(&pattern, &key));
+    }
+
+    #[test]
+    fn cache() {
+        let mut cache = PatternCache::new(2);
+
+        let first = cache.get("a*");
+        assert!(Arc::ptr_eq(&first, &cache.get("a*")));
+        cache.get("b*");
+        assert_eq!(cache.len(), 2);
+
+        // The oldest compilation is evicted.
+        cache.get("c*");
+        assert_eq!(cache.len(), 2);
+        assert!(!Arc::ptr_eq(&first, &cache.get("a*")));
+
+        let mut cache = PatternCache::new(0);
+        assert!(cache.get("a*").matches("abc"));
+        assert!(cache.is_empty());
+    }
+}
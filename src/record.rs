@@ -0,0 +1,147 @@
+//! Recording of the commands clients send, and their replay.
+//!
+//! When the server runs with `--record <dir>`, every connection writes the commands it receives to
+//! `<dir>/<connection id>.resp`, RESP encoded, in the order they were received. [`replay`] feeds
+//! such a file back to a store, outside of any server, so a bug reported by a user can be
+//! reproduced deterministically from their recording: `rustdis --replay <file>` prints the reply
+//! to each recorded command.
+//!
+//! Recordings are regular streams of RESP commands, like the output of the `dump` module, so they
+//! can also be replayed into any Redis server with `redis-cli --pipe`.
+
+use std::fs::{self, File};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::commands::executable::Executable;
+use crate::commands::Command;
+use crate::error::RustdisError;
+use crate::frame::Frame;
+use crate::store::Store;
+
+/// Writes the commands received by one connection to its recording file.
+///
+/// Every command is written as soon as it is received, with a blocking write, so the recording
+/// is complete even if the server crashes right after. This slows the server down, recording is
+/// meant for debugging sessions only.
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+}
+
+impl Recorder {
+    /// Creates the recording file of the connection `connection_id` in `dir`.
+    pub fn create(dir: &Path, connection_id: Uuid) -> Result<Recorder, RustdisError> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.resp", connection_id));
+        let file = File::create(&path)?;
+
+        Ok(Recorder { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, frame: &Frame) -> Result<(), RustdisError> {
+        self.file.write_all(&frame.serialize())?;
+        Ok(())
+    }
+}
+
+/// Executes every command recorded in `data` against `store`, in order, returning their replies.
+///
+/// Commands with invalid arguments are replied to with an error like the server does. A frame that
+/// isn't a command stops the replay with an error, like it closes the connection that sent it.
+pub fn replay(store: &Store, data: &[u8]) -> Result<Vec<Frame>, RustdisError> {
+    let mut cursor = Cursor::new(data);
+    let mut replies = vec![];
+
+    while (cursor.position() as usize) < data.len() {
+        let frame = match Frame::parse(&mut cursor) {
+            Ok(frame) => frame,
+            Err(RustdisError::Incomplete) => {
+                return Err(RustdisError::Protocol(
+                    "unexpected end of the recording".to_string(),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let reply = match Command::try_from(frame) {
+            Ok(cmd) => cmd.exec(store.clone())?,
+            Err(err @ RustdisError::Command { .. }) => Frame::Error(err.to_string()),
+            Err(err) => return Err(err),
+        };
+        replies.push(reply);
+    }
+
+    Ok(replies)
+}
+
+/// Runs [`replay`] over the content of the file at `path`.
+pub fn replay_file(store: &Store, path: &Path) -> Result<Vec<Frame>, RustdisError> {
+    let data = fs::read(path)?;
+    replay(store, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn command(args: &[&str]) -> Frame {
+        Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("rustdis-record-{}", Uuid::new_v4()));
+        let id = Uuid::new_v4();
+
+        let mut recorder = Recorder::create(&dir, id).unwrap();
+        assert_eq!(recorder.path(), dir.join(format!("{}.resp", id)));
+
+        for frame in [
+            command(&["SET", "key", "1"]),
+            command(&["SET", "key", "2", "NX"]),
+            command(&["SET", "key", "3", "NX", "XX"]),
+            command(&["GET", "key"]),
+        ] {
+            recorder.record(&frame).unwrap();
+        }
+
+        let store = Store::new();
+        let replies = replay_file(&store, recorder.path()).unwrap();
+
+        assert_eq!(
+            replies,
+            vec![
+                Frame::Simple("OK".to_string()),
+                Frame::NullBulkString,
+                Frame::Error("ERR syntax error".to_string()),
+                Frame::Bulk(Bytes::from("1")),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replay_truncated_recording() {
+        let store = Store::new();
+
+        let data = b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$1\r\n1\r\n*2\r\n$3\r\nGET\r\n";
+
+        let res = replay(&store, data);
+
+        assert!(matches!(res, Err(RustdisError::Protocol(_))));
+        assert_eq!(store.lock().get("key"), Some(Bytes::from("1")));
+    }
+}
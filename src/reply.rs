@@ -0,0 +1,203 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::frame::{Frame, Protocol};
+
+/// Serializes replies for the protocol version negotiated with a client.
+///
+/// Commands build their replies out of every frame type RESP3 offers and stay unaware of the
+/// protocol version spoken by the client. The writer converts the types RESP2 lacks, and the
+/// RESP2 null variants RESP3 replaces, right before a reply is sent. Adopting a RESP3 type in a
+/// command doesn't need a protocol branch in it.
+///
+/// Ref: <https://redis.io/docs/latest/develop/reference/protocol-spec/#resp-versions>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplyWriter {
+    protocol: Protocol,
+}
+
+impl ReplyWriter {
+    pub fn new(protocol: Protocol) -> ReplyWriter {
+        ReplyWriter { protocol }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Converts `frame` into the frame types available in the protocol version of the client.
+    pub fn adapt(&self, frame: Frame) -> Frame {
+        match self.protocol {
+            Protocol::Resp2 => frame.into_resp2(),
+            Protocol::Resp3 => frame.into_resp3(),
+        }
+    }
+
+    /// Serializes `frame` as it must be sent to the client.
+    pub fn serialize(&self, frame: Frame) -> Bytes {
+        let mut buf = BytesMut::new();
+        self.serialize_into(frame, &mut buf);
+        buf.freeze()
+    }
+
+    /// Appends the serialization of `frame`, as it must be sent to the client, to `buf`.
+    pub fn serialize_into(&self, frame: Frame, buf: &mut BytesMut) {
+        self.adapt(frame).serialize_into(buf);
+    }
+}
+
+/// The serialization of `frame` when it is one of the small replies sent most often, like the OK
+/// of SET or the PONG of PING. These are kept serialized, so sending them formats and copies
+/// nothing. `frame` must already be adapted to the protocol of the client, see
+/// `ReplyWriter::adapt`.
+pub fn shared_reply(frame: &Frame) -> Option<&'static [u8]> {
+    match frame {
+        Frame::Simple(s) if s == "OK" => Some(b"+OK\r\n"),
+        Frame::Simple(s) if s == "PONG" => Some(b"+PONG\r\n"),
+        Frame::Integer(0) => Some(b":0\r\n"),
+        Frame::Integer(1) => Some(b":1\r\n"),
+        Frame::NullBulkString => Some(b"$-1\r\n"),
+        Frame::NullArray => Some(b"*-1\r\n"),
+        Frame::Null => Some(b"_\r\n"),
+        _ => None,
+    }
+}
+
+/// Whether replies are sent to a client, set with CLIENT REPLY.
+///
+/// Ref: <https://redis.io/docs/latest/commands/client-reply/>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplyMode {
+    /// Every reply is sent.
+    #[default]
+    On,
+    /// No reply is sent until the mode is set back to `On`.
+    Off,
+    /// The reply to the next command is not sent.
+    Skip,
+}
+
+/// Decides which replies are sent to a client, following the mode set with CLIENT REPLY.
+///
+/// Commands are executed whatever the mode is, only their replies are dropped. A client
+/// pipelining commands with replies off still gets them all executed, in order.
+#[derive(Debug, Default)]
+pub struct ReplyFilter {
+    off: bool,
+    skip_next: bool,
+}
+
+impl ReplyFilter {
+    /// Returns whether the reply to the command that was just executed is sent. `mode` is the
+    /// mode it set, when the command is CLIENT REPLY.
+    ///
+    /// Like Redis, CLIENT REPLY OFF and SKIP are not replied to, while ON is.
+    pub fn accept(&mut self, mode: Option<ReplyMode>) -> bool {
+        match mode {
+            Some(ReplyMode::On) => {
+                self.off = false;
+                self.skip_next = false;
+                true
+            }
+            Some(ReplyMode::Off) => {
+                self.off = true;
+                false
+            }
+            Some(ReplyMode::Skip) => {
+                self.skip_next = true;
+                false
+            }
+            None => {
+                let skip = std::mem::take(&mut self.skip_next);
+                !self.off && !skip
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_per_protocol() {
+        let frame = Frame::Array(vec![
+            Frame::NullBulkString,
+            Frame::Boolean(true),
+            Frame::Verbatim {
+                encoding: "txt".to_string(),
+                text: Bytes::from("hi"),
+            },
+        ]);
+
+        let resp2 = ReplyWriter::new(Protocol::Resp2);
+        assert_eq!(
+            resp2.serialize(frame.clone()),
+            Bytes::from("*3\r\n$-1\r\n:1\r\n$2\r\nhi\r\n")
+        );
+
+        let resp3 = ReplyWriter::new(Protocol::Resp3);
+        assert_eq!(
+            resp3.serialize(frame),
+            Bytes::from("*3\r\n_\r\n#t\r\n=6\r\ntxt:hi\r\n")
+        );
+
+        let frame = Frame::Map(vec![(
+            Frame::Bulk(Bytes::from("k")),
+            Frame::Set(vec![Frame::Integer(1)]),
+        )]);
+        assert_eq!(
+            resp2.serialize(frame.clone()),
+            Bytes::from("*2\r\n$1\r\nk\r\n*1\r\n:1\r\n")
+        );
+        assert_eq!(
+            resp3.serialize(frame),
+            Bytes::from("%1\r\n$1\r\nk\r\n~1\r\n:1\r\n")
+        );
+    }
+
+    #[test]
+    fn shared_replies() {
+        let frames = [
+            Frame::Simple("OK".to_string()),
+            Frame::Simple("PONG".to_string()),
+            Frame::Integer(0),
+            Frame::Integer(1),
+            Frame::NullBulkString,
+            Frame::NullArray,
+        ];
+
+        for protocol in [Protocol::Resp2, Protocol::Resp3] {
+            let writer = ReplyWriter::new(protocol);
+            for frame in frames.clone() {
+                let shared = shared_reply(&writer.adapt(frame.clone()));
+                assert_eq!(
+                    shared.map(Bytes::from_static),
+                    Some(writer.serialize(frame.clone())),
+                    "{:?} {:?}",
+                    protocol,
+                    frame
+                );
+            }
+        }
+
+        assert_eq!(shared_reply(&Frame::Simple("QUEUED".to_string())), None);
+        assert_eq!(shared_reply(&Frame::Integer(2)), None);
+    }
+
+    #[test]
+    fn reply_filter() {
+        let mut filter = ReplyFilter::default();
+        assert!(filter.accept(None));
+
+        assert!(!filter.accept(Some(ReplyMode::Skip)));
+        assert!(!filter.accept(None));
+        assert!(filter.accept(None));
+
+        assert!(!filter.accept(Some(ReplyMode::Off)));
+        assert!(!filter.accept(None));
+        assert!(!filter.accept(Some(ReplyMode::Skip)));
+        assert!(!filter.accept(None));
+        assert!(filter.accept(Some(ReplyMode::On)));
+        assert!(filter.accept(None));
+    }
+}
@@ -0,0 +1,163 @@
+//! An in-process smoke suite, run by `rustdis --self-test`.
+//!
+//! The checks exercise the protocol codec, the store and a few commands without opening a socket,
+//! so a container can verify that the binary works on its platform before rustdis is wired in as
+//! a Redis stand-in. They are a sanity check of the build, not a replacement for the test suite.
+
+use bytes::{Bytes, BytesMut};
+use tokio::time::{sleep, Duration};
+use tokio_util::codec::Decoder;
+
+use crate::codec::FrameCodec;
+use crate::commands::executable::Executable;
+use crate::commands::Command;
+use crate::dump;
+use crate::frame::Frame;
+use crate::store::{NewValue, Store};
+
+/// The outcome of one check of the suite.
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub result: Result<(), String>,
+}
+
+/// Runs every check, in order, returning their outcomes.
+pub async fn run() -> Vec<Check> {
+    vec![
+        Check {
+            name: "codec round-trip",
+            result: codec_round_trip(),
+        },
+        Check {
+            name: "string commands",
+            result: string_commands(),
+        },
+        Check {
+            name: "key expiration",
+            result: key_expiration().await,
+        },
+        Check {
+            name: "dump round-trip",
+            result: dump_round_trip(),
+        },
+    ]
+}
+
+fn ensure_eq<T: PartialEq + std::fmt::Debug>(actual: T, expected: T) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("expected {:?}, got {:?}", expected, actual))
+    }
+}
+
+/// Every frame type is decoded back to itself once serialized, even when it arrives split.
+fn codec_round_trip() -> Result<(), String> {
+    let frames = vec![
+        Frame::Simple("OK".to_string()),
+        Frame::Error("ERR oops".to_string()),
+        Frame::Integer(-42),
+        Frame::Bulk(Bytes::from("hello\r\nworld")),
+        Frame::NullBulkString,
+        Frame::Array(vec![Frame::Bulk(Bytes::from("GET")), Frame::Integer(1)]),
+    ];
+
+    let mut codec = FrameCodec::default();
+    for frame in frames {
+        let serialized = frame.serialize();
+        let (head, tail) = serialized.split_at(serialized.len() / 2);
+
+        let mut buf = BytesMut::from(head);
+        let decoded = codec.decode(&mut buf).map_err(|e| e.to_string())?;
+        ensure_eq(decoded, None)?;
+
+        buf.extend_from_slice(tail);
+        let decoded = codec.decode(&mut buf).map_err(|e| e.to_string())?;
+        ensure_eq(decoded, Some(frame))?;
+        ensure_eq(buf.len(), 0)?;
+    }
+
+    Ok(())
+}
+
+fn exec(store: &Store, args: &[&str]) -> Result<Frame, String> {
+    let frame = Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+            .collect(),
+    );
+    Command::try_from(frame)
+        .and_then(|cmd| cmd.exec(store.clone()))
+        .map_err(|e| format!("{}: {}", args.join(" "), e))
+}
+
+fn string_commands() -> Result<(), String> {
+    let store = Store::new();
+
+    ensure_eq(
+        exec(&store, &["SET", "key", "10"])?,
+        Frame::Simple("OK".to_string()),
+    )?;
+    ensure_eq(exec(&store, &["APPEND", "key", "0"])?, Frame::Integer(3))?;
+    ensure_eq(exec(&store, &["STRLEN", "key"])?, Frame::Integer(3))?;
+    ensure_eq(
+        exec(&store, &["GET", "key"])?,
+        Frame::Bulk(Bytes::from("100")),
+    )?;
+    ensure_eq(exec(&store, &["DEL", "key"])?, Frame::Integer(1))?;
+    ensure_eq(exec(&store, &["GET", "key"])?, Frame::NullBulkString)?;
+
+    Ok(())
+}
+
+/// Keys are removed in the background once their time to live elapses.
+async fn key_expiration() -> Result<(), String> {
+    let store = Store::new();
+
+    store.set2(
+        String::from("key"),
+        NewValue {
+            data: Bytes::from("value"),
+            ttl: Some(Duration::from_millis(20)),
+        },
+    );
+    ensure_eq(store.lock().exists("key"), true)?;
+
+    sleep(Duration::from_millis(100)).await;
+    ensure_eq(store.lock().exists("key"), false)?;
+    ensure_eq(store.stats.expired_keys(), 1)?;
+
+    Ok(())
+}
+
+/// A keyspace exported and imported into another store is the same.
+fn dump_round_trip() -> Result<(), String> {
+    let store = Store::new();
+    exec(&store, &["SET", "key1", "value1"])?;
+    exec(&store, &["SET", "key2", "value2", "EX", "100"])?;
+
+    let other = Store::new();
+    dump::import(&other, &dump::export(&store)).map_err(|e| e.to_string())?;
+
+    let state = other.lock();
+    ensure_eq(state.size(), 2)?;
+    ensure_eq(state.get("key1"), Some(Bytes::from("value1")))?;
+    ensure_eq(state.get("key2"), Some(Bytes::from("value2")))?;
+    ensure_eq(
+        state.get_value("key2").and_then(|v| v.expires_at).is_some(),
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_check_passes() {
+        for check in run().await {
+            assert_eq!(check.result, Ok(()), "{}", check.name);
+        }
+    }
+}
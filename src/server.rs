@@ -1,62 +1,674 @@
+use socket2::{SockRef, TcpKeepalive};
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::mem;
 use std::net::SocketAddr;
-use tokio::io::AsyncWriteExt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process;
+use std::sync::Mutex;
 use tokio::net::{TcpListener, TcpStream};
-use tracing::{error, info, instrument};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{self, Duration};
+use tracing::{error, info, info_span, instrument, warn, Instrument};
 
+use crate::commands::client::{Client, Kill, Reply};
+use crate::commands::debug;
 use crate::commands::executable::Executable;
-use crate::commands::Command;
+use crate::commands::{Command, CommandInfo};
+use crate::config::Config;
 use crate::connection::Connection;
-use crate::store::Store;
-use crate::Error;
+use crate::dump;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+use crate::journal::JournalEntry;
+use crate::record::Recorder;
+use crate::reply::{ReplyMode, ReplyWriter};
+use crate::store::{ServerState, Store};
+use crate::transaction::Queued;
+use crate::utils::compression;
 
-pub async fn run(port: u16) -> Result<(), Error> {
-    let subscriber = tracing_subscriber::FmtSubscriber::new();
-    tracing::subscriber::set_global_default(subscriber)?;
+/// Grace period given to connections to finish their in-flight commands once a shutdown starts.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
-    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
-    let store = Store::new();
+/// The reply to the commands received while the dataset is loaded, see `ServerState::Loading`.
+const LOADING_MESSAGE: &str = "Redis is loading the dataset in memory";
+
+/// Commands a connection executes in a row before yielding to the other tasks. A pipeline that
+/// is already buffered is processed without ever waiting on the socket, so without yielding a
+/// client sending a huge pipeline would hold its worker thread until the whole pipeline is done.
+const COMMANDS_PER_YIELD: u64 = 64;
+
+/// Runs the server until the process receives SIGINT or SIGTERM.
+pub async fn run(config: Config) -> Result<(), RustdisError> {
+    run_until(config, shutdown_signal()).await
+}
+
+/// Runs the server until `shutdown` completes. The server then stops accepting connections, lets
+/// every connection finish the command it is executing and flush its replies, exports the keyspace
+/// if configured to, and returns.
+pub async fn run_until(
+    config: Config,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), RustdisError> {
+    serve(Store::with_config(config), shutdown).await
+}
+
+/// Like `run_until`, serving `store`, configured with the configuration it was created with.
+/// Embedders keep a handle on the store, to read its journal or inspect its keys directly.
+pub async fn serve(store: Store, shutdown: impl Future<Output = ()>) -> Result<(), RustdisError> {
+    let config = store.config().clone();
+    print_banner(&config);
+
+    let listener = TcpListener::bind((config.bind.as_str(), config.port)).await?;
+
+    // The preload runs before the first connection is accepted, clients connecting meanwhile wait
+    // in the listen backlog, so they never see the dataset partially loaded.
+    if let Some(path) = &config.preload {
+        let (preloaded, file) = (store.clone(), path.clone());
+        let count = tokio::task::spawn_blocking(move || dump::preload_from_file(&preloaded, &file))
+            .await
+            .map_err(|e| RustdisError::Other(e.into()))?
+            .inspect_err(|err| error!("Failed to preload the dataset: {}", err))?;
+        info!("Preloaded {} commands from {}", count, path.display());
+    }
+
+    // The import is loaded in the background while connections are accepted, which are replied
+    // to with a LOADING error until it is done, like Redis does while it loads its dataset.
+    let mut loading = config.import.clone().map(|path| {
+        store.set_server_state(ServerState::Loading);
+        let store = store.clone();
+        tokio::task::spawn_blocking(move || {
+            let count = dump::import_from_file(&store, &path)?;
+            info!("Imported {} commands from {}", count, path.display());
+            Ok::<_, RustdisError>(())
+        })
+    });
+    let mut load_error = None;
 
     info!("Redis server listening on {}", listener.local_addr()?);
 
+    // The handler is installed before the first connection is accepted, a SIGHUP received from
+    // then on never terminates the process.
+    #[cfg(unix)]
+    let reloader = match config.config_file {
+        Some(_) => match signal(SignalKind::hangup()) {
+            Ok(hangup) => Some(tokio::spawn(reload_on_hangup(store.clone(), hangup))),
+            Err(e) => {
+                error!("Failed to listen for SIGHUP: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+
     loop {
-        let (socket, client_address) = listener.accept().await?;
+        let (socket, client_address) = tokio::select! {
+            res = listener.accept() => res?,
+            res = async { loading.as_mut().expect("checked by the guard").await },
+                if loading.is_some() =>
+            {
+                loading = None;
+                match res.map_err(|e| RustdisError::Other(e.into())).and_then(|res| res) {
+                    Ok(()) => {
+                        store.set_server_state(ServerState::Ready);
+                        info!("DB loaded, ready to accept commands");
+                        continue;
+                    }
+                    Err(err) => {
+                        error!("Failed to load the import: {}", err);
+                        load_error = Some(err);
+                        break;
+                    }
+                }
+            }
+            // Reap the connections that are already closed.
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = &mut shutdown => break,
+        };
+        if let Err(e) = configure_socket(&socket, &config) {
+            error!("Failed to configure socket options: {}", e);
+        }
         let store = store.clone();
+        let read_buffer_size = config.read_buffer_size;
+        let record = config.record.clone();
+        let shutdown = shutdown_rx.clone();
         info!("Accepted connection from {:?}", client_address);
+        store.stats.incr_connections_received();
 
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(socket, client_address, store).await {
-                error!(e);
+        connections.spawn(async move {
+            let res = handle_connection(
+                socket,
+                client_address,
+                read_buffer_size,
+                record,
+                store,
+                shutdown,
+            )
+            .await;
+            if let Err(e) = res {
+                error!("{}", e);
             }
         });
     }
+
+    if load_error.is_none() {
+        info!("User requested shutdown...");
+    }
+    drop(listener);
+    let _ = shutdown_tx.send(true);
+    #[cfg(unix)]
+    if let Some(reloader) = reloader {
+        reloader.abort();
+    }
+
+    let closed = time::timeout(SHUTDOWN_TIMEOUT, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if closed.is_err() {
+        warn!(
+            "{} connections did not close in time, dropping them",
+            connections.len()
+        );
+        connections.shutdown().await;
+    }
+
+    // A keyspace that failed to load is incomplete, so it is not exported over the previous one.
+    if let Some(err) = load_error {
+        return Err(err);
+    }
+
+    if let Some(path) = &config.export {
+        dump::export_to_file(&store, path)?;
+        info!("Exported the keyspace to {}", path.display());
+    }
+
+    info!("rustdis is now ready to exit, bye bye...");
+    Ok(())
+}
+
+/// Completes once the process receives SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                return;
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => info!("Received SIGINT scheduling shutdown..."),
+            _ = terminate.recv() => info!("Received SIGTERM scheduling shutdown..."),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Received SIGINT scheduling shutdown...");
+    }
+}
+
+/// Reloads the configuration file each time the process receives SIGHUP, like many daemons do.
+#[cfg(unix)]
+async fn reload_on_hangup(store: Store, mut hangup: Signal) {
+    while hangup.recv().await.is_some() {
+        info!("Received SIGHUP, reloading the configuration...");
+        reload_config(&store);
+    }
+}
+
+/// Reloads the configuration file of `store`, see `Config::reload`, logging every directive that
+/// changed and whether the change is in effect.
+pub fn reload_config(store: &Store) {
+    let (config, changes) = match store.config().reload() {
+        Ok(reload) => reload,
+        Err(e) => {
+            error!(
+                "Failed to reload the configuration, keeping it as is: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    for change in &changes {
+        match change.applied {
+            true => info!(
+                "Reloaded {}: {} => {}",
+                change.directive, change.from, change.to
+            ),
+            false => warn!(
+                "Not reloading {}: {} => {}, it only changes on restart",
+                change.directive, change.from, change.to
+            ),
+        }
+    }
+    if changes.is_empty() {
+        info!("The configuration is unchanged");
+    }
+
+    store.set_config(config);
+}
+
+/// Installs the global tracing subscriber, writing to the configured log file if any.
+pub fn init_tracing(config: &Config) -> Result<(), RustdisError> {
+    let res = match &config.logfile {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(Mutex::new(file))
+                .with_ansi(false)
+                .finish();
+            tracing::subscriber::set_global_default(subscriber)
+        }
+        None => {
+            let subscriber = tracing_subscriber::FmtSubscriber::new();
+            tracing::subscriber::set_global_default(subscriber)
+        }
+    };
+
+    res.map_err(|e| RustdisError::Other(e.into()))
+}
+
+fn print_banner(config: &Config) {
+    info!(
+        "rustdis version={}, bits={}, pid={}, just started",
+        env!("CARGO_PKG_VERSION"),
+        usize::BITS,
+        process::id()
+    );
+    info!(
+        r#"
+                _      _ _
+ _ __ _   _ ___| |_ __| (_)___
+| '__| | | / __| __/ _` | / __|     rustdis {}
+| |  | |_| \__ \ || (_| | \__ \     Port: {}
+|_|   \__,_|___/\__\__,_|_|___/     PID: {}
+"#,
+        env!("CARGO_PKG_VERSION"),
+        config.port,
+        process::id()
+    );
+
+    if config.daemonize {
+        warn!("daemonize is not supported, running in the foreground");
+    }
+    if config.appendonly {
+        warn!("appendonly is not supported, data will not be persisted");
+    }
+    if config.maxmemory > 0 {
+        warn!("maxmemory is not enforced, there is no eviction");
+    }
+    if config.value_compression_threshold > 0 && !compression::ENABLED {
+        warn!("value-compression-threshold requires the compression feature, values will not be compressed");
+    }
+}
+
+fn configure_socket(socket: &TcpStream, config: &Config) -> Result<(), RustdisError> {
+    socket.set_nodelay(config.tcp_nodelay)?;
+
+    let socket = SockRef::from(socket);
+    match config.tcp_keepalive() {
+        Some(time) => socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(time))?,
+        None => socket.set_keepalive(false)?,
+    }
+
+    Ok(())
 }
 
 #[instrument(
     name = "connection",
-    skip(stream, store),
+    skip(stream, store, shutdown),
     fields(connection_id, client_address)
 )]
 async fn handle_connection(
     stream: TcpStream,
     client_address: SocketAddr,
+    read_buffer_size: usize,
+    record: Option<PathBuf>,
     store: Store,
-) -> Result<(), Error> {
-    let mut conn = Connection::new(stream, client_address);
+    shutdown: watch::Receiver<bool>,
+) -> Result<(), RustdisError> {
+    let mut conn = Connection::with_read_buffer_size(stream, client_address, read_buffer_size);
 
     tracing::Span::current()
         .record("connection_id", conn.id.to_string())
         .record("client_address", client_address.to_string());
 
-    while let Some(frame) = conn.read_frame().await? {
-        info!("Received frame from client: {:?}", frame);
-        let cmd = Command::try_from(frame)?;
-        let res = cmd.exec(store.clone())?;
-        info!("Sending response to client: {:?}", res);
-        let res: Vec<u8> = res.into();
+    let recorder = match record {
+        Some(dir) => {
+            let recorder = Recorder::create(&dir, conn.id)?;
+            info!("Recording commands to {}", recorder.path().display());
+            Some(recorder)
+        }
+        None => None,
+    };
 
-        conn.writer.write_all(&res).await?;
-    }
+    let res = handle_frames(&mut conn, &store, recorder, shutdown).await;
+
+    // Replies are buffered, make sure the ones sent before a failure reach the client.
+    conn.flush().await?;
 
     info!("Connection closed");
+    res
+}
+
+/// Executes `cmd`, turning a panic into an error reply so it only affects the command that
+/// caused it instead of the whole connection.
+fn execute(cmd: Command, store: &Store) -> Result<Frame, RustdisError> {
+    let name = cmd.name();
+
+    match panic::catch_unwind(AssertUnwindSafe(|| cmd.exec(store.clone()))) {
+        Ok(res) => res,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            error!("Command {} panicked: {}", name.to_uppercase(), message);
+
+            Ok(Frame::Error("ERR internal error".to_string()))
+        }
+    }
+}
+
+/// The reply mode set by `cmd` when it is CLIENT REPLY, which the connection applies itself.
+fn reply_mode(cmd: &Command) -> Option<ReplyMode> {
+    match cmd {
+        Command::Client(Client::Reply(Reply { mode })) => Some(*mode),
+        _ => None,
+    }
+}
+
+/// Writes `res`, the reply to a command, to `conn`.
+async fn send_reply(conn: &mut Connection, store: &Store, res: Frame) -> Result<(), RustdisError> {
+    info!("Sending response to client: {:?}", res);
+    let shared_replies = conn.shared_replies_written();
+    let bytes_written = conn.write_frame(res).await?;
+    store.stats.incr_net_output_bytes(bytes_written as u64);
+    store
+        .stats
+        .incr_replies_sent(conn.shared_replies_written() > shared_replies);
+    Ok(())
+}
+
+/// Whether `cmd` starts or ends a transaction, which the connection applies itself, instead of
+/// being queued by it.
+fn controls_transaction(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::Multi(_) | Command::Exec(_) | Command::Discard(_)
+    )
+}
+
+/// Executes CLIENT KILL, which only ever matches the connection sending it. The connection is
+/// closed once the reply is sent, after the replies queued before it.
+fn kill_self(conn: &mut Connection, kill: &Kill) -> Frame {
+    let killed = kill.matches(conn.client_address);
+    if killed {
+        info!("Connection killed by the client");
+        conn.killed = true;
+    }
+    kill.reply(killed)
+}
+
+/// Executes `cmd`, received from `conn` as `frame` in the request `request_id`, appending it to
+/// the journal of the store when it is enabled. EXEC executes the commands queued by the
+/// transaction of the connection, each replied to in its reply.
+fn dispatch(
+    conn: &mut Connection,
+    store: &Store,
+    cmd: Command,
+    request_id: u64,
+    frame: Option<&Frame>,
+) -> Result<Frame, RustdisError> {
+    if let (Some(journal), Some(frame)) = (store.journal(), frame) {
+        journal.append(JournalEntry::new(conn.id, request_id, cmd.name(), frame));
+    }
+
+    let res = match cmd {
+        Command::Client(Client::Kill(kill)) => kill_self(conn, &kill),
+        // Replied to in the protocol version it switches to.
+        Command::Hello(hello) => {
+            if let Some(protocol) = hello.protocol {
+                conn.reply_writer = ReplyWriter::new(protocol);
+            }
+            hello.reply(conn.reply_writer.protocol())
+        }
+        // Within a transaction, the reply to EXEC is delayed by every DEBUG SLEEP it executed.
+        Command::Debug(debug::Debug::Sleep(sleep)) => {
+            conn.delay = conn.delay.saturating_add(sleep.duration);
+            Frame::Simple("OK".to_string())
+        }
+        Command::Multi(_) => conn.transaction.begin(),
+        Command::Watch(watch) => {
+            let state = store.lock();
+            for key in watch.keys {
+                let version = state.watch_version(&key);
+                conn.watched.watch(key, version);
+            }
+            Frame::Simple("OK".to_string())
+        }
+        Command::Unwatch(_) => {
+            conn.watched.clear();
+            Frame::Simple("OK".to_string())
+        }
+        Command::Discard(_) => {
+            if conn.transaction.is_active() {
+                conn.watched.clear();
+            }
+            conn.transaction.discard()
+        }
+        Command::Exec(_) => exec_transaction(conn, store)?,
+        cmd => execute(cmd, store)?,
+    };
+
+    Ok(res)
+}
+
+/// Ends the transaction of `conn` on EXEC, unwatching every key, and executes its commands, see
+/// `Transaction::exec`. They are executed only if none of the watched keys was written, and no
+/// command of another connection is executed until they all are, see
+/// `InnerStore::compare_and_exec`.
+fn exec_transaction(conn: &mut Connection, store: &Store) -> Result<Frame, RustdisError> {
+    if !conn.transaction.is_active() {
+        return run_transaction(conn, store, false);
+    }
+
+    let watched = mem::take(&mut conn.watched);
+    match store.compare_and_exec(watched.versions(), || run_transaction(conn, store, false)) {
+        Some(res) => res,
+        None => run_transaction(conn, store, true),
+    }
+}
+
+/// Executes the commands of the transaction of `conn`, replying to EXEC with an array holding
+/// the reply to each, or with the reply of `Transaction::exec` when there are none to execute.
+fn run_transaction(
+    conn: &mut Connection,
+    store: &Store,
+    modified: bool,
+) -> Result<Frame, RustdisError> {
+    let queued = match conn.transaction.exec(modified) {
+        Ok(queued) => queued,
+        Err(res) => return Ok(res),
+    };
+
+    let mut replies = Vec::with_capacity(queued.len());
+    for queued in queued {
+        let reply = match queued {
+            // Traced under the request the command was queued in.
+            Queued::Command {
+                command,
+                request_id,
+                frame,
+            } => info_span!("request", request_id)
+                .in_scope(|| dispatch(conn, store, command, request_id, frame.as_ref()))?,
+            Queued::Error(res) => res,
+        };
+        count_error_reply(store, &reply);
+        replies.push(reply);
+    }
+    Ok(Frame::Array(replies))
+}
+
+/// Executes the command received from `conn` as `frame`, in the request `request_id`, or queues
+/// it when a transaction is active. Returns the reply, and the reply mode set by the command when
+/// it is CLIENT REPLY.
+fn handle_frame(
+    conn: &mut Connection,
+    store: &Store,
+    frame: Frame,
+    request_id: u64,
+    recorder: Option<&mut Recorder>,
+) -> Result<(Frame, Option<ReplyMode>), RustdisError> {
+    info!("Received frame from client: {:?}", frame);
+    if let Some(recorder) = recorder {
+        recorder.record(&frame)?;
+    }
+    // The arguments are only kept for the journal when it is enabled, cloning them is cheap.
+    let journaled = store.journal().map(|_| frame.clone());
+    // Within a transaction, a known command sent with a valid number of arguments is queued
+    // even when its arguments are invalid, its error is then part of the reply of EXEC.
+    let queueable = conn.transaction.is_active() && CommandInfo::of_frame(&frame).is_some();
+    let res = match Command::try_from(frame) {
+        Ok(cmd)
+            if store.server_state() == ServerState::Loading
+                && !cmd.info().is_allowed_while_loading() =>
+        {
+            conn.transaction.abort();
+            let err = RustdisError::command(ErrorKind::Loading, LOADING_MESSAGE);
+            (Frame::Error(err.to_string()), None)
+        }
+        Ok(cmd) if conn.transaction.is_active() && !cmd.info().is_allowed_in_transaction() => {
+            conn.transaction.abort();
+            let err =
+                RustdisError::command(ErrorKind::Err, "Command not allowed inside a transaction");
+            (Frame::Error(err.to_string()), None)
+        }
+        Ok(cmd) if conn.transaction.is_active() && !controls_transaction(&cmd) => {
+            let queued = Queued::Command {
+                command: cmd,
+                request_id,
+                frame: journaled,
+            };
+            (conn.transaction.queue(queued), None)
+        }
+        Ok(cmd) => {
+            let reply_mode = reply_mode(&cmd);
+            // EXEC excludes the other commands itself, see `exec_transaction`.
+            let _shared = (!matches!(cmd, Command::Exec(_))).then(|| store.share_commands());
+            let res = dispatch(conn, store, cmd, request_id, journaled.as_ref())?;
+            (res, reply_mode)
+        }
+        Err(err @ RustdisError::Command { .. }) if queueable => {
+            log_rejected(store, &err);
+            let queued = Queued::Error(Frame::Error(err.to_string()));
+            (conn.transaction.queue(queued), None)
+        }
+        // A command that is well formed but has invalid arguments, like a syntax error in its
+        // options, is rejected like Redis does, keeping the connection open. Within a
+        // transaction, an unknown command or one with a wrong number of arguments aborts it.
+        Err(err @ RustdisError::Command { .. }) => {
+            log_rejected(store, &err);
+            conn.transaction.abort();
+            (Frame::Error(err.to_string()), None)
+        }
+        Err(err) => return Err(err),
+    };
+    count_error_reply(store, &res.0);
+
+    Ok(res)
+}
+
+/// Counts `reply` in INFO errorstats when it is an error.
+fn count_error_reply(store: &Store, reply: &Frame) {
+    if let Frame::Error(message) = reply {
+        store.stats.incr_error_replies(message);
+    }
+}
+
+/// Logs a command rejected as unknown or for its arguments, unless too many were logged in the
+/// last second, see `Config::rejected_commands_log_rate`.
+fn log_rejected(store: &Store, err: &RustdisError) {
+    let Some(suppressed) = store.rejections_log().check() else {
+        return;
+    };
+    if suppressed > 0 {
+        warn!("{} rejected commands were not logged", suppressed);
+    }
+    warn!("Rejected command: {}", err);
+}
+
+async fn handle_frames(
+    conn: &mut Connection,
+    store: &Store,
+    mut recorder: Option<Recorder>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), RustdisError> {
+    let mut bytes_read = 0;
+    let mut commands = 0;
+
+    loop {
+        commands += 1;
+        if commands % COMMANDS_PER_YIELD == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // Reading the next frame is cancel safe, so no frame is lost when the server shuts down
+        // while waiting for one. A command that has been read is always executed and replied to.
+        let frame = tokio::select! {
+            frame = conn.read_frame() => frame?,
+            _ = shutdown.wait_for(|shutdown| *shutdown) => break,
+        };
+        let Some(frame) = frame else {
+            break;
+        };
+
+        store
+            .stats
+            .incr_net_input_bytes(conn.bytes_read() - bytes_read);
+        bytes_read = conn.bytes_read();
+
+        // Every trace of the request, up to its reply, carries its id.
+        let request_id = conn.next_request_id();
+        let span = info_span!("request", request_id);
+        let (res, reply_mode) =
+            span.in_scope(|| handle_frame(conn, store, frame, request_id, recorder.as_mut()))?;
+        store.stats.incr_commands_processed();
+
+        // Delayed by DEBUG SLEEP. The replies to the commands before it are sent right away, and
+        // the other connections are served meanwhile.
+        if !conn.delay.is_zero() {
+            conn.flush().await?;
+            time::sleep(mem::take(&mut conn.delay)).await;
+        }
+
+        if conn.reply_filter.accept(reply_mode) {
+            send_reply(conn, store, res).instrument(span).await?;
+        } else {
+            span.in_scope(|| info!("Reply turned off by the client: {:?}", res));
+        }
+
+        // The queued replies are flushed once the loop ends, before the connection is closed.
+        if conn.killed {
+            break;
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// How many distinct error prefixes are counted, like Redis does, so the map can't grow without
+/// bound. Errors with other prefixes are only counted in `total_error_replies`.
+const MAX_ERROR_CODES: usize = 128;
+
+/// Server wide counters, surfaced through the `# Stats` section of the INFO command.
+///
+/// Ref: <https://redis.io/docs/latest/commands/info/>
+#[derive(Debug, Default)]
+pub struct Stats {
+    total_connections_received: AtomicU64,
+    total_commands_processed: AtomicU64,
+    total_net_input_bytes: AtomicU64,
+    total_net_output_bytes: AtomicU64,
+    replies_sent: AtomicU64,
+    shared_replies_sent: AtomicU64,
+    expired_keys: AtomicU64,
+    evicted_keys: AtomicU64,
+    expire_wakeups: AtomicU64,
+    total_error_replies: AtomicU64,
+    error_replies: Mutex<BTreeMap<String, u64>>,
+}
+
+impl Stats {
+    pub fn incr_connections_received(&self) {
+        self.total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn incr_commands_processed(&self) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn incr_net_input_bytes(&self, bytes: u64) {
+        self.total_net_input_bytes
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn incr_net_output_bytes(&self, bytes: u64) {
+        self.total_net_output_bytes
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Counts a reply sent to a client, `shared` when it was already serialized, see
+    /// `reply::shared_reply`.
+    pub fn incr_replies_sent(&self, shared: bool) {
+        self.replies_sent.fetch_add(1, Ordering::Relaxed);
+        if shared {
+            self.shared_replies_sent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts keys deleted because their time to live elapsed, either by the task removing
+    /// expired keys or by a command setting an expiration time in the past.
+    pub fn incr_expired_keys(&self, keys: u64) {
+        self.expired_keys.fetch_add(keys, Ordering::Relaxed);
+    }
+
+    /// Counts the times a write woke up the task removing expired keys, because it set a time to
+    /// live elapsing before the task planned to wake up, see `Config::expire_wakeup_threshold`.
+    pub fn incr_expire_wakeups(&self) {
+        self.expire_wakeups.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts keys deleted to stay below `maxmemory`.
+    ///
+    /// **NOTE**: there is no eviction yet, so it is never incremented.
+    pub fn incr_evicted_keys(&self, keys: u64) {
+        self.evicted_keys.fetch_add(keys, Ordering::Relaxed);
+    }
+
+    /// Counts an error reply, by the prefix of its `message`, like `ERR` or `WRONGTYPE`, for the
+    /// `# Errorstats` section of INFO.
+    pub fn incr_error_replies(&self, message: &str) {
+        self.total_error_replies.fetch_add(1, Ordering::Relaxed);
+
+        let code = message.split(' ').next().unwrap_or_default();
+        let mut error_replies = self
+            .error_replies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = error_replies.get_mut(code) {
+            *count += 1;
+        } else if error_replies.len() < MAX_ERROR_CODES {
+            error_replies.insert(code.to_string(), 1);
+        }
+    }
+
+    pub fn total_connections_received(&self) -> u64 {
+        self.total_connections_received.load(Ordering::Relaxed)
+    }
+
+    pub fn total_commands_processed(&self) -> u64 {
+        self.total_commands_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn total_net_input_bytes(&self) -> u64 {
+        self.total_net_input_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn total_net_output_bytes(&self) -> u64 {
+        self.total_net_output_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn replies_sent(&self) -> u64 {
+        self.replies_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn shared_replies_sent(&self) -> u64 {
+        self.shared_replies_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn expire_wakeups(&self) -> u64 {
+        self.expire_wakeups.load(Ordering::Relaxed)
+    }
+
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn total_error_replies(&self) -> u64 {
+        self.total_error_replies.load(Ordering::Relaxed)
+    }
+
+    /// The number of error replies by prefix, sorted by prefix.
+    pub fn error_replies(&self) -> Vec<(String, u64)> {
+        self.error_replies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(code, count)| (code.clone(), *count))
+            .collect()
+    }
+}
@@ -1,12 +1,88 @@
 use bytes::Bytes;
-use std::collections::{BTreeSet, HashMap};
-use std::ops::AddAssign;
-use std::ops::Deref;
-use std::str::FromStr;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet, TryReserveError};
+use std::hash::{Hash, Hasher};
+use std::ops::{Bound, Deref};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
 use tokio::sync::Notify;
 use tokio::time::{sleep_until, Duration, Instant};
 
+use crate::config::Config;
+use crate::engine::KvEngine;
+use crate::error::{ErrorKind, RustdisError};
+use crate::journal::Journal;
+use crate::pattern::{Pattern, PatternCache};
+use crate::stats::Stats;
+use crate::utils::compression;
+use crate::utils::deadline::Deadline;
+use crate::utils::random::Rng;
+use crate::utils::rate_limit::RateLimiter;
+
+/// How many compiled patterns are kept for KEYS and SCAN MATCH.
+const PATTERN_CACHE_SIZE: usize = 64;
+
+/// Whether the server serves commands, see `InnerStore::server_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerState {
+    /// The dataset is being loaded at startup, only the commands allowed while loading are
+    /// executed, see `CommandInfo::is_allowed_while_loading`.
+    Loading,
+    Ready,
+}
+
+/// A number the INCR family of commands adds to the value of a key: integers for INCR, DECR,
+/// INCRBY and DECRBY, and floats for INCRBYFLOAT. It defines how values and arguments are parsed
+/// and the errors Redis replies with, so every command of the family replies with the same ones.
+pub trait Number: Copy + ToString {
+    /// The error when the value of the key, or an argument, is not such a number.
+    const INVALID: &'static str;
+
+    /// Parses `s` like Redis does, `None` when it is not such a number.
+    fn parse(s: &str) -> Option<Self>;
+
+    /// Adds `increment`, failing with the error Redis replies with when the result can't be
+    /// stored.
+    fn add(self, increment: Self) -> Result<Self, &'static str>;
+}
+
+/// Integers are parsed strictly, like Redis: no sign other than `-`, no leading zeros and no
+/// spaces, so only the canonical form of a number is one.
+impl Number for i64 {
+    const INVALID: &'static str = "value is not an integer or out of range";
+
+    fn parse(s: &str) -> Option<i64> {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        let canonical = s == "0"
+            || (digits.starts_with(|c: char| ('1'..='9').contains(&c))
+                && digits.bytes().all(|b| b.is_ascii_digit()));
+        canonical.then(|| s.parse().ok()).flatten()
+    }
+
+    fn add(self, increment: i64) -> Result<i64, &'static str> {
+        self.checked_add(increment)
+            .ok_or("increment or decrement would overflow")
+    }
+}
+
+/// Infinite floats are parsed, but never stored, like Redis does.
+impl Number for f64 {
+    const INVALID: &'static str = "value is not a valid float";
+
+    fn parse(s: &str) -> Option<f64> {
+        s.parse::<f64>().ok().filter(|float| !float.is_nan())
+    }
+
+    fn add(self, increment: f64) -> Result<f64, &'static str> {
+        let res = self + increment;
+        if res.is_finite() {
+            Ok(res)
+        } else {
+            Err("increment would produce NaN or Infinity")
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Store {
     inner: Arc<InnerStore>,
@@ -14,15 +90,42 @@ pub struct Store {
 
 impl Store {
     pub fn new() -> Store {
+        Store::with_config(Config::default())
+    }
+
+    /// Creates a store whose commands follow the limits set in `config`.
+    pub fn with_config(config: Config) -> Store {
         let state = State {
-            keys: HashMap::new(),
+            keys: config
+                .storage_engine
+                .build(config.keyspace_initial_capacity),
             ttls: BTreeSet::new(),
+            compression_threshold: config.value_compression_threshold,
+            tombstone_grace_period: config.tombstone_grace_period(),
+            tombstones: HashMap::new(),
+            tombstones_by_age: BTreeSet::new(),
+            scheduled_expiration: None,
+            sample: Vec::with_capacity(config.keyspace_initial_capacity),
+            scan_order: BTreeSet::new(),
+            last_version: 0,
+            last_removal: 0,
+            rng: Rng::new(),
+            now: Instant::now(),
         };
 
         let waker = Notify::new();
+        let journal = config.journal.then(Journal::default);
+        let rejections_log = RateLimiter::new(config.rejected_commands_log_rate);
         let inner = Arc::new(InnerStore {
             state: Mutex::new(state),
+            commands: RwLock::new(()),
             waker,
+            stats: Stats::default(),
+            config: RwLock::new(Arc::new(config)),
+            patterns: Mutex::new(PatternCache::new(PATTERN_CACHE_SIZE)),
+            loading: AtomicBool::new(false),
+            journal,
+            rejections_log,
         });
 
         tokio::spawn({
@@ -42,7 +145,17 @@ impl Default for Store {
 
 pub struct InnerStore {
     state: Mutex<State>,
+    /// Shared by every command while it executes, and held exclusively by EXEC while it executes
+    /// a transaction, see `InnerStore::compare_and_exec`.
+    commands: RwLock<()>,
     waker: Notify,
+    pub stats: Stats,
+    /// Replaced as a whole when the configuration is reloaded, see `InnerStore::set_config`.
+    config: RwLock<Arc<Config>>,
+    patterns: Mutex<PatternCache>,
+    loading: AtomicBool,
+    journal: Option<Journal>,
+    rejections_log: RateLimiter,
 }
 
 impl Deref for Store {
@@ -54,8 +167,110 @@ impl Deref for Store {
 }
 
 impl InnerStore {
-    pub fn lock(&self) -> MutexGuard<State> {
-        self.state.lock().unwrap()
+    /// Locks the state, removing the keys whose time to live elapsed first, so a key that expired
+    /// is never seen, whether or not the task removing expired keys got to it yet.
+    ///
+    /// The lock is the unit of atomicity of expiration: every command takes it once and holds it
+    /// until it is done, and while it is held no key expires, `State::now` stays the instant the
+    /// lock was taken. A command sees each key either alive or gone for its whole execution, and
+    /// a key alive in the reply to a command, like PTTL, may be gone for the next one in the same
+    /// pipeline.
+    ///
+    /// **NOTE**: Redis freezes the clock for a whole transaction or script, rustdis only for a
+    /// single command, a key may expire between two commands of a transaction.
+    pub fn lock(&self) -> MutexGuard<'_, State> {
+        // A command that panics while holding the lock poisons it. The panic is caught and
+        // reported to the client, and the state is still usable, so keep serving the others.
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        state.now = Instant::now();
+        let expired = state.remove_expired();
+        if expired > 0 {
+            self.stats.incr_expired_keys(expired as u64);
+        }
+
+        state
+    }
+
+    /// Held while a command executes, so no command is executed while EXEC executes a
+    /// transaction, see `InnerStore::compare_and_exec`. The commands of the transaction are
+    /// executed without it.
+    pub fn share_commands(&self) -> RwLockReadGuard<'_, ()> {
+        self.commands.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs `exec` only if every key of `expected` still has the version it is paired with, see
+    /// `State::watch_version`. This is `State::compare_and_set` for several keys and any number of
+    /// writes: no other command is executed from the check until `exec` returns, so none of the
+    /// keys can be written in between. Returns `None` when a version doesn't match.
+    ///
+    /// EXEC executes the commands of a transaction with it, comparing the keys the client watches.
+    ///
+    /// **NOTE**: keys may still expire while `exec` runs, see `InnerStore::lock`.
+    pub fn compare_and_exec<R>(
+        &self,
+        expected: &[(String, u64)],
+        exec: impl FnOnce() -> R,
+    ) -> Option<R> {
+        let _exclusive = self
+            .commands
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if !self.lock().versions_match(expected) {
+            return None;
+        }
+        Some(exec())
+    }
+
+    /// The configuration in effect. A command reads it once, so a reload doesn't change it
+    /// halfway through.
+    pub fn config(&self) -> Arc<Config> {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the configuration in effect, see `Config::reload`. Only the directives read each
+    /// time they are used change anything, the others were read when the store was created.
+    pub fn set_config(&self, config: Config) {
+        *self.config.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config);
+    }
+
+    /// The deadline of a command starting now, see `Config::command_time_limit`.
+    pub fn command_deadline(&self) -> Deadline {
+        Deadline::after(self.config().command_time_limit())
+    }
+
+    /// The journal of the commands executed by the server, when enabled with `Config::journal`.
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_ref()
+    }
+
+    /// Limits how many rejected commands are logged, see `Config::rejected_commands_log_rate`.
+    pub fn rejections_log(&self) -> &RateLimiter {
+        &self.rejections_log
+    }
+
+    pub fn server_state(&self) -> ServerState {
+        match self.loading.load(Ordering::Acquire) {
+            true => ServerState::Loading,
+            false => ServerState::Ready,
+        }
+    }
+
+    pub fn set_server_state(&self, state: ServerState) {
+        self.loading
+            .store(state == ServerState::Loading, Ordering::Release);
+    }
+
+    /// Returns `pattern` compiled, reusing a recent compilation of the same pattern.
+    pub fn pattern(&self, pattern: &[u8]) -> Arc<Pattern> {
+        self.patterns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(pattern)
     }
 
     pub fn set2(&self, key: Key, value: NewValue) {
@@ -65,64 +280,154 @@ impl InnerStore {
         state.set2(key.clone(), value);
 
         if has_ttl {
-            let next_to_expire = state.ttls.iter().next().map(|(_, key)| key);
-            let expires_next = next_to_expire == Some(&key);
-            if expires_next {
-                self.waker.notify_one();
-            }
+            self.notify_if_expires_next(&mut state, &key);
         }
     }
 
-    pub fn incr_by<T>(&self, key: &str, increment: T) -> Result<T, String>
-    where
-        T: FromStr + ToString + AddAssign + Default,
-    {
-        let err = "value is not of the correct type or out of range".to_string();
+    /// Sets every entry under a single lock, like `set2` does for one. The task removing expired
+    /// keys is woken up at most once, when the batch brings the next expiration forward, instead
+    /// of once for each key that becomes the next to expire while the batch is applied.
+    pub fn set_many(&self, entries: impl IntoIterator<Item = (Key, NewValue)>) {
         let mut state = self.lock();
 
-        let mut value = match state.get(key) {
-            Some(value) => match std::str::from_utf8(value.as_ref())
-                .map_err(|_| err.clone())
-                .and_then(|s| s.parse::<T>().map_err(|_| err.clone()))
-            {
-                Ok(value) => value,
-                Err(e) => return Err(e),
-            },
-            None => T::default(),
-        };
+        state.set_many(entries);
 
-        value += increment;
+        if let Some(next_expiration) = state.next_expiration() {
+            self.notify_if_expires_before(&mut state, next_expiration);
+        }
+    }
 
-        state.set(key.to_string(), value.to_string().into());
+    /// Returns up to `count` distinct keys picked at random, see `State::sample_keys`.
+    pub fn sample_keys(&self, count: usize, type_filter: Option<&str>) -> Vec<Key> {
+        self.lock().sample_keys(count, type_filter)
+    }
+
+    /// Wakes up the task removing expired keys when `key` now expires before the task planned to
+    /// wake up, so it doesn't keep sleeping until a later expiration. Commands setting a time to
+    /// live through `State` call it once they are done.
+    pub fn notify_if_expires_next(&self, state: &mut State, key: &str) {
+        if let Some(expires_at) = state.keys.get(key).and_then(|value| value.expires_at) {
+            self.notify_if_expires_before(state, expires_at);
+        }
+    }
+
+    /// Wakes up the task removing expired keys when `expires_at` is more than
+    /// `Config::expire_wakeup_threshold` before it planned to wake up. Under heavy churn, keys set
+    /// with ever shorter times to live wake it up once per threshold instead of once per key.
+    fn notify_if_expires_before(&self, state: &mut State, expires_at: Instant) {
+        let threshold = self.config().expire_wakeup_threshold();
+        let too_late = state
+            .scheduled_expiration
+            .is_none_or(|scheduled| expires_at + threshold < scheduled);
+
+        if too_late {
+            // Until the task wakes up and schedules its next wake up, later writes compare
+            // against this expiration, so they don't wake it up again.
+            state.scheduled_expiration = Some(expires_at);
+            self.stats.incr_expire_wakeups();
+            self.waker.notify_one();
+        }
+    }
+
+    /// Wakes up the task removing expired keys and purging tombstones, so it accounts for
+    /// tombstones left by a command once it is done.
+    pub fn notify_tombstones_added(&self) {
+        self.waker.notify_one();
+    }
+
+    /// Adds `increment` to the number held by `key`, or to 0 when it doesn't exist, keeping its
+    /// time to live. Returns the new value, or the error of the INCR family of commands when the
+    /// value is not a number or the result can't be stored, see `Number`.
+    pub fn incr_by<T: Number>(&self, key: &str, increment: T) -> Result<T, RustdisError> {
+        let err = |message: &str| RustdisError::Store(message.to_string());
+        let mut state = self.lock();
+
+        state.check_type(key, STRING)?;
+        let value = match state.get(key) {
+            Some(value) => std::str::from_utf8(value.as_ref())
+                .ok()
+                .and_then(T::parse)
+                .ok_or_else(|| err(T::INVALID))?,
+            None => T::parse("0").expect("0 is a number"),
+        };
+        let value = value.add(increment).map_err(err)?;
+
+        state.update_value(key.to_string(), value.to_string().into());
 
         Ok(value)
     }
 
+    /// Removes the keys whose time to live elapsed, returning when the next one expires.
     pub fn remove_expired_keys(&self) -> Option<Instant> {
+        // Taking the lock removes them, see `InnerStore::lock`.
+        let mut state = self.lock();
+
+        state.scheduled_expiration = state.next_expiration();
+        state.scheduled_expiration
+    }
+
+    /// Purges the tombstones whose grace period has elapsed, returning when the next one is due.
+    pub fn purge_tombstones(&self) -> Option<Instant> {
         let mut state = self.lock();
+        let grace_period = state.tombstone_grace_period?;
         let now = Instant::now();
 
-        let expired_keys: Vec<(Instant, String)> = state
-            .ttls
+        let purged: Vec<(Instant, String)> = state
+            .tombstones_by_age
             .iter()
-            .take_while(|(expires_at, _)| expires_at <= &now)
+            .take_while(|(deleted_at, _)| *deleted_at + grace_period <= now)
             .cloned()
             .collect();
 
-        for (when, key) in expired_keys {
-            state.remove(&key);
-            state.ttls.remove(&(when, key));
+        for entry in purged {
+            state.tombstones.remove(&entry.1);
+            state.tombstones_by_age.remove(&entry);
         }
 
-        state.ttls.iter().next().map(|&(expires_at, _)| expires_at)
+        state
+            .tombstones_by_age
+            .iter()
+            .next()
+            .map(|&(deleted_at, _)| deleted_at + grace_period)
     }
 }
 
 type Key = String;
 
+/// The name TYPE replies with for keys holding a string.
+pub const STRING: &str = "string";
+
+/// The name TYPE replies with for keys holding a hash.
+pub const HASH: &str = "hash";
+
+/// The error commands reply with when the key they work on holds a value of another type, see
+/// `State::check_type`.
+pub fn wrong_type() -> RustdisError {
+    RustdisError::command(
+        ErrorKind::WrongType,
+        "Operation against a key holding the wrong kind of value",
+    )
+}
+
+/// What a key holds.
+enum Data {
+    /// Compressed when `Value::compressed` is set.
+    String(Bytes),
+    Hash(HashMap<String, Bytes>),
+}
+
 pub struct Value {
-    pub data: Bytes,
+    data: Data,
+    /// Only strings are compressed, see `Value::with_compression`.
+    compressed: bool,
     pub expires_at: Option<Instant>,
+    /// Position of the key in `State::sample`, maintained by the state as keys come and go.
+    slot: usize,
+    /// When the key was created. Like the time to live, it is kept when the value is modified in
+    /// place and reset when it is replaced as a whole, see `State::update_value`.
+    pub created_at: Instant,
+    /// Changes every time the key is written, see `State::version`.
+    version: u64,
 }
 
 pub struct NewValue {
@@ -130,44 +435,572 @@ pub struct NewValue {
     pub ttl: Option<Duration>,
 }
 
+/// How many values are stored compressed, reported by INFO.
+#[derive(Debug, Default, PartialEq)]
+pub struct CompressionStats {
+    pub values: usize,
+    /// Bytes taken by the compressed values.
+    pub bytes: usize,
+    /// Bytes the compressed values would take uncompressed.
+    pub original_bytes: usize,
+}
+
+/// How many keys the keyspace holds and how many of them expire, reported by INFO.
+#[derive(Debug, Default, PartialEq)]
+pub struct KeyspaceStats {
+    pub keys: usize,
+    /// Keys with a time to live.
+    pub expires: usize,
+    /// Average time to live left of the keys that have one, in milliseconds.
+    pub avg_ttl: u64,
+}
+
+/// Upper bounds of the buckets of `TtlStats::histogram`, with the names DEBUG TTL-STATS gives them.
+pub const TTL_HISTOGRAM_BOUNDS: [(&str, Duration); 6] = [
+    ("1s", Duration::from_secs(1)),
+    ("10s", Duration::from_secs(10)),
+    ("1m", Duration::from_secs(60)),
+    ("10m", Duration::from_secs(10 * 60)),
+    ("1h", Duration::from_secs(60 * 60)),
+    ("1d", Duration::from_secs(24 * 60 * 60)),
+];
+
+/// How the expirations the task removing expired keys has scheduled are spread over time,
+/// reported by DEBUG TTL-STATS.
+#[derive(Debug, Default, PartialEq)]
+pub struct TtlStats {
+    /// Keys with a time to live, each one scheduled to be removed once it elapses.
+    pub scheduled: usize,
+    /// Scheduled keys whose time to live has elapsed, none once `InnerStore::lock` removed them.
+    pub overdue: usize,
+    /// Time left until the first scheduled expiration, zero when it is overdue.
+    pub next_expiration: Option<Duration>,
+    /// Time left until the last scheduled expiration.
+    pub last_expiration: Option<Duration>,
+    /// How many keys have a time to live left below each bound of `TTL_HISTOGRAM_BOUNDS` and not
+    /// below the previous one. The last bucket counts the keys left above every bound.
+    pub histogram: [usize; TTL_HISTOGRAM_BOUNDS.len() + 1],
+}
+
+/// What DEL leaves of a key when tombstones are enabled, see `Config::tombstone_grace_period`.
+pub struct Tombstone {
+    pub value: Value,
+    pub deleted_at: Instant,
+}
+
 impl Value {
     pub fn new(value: Bytes) -> Value {
         Value {
-            data: value,
+            data: Data::String(value),
+            compressed: false,
             expires_at: None,
+            slot: 0,
+            created_at: Instant::now(),
+            version: 0,
+        }
+    }
+
+    /// Creates a value, compressing `data` when it is at least `compression_threshold` bytes long
+    /// and compressing it actually saves space. A threshold of 0 disables compression.
+    fn with_compression(data: Bytes, compression_threshold: usize) -> Value {
+        if compression_threshold > 0 && data.len() >= compression_threshold {
+            if let Some(compressed) = compression::compress(&data) {
+                return Value {
+                    data: Data::String(Bytes::from(compressed)),
+                    compressed: true,
+                    expires_at: None,
+                    slot: 0,
+                    created_at: Instant::now(),
+                    version: 0,
+                };
+            }
+        }
+
+        Value::new(data)
+    }
+
+    fn new_hash(hash: HashMap<String, Bytes>) -> Value {
+        Value {
+            data: Data::Hash(hash),
+            ..Value::new(Bytes::new())
+        }
+    }
+
+    /// The name of the type of the value, as TYPE replies with it.
+    pub fn type_name(&self) -> &'static str {
+        match self.data {
+            Data::String(_) => STRING,
+            Data::Hash(_) => HASH,
+        }
+    }
+
+    /// The data of the value when it is a string, decompressed if needed.
+    pub fn string(&self) -> Option<Bytes> {
+        match &self.data {
+            Data::String(data) if self.compressed => {
+                Some(Bytes::from(compression::decompress(data)))
+            }
+            Data::String(data) => Some(data.clone()),
+            Data::Hash(_) => None,
         }
     }
+
+    /// The fields of the value, and their values, when it is a hash.
+    pub fn hash(&self) -> Option<&HashMap<String, Bytes>> {
+        match &self.data {
+            Data::Hash(hash) => Some(hash),
+            Data::String(_) => None,
+        }
+    }
+
+    /// How long ago the key was created.
+    pub fn age(&self) -> Duration {
+        self.created_at.elapsed()
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Number of bytes the value takes in the store, which is less than its length when it is
+    /// compressed.
+    pub fn stored_len(&self) -> usize {
+        match &self.data {
+            Data::String(data) => data.len(),
+            Data::Hash(_) => self.len(),
+        }
+    }
+
+    /// Length of the data of the value, without decompressing it. The length of a hash adds up
+    /// the lengths of its fields and their values.
+    pub fn len(&self) -> usize {
+        match &self.data {
+            Data::String(data) if self.compressed => compression::decompressed_len(data),
+            Data::String(data) => data.len(),
+            Data::Hash(hash) => hash
+                .iter()
+                .map(|(field, value)| field.len() + value.len())
+                .sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub struct State {
-    keys: HashMap<Key, Value>,
+    keys: Box<dyn KvEngine>,
     ttls: BTreeSet<(Instant, Key)>,
+    /// See `Config::value_compression_threshold`.
+    compression_threshold: usize,
+    /// See `Config::tombstone_grace_period`.
+    tombstone_grace_period: Option<Duration>,
+    tombstones: HashMap<Key, Tombstone>,
+    tombstones_by_age: BTreeSet<(Instant, Key)>,
+    /// When the task removing expired keys wakes up next to remove a key, as far as writers are
+    /// concerned, see `InnerStore::notify_if_expires_next`.
+    scheduled_expiration: Option<Instant>,
+    /// Every key, in no particular order, so keys are sampled by position without walking the
+    /// keyspace, whatever the storage engine. Each value holds the position of its key, see
+    /// `Value::slot`.
+    sample: Vec<Key>,
+    /// Every key, ordered by its scan hash, see `State::scan`.
+    scan_order: BTreeSet<(u64, Key)>,
+    rng: Rng,
+    /// The version of the last key written, see `State::version`.
+    last_version: u64,
+    /// The value `last_version` was bumped to when a key was last removed, see
+    /// `State::watch_version`.
+    last_removal: u64,
+    /// When the lock was taken, see `InnerStore::lock`.
+    now: Instant,
 }
 
 impl State {
+    /// The instant the current command sees, when the lock was taken. Keys expiring at or before
+    /// it are already removed, so every key left lives past it. See `InnerStore::lock`.
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Removes the keys expiring at or before `State::now`, returning how many were.
+    fn remove_expired(&mut self) -> usize {
+        let now = self.now;
+        let mut expired = 0;
+        while self
+            .ttls
+            .first()
+            .is_some_and(|(expires_at, _)| *expires_at <= now)
+        {
+            let (_, key) = self
+                .ttls
+                .pop_first()
+                .expect("the first time to live was checked");
+            if self.remove(&key).is_some() {
+                expired += 1;
+            }
+        }
+        expired
+    }
+
     pub fn set(&mut self, key: String, value: Bytes) {
-        self.keys.insert(key, Value::new(value));
+        let value = Value::with_compression(value, self.compression_threshold);
+        self.insert(key, value);
+    }
+
+    /// Sets `key` to hold `value`, retaining the time to live of the value it replaces, if any.
+    ///
+    /// This is how commands modifying a value update it, like APPEND, SETRANGE or INCR, and
+    /// eventually container commands like LPUSH or HSET: a key that is modified keeps its time to
+    /// live, while a key they create has none. Only commands replacing the value as a whole, like
+    /// SET without KEEPTTL, discard it.
+    pub fn update_value(&mut self, key: String, value: Bytes) {
+        let mut value = Value::with_compression(value, self.compression_threshold);
+        if let Some(current) = self.keys.get(&key) {
+            value.expires_at = current.expires_at;
+            value.created_at = current.created_at;
+        }
+        self.insert(key, value);
     }
 
     pub fn set2(&mut self, key: String, value: NewValue) {
         let ttl = value.ttl;
         let expires_at = ttl.map(|ttl| Instant::now() + ttl);
         let value = Value {
-            data: value.data,
             expires_at,
+            ..Value::with_compression(value.data, self.compression_threshold)
+        };
+        self.insert(key, value);
+    }
+
+    /// Sets every entry like `set2` does, with times to live starting at the same instant.
+    ///
+    /// Commands setting many keys at once, like MSET, use this through `InnerStore::set_many`,
+    /// which also wakes up the task removing expired keys when needed.
+    pub fn set_many(&mut self, entries: impl IntoIterator<Item = (Key, NewValue)>) {
+        let now = Instant::now();
+        for (key, value) in entries {
+            let value = Value {
+                expires_at: value.ttl.map(|ttl| now + ttl),
+                ..Value::with_compression(value.data, self.compression_threshold)
+            };
+            self.insert(key, value);
+        }
+    }
+
+    /// When the next key to expire does, if any key has a time to live.
+    pub fn next_expiration(&self) -> Option<Instant> {
+        self.ttls.iter().next().map(|&(expires_at, _)| expires_at)
+    }
+
+    /// Inserts `value`, replacing the time to live of the previous value of `key` with its own so
+    /// the previous one doesn't remove it once it elapses.
+    fn insert(&mut self, key: String, mut value: Value) {
+        // The key is written again after being deleted, the write wins over the deletion.
+        self.remove_tombstone(&key);
+        if let Some(expires_at) = self.keys.get(&key).and_then(|value| value.expires_at) {
+            self.ttls.remove(&(expires_at, key.clone()));
+        }
+        if let Some(expires_at) = value.expires_at {
+            self.ttls.insert((expires_at, key.clone()));
+        }
+        self.last_version += 1;
+        value.version = self.last_version;
+        value.slot = match self.keys.get(&key) {
+            Some(previous) => previous.slot,
+            None => {
+                self.scan_order.insert((scan_hash(&key), key.clone()));
+                self.sample.push(key.clone());
+                self.sample.len() - 1
+            }
+        };
+        self.keys.insert(key, value);
+    }
+
+    /// Sets the time to live of `key`, or removes it when `ttl` is `None`. Returns whether the key
+    /// exists.
+    pub fn set_ttl(&mut self, key: &str, ttl: Option<Duration>) -> bool {
+        let Some(value) = self.keys.get_mut(key) else {
+            return false;
         };
-        self.keys.insert(key.clone(), value);
-        if let Some(expires_at) = expires_at {
-            self.ttls.insert((expires_at, key));
+
+        if let Some(expires_at) = value.expires_at {
+            self.ttls.remove(&(expires_at, key.to_string()));
+        }
+        value.expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        self.last_version += 1;
+        value.version = self.last_version;
+        if let Some(expires_at) = value.expires_at {
+            self.ttls.insert((expires_at, key.to_string()));
+        }
+
+        true
+    }
+
+    /// The version of `key`, which changes every time the key is written, including when its time
+    /// to live is, and 0 when the key doesn't exist. Versions are never reused, not even by a key
+    /// deleted and then created again, so a version compared with a later one tells whether the
+    /// key was written in between.
+    pub fn version(&self, key: &str) -> u64 {
+        self.keys.get(key).map_or(0, |value| value.version)
+    }
+
+    /// The version WATCH pairs `key` with: its version when it exists, see `State::version`, and
+    /// otherwise the version of the last removal of any key. A missing key written and removed
+    /// again is then told apart from one that was never written, at the cost of also telling it
+    /// apart when only other keys were removed in between.
+    pub fn watch_version(&self, key: &str) -> u64 {
+        self.keys
+            .get(key)
+            .map_or(self.last_removal, |value| value.version)
+    }
+
+    /// Whether every key of `expected` still has the version it is paired with, see
+    /// `State::watch_version`.
+    pub fn versions_match(&self, expected: &[(String, u64)]) -> bool {
+        expected
+            .iter()
+            .all(|(key, version)| self.watch_version(key) == *version)
+    }
+
+    /// Sets `key` to hold `value`, retaining its time to live like `update_value` does, only if
+    /// its version is still `expected_version`, see `State::version`. A version of 0 expects the
+    /// key not to exist. Returns the new version of the key, or its current version when it
+    /// doesn't match.
+    pub fn compare_and_set(
+        &mut self,
+        key: String,
+        expected_version: u64,
+        value: Bytes,
+    ) -> Result<u64, u64> {
+        let version = self.version(&key);
+        if version != expected_version {
+            return Err(version);
         }
+
+        self.update_value(key, value);
+        Ok(self.last_version)
     }
 
+    /// The string `key` holds, `None` when it doesn't exist or holds another type. Commands
+    /// replying with an error for other types call `State::check_type` first.
     pub fn get(&self, key: &str) -> Option<Bytes> {
-        self.keys.get(key).map(|v| v.data.clone())
+        self.keys.get(key).and_then(Value::string)
+    }
+
+    /// The name of the type of the value `key` holds, as TYPE replies with it.
+    pub fn type_of(&self, key: &str) -> Option<&'static str> {
+        self.keys.get(key).map(Value::type_name)
+    }
+
+    /// Fails with the WRONGTYPE error when `key` holds a value whose type is not `type_name`, see
+    /// `wrong_type`. A key that doesn't exist holds no value of any type, so it passes.
+    pub fn check_type(&self, key: &str, type_name: &str) -> Result<(), RustdisError> {
+        match self.type_of(key) {
+            Some(type_) if type_ != type_name => Err(wrong_type()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The hash `key` holds, `None` when it doesn't exist, or the WRONGTYPE error when it holds
+    /// another type.
+    pub fn get_hash(&self, key: &str) -> Result<Option<&HashMap<String, Bytes>>, RustdisError> {
+        self.check_type(key, HASH)?;
+        Ok(self.keys.get(key).and_then(Value::hash))
+    }
+
+    /// Sets `fields` of the hash `key` holds to their values, creating the hash when the key
+    /// doesn't exist, like HSET does. A field given more than once takes its last value. The key
+    /// keeps its time to live. Returns how many fields were added, or the WRONGTYPE error when
+    /// the key holds another type.
+    pub fn hset(
+        &mut self,
+        key: String,
+        fields: impl IntoIterator<Item = (String, Bytes)>,
+    ) -> Result<usize, RustdisError> {
+        self.check_type(&key, HASH)?;
+
+        let Some(value) = self.keys.get_mut(&key) else {
+            let hash: HashMap<String, Bytes> = fields.into_iter().collect();
+            let added = hash.len();
+            self.insert(key, Value::new_hash(hash));
+            return Ok(added);
+        };
+        let Data::Hash(hash) = &mut value.data else {
+            unreachable!("checked by `check_type`");
+        };
+
+        let mut added = 0;
+        for (field, value) in fields {
+            if hash.insert(field, value).is_none() {
+                added += 1;
+            }
+        }
+        self.last_version += 1;
+        value.version = self.last_version;
+
+        Ok(added)
+    }
+
+    /// Removes `fields` from the hash `key` holds, like HDEL does, removing the key once the hash
+    /// has no field left, like Redis does. Returns how many fields were removed, or the WRONGTYPE
+    /// error when the key holds another type.
+    pub fn hdel(&mut self, key: &str, fields: &[String]) -> Result<usize, RustdisError> {
+        self.check_type(key, HASH)?;
+
+        let Some(value) = self.keys.get_mut(key) else {
+            return Ok(0);
+        };
+        let Data::Hash(hash) = &mut value.data else {
+            unreachable!("checked by `check_type`");
+        };
+
+        let removed = fields
+            .iter()
+            .filter(|field| hash.remove(field.as_str()).is_some())
+            .count();
+        if hash.is_empty() {
+            self.remove(key);
+        } else if removed > 0 {
+            self.last_version += 1;
+            value.version = self.last_version;
+        }
+
+        Ok(removed)
+    }
+
+    pub fn get_value(&self, key: &str) -> Option<&Value> {
+        self.keys.get(key)
     }
 
     pub fn remove(&mut self, key: &str) -> Option<Value> {
-        self.keys.remove(key)
+        let value = self.keys.remove(key)?;
+        self.last_version += 1;
+        self.last_removal = self.last_version;
+        if let Some(expires_at) = value.expires_at {
+            self.ttls.remove(&(expires_at, key.to_string()));
+        }
+        self.scan_order.remove(&(scan_hash(key), key.to_string()));
+
+        // The last key takes the place of the removed one.
+        self.sample.swap_remove(value.slot);
+        if let Some(moved) = self.sample.get(value.slot) {
+            if let Some(moved) = self.keys.get_mut(moved) {
+                moved.slot = value.slot;
+            }
+        }
+
+        Some(value)
+    }
+
+    /// Returns up to `count` distinct keys picked at random, in no particular order, all of them
+    /// when the keyspace holds fewer. With a `type_filter`, only keys holding values of that type
+    /// are picked, as named by the TYPE command.
+    ///
+    /// Picking a key takes constant time, so sampling a few keys is cheap however large the
+    /// keyspace is. This is what RANDOMKEY uses.
+    pub fn sample_keys(&mut self, count: usize, type_filter: Option<&str>) -> Vec<Key> {
+        // With a filter, the keys of other types are left out first, walking the keyspace.
+        let candidates: Option<Vec<usize>> = type_filter.map(|type_| {
+            (0..self.sample.len())
+                .filter(|&position| {
+                    self.type_of(&self.sample[position])
+                        .is_some_and(|type_name| type_name.eq_ignore_ascii_case(type_))
+                })
+                .collect()
+        });
+        let key = |position: usize| match &candidates {
+            Some(candidates) => self.sample[candidates[position]].clone(),
+            None => self.sample[position].clone(),
+        };
+
+        let len = candidates
+            .as_ref()
+            .map_or(self.sample.len(), |candidates| candidates.len());
+        if count >= len {
+            return (0..len).map(key).collect();
+        }
+
+        // Floyd's algorithm picks `count` distinct positions with exactly `count` draws.
+        let mut picked = HashSet::with_capacity(count);
+        for upper in len - count..len {
+            let position = self.rng.below(upper + 1);
+            if !picked.insert(position) {
+                picked.insert(upper);
+            }
+        }
+
+        picked.into_iter().map(key).collect()
+    }
+
+    /// Walks the keys from `cursor` on, returning the ones `accept` keeps and the cursor to
+    /// continue from, 0 once every key was walked.
+    ///
+    /// Keys are walked in the order of their scan hash, which doesn't change while they exist,
+    /// and the cursor is the hash of the next key to walk. So a key present during the whole
+    /// iteration is returned, whatever is written in between, and a key may be returned twice
+    /// only if it shares its hash with another.
+    ///
+    /// `count` is a hint of how much work is done: keys are walked until `count` of them are
+    /// kept, or ten times as many were walked when few of them are, like Redis does. Every call
+    /// but the last walks at least `count` keys, so the iteration ends.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        mut accept: impl FnMut(&str, &Value) -> bool,
+    ) -> (u64, Vec<Key>) {
+        let mut order = self
+            .scan_order
+            .range((Bound::Included((cursor, String::new())), Bound::Unbounded))
+            .peekable();
+        let mut keys = vec![];
+        let mut walked = 0;
+
+        while keys.len() < count && walked < count.saturating_mul(10) {
+            let Some((_, key)) = order.next() else {
+                break;
+            };
+            walked += 1;
+            let value = self.keys.get(key).expect("every key is in the scan order");
+            if accept(key, value) {
+                keys.push(key.clone());
+            }
+        }
+
+        let next_cursor = order.peek().map_or(0, |(hash, _)| *hash);
+        (next_cursor, keys)
+    }
+
+    /// Deletes `key` like DEL does, returning whether it existed. The value is kept as a tombstone
+    /// when `Config::tombstone_grace_period` is set, and `InnerStore::notify_tombstones_added`
+    /// must then be called once the command is done.
+    pub fn delete(&mut self, key: &str) -> bool {
+        let Some(value) = self.remove(key) else {
+            return false;
+        };
+
+        if self.tombstone_grace_period.is_some() {
+            let deleted_at = Instant::now();
+            self.tombstones_by_age.insert((deleted_at, key.to_string()));
+            self.tombstones
+                .insert(key.to_string(), Tombstone { value, deleted_at });
+        }
+
+        true
+    }
+
+    fn remove_tombstone(&mut self, key: &str) {
+        if let Some(tombstone) = self.tombstones.remove(key) {
+            self.tombstones_by_age
+                .remove(&(tombstone.deleted_at, key.to_string()));
+        }
+    }
+
+    /// The tombstones of deleted keys not purged yet, see `Config::tombstone_grace_period`.
+    pub fn tombstones(&self) -> impl Iterator<Item = (&String, &Tombstone)> {
+        self.tombstones.iter()
     }
 
     pub fn exists(&self, key: &str) -> bool {
@@ -179,21 +1012,128 @@ impl State {
     }
 
     pub fn keys(&self) -> impl Iterator<Item = &String> {
-        self.keys.keys()
+        self.keys.iter().map(|(key, _)| key)
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (&String, &Bytes)> {
-        self.keys.iter().map(|(key, value)| (key, &value.data))
+    /// Every key holding a string, along with it.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, Bytes)> {
+        self.keys
+            .iter()
+            .filter_map(|(key, value)| Some((key, value.string()?)))
+    }
+
+    /// Aggregates how many values are stored compressed and how much space they take, computed on
+    /// demand by walking the keyspace.
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.keys
+            .iter()
+            .map(|(_, value)| value)
+            .filter(|value| value.is_compressed())
+            .fold(CompressionStats::default(), |mut stats, value| {
+                stats.values += 1;
+                stats.bytes += value.stored_len();
+                stats.original_bytes += value.len();
+                stats
+            })
+    }
+
+    /// Counts the keys and the keys with a time to live, and averages the time to live left of
+    /// the latter. Unlike Redis, which estimates the average from a sample, it is exact.
+    pub fn keyspace_stats(&self) -> KeyspaceStats {
+        let now = Instant::now();
+        let expires = self.ttls.len();
+        let total_ttl: u128 = self
+            .ttls
+            .iter()
+            .map(|(expires_at, _)| expires_at.saturating_duration_since(now).as_millis())
+            .sum();
+
+        KeyspaceStats {
+            keys: self.keys.len(),
+            expires,
+            avg_ttl: total_ttl.checked_div(expires as u128).unwrap_or(0) as u64,
+        }
+    }
+
+    /// Walks the scheduled expirations. They are kept sorted by deadline, so the first and the
+    /// last ones are found at both ends of the set, without walking it.
+    pub fn ttl_stats(&self) -> TtlStats {
+        let now = self.now;
+        let time_left =
+            |&(expires_at, _): &(Instant, Key)| expires_at.saturating_duration_since(now);
+
+        let mut stats = TtlStats {
+            scheduled: self.ttls.len(),
+            next_expiration: self.ttls.first().map(time_left),
+            last_expiration: self.ttls.last().map(time_left),
+            ..TtlStats::default()
+        };
+
+        for ttl in self.ttls.iter() {
+            if ttl.0 <= now {
+                stats.overdue += 1;
+            }
+            let left = time_left(ttl);
+            let bucket = TTL_HISTOGRAM_BOUNDS
+                .iter()
+                .position(|(_, bound)| left < *bound)
+                .unwrap_or(TTL_HISTOGRAM_BOUNDS.len());
+            stats.histogram[bucket] += 1;
+        }
+
+        stats
+    }
+
+    /// The keys with a time to live left between `min` and `max`, both included, in the order
+    /// they expire, with the time they have left at `State::now`.
+    ///
+    /// The scheduled expirations are sorted by deadline, so only the keys within the range are
+    /// walked.
+    pub fn expiring_between(
+        &self,
+        min: Duration,
+        max: Duration,
+    ) -> impl Iterator<Item = (&Key, Duration)> {
+        let now = self.now;
+        let from = Bound::Included((now + min, Key::new()));
+
+        self.ttls
+            .range((from, Bound::Unbounded))
+            .take_while(move |(expires_at, _)| *expires_at <= now + max)
+            .map(move |(expires_at, key)| (key, expires_at.saturating_duration_since(now)))
+    }
+
+    /// How many keys fit before the keyspace grows, see `KvEngine::capacity`.
+    pub fn capacity(&self) -> Option<usize> {
+        self.keys.capacity()
+    }
+
+    /// Reallocates the keyspace to hold at least `capacity` keys, see `KvEngine::rehash`.
+    pub fn rehash(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        self.keys.rehash(capacity)
+    }
+
+    pub fn iter_values(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.keys.iter()
     }
 }
 
+/// The position of `key` in the order SCAN walks keys in, see `State::scan`. Never 0, the cursor
+/// ending an iteration.
+fn scan_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish().max(1)
+}
+
 async fn remove_expired_keys(store: Arc<InnerStore>) {
     loop {
         let next_expiration = store.remove_expired_keys();
+        let next_purge = store.purge_tombstones();
 
-        if let Some(next_expiration) = next_expiration {
+        if let Some(next_wake) = next_expiration.into_iter().chain(next_purge).min() {
             tokio::select! {
-                _ = sleep_until(next_expiration) => {}
+                _ = sleep_until(next_wake) => {}
                 _ = store.waker.notified() => {}
             }
         } else {
@@ -208,6 +1148,125 @@ mod tests {
     use tokio::time;
     use tokio::time::Duration;
 
+    #[tokio::test]
+    async fn sample_keys() {
+        let store = Store::new();
+        let mut state = store.lock();
+
+        assert!(state.sample_keys(3, None).is_empty());
+
+        for i in 0..100 {
+            state.set(format!("key{}", i), Bytes::from("value"));
+        }
+        // Overwriting a key doesn't add it again, removing one moves the last key in its place.
+        state.set(String::from("key0"), Bytes::from("other"));
+        for i in (0..100).step_by(3) {
+            state.remove(&format!("key{}", i));
+        }
+        state.delete("key1");
+
+        let mut expected: Vec<String> = state.keys().cloned().collect();
+        expected.sort();
+        let mut all = state.sample_keys(1000, None);
+        all.sort();
+        assert_eq!(all, expected);
+
+        for _ in 0..100 {
+            let mut sample = state.sample_keys(10, Some("STRING"));
+            assert_eq!(sample.len(), 10);
+            assert!(sample.iter().all(|key| state.exists(key)));
+            sample.sort();
+            sample.dedup();
+            assert_eq!(sample.len(), 10);
+        }
+
+        assert!(state.sample_keys(10, Some("list")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_many() {
+        time::pause();
+
+        let store = Store::new();
+        let value = |data: &'static str, ttl: Option<u64>| NewValue {
+            data: Bytes::from(data),
+            ttl: ttl.map(Duration::from_secs),
+        };
+
+        store.set2("key1".to_string(), value("value1", Some(100)));
+        // Let the task removing expired keys go to sleep until `key1` expires.
+        time::sleep(Duration::from_millis(1)).await;
+
+        store.set_many([
+            ("key1".to_string(), value("value1", None)),
+            ("key2".to_string(), value("value2", Some(2))),
+            ("key3".to_string(), value("value3", Some(1))),
+        ]);
+
+        {
+            let state = store.lock();
+            assert_eq!(state.size(), 3);
+            assert_eq!(state.get("key1"), Some(Bytes::from("value1")));
+            assert_eq!(state.get_value("key1").unwrap().expires_at, None);
+            assert_eq!(state.ttls.len(), 2);
+        }
+
+        // The task was woken up by the batch, otherwise it would sleep until `key1` expired.
+        time::advance(Duration::from_secs(1)).await;
+        time::sleep(Duration::from_millis(1)).await;
+        assert!(!store.lock().exists("key3"));
+
+        time::advance(Duration::from_secs(1)).await;
+        time::sleep(Duration::from_millis(1)).await;
+        assert_eq!(store.lock().keys().collect::<Vec<_>>(), ["key1"]);
+    }
+
+    #[tokio::test]
+    async fn expire_wakeups_are_coalesced() {
+        time::pause();
+
+        let set = |store: &Store, key: usize, ttl: Duration| {
+            let value = NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(ttl),
+            };
+            store.set2(format!("key{}", key), value);
+        };
+        let shorter_ttls = |store: &Store| {
+            for i in 0..100 {
+                set(
+                    store,
+                    i,
+                    Duration::from_secs(10) - Duration::from_micros(i as u64),
+                );
+            }
+        };
+
+        // The keys all expire within the default threshold, only the first one wakes up the task.
+        let store = Store::new();
+        shorter_ttls(&store);
+        assert_eq!(store.stats.expire_wakeups(), 1);
+
+        // A key expiring well before the others does wake it up, and is removed in time.
+        set(&store, 100, Duration::from_secs(1));
+        assert_eq!(store.stats.expire_wakeups(), 2);
+        time::advance(Duration::from_secs(1)).await;
+        time::sleep(Duration::from_millis(1)).await;
+        assert!(!store.lock().exists("key100"));
+
+        // The coalesced keys are removed when the task wakes up, at most the threshold late.
+        time::advance(Duration::from_secs(9)).await;
+        time::sleep(Duration::from_millis(1)).await;
+        assert_eq!(store.lock().size(), 0);
+
+        let store = Store::with_config(Config {
+            expire_wakeup_threshold: 0,
+            ..Config::default()
+        });
+        shorter_ttls(&store);
+        assert_eq!(store.stats.expire_wakeups(), 100);
+    }
+
     #[tokio::test]
     async fn ttl() {
         time::pause();
@@ -237,6 +1296,7 @@ mod tests {
 
         assert_eq!(store.lock().keys().count(), 1);
         assert!(store.lock().exists("key2"));
+        assert_eq!(store.stats.expired_keys(), 1);
 
         time::advance(Duration::from_secs(20)).await;
         time::sleep(Duration::from_millis(1)).await;
@@ -256,4 +1316,436 @@ mod tests {
         time::sleep(Duration::from_millis(1)).await;
         assert_eq!(store.lock().keys().count(), 0);
     }
+
+    #[tokio::test]
+    async fn overwrite_discards_ttl() {
+        time::pause();
+
+        let store = Store::new();
+
+        store.set2(
+            "key1".to_string(),
+            NewValue {
+                data: Bytes::from("value1"),
+                ttl: Some(Duration::from_secs(10)),
+            },
+        );
+        store.lock().set("key1".to_string(), Bytes::from("value2"));
+
+        time::advance(Duration::from_secs(10)).await;
+        time::sleep(Duration::from_millis(1)).await;
+
+        assert_eq!(store.lock().get("key1"), Some(Bytes::from("value2")));
+    }
+
+    #[tokio::test]
+    async fn update_value_keeps_ttl() {
+        let store = Store::new();
+
+        store.set2(
+            "key1".to_string(),
+            NewValue {
+                data: Bytes::from("value1"),
+                ttl: Some(Duration::from_secs(10)),
+            },
+        );
+        let expires_at = store.lock().get_value("key1").unwrap().expires_at;
+
+        let mut state = store.lock();
+
+        state.update_value("key1".to_string(), Bytes::from("value2"));
+        assert_eq!(state.get("key1"), Some(Bytes::from("value2")));
+        assert_eq!(state.get_value("key1").unwrap().expires_at, expires_at);
+
+        state.update_value("key2".to_string(), Bytes::from("value2"));
+        assert_eq!(state.get_value("key2").unwrap().expires_at, None);
+
+        state.set("key1".to_string(), Bytes::from("value3"));
+        assert_eq!(state.get_value("key1").unwrap().expires_at, None);
+    }
+
+    #[tokio::test]
+    async fn incr_by_keeps_ttl() {
+        let store = Store::new();
+
+        store.set2(
+            "key1".to_string(),
+            NewValue {
+                data: Bytes::from("1"),
+                ttl: Some(Duration::from_secs(10)),
+            },
+        );
+
+        assert_eq!(store.incr_by("key1", 1).unwrap(), 2);
+        assert!(store.lock().get_value("key1").unwrap().expires_at.is_some());
+
+        assert_eq!(store.incr_by("key2", 1).unwrap(), 1);
+        assert_eq!(store.lock().get_value("key2").unwrap().expires_at, None);
+    }
+
+    #[tokio::test]
+    async fn versions() {
+        let store = Store::new();
+        let mut state = store.lock();
+
+        assert_eq!(state.version("key"), 0);
+
+        state.set("key".to_string(), Bytes::from("1"));
+        let created = state.version("key");
+        assert!(created > 0);
+
+        state.set_ttl("key", Some(Duration::from_secs(10)));
+        let expiring = state.version("key");
+        assert!(expiring > created);
+
+        // A key deleted and created again doesn't get a version it had before.
+        state.remove("key");
+        assert_eq!(state.version("key"), 0);
+        state.set("key".to_string(), Bytes::from("1"));
+        assert!(state.version("key") > expiring);
+    }
+
+    #[tokio::test]
+    async fn compare_and_set() {
+        let store = Store::new();
+        let mut state = store.lock();
+
+        let version = state
+            .compare_and_set("key".to_string(), 0, Bytes::from("1"))
+            .unwrap();
+        assert_eq!(state.version("key"), version);
+        assert_eq!(
+            state.compare_and_set("key".to_string(), 0, Bytes::from("2")),
+            Err(version)
+        );
+
+        state.set_ttl("key", Some(Duration::from_secs(10)));
+        let version = state.version("key");
+        let updated = state
+            .compare_and_set("key".to_string(), version, Bytes::from("2"))
+            .unwrap();
+        assert!(updated > version);
+        assert_eq!(state.get("key"), Some(Bytes::from("2")));
+        assert!(state.get_value("key").unwrap().expires_at.is_some());
+
+        assert_eq!(
+            state.compare_and_set("key".to_string(), version, Bytes::from("3")),
+            Err(updated)
+        );
+        assert_eq!(state.get("key"), Some(Bytes::from("2")));
+    }
+
+    #[tokio::test]
+    async fn compare_and_exec() {
+        let store = Store::new();
+        store.lock().set("key".to_string(), Bytes::from("1"));
+        let expected = vec![
+            ("key".to_string(), store.lock().version("key")),
+            ("missing".to_string(), 0),
+        ];
+
+        // No command is executed meanwhile.
+        let res = store.compare_and_exec(&expected, || {
+            assert!(store.commands.try_read().is_err());
+            store.lock().set("key".to_string(), Bytes::from("2"));
+        });
+        assert_eq!(res, Some(()));
+        assert!(store.commands.try_read().is_ok());
+
+        assert_eq!(store.compare_and_exec(&expected, || unreachable!()), None);
+        assert_eq!(store.lock().get("key"), Some(Bytes::from("2")));
+    }
+
+    #[test]
+    fn numbers() {
+        for valid in [
+            "0",
+            "1",
+            "-1",
+            "9223372036854775807",
+            "-9223372036854775808",
+        ] {
+            assert_eq!(
+                <i64 as Number>::parse(valid),
+                valid.parse().ok(),
+                "{}",
+                valid
+            );
+        }
+        for invalid in [
+            "",
+            "-",
+            "-0",
+            "+1",
+            "01",
+            " 1",
+            "1 ",
+            "1.0",
+            "9223372036854775808",
+        ] {
+            assert_eq!(<i64 as Number>::parse(invalid), None, "{}", invalid);
+        }
+        assert_eq!(i64::MAX.add(-1), Ok(i64::MAX - 1));
+        assert_eq!(
+            i64::MAX.add(1),
+            Err("increment or decrement would overflow")
+        );
+
+        assert_eq!(<f64 as Number>::parse("1.5e3"), Some(1500.0));
+        assert_eq!(<f64 as Number>::parse("-inf"), Some(f64::NEG_INFINITY));
+        assert_eq!(<f64 as Number>::parse("nan"), None);
+        assert_eq!(<f64 as Number>::parse("1,5"), None);
+        assert_eq!(1.5.add(1.0), Ok(2.5));
+        assert_eq!(
+            f64::MAX.add(f64::MAX),
+            Err("increment would produce NaN or Infinity")
+        );
+    }
+
+    #[tokio::test]
+    async fn incr_by_errors() {
+        let store = Store::new();
+        store.lock().set("text".to_string(), Bytes::from("one"));
+        store
+            .lock()
+            .set("max".to_string(), Bytes::from(i64::MAX.to_string()));
+
+        for (key, err) in [
+            ("text", "ERR value is not an integer or out of range"),
+            ("max", "ERR increment or decrement would overflow"),
+        ] {
+            assert_eq!(store.incr_by(key, 1).unwrap_err().to_string(), err);
+        }
+        assert_eq!(
+            store.incr_by("text", 1.0).unwrap_err().to_string(),
+            "ERR value is not a valid float"
+        );
+        assert_eq!(
+            store.incr_by("max", f64::INFINITY).unwrap_err().to_string(),
+            "ERR increment would produce NaN or Infinity"
+        );
+
+        // Failed increments leave the value untouched.
+        assert_eq!(store.lock().get("text"), Some(Bytes::from("one")));
+        assert_eq!(
+            store.lock().get("max"),
+            Some(Bytes::from(i64::MAX.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn hashes() {
+        const WRONG_TYPE: &str =
+            "WRONGTYPE Operation against a key holding the wrong kind of value";
+        let store = Store::new();
+        let mut state = store.lock();
+        let fields = |fields: &[&str]| -> Vec<(String, Bytes)> {
+            fields
+                .iter()
+                .map(|field| (field.to_string(), Bytes::from("value")))
+                .collect()
+        };
+
+        assert_eq!(
+            state
+                .hset("user".to_string(), fields(&["a", "b"]))
+                .map_err(|err| err.to_string()),
+            Ok(2)
+        );
+        assert_eq!(
+            state
+                .hset("user".to_string(), fields(&["b", "c"]))
+                .map_err(|err| err.to_string()),
+            Ok(1)
+        );
+        assert_eq!(state.type_of("user"), Some(HASH));
+        assert_eq!(state.get_hash("user").unwrap().map(HashMap::len), Some(3));
+        assert_eq!(state.get_hash("missing").unwrap(), None);
+
+        // Strings and hashes don't mix.
+        state.set("text".to_string(), Bytes::from("value"));
+        assert_eq!(
+            state
+                .hset("text".to_string(), fields(&["a"]))
+                .map_err(|err| err.to_string()),
+            Err(WRONG_TYPE.to_string())
+        );
+        assert_eq!(
+            state
+                .hdel("text", &["a".to_string()])
+                .map_err(|err| err.to_string()),
+            Err(WRONG_TYPE.to_string())
+        );
+        assert_eq!(state.get_hash("text").unwrap_err().to_string(), WRONG_TYPE);
+        assert_eq!(state.get("user"), None);
+        assert_eq!(
+            state.check_type("user", STRING).unwrap_err().to_string(),
+            WRONG_TYPE
+        );
+        assert!(state.check_type("missing", STRING).is_ok());
+
+        let fields: Vec<String> = ["a", "b", "d"].map(String::from).to_vec();
+        assert_eq!(
+            state.hdel("user", &fields).map_err(|err| err.to_string()),
+            Ok(2)
+        );
+        assert_eq!(
+            state
+                .hdel("user", &["c".to_string()])
+                .map_err(|err| err.to_string()),
+            Ok(1)
+        );
+        assert!(!state.exists("user"));
+        assert_eq!(
+            state
+                .hdel("user", &["c".to_string()])
+                .map_err(|err| err.to_string()),
+            Ok(0)
+        );
+
+        // SET replaces a hash like any other value.
+        state
+            .hset("user".to_string(), [("a".to_string(), Bytes::new())])
+            .unwrap();
+        state.set("user".to_string(), Bytes::from("value"));
+        assert_eq!(state.type_of("user"), Some(STRING));
+        drop(state);
+
+        store
+            .lock()
+            .hset("hash".to_string(), [("a".to_string(), Bytes::from("1"))])
+            .unwrap();
+        assert_eq!(
+            store.incr_by("hash", 1).unwrap_err().to_string(),
+            WRONG_TYPE
+        );
+    }
+
+    /// Time stands still for a command holding the lock: a key expiring meanwhile is removed
+    /// once the lock is taken again, by the next command.
+    #[tokio::test(start_paused = true)]
+    #[allow(clippy::await_holding_lock)]
+    async fn expiration_waits_for_the_lock() {
+        let store = Store::new();
+        // The task removing expired keys waits once it found no key to expire, and isn't woken
+        // up by `State::set2`, so it can't contend for the lock held below.
+        tokio::task::yield_now().await;
+        store.lock().set2(
+            "key".to_string(),
+            NewValue {
+                data: Bytes::from("value"),
+                ttl: Some(Duration::from_millis(100)),
+            },
+        );
+
+        {
+            let state = store.lock();
+            let now = state.now();
+
+            time::advance(Duration::from_millis(100)).await;
+
+            assert_eq!(state.now(), now);
+            assert_eq!(state.get("key"), Some(Bytes::from("value")));
+            assert_eq!(store.stats.expired_keys(), 0);
+        }
+
+        let state = store.lock();
+        assert_eq!(state.now(), Instant::now());
+        assert_eq!(state.get("key"), None);
+        assert_eq!(state.next_expiration(), None);
+        assert_eq!(store.stats.expired_keys(), 1);
+    }
+
+    #[tokio::test]
+    async fn tombstones() {
+        time::pause();
+
+        let store = Store::with_config(Config {
+            tombstone_grace_period: 10_000,
+            ..Config::default()
+        });
+
+        store.lock().set("key1".to_string(), Bytes::from("value1"));
+        store.lock().set("key2".to_string(), Bytes::from("value2"));
+        assert!(store.lock().delete("key1"));
+        assert!(store.lock().delete("key2"));
+        assert!(!store.lock().delete("key3"));
+        store.notify_tombstones_added();
+
+        assert_eq!(store.lock().size(), 0);
+        assert_eq!(store.lock().get("key1"), None);
+        assert_eq!(store.lock().tombstones().count(), 2);
+
+        // Writing a deleted key again discards its tombstone.
+        store.lock().set("key2".to_string(), Bytes::from("value3"));
+        assert_eq!(store.lock().tombstones().count(), 1);
+
+        time::advance(Duration::from_secs(10)).await;
+        time::sleep(Duration::from_millis(1)).await;
+
+        assert_eq!(store.lock().tombstones().count(), 0);
+        assert_eq!(store.lock().get("key2"), Some(Bytes::from("value3")));
+    }
+
+    #[tokio::test]
+    async fn keyspace_stats() {
+        time::pause();
+
+        let store = Store::new();
+        assert_eq!(store.lock().keyspace_stats(), KeyspaceStats::default());
+
+        store.lock().set("key1".to_string(), Bytes::from("value1"));
+        for (key, secs) in [("key2", 10), ("key3", 20)] {
+            store.set2(
+                key.to_string(),
+                NewValue {
+                    data: Bytes::from("value"),
+                    ttl: Some(Duration::from_secs(secs)),
+                },
+            );
+        }
+
+        assert_eq!(
+            store.lock().keyspace_stats(),
+            KeyspaceStats {
+                keys: 3,
+                expires: 2,
+                avg_ttl: 15_000,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_without_tombstones() {
+        let store = Store::new();
+
+        store.lock().set("key1".to_string(), Bytes::from("value1"));
+        assert!(store.lock().delete("key1"));
+        assert_eq!(store.lock().tombstones().count(), 0);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn compression() {
+        let store = Store::with_config(Config {
+            value_compression_threshold: 64,
+            ..Config::default()
+        });
+        let large = Bytes::from("abc".repeat(100));
+        let small = Bytes::from("abc");
+
+        store.lock().set("large".to_string(), large.clone());
+        store.lock().set("small".to_string(), small.clone());
+
+        let state = store.lock();
+        assert!(state.get_value("large").unwrap().is_compressed());
+        assert!(!state.get_value("small").unwrap().is_compressed());
+        assert_eq!(state.get("large"), Some(large.clone()));
+        assert_eq!(state.get("small"), Some(small));
+
+        let stats = state.compression_stats();
+        assert_eq!(stats.values, 1);
+        assert!(stats.bytes < large.len());
+        assert_eq!(stats.original_bytes, large.len());
+    }
 }
@@ -0,0 +1,289 @@
+use crate::commands::Command;
+use crate::error::{ErrorKind, RustdisError};
+use crate::frame::Frame;
+
+/// A command queued by a transaction, executed by EXEC.
+#[derive(Debug, PartialEq)]
+pub enum Queued {
+    /// A command to execute, with the id of the request it was received in, see
+    /// `Connection::next_request_id`, and the frame it was received as when the journal is
+    /// enabled.
+    Command {
+        command: Command,
+        request_id: u64,
+        frame: Option<Frame>,
+    },
+    /// A known command whose arguments are invalid, like a syntax error in its options. It is
+    /// replied to with this error in the reply of EXEC, where Redis reports such errors too.
+    Error(Frame),
+}
+
+/// The transaction a client started with MULTI, queuing its commands until EXEC or DISCARD.
+///
+/// Like Redis, errors are reported in two tiers:
+///
+/// * A command rejected when it is queued, because it is unknown, has a wrong number of arguments
+///   or can't be executed while the dataset loads, is replied to with its error right away and
+///   aborts the transaction: EXEC then discards every command with an EXECABORT error.
+/// * A command that fails once executed, including one with invalid arguments, is replied to with
+///   its error in the reply of EXEC, and the other commands are executed regardless.
+///
+/// A transaction is also discarded by EXEC, replying with a null array, when a key the client
+/// watches was modified since it watched it, see `Watched`.
+///
+/// The commands of a transaction are executed in a row, no command of another connection is
+/// executed in between, see `InnerStore::compare_and_exec`.
+///
+/// **NOTE**: keys may still expire in between, Redis freezes the clock for the whole transaction.
+///
+/// Ref: <https://redis.io/docs/latest/develop/interactions/transactions/>
+#[derive(Debug, Default, PartialEq)]
+pub enum Transaction {
+    #[default]
+    Inactive,
+    Queuing {
+        commands: Vec<Queued>,
+        aborted: bool,
+    },
+}
+
+impl Transaction {
+    pub fn is_active(&self) -> bool {
+        matches!(self, Transaction::Queuing { .. })
+    }
+
+    /// Starts a transaction, on MULTI. Transactions can't be nested.
+    pub fn begin(&mut self) -> Frame {
+        if self.is_active() {
+            return error("MULTI calls can not be nested");
+        }
+
+        *self = Transaction::Queuing {
+            commands: vec![],
+            aborted: false,
+        };
+        Frame::Simple("OK".to_string())
+    }
+
+    /// Queues `queued` to be executed by EXEC, returning the reply to the command.
+    pub fn queue(&mut self, queued: Queued) -> Frame {
+        if let Transaction::Queuing { commands, .. } = self {
+            commands.push(queued);
+        }
+        Frame::Simple("QUEUED".to_string())
+    }
+
+    /// Marks the transaction as aborted after a command was rejected when queued. Does nothing
+    /// outside of a transaction.
+    pub fn abort(&mut self) {
+        if let Transaction::Queuing { aborted, .. } = self {
+            *aborted = true;
+        }
+    }
+
+    /// Ends the transaction without executing its commands, on DISCARD.
+    pub fn discard(&mut self) -> Frame {
+        match std::mem::take(self) {
+            Transaction::Inactive => error("DISCARD without MULTI"),
+            Transaction::Queuing { .. } => Frame::Simple("OK".to_string()),
+        }
+    }
+
+    /// Ends the transaction, on EXEC, returning the commands to execute, or the reply to EXEC
+    /// when there are none to execute because the transaction was aborted, a watched key was
+    /// `modified`, or the transaction never started.
+    pub fn exec(&mut self, modified: bool) -> Result<Vec<Queued>, Frame> {
+        match std::mem::take(self) {
+            Transaction::Inactive => Err(error("EXEC without MULTI")),
+            Transaction::Queuing { aborted: true, .. } => Err(Frame::Error(
+                RustdisError::command(
+                    ErrorKind::ExecAbort,
+                    "Transaction discarded because of previous errors.",
+                )
+                .to_string(),
+            )),
+            Transaction::Queuing { .. } if modified => Err(Frame::NullArray),
+            Transaction::Queuing { commands, .. } => Ok(commands),
+        }
+    }
+}
+
+/// The keys a client watches with WATCH, each with the version it had then, see
+/// `State::watch_version`. EXEC discards the transaction when any of them was written since, including
+/// when it expired or was deleted, which is how clients implement optimistic locking: watch the
+/// keys, read them, and write them in a transaction that only executes when nobody wrote them in
+/// between.
+///
+/// The keys are watched until EXEC or DISCARD end a transaction, or until UNWATCH.
+///
+/// Ref: <https://redis.io/docs/latest/develop/interactions/transactions/#optimistic-locking-using-check-and-set>
+#[derive(Debug, Default, PartialEq)]
+pub struct Watched {
+    keys: Vec<(String, u64)>,
+}
+
+impl Watched {
+    /// Watches `key`, whose version is currently `version`. Watching a key again keeps the
+    /// version it had when first watched.
+    pub fn watch(&mut self, key: String, version: u64) {
+        if !self.keys.iter().any(|(watched, _)| *watched == key) {
+            self.keys.push((key, version));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// The watched keys, each with the version it had when watched, as
+    /// `InnerStore::compare_and_exec` expects them.
+    pub fn versions(&self) -> &[(String, u64)] {
+        &self.keys
+    }
+}
+
+fn error(message: &str) -> Frame {
+    Frame::Error(RustdisError::command(ErrorKind::Err, message).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::commands::ping::Ping;
+    use crate::store::Store;
+
+    fn ping() -> Queued {
+        Queued::Command {
+            command: Command::Ping(Ping { payload: None }),
+            request_id: 1,
+            frame: None,
+        }
+    }
+
+    #[test]
+    fn exec() {
+        let mut transaction = Transaction::default();
+        assert!(!transaction.is_active());
+
+        assert_eq!(transaction.begin(), Frame::Simple("OK".to_string()));
+        assert!(transaction.is_active());
+        assert_eq!(
+            transaction.begin(),
+            Frame::Error("ERR MULTI calls can not be nested".to_string())
+        );
+
+        let invalid = || Queued::Error(Frame::Error("ERR syntax error".to_string()));
+        assert_eq!(
+            transaction.queue(ping()),
+            Frame::Simple("QUEUED".to_string())
+        );
+        assert_eq!(
+            transaction.queue(invalid()),
+            Frame::Simple("QUEUED".to_string())
+        );
+
+        assert_eq!(transaction.exec(false), Ok(vec![ping(), invalid()]));
+        assert!(!transaction.is_active());
+        assert_eq!(
+            transaction.exec(false),
+            Err(Frame::Error("ERR EXEC without MULTI".to_string()))
+        );
+    }
+
+    #[test]
+    fn aborted() {
+        let mut transaction = Transaction::default();
+
+        // Outside of a transaction there is nothing to abort.
+        transaction.abort();
+        assert_eq!(transaction, Transaction::Inactive);
+
+        transaction.begin();
+        transaction.queue(ping());
+        transaction.abort();
+        transaction.queue(ping());
+
+        assert_eq!(
+            transaction.exec(false),
+            Err(Frame::Error(
+                "EXECABORT Transaction discarded because of previous errors.".to_string()
+            ))
+        );
+        assert!(!transaction.is_active());
+    }
+
+    #[test]
+    fn discard() {
+        let mut transaction = Transaction::default();
+        assert_eq!(
+            transaction.discard(),
+            Frame::Error("ERR DISCARD without MULTI".to_string())
+        );
+
+        transaction.begin();
+        transaction.queue(ping());
+        transaction.abort();
+        assert_eq!(transaction.discard(), Frame::Simple("OK".to_string()));
+
+        // The next transaction starts over, it is neither aborted nor holds the discarded commands.
+        transaction.begin();
+        assert_eq!(transaction.exec(false), Ok(vec![]));
+    }
+
+    #[test]
+    fn modified() {
+        let mut transaction = Transaction::default();
+        assert_eq!(
+            transaction.exec(true),
+            Err(Frame::Error("ERR EXEC without MULTI".to_string()))
+        );
+
+        transaction.begin();
+        transaction.queue(ping());
+        assert_eq!(transaction.exec(true), Err(Frame::NullArray));
+        assert!(!transaction.is_active());
+    }
+
+    #[tokio::test]
+    async fn watched() {
+        let store = Store::new();
+        let mut state = store.lock();
+        let mut watched = Watched::default();
+
+        state.set("key".to_string(), Bytes::from("1"));
+        watched.watch("key".to_string(), state.watch_version("key"));
+        watched.watch("missing".to_string(), state.watch_version("missing"));
+        assert!(state.versions_match(watched.versions()));
+
+        // Setting the same value is a write too.
+        state.set("key".to_string(), Bytes::from("1"));
+        assert!(!state.versions_match(watched.versions()));
+
+        // Watching the key again keeps the version it was first watched with.
+        watched.watch("key".to_string(), state.watch_version("key"));
+        assert!(!state.versions_match(watched.versions()));
+
+        watched.clear();
+        watched.watch("key".to_string(), state.watch_version("key"));
+        watched.watch("missing".to_string(), state.watch_version("missing"));
+        assert!(state.versions_match(watched.versions()));
+
+        state.set("missing".to_string(), Bytes::from("1"));
+        assert!(!state.versions_match(watched.versions()));
+
+        watched.clear();
+        watched.watch("key".to_string(), state.watch_version("key"));
+        state.remove("key");
+        assert!(!state.versions_match(watched.versions()));
+
+        // A missing key written and removed again was modified too.
+        state.remove("missing");
+        watched.clear();
+        watched.watch("missing".to_string(), state.watch_version("missing"));
+        state.set("missing".to_string(), Bytes::from("1"));
+        state.remove("missing");
+        assert!(!state.versions_match(watched.versions()));
+    }
+}
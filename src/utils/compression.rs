@@ -0,0 +1,67 @@
+// Transparent compression of large values, enabled with the `compression` feature.
+//
+// Values are compressed with LZ4, prefixed with their decompressed length
+// as a little endian u32.
+//
+// Reference: https://github.com/PSeitz/lz4_flex
+
+/// compress(data) returns the compressed data, or None when compression
+/// is not enabled or would not make the data any smaller.
+#[cfg(feature = "compression")]
+pub fn compress(data: &[u8]) -> Option<Vec<u8>> {
+    // The prepended length is a u32, larger values are kept as they are.
+    if u32::try_from(data.len()).is_err() {
+        return None;
+    }
+    let compressed = lz4_flex::compress_prepend_size(data);
+    (compressed.len() < data.len()).then_some(compressed)
+}
+
+#[cfg(not(feature = "compression"))]
+pub fn compress(_data: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+/// decompress(data) returns the original data of a value compressed with
+/// compress.
+#[cfg(feature = "compression")]
+pub fn decompress(data: &[u8]) -> Vec<u8> {
+    lz4_flex::decompress_size_prepended(data).expect("stored values are valid LZ4 blocks")
+}
+
+#[cfg(not(feature = "compression"))]
+pub fn decompress(_data: &[u8]) -> Vec<u8> {
+    unreachable!("values are only compressed with the compression feature")
+}
+
+/// decompressed_len(data) returns the length of the original data of a
+/// value compressed with compress, without decompressing it.
+pub fn decompressed_len(data: &[u8]) -> usize {
+    let mut len = [0; 4];
+    len.copy_from_slice(&data[..4]);
+    u32::from_le_bytes(len) as usize
+}
+
+/// Whether values can be compressed at all.
+pub const ENABLED: bool = cfg!(feature = "compression");
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let data = "abc".repeat(100);
+
+        let compressed = compress(data.as_bytes()).unwrap();
+
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompressed_len(&compressed), data.len());
+        assert_eq!(decompress(&compressed), data.as_bytes());
+    }
+
+    #[test]
+    fn test_incompressible() {
+        assert_eq!(compress(b"abc"), None);
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+use thiserror::Error as ThisError;
+
+/// A point in time after which a long running command gives up.
+///
+/// Commands run while holding the store lock, so a command that takes too long stalls every other
+/// client. Algorithms that can run for a long time, like [`super::lcs::lcs`], check the deadline
+/// now and then and bail out once it has passed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Deadline {
+    at: Option<Instant>,
+    limit: Duration,
+}
+
+#[derive(Debug, ThisError, PartialEq)]
+#[error("command exceeded the time limit of {} ms", .limit.as_millis())]
+pub struct DeadlineExceeded {
+    pub limit: Duration,
+}
+
+impl Deadline {
+    /// A deadline `limit` from now, or no deadline at all when `limit` is `None`.
+    pub fn after(limit: Option<Duration>) -> Deadline {
+        match limit {
+            Some(limit) => Deadline {
+                at: Some(Instant::now() + limit),
+                limit,
+            },
+            None => Deadline::default(),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), DeadlineExceeded> {
+        match self.at {
+            Some(at) if Instant::now() >= at => Err(DeadlineExceeded { limit: self.limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check() {
+        assert_eq!(Deadline::after(None).check(), Ok(()));
+        assert_eq!(
+            Deadline::after(Some(Duration::from_secs(60))).check(),
+            Ok(())
+        );
+        assert_eq!(
+            Deadline::after(Some(Duration::ZERO)).check(),
+            Err(DeadlineExceeded {
+                limit: Duration::ZERO
+            })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// Deterministic digests of the keyspace, as computed by DEBUG DIGEST.
+//
+// Reference: http://www.isthe.com/chongo/tech/comp/fnv/
+
+use bytes::Bytes;
+use std::collections::HashMap;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// fnv1a(digest, bytes) feeds bytes into a running 64-bit FNV-1a digest.
+fn fnv1a(mut digest: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        digest ^= *byte as u64;
+        digest = digest.wrapping_mul(FNV_PRIME);
+    }
+    digest
+}
+
+/// digest_value(value) returns the digest of a single value.
+pub fn digest_value(value: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, value)
+}
+
+/// digest_key(key, value) returns the digest of a key along with its
+/// value. The length of the key is part of it, so moving bytes between
+/// the key and the value changes the digest.
+pub fn digest_key(key: &str, value: &[u8]) -> u64 {
+    let digest = fnv1a(FNV_OFFSET_BASIS, &(key.len() as u64).to_le_bytes());
+    let digest = fnv1a(digest, key.as_bytes());
+    fnv1a(digest, value)
+}
+
+/// digest_hash(hash) combines the digests of every field of a hash along
+/// with its value with XOR, so it doesn't depend on the iteration order
+/// either.
+pub fn digest_hash(hash: &HashMap<String, Bytes>) -> u64 {
+    hash.iter().fold(0, |digest, (field, value)| {
+        digest ^ digest_key(field, value)
+    })
+}
+
+/// digest_keyspace(entries) combines the digests of every key with XOR,
+/// like Redis does, so the result does not depend on the iteration order.
+/// An empty keyspace has a digest of 0.
+pub fn digest_keyspace<'a, V: AsRef<[u8]>>(entries: impl Iterator<Item = (&'a String, V)>) -> u64 {
+    entries.fold(0, |digest, (key, value)| {
+        digest ^ digest_key(key, value.as_ref())
+    })
+}
+
+/// to_hex(digest) formats a digest the way DEBUG DIGEST replies with it.
+pub fn to_hex(digest: u64) -> String {
+    format!("{:016x}", digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_keyspace() {
+        let (k1, k2) = (String::from("key1"), String::from("key2"));
+
+        assert_eq!(digest_keyspace(std::iter::empty::<(&String, &[u8])>()), 0);
+
+        let a = digest_keyspace([(&k1, &b"a"[..]), (&k2, &b"b"[..])].into_iter());
+        let b = digest_keyspace([(&k2, &b"b"[..]), (&k1, &b"a"[..])].into_iter());
+        let c = digest_keyspace([(&k1, &b"b"[..]), (&k2, &b"a"[..])].into_iter());
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_digest_hash() {
+        let hash = |fields: &[(&str, &str)]| -> HashMap<String, Bytes> {
+            fields
+                .iter()
+                .map(|(field, value)| (field.to_string(), Bytes::from(value.to_string())))
+                .collect()
+        };
+
+        assert_eq!(digest_hash(&hash(&[])), 0);
+        assert_ne!(
+            digest_hash(&hash(&[("a", "1"), ("b", "2")])),
+            digest_hash(&hash(&[("a", "2"), ("b", "1")]))
+        );
+    }
+
+    #[test]
+    fn test_digest_key() {
+        assert_ne!(digest_key("ab", b"c"), digest_key("a", b"bc"));
+        assert_eq!(to_hex(0), "0000000000000000");
+    }
+}
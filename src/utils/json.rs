@@ -0,0 +1,233 @@
+// JSON documents stored in string keys, enabled with the `json` feature.
+//
+// Documents are stored as their JSON text, so they remain plain strings to every other command.
+// Paths are a small subset of JSONPath: `$` is the document itself, followed by any number of
+// `.field` and `[index]` steps, like `$.users[0].name`.
+//
+// Reference: https://redis.io/docs/latest/develop/data-types/json/path/
+
+/// One step of a `Path`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Field(String),
+    Index(usize),
+}
+
+/// A path within a JSON document, see `Path::parse`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub steps: Vec<Step>,
+}
+
+impl Path {
+    /// The document itself.
+    pub fn root() -> Path {
+        Path { steps: vec![] }
+    }
+
+    /// Parses a path like `$.users[0].name`, returning `None` when it is invalid.
+    pub fn parse(path: &str) -> Option<Path> {
+        let mut rest = path.strip_prefix('$')?;
+        let mut steps = vec![];
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end == 0 {
+                    return None;
+                }
+                steps.push(Step::Field(after[..end].to_string()));
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']')?;
+                steps.push(Step::Index(after[..end].parse().ok()?));
+                rest = &after[end + 1..];
+            } else {
+                return None;
+            }
+        }
+
+        Some(Path { steps })
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// The error message when JSON documents are not supported.
+pub const DISABLED: &str = "rustdis was built without the json feature";
+
+/// get(document, path) returns the JSON text of the value at `path` in
+/// `document`, or None when there is no value there.
+#[cfg(feature = "json")]
+pub fn get(document: &[u8], path: &Path) -> Result<Option<String>, String> {
+    let document = parse(document, "existing value is not a JSON document")?;
+
+    Ok(find(&document, path).map(|value| value.to_string()))
+}
+
+#[cfg(not(feature = "json"))]
+pub fn get(_document: &[u8], _path: &Path) -> Result<Option<String>, String> {
+    Err(DISABLED.to_string())
+}
+
+/// set(document, path, value) returns `document` with the value at `path`
+/// replaced by the JSON text `value`, or None when the parent of `path`
+/// doesn't exist. A missing field of an object is added, a missing index of
+/// an array is not. Without a document, only the root can be set.
+#[cfg(feature = "json")]
+pub fn set(document: Option<&[u8]>, path: &Path, value: &[u8]) -> Result<Option<String>, String> {
+    let value = parse(value, "invalid JSON value")?;
+    let Some(document) = document else {
+        if !path.is_root() {
+            return Err("new documents must be created at the root".to_string());
+        }
+        return Ok(Some(value.to_string()));
+    };
+    let mut document = parse(document, "existing value is not a JSON document")?;
+
+    let Some((last, parent)) = path.steps.split_last() else {
+        return Ok(Some(value.to_string()));
+    };
+    let parent = Path {
+        steps: parent.to_vec(),
+    };
+    let target = match (find_mut(&mut document, &parent), last) {
+        (Some(serde_json::Value::Object(object)), Step::Field(field)) => object
+            .entry(field.clone())
+            .or_insert(serde_json::Value::Null),
+        (Some(serde_json::Value::Array(array)), Step::Index(index)) => {
+            match array.get_mut(*index) {
+                Some(target) => target,
+                None => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+    *target = value;
+
+    Ok(Some(document.to_string()))
+}
+
+#[cfg(not(feature = "json"))]
+pub fn set(
+    _document: Option<&[u8]>,
+    _path: &Path,
+    _value: &[u8],
+) -> Result<Option<String>, String> {
+    Err(DISABLED.to_string())
+}
+
+#[cfg(feature = "json")]
+fn parse(text: &[u8], message: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_slice(text).map_err(|_| message.to_string())
+}
+
+#[cfg(feature = "json")]
+fn find<'a>(document: &'a serde_json::Value, path: &Path) -> Option<&'a serde_json::Value> {
+    path.steps
+        .iter()
+        .try_fold(document, |value, step| match step {
+            Step::Field(field) => value.get(field),
+            Step::Index(index) => value.get(index),
+        })
+}
+
+#[cfg(feature = "json")]
+fn find_mut<'a>(
+    document: &'a mut serde_json::Value,
+    path: &Path,
+) -> Option<&'a mut serde_json::Value> {
+    path.steps
+        .iter()
+        .try_fold(document, |value, step| match step {
+            Step::Field(field) => value.get_mut(field),
+            Step::Index(index) => value.get_mut(index),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_path() {
+        assert_eq!(Path::parse("$"), Some(Path::root()));
+        assert_eq!(
+            Path::parse("$.users[10].name"),
+            Some(Path {
+                steps: vec![
+                    Step::Field("users".to_string()),
+                    Step::Index(10),
+                    Step::Field("name".to_string()),
+                ]
+            })
+        );
+        for invalid in [
+            "", "users", "$.", "$..name", "$[x]", "$[-1]", "$[0", "$name",
+        ] {
+            assert_eq!(Path::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn get_and_set() {
+        let path = |path: &str| Path::parse(path).unwrap();
+        let document = br#"{"users":[{"name":"ana"}],"count":1}"#;
+
+        assert_eq!(
+            get(document, &path("$.users[0].name")),
+            Ok(Some(r#""ana""#.to_string()))
+        );
+        assert_eq!(get(document, &path("$.users[1]")), Ok(None));
+        assert_eq!(get(document, &path("$.count.value")), Ok(None));
+
+        assert_eq!(
+            set(Some(document), &path("$.users[0].age"), b"30"),
+            Ok(Some(
+                r#"{"count":1,"users":[{"age":30,"name":"ana"}]}"#.to_string()
+            ))
+        );
+        assert_eq!(
+            set(Some(document), &path("$.count"), b"[1, 2]"),
+            Ok(Some(
+                r#"{"count":[1,2],"users":[{"name":"ana"}]}"#.to_string()
+            ))
+        );
+        assert_eq!(set(Some(document), &path("$.users[1]"), b"{}"), Ok(None));
+        assert_eq!(
+            set(Some(document), &path("$.missing.field"), b"1"),
+            Ok(None)
+        );
+        assert_eq!(
+            set(None, &Path::root(), br#" {"a": true} "#),
+            Ok(Some(r#"{"a":true}"#.to_string()))
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn invalid_json() {
+        assert_eq!(
+            get(b"plain text", &Path::root()),
+            Err("existing value is not a JSON document".to_string())
+        );
+        assert_eq!(
+            set(Some(b"{}"), &Path::root(), b"{"),
+            Err("invalid JSON value".to_string())
+        );
+        assert_eq!(
+            set(None, &Path::parse("$.a").unwrap(), b"1"),
+            Err("new documents must be created at the root".to_string())
+        );
+    }
+
+    #[cfg(not(feature = "json"))]
+    #[test]
+    fn disabled() {
+        assert_eq!(get(b"{}", &Path::root()), Err(DISABLED.to_string()));
+        assert_eq!(set(None, &Path::root(), b"{}"), Err(DISABLED.to_string()));
+    }
+}
@@ -0,0 +1,72 @@
+// Hash slots of keys, as computed by Redis Cluster.
+//
+// A key belongs to one of 16384 slots, the CRC16 of the key modulo 16384. When the key holds a
+// non-empty hash tag, the part between the first `{` and the next `}`, only the tag is hashed,
+// so keys sharing a tag land in the same slot.
+//
+// Reference: https://redis.io/docs/latest/operate/oss_and_stack/reference/cluster-spec/#key-distribution-model
+
+/// The number of hash slots of a cluster.
+pub const SLOTS: u16 = 16384;
+
+/// crc16(bytes) returns the CRC16-CCITT (XMODEM) checksum of bytes, the variant Redis uses:
+/// polynomial 0x1021, initial value 0, no reflection.
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |mut crc: u16, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// key_slot(key) returns the hash slot of key, hashing only its hash tag when it has one.
+pub fn key_slot(key: &[u8]) -> u16 {
+    let hashed = key
+        .iter()
+        .position(|byte| *byte == b'{')
+        .and_then(|open| {
+            let tag = &key[open + 1..];
+            let close = tag.iter().position(|byte| *byte == b'}')?;
+            // An empty tag, like in `{}key`, is no tag: the whole key is hashed.
+            (close > 0).then(|| &tag[..close])
+        })
+        .unwrap_or(key);
+
+    crc16(hashed) % SLOTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        // The check value of CRC16-XMODEM, also used by the Redis Cluster specification.
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        assert_eq!(crc16(b""), 0);
+    }
+
+    #[test]
+    fn slots() {
+        // Slots as replied by CLUSTER KEYSLOT on Redis.
+        assert_eq!(key_slot(b"somekey"), 11058);
+        assert_eq!(key_slot(b"foo"), 12182);
+        assert_eq!(key_slot(b""), 0);
+    }
+
+    #[test]
+    fn hash_tags() {
+        assert_eq!(key_slot(b"{user1000}.following"), key_slot(b"user1000"));
+        assert_eq!(key_slot(b"foo{bar}{zap}"), key_slot(b"bar"));
+        assert_eq!(key_slot(b"{}foo"), key_slot(b"{}foo"));
+        assert_ne!(key_slot(b"{}foo"), key_slot(b"foo"));
+        assert_eq!(key_slot(b"foo{}{bar}"), crc16(b"foo{}{bar}") % SLOTS);
+        assert_eq!(key_slot(b"foo{bar"), crc16(b"foo{bar") % SLOTS);
+    }
+}
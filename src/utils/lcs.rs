@@ -1,19 +1,25 @@
-/// Longest common subsequence via Dynamic Programming
-///
-/// Reference: https://github.com/TheAlgorithms/Rust/blob/master/src/dynamic_programming/longest_common_subsequence.rs
+// Longest common subsequence via Dynamic Programming
+//
+// Reference: https://github.com/TheAlgorithms/Rust/blob/master/src/dynamic_programming/longest_common_subsequence.rs
+
+use super::deadline::{Deadline, DeadlineExceeded};
 
 /// longest_common_subsequence(a, b) returns the longest common subsequence
 /// between the strings a and b.
-pub fn lcs(a: &str, b: &str) -> String {
+///
+/// The deadline is checked once per character of a, so a computation on
+/// large strings can be given up before it stalls the server.
+pub fn lcs(a: &str, b: &str, deadline: &Deadline) -> Result<String, DeadlineExceeded> {
     let a: Vec<_> = a.chars().collect();
     let b: Vec<_> = b.chars().collect();
     let (na, nb) = (a.len(), b.len());
 
     // solutions[i][j] is the length of the longest common subsequence
     // between a[0..i-1] and b[0..j-1]
-    let mut solutions = vec![vec![0; nb + 1]; na + 1];
+    let mut solutions = vec![vec![0u32; nb + 1]; na + 1];
 
     for (i, ci) in a.iter().enumerate() {
+        deadline.check()?;
         for (j, cj) in b.iter().enumerate() {
             // if ci == cj, there is a new common character;
             // otherwise, take the best of the two solutions
@@ -42,12 +48,46 @@ pub fn lcs(a: &str, b: &str) -> String {
     }
 
     result.reverse();
-    result.iter().collect()
+    Ok(result.iter().collect())
+}
+
+/// lcs_len(a, b) returns the length of the longest common subsequence
+/// between the strings a and b.
+///
+/// Only the length is needed, so instead of the whole table it keeps the
+/// previous and the current rows, iterating over the shorter string to
+/// use O(min(a, b)) memory.
+pub fn lcs_len(a: &str, b: &str, deadline: &Deadline) -> Result<usize, DeadlineExceeded> {
+    let a: Vec<_> = a.chars().collect();
+    let b: Vec<_> = b.chars().collect();
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut previous = vec![0; short.len() + 1];
+    let mut current = vec![0; short.len() + 1];
+
+    for cl in long.iter() {
+        deadline.check()?;
+        for (j, cs) in short.iter().enumerate() {
+            current[j + 1] = if cl == cs {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            }
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Ok(previous[short.len()])
 }
 
 #[cfg(test)]
 mod tests {
-    use super::lcs;
+    use super::*;
+    use std::time::Duration;
+
+    fn lcs(a: &str, b: &str) -> String {
+        super::lcs(a, b, &Deadline::default()).unwrap()
+    }
 
     #[test]
     fn test_longest_common_subsequence() {
@@ -69,4 +109,34 @@ mod tests {
         // unicode
         assert_eq!(&lcs("你好，世界", "再见世界"), "世界");
     }
+
+    #[test]
+    fn test_longest_common_subsequence_len() {
+        let cases = [
+            ("", ""),
+            ("", "abcd"),
+            ("abcd", "c"),
+            ("abcdefghi", "acegi"),
+            ("abcdgh", "aedfhr"),
+            ("gxtxayb", "aggtab"),
+            ("你好，世界", "再见世界"),
+        ];
+
+        for (a, b) in cases {
+            let len = lcs_len(a, b, &Deadline::default()).unwrap();
+            assert_eq!(len, lcs(a, b).chars().count(), "lcs_len({:?}, {:?})", a, b);
+        }
+    }
+
+    #[test]
+    fn test_longest_common_subsequence_past_deadline() {
+        let deadline = Deadline::after(Some(Duration::ZERO));
+
+        assert_eq!(
+            super::lcs("abcd", "abcd", &deadline),
+            Err(DeadlineExceeded {
+                limit: Duration::ZERO
+            })
+        );
+    }
 }
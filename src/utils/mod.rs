@@ -1 +1,8 @@
+pub mod compression;
+pub mod deadline;
+pub mod digest;
+pub mod json;
+pub mod keyslot;
 pub mod lcs;
+pub mod random;
+pub mod rate_limit;
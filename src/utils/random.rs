@@ -0,0 +1,57 @@
+// A small pseudo-random number generator, for sampling keys.
+//
+// Samples don't need to be unpredictable, only spread evenly, so a xorshift generator does and
+// saves a dependency. Each generator is seeded from the random keys the standard library seeds
+// hash maps with.
+//
+// Reference: https://www.jstatsoft.org/article/view/v008i14
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// A xorshift64* generator.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new() -> Rng {
+        // The state must not be zero, or the generator only ever yields zero.
+        let seed = RandomState::new().hash_one(0u64) | 1;
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// below(n) returns a number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        // The modulo bias is negligible for the sizes of a keyspace.
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Rng {
+        Rng::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn below() {
+        let mut rng = Rng::new();
+        let mut seen = [false; 10];
+        for _ in 0..1000 {
+            seen[rng.below(10)] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::{Mutex, PoisonError};
+use tokio::time::{Duration, Instant};
+
+/// Lets through at most a number of events per second, like log lines that a misbehaving client
+/// could otherwise emit for each of its requests.
+///
+/// Seconds are counted from the first event of each, and the events over the limit are counted
+/// so the next one let through can report how many were dropped.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: u64,
+    window: Mutex<Window>,
+}
+
+#[derive(Debug)]
+struct Window {
+    started_at: Option<Instant>,
+    allowed: u64,
+    suppressed: u64,
+}
+
+impl RateLimiter {
+    /// A limiter letting through `per_second` events per second, none when it is 0.
+    pub fn new(per_second: u64) -> RateLimiter {
+        RateLimiter {
+            per_second,
+            window: Mutex::new(Window {
+                started_at: None,
+                allowed: 0,
+                suppressed: 0,
+            }),
+        }
+    }
+
+    /// Returns the number of events dropped since the last one let through when an event
+    /// happening now is let through, `None` when it is over the limit.
+    pub fn check(&self) -> Option<u64> {
+        if self.per_second == 0 {
+            return None;
+        }
+
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if window
+            .started_at
+            .is_none_or(|started_at| now >= started_at + Duration::from_secs(1))
+        {
+            window.started_at = Some(now);
+            window.allowed = 0;
+        }
+
+        if window.allowed == self.per_second {
+            window.suppressed += 1;
+            return None;
+        }
+
+        window.allowed += 1;
+        Some(std::mem::take(&mut window.suppressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time;
+
+    #[tokio::test]
+    async fn check() {
+        time::pause();
+
+        let limiter = RateLimiter::new(2);
+        assert_eq!(limiter.check(), Some(0));
+        assert_eq!(limiter.check(), Some(0));
+        assert_eq!(limiter.check(), None);
+        assert_eq!(limiter.check(), None);
+
+        time::advance(Duration::from_millis(999)).await;
+        assert_eq!(limiter.check(), None);
+
+        time::advance(Duration::from_millis(1)).await;
+        assert_eq!(limiter.check(), Some(3));
+        assert_eq!(limiter.check(), Some(0));
+        assert_eq!(limiter.check(), None);
+
+        let disabled = RateLimiter::new(0);
+        assert_eq!(disabled.check(), None);
+    }
+}
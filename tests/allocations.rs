@@ -0,0 +1,109 @@
+//! Counts the allocations the server makes to process pipelined commands.
+//!
+//! Every allocation of the test binary is counted by a global allocator, so this file holds a
+//! single test: the server runs on its own single threaded runtime and the client uses blocking
+//! sockets and buffers allocated ahead, so nearly every allocation counted while the pipeline is
+//! processed is made by the server.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run;
+
+const PORT: u16 = 6372;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Sends `pipeline` and reads its replies until `replies` is full, returning how many allocations
+/// were made meanwhile.
+fn count_allocations(stream: &mut TcpStream, pipeline: &[u8], replies: &mut [u8]) -> u64 {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    stream.write_all(pipeline).unwrap();
+    stream.read_exact(replies).unwrap();
+    ALLOCATIONS.load(Ordering::SeqCst) - before
+}
+
+/// Replies to pipelined commands are serialized into a buffer reused by the connection, so
+/// replying costs no allocation of its own. The cost is measured against the same pipeline run
+/// with replies turned off with CLIENT REPLY, which parses and executes the same commands.
+#[test]
+fn test_pipelined_get_allocations() {
+    thread::spawn(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run(Config {
+            port: PORT,
+            ..Config::default()
+        }))
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).unwrap();
+    stream.set_nodelay(true).unwrap();
+
+    let value = "v".repeat(100);
+    let set = format!("*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$100\r\n{}\r\n", value);
+    let mut ok = [0; 5];
+    count_allocations(&mut stream, set.as_bytes(), &mut ok);
+    assert_eq!(&ok, b"+OK\r\n");
+
+    let commands = 10_000;
+    let gets = b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n".repeat(commands);
+
+    let reply = format!("$100\r\n{}\r\n", value);
+    let mut replies = vec![0; reply.len() * commands];
+
+    let mut silent_gets = b"*3\r\n$6\r\nCLIENT\r\n$5\r\nREPLY\r\n$3\r\nOFF\r\n".to_vec();
+    silent_gets.extend_from_slice(&gets);
+    silent_gets.extend_from_slice(b"*3\r\n$6\r\nCLIENT\r\n$5\r\nREPLY\r\n$2\r\nON\r\n");
+    let mut silent_replies = [0; 5];
+
+    // The first rounds grow the buffers of the connection to their working size.
+    count_allocations(&mut stream, &gets, &mut replies);
+    assert_eq!(replies, reply.repeat(commands).as_bytes());
+    count_allocations(&mut stream, &silent_gets, &mut silent_replies);
+    assert_eq!(&silent_replies, b"+OK\r\n");
+
+    let with_replies = count_allocations(&mut stream, &gets, &mut replies);
+    let without_replies = count_allocations(&mut stream, &silent_gets, &mut silent_replies);
+
+    let per_command = |allocations: u64| allocations as f64 / commands as f64;
+    let per_reply = per_command(with_replies.saturating_sub(without_replies));
+    println!(
+        "{} pipelined GETs: {:.2} allocations per command, {:.2} without replies, {:.2} per reply",
+        commands,
+        per_command(with_replies),
+        per_command(without_replies),
+        per_reply
+    );
+
+    assert!(per_reply < 0.1, "{:.2} allocations per reply", per_reply);
+}
@@ -0,0 +1,112 @@
+use std::future;
+use std::sync::Once;
+use std::thread;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{self, Duration};
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6368;
+
+static SERVER: Once = Once::new();
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+/// Starts the server once for every test, in a runtime of its own so it outlives the runtime of
+/// the test that happened to start it.
+async fn connect() -> TcpStream {
+    SERVER.call_once(|| {
+        thread::spawn(|| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(run_until(
+                Config {
+                    port: PORT,
+                    ..Config::default()
+                },
+                future::pending(),
+            ))
+        });
+        thread::sleep(Duration::from_millis(100));
+    });
+
+    TcpStream::connect(("127.0.0.1", PORT)).await.unwrap()
+}
+
+/// Sends `pipeline` at once and reads every byte the server sends until it closes the connection.
+async fn send_until_closed(stream: &mut TcpStream, pipeline: Vec<Vec<u8>>) -> String {
+    stream.write_all(&pipeline.concat()).await.unwrap();
+
+    let mut replies = vec![];
+    time::timeout(Duration::from_secs(5), stream.read_to_end(&mut replies))
+        .await
+        .expect("the connection is closed")
+        .unwrap();
+    String::from_utf8_lossy(&replies).into_owned()
+}
+
+/// The replies to the commands pipelined before CLIENT KILL are sent, in order, followed by its
+/// own reply, and the commands pipelined after it are not executed.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kill_self() {
+    let mut stream = connect().await;
+    let address = stream.local_addr().unwrap().to_string();
+
+    let replies = send_until_closed(
+        &mut stream,
+        vec![
+            command(&["SET", "kill:key", "1"]),
+            command(&["GET", "kill:key"]),
+            command(&["CLIENT", "KILL", "ADDR", &address]),
+            command(&["CLIENT", "KILL", "ADDR", &address, "SKIPME", "no"]),
+            command(&["SET", "kill:key", "2"]),
+        ],
+    )
+    .await;
+    assert_eq!(replies, "+OK\r\n$1\r\n1\r\n:0\r\n:1\r\n");
+
+    let mut stream = connect().await;
+    let address = stream.local_addr().unwrap().to_string();
+
+    let replies = send_until_closed(
+        &mut stream,
+        vec![
+            command(&["GET", "kill:key"]),
+            command(&["CLIENT", "KILL", &address]),
+            command(&["PING"]),
+        ],
+    )
+    .await;
+    assert_eq!(replies, "$1\r\n1\r\n+OK\r\n");
+}
+
+/// Killing the connection from within a transaction closes it once the reply to EXEC is sent.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_kill_self_in_transaction() {
+    let mut stream = connect().await;
+    let address = stream.local_addr().unwrap().to_string();
+
+    let replies = send_until_closed(
+        &mut stream,
+        vec![
+            command(&["MULTI"]),
+            command(&["CLIENT", "KILL", "SKIPME", "no", "ADDR", &address]),
+            command(&["PING"]),
+            command(&["EXEC"]),
+            command(&["PING"]),
+        ],
+    )
+    .await;
+    assert_eq!(
+        replies,
+        "+OK\r\n+QUEUED\r\n+QUEUED\r\n*2\r\n:1\r\n+PONG\r\n"
+    );
+}
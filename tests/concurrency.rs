@@ -0,0 +1,153 @@
+//! Concurrent access to the store from many tasks of a multi-threaded runtime, guarding the
+//! locking of the store against regressions.
+
+use bytes::Bytes;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tokio::time::{sleep, Duration};
+
+use rustdis::commands::executable::Executable;
+use rustdis::commands::Command;
+use rustdis::frame::Frame;
+use rustdis::store::{NewValue, Store};
+
+const TASKS: usize = 16;
+const ITERATIONS: usize = 500;
+
+fn exec(store: &Store, args: &[&str]) -> Frame {
+    let frame = Frame::Array(
+        args.iter()
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg.as_bytes())))
+            .collect(),
+    );
+    Command::try_from(frame)
+        .unwrap()
+        .exec(store.clone())
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_read_your_writes() {
+    let store = Store::new();
+    let mut tasks = JoinSet::new();
+
+    for task in 0..TASKS {
+        let store = store.clone();
+        tasks.spawn(async move {
+            let key = format!("key:{}", task);
+            for i in 0..ITERATIONS {
+                let value = format!("{}:{}", task, i);
+                exec(&store, &["SET", &key, &value]);
+                assert_eq!(
+                    exec(&store, &["GET", &key]),
+                    Frame::Bulk(Bytes::from(value))
+                );
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        res.unwrap();
+    }
+
+    assert_eq!(store.lock().size(), TASKS);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_increments() {
+    let store = Store::new();
+    let mut tasks = JoinSet::new();
+
+    for _ in 0..TASKS {
+        let store = store.clone();
+        tasks.spawn(async move {
+            for _ in 0..ITERATIONS {
+                exec(&store, &["INCR", "counter"]);
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        res.unwrap();
+    }
+
+    assert_eq!(
+        exec(&store, &["GET", "counter"]),
+        Frame::Bulk(Bytes::from((TASKS * ITERATIONS).to_string()))
+    );
+}
+
+/// Writers and deleters race on the same keys, readers must only ever see a missing key or a value
+/// that was written in full.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_set_and_del() {
+    let store = Store::new();
+    let values: Arc<Vec<String>> = Arc::new((0..TASKS).map(|i| "x".repeat(i + 1)).collect());
+    let mut tasks = JoinSet::new();
+
+    for task in 0..TASKS {
+        let store = store.clone();
+        let values = values.clone();
+        tasks.spawn(async move {
+            for i in 0..ITERATIONS {
+                let key = format!("key:{}", i % 4);
+                match task % 3 {
+                    0 => {
+                        exec(&store, &["SET", &key, &values[task]]);
+                    }
+                    1 => {
+                        exec(&store, &["DEL", &key]);
+                    }
+                    _ => match exec(&store, &["GET", &key]) {
+                        Frame::NullBulkString => {}
+                        Frame::Bulk(value) => {
+                            assert!(values.iter().any(|v| v.as_bytes() == &value[..]))
+                        }
+                        frame => panic!("unexpected reply {:?}", frame),
+                    },
+                }
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        res.unwrap();
+    }
+}
+
+/// Keys set with a time to live from many tasks are all removed by the background task, even
+/// while other tasks keep reading them.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_expiration() {
+    let store = Store::new();
+    let mut tasks = JoinSet::new();
+
+    for task in 0..TASKS {
+        let store = store.clone();
+        tasks.spawn(async move {
+            for i in 0..ITERATIONS / 10 {
+                let key = format!("key:{}:{}", task, i);
+                store.set2(
+                    key.clone(),
+                    NewValue {
+                        data: Bytes::from("value"),
+                        ttl: Some(Duration::from_millis((i % 5) as u64 * 10 + 1)),
+                    },
+                );
+                exec(&store, &["GET", &key]);
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        res.unwrap();
+    }
+
+    sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(store.lock().size(), 0);
+}
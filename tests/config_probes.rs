@@ -0,0 +1,120 @@
+//! The CONFIG GET probes clients and frameworks send when they connect, checked against a running
+//! server. Each of them fails to start, or warns, when the parameter it reads is missing from the
+//! reply.
+
+use std::collections::HashMap;
+use std::future;
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6367;
+
+static SERVER: Once = Once::new();
+
+/// Starts the server once for every test, in a runtime of its own.
+fn connect() -> redis::Connection {
+    SERVER.call_once(|| {
+        thread::spawn(|| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(run_until(
+                Config {
+                    port: PORT,
+                    ..Config::default()
+                },
+                future::pending(),
+            ))
+        });
+        thread::sleep(Duration::from_millis(100));
+    });
+
+    redis::Client::open(format!("redis://127.0.0.1:{}/", PORT))
+        .unwrap()
+        .get_connection()
+        .unwrap()
+}
+
+fn config_get(connection: &mut redis::Connection, patterns: &[&str]) -> HashMap<String, String> {
+    redis::cmd("CONFIG")
+        .arg("GET")
+        .arg(patterns)
+        .query(connection)
+        .unwrap()
+}
+
+/// Job queues, like Sidekiq and BullMQ, check that keys are never evicted, since an evicted job is
+/// a lost one.
+#[test]
+fn test_job_queue_probe() {
+    let mut connection = connect();
+
+    let config = config_get(&mut connection, &["maxmemory-policy"]);
+    assert_eq!(config["maxmemory-policy"], "noeviction");
+}
+
+/// Session stores relying on keyspace notifications, like Spring Session, read the events enabled
+/// before enabling the ones they need.
+#[test]
+fn test_keyspace_notifications_probe() {
+    let mut connection = connect();
+
+    let config = config_get(&mut connection, &["notify-keyspace-events"]);
+    assert_eq!(config["notify-keyspace-events"], "");
+}
+
+/// Monitoring exporters and GUI clients read the limits and persistence settings of the server,
+/// several of them in a single command since Redis 7.0.
+#[test]
+fn test_monitoring_probe() {
+    let mut connection = connect();
+
+    let parameters = [
+        "maxmemory",
+        "maxclients",
+        "databases",
+        "save",
+        "appendonly",
+        "timeout",
+        "tcp-keepalive",
+        "slowlog-log-slower-than",
+        "slowlog-max-len",
+        "io-threads",
+        "cluster-enabled",
+        "dir",
+        "dbfilename",
+        "port",
+        "bind",
+        "hz",
+        "loglevel",
+        "protected-mode",
+        "replica-read-only",
+        "client-output-buffer-limit",
+    ];
+    let config = config_get(&mut connection, &parameters);
+
+    for parameter in parameters {
+        assert!(config.contains_key(parameter), "{} is missing", parameter);
+    }
+    assert_eq!(config["maxmemory"], "0");
+    assert_eq!(config["databases"], "16");
+    assert_eq!(config["appendonly"], "no");
+    assert_eq!(config["port"], PORT.to_string());
+    assert_eq!(config["cluster-enabled"], "no");
+}
+
+/// Tools listing every parameter, like RedisInsight, match them with a pattern.
+#[test]
+fn test_pattern_probe() {
+    let mut connection = connect();
+
+    let config = config_get(&mut connection, &["*"]);
+    assert!(config.len() > 20, "{:?}", config);
+
+    let config = config_get(&mut connection, &["slowlog-*"]);
+    let mut names: Vec<&str> = config.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["slowlog-log-slower-than", "slowlog-max-len"]);
+}
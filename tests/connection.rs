@@ -5,6 +5,7 @@ use tokio::sync::mpsc::{self, UnboundedSender};
 
 use rustdis::connection::Connection;
 use rustdis::frame::Frame;
+use rustdis::RustdisError;
 
 async fn create_tcp_connection() -> Result<(UnboundedSender<Vec<u8>>, TcpStream), std::io::Error> {
     let listener = TcpListener::bind("127.0.0.1:0").await?;
@@ -125,7 +126,7 @@ async fn test_parse_null_bulk_string() {
     tcp_stream_tx.send(bytes.to_vec()).unwrap();
 
     let actual = connection.read_frame().await.unwrap();
-    let expected = Some(Frame::Null);
+    let expected = Some(Frame::NullBulkString);
 
     assert_eq!(actual, expected);
 }
@@ -138,8 +139,8 @@ async fn test_parse_multiple_commands_sequentially() {
 
     let simple_string = b"+OK\r\n";
     let bulk_string = b"$5\r\nhello\r\n";
-    let array_1 = b"*3\r\n$3\r\nSET\r\n$5\r\nmykey_1\r\n$7\r\nmyvalue_1\r\n";
-    let array_2 = b"*3\r\n$3\r\nSET\r\n$5\r\nmykey_2\r\n$7\r\nmyvalue_2\r\n";
+    let array_1 = b"*3\r\n$3\r\nSET\r\n$7\r\nmykey_1\r\n$9\r\nmyvalue_1\r\n";
+    let array_2 = b"*3\r\n$3\r\nSET\r\n$7\r\nmykey_2\r\n$9\r\nmyvalue_2\r\n";
     let simple_error = b"-Error message\r\n";
     let integer = b":1000\r\n";
 
@@ -189,6 +190,30 @@ async fn test_parse_multiple_commands_sequentially() {
     assert_eq!(actual, expected);
 }
 
+#[tokio::test]
+async fn test_bytes_read() {
+    let (tcp_stream_tx, tcp_stream) = create_tcp_connection().await.unwrap();
+    let peer_addr = tcp_stream.peer_addr().unwrap();
+    let mut connection = Connection::new(tcp_stream, peer_addr);
+
+    let simple_string = b"+OK\r\n";
+    let bulk_string = b"$5\r\nhello\r\n";
+
+    tcp_stream_tx.send(simple_string.to_vec()).unwrap();
+    tcp_stream_tx.send(bulk_string.to_vec()).unwrap();
+
+    assert_eq!(connection.bytes_read(), 0);
+
+    connection.read_frame().await.unwrap();
+    assert_eq!(connection.bytes_read(), simple_string.len() as u64);
+
+    connection.read_frame().await.unwrap();
+    assert_eq!(
+        connection.bytes_read(),
+        (simple_string.len() + bulk_string.len()) as u64
+    );
+}
+
 #[tokio::test]
 async fn test_parse_incomplete_frame() {
     let (tcp_stream_tx, tcp_stream) = create_tcp_connection().await.unwrap();
@@ -230,15 +255,16 @@ async fn test_max_frame_size_limit() {
 
     // Frame below limit size calculation:
     // The frame format includes a length indicator and data terminated with \r\n.
-    // For a frame just below the 1 MB limit (one_mb - 1 bytes):
-    // - Length Indicator: $1048575\r\n
+    // For a frame that fits the 1 MB limit:
+    // - Length Indicator: $1048564\r\n
     //   - $: 1 byte
-    //   - 1048575: 7 bytes (for the length)
+    //   - 1048564: 7 bytes (for the length)
     //   - \r\n: 2 bytes (CRLF)
     //   Total length indicator size: 1 + 7 + 2 = 10 bytes
-    // - Data size: To fit within the limit, the data itself should be one_mb - 1 - 10 bytes.
-    //   Since the data terminates with \r\n, the actual data size should be one_mb - 12 bytes.
-    let frame_below_limit = format!("${}\r\n{}\r\n", one_mb - 1, "A".repeat(one_mb - 12));
+    // - Data size: To fit within the limit, the data itself should be one_mb - 10 bytes.
+    //   Since the data terminates with \r\n, the actual data size should be one_mb - 12 bytes,
+    //   which is also the length the indicator declares.
+    let frame_below_limit = format!("${}\r\n{}\r\n", one_mb - 12, "A".repeat(one_mb - 12));
 
     let frame_above_limit = format!("${}\r\n{}\r\n", one_mb + 1, "A".repeat(one_mb + 1));
 
@@ -249,8 +275,9 @@ async fn test_max_frame_size_limit() {
     let frame_above_limit_result = connection.read_frame().await;
     let frame_above_limit_error = frame_above_limit_result.unwrap_err();
 
+    assert!(matches!(frame_above_limit_error, RustdisError::Protocol(_)));
     assert_eq!(
         frame_above_limit_error.to_string(),
-        "frame size exceeds limit"
+        "ERR frame size exceeds limit"
     );
 }
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{Duration, Instant};
+
+use rustdis::config::Config;
+use rustdis::server::run;
+
+const PORT: u16 = 6373;
+const SLEEP_PORT: u16 = 6363;
+
+/// Starts a server running on a single thread, so a client could only starve the others
+/// by holding it.
+fn start_single_threaded(config: Config) {
+    thread::spawn(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run(config))
+    });
+}
+
+/// A client sending a huge pipeline doesn't starve the others: with the server running on a
+/// single thread, a second client is accepted and served while the pipeline is still being
+/// processed, instead of once it is done.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_big_pipeline_fairness() {
+    start_single_threaded(Config {
+        port: PORT,
+        // Lets the whole pipeline be buffered, so it is processed without waiting on the socket.
+        read_buffer_size: 64 * 1024 * 1024,
+        ..Config::default()
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let count = 200_000;
+    let sentinel = "c0ffee";
+
+    let mut data = Vec::new();
+    for i in 0..count {
+        let key = format!("key:{}", i);
+        data.extend_from_slice(
+            format!(
+                "*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n$1\r\n1\r\n",
+                key.len(),
+                key
+            )
+            .as_bytes(),
+        );
+    }
+    data.extend_from_slice(format!("*2\r\n$4\r\nECHO\r\n$6\r\n{}\r\n", sentinel).as_bytes());
+
+    let stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    let (mut reader, mut writer) = stream.into_split();
+    let pipeline_started = Arc::new(tokio::sync::Notify::new());
+    let pipeline_done = Arc::new(AtomicBool::new(false));
+
+    let write = tokio::spawn(async move { writer.write_all(&data).await.unwrap() });
+    let read = tokio::spawn({
+        let pipeline_started = pipeline_started.clone();
+        let pipeline_done = pipeline_done.clone();
+        async move {
+            let expected_tail = format!("$6\r\n{}\r\n", sentinel);
+            let mut replies = Vec::new();
+            let mut buf = vec![0; 64 * 1024];
+            while !replies.ends_with(expected_tail.as_bytes()) {
+                let n = reader.read(&mut buf).await.unwrap();
+                assert!(n > 0, "connection closed before the sentinel was echoed");
+                if replies.is_empty() {
+                    pipeline_started.notify_one();
+                }
+                replies.extend_from_slice(&buf[..n]);
+            }
+            pipeline_done.store(true, Ordering::SeqCst);
+        }
+    });
+
+    // Wait for the first replies to the pipeline, so it is being processed.
+    pipeline_started.notified().await;
+
+    let start = Instant::now();
+    let mut other = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    other.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+    let mut reply = vec![0; 7];
+    other.read_exact(&mut reply).await.unwrap();
+    let latency = start.elapsed();
+
+    assert_eq!(reply, b"+PONG\r\n");
+    assert!(
+        !pipeline_done.load(Ordering::SeqCst),
+        "the second client was served only after the pipeline, in {:?}",
+        latency
+    );
+
+    write.await.unwrap();
+    read.await.unwrap();
+}
+
+/// DEBUG SLEEP only delays the client sending it: the replies it was sent before are not held
+/// back, and another client is served meanwhile.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_sleep_fairness() {
+    start_single_threaded(Config {
+        port: SLEEP_PORT,
+        ..Config::default()
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut sleeping = TcpStream::connect(("127.0.0.1", SLEEP_PORT)).await.unwrap();
+    let start = Instant::now();
+    sleeping
+        .write_all(b"*1\r\n$4\r\nPING\r\n*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$1\r\n1\r\n")
+        .await
+        .unwrap();
+    let mut reply = vec![0; 7];
+    sleeping.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply, b"+PONG\r\n");
+
+    let mut other = TcpStream::connect(("127.0.0.1", SLEEP_PORT)).await.unwrap();
+    other.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+    other.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply, b"+PONG\r\n");
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    let mut reply = vec![0; 5];
+    sleeping.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply, b"+OK\r\n");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
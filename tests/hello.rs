@@ -0,0 +1,86 @@
+//! Protocol negotiation with HELLO, checked on the bytes sent by a running server.
+
+use std::future;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6361;
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+/// The reply to HELLO, a map of 7 fields sent as an array of 14 elements to RESP2 clients.
+fn hello_reply(header: &str, proto: u8) -> Vec<u8> {
+    format!(
+        "{}\r\n$6\r\nserver\r\n$5\r\nredis\r\n$7\r\nversion\r\n$5\r\n7.2.4\r\n\
+         $5\r\nproto\r\n:{}\r\n$2\r\nid\r\n:0\r\n$4\r\nmode\r\n$10\r\nstandalone\r\n\
+         $4\r\nrole\r\n$6\r\nmaster\r\n$7\r\nmodules\r\n*0\r\n",
+        header, proto
+    )
+    .into_bytes()
+}
+
+async fn request(stream: &mut TcpStream, args: &[&str], expected: &[u8]) {
+    stream.write_all(&command(args)).await.unwrap();
+    let mut reply = vec![0; expected.len()];
+    stream.read_exact(&mut reply).await.unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&reply),
+        String::from_utf8_lossy(expected),
+        "{:?}",
+        args
+    );
+}
+
+/// Replies switch to RESP3 right from the reply to HELLO 3, and back to RESP2 from the reply to
+/// HELLO 2. A failed HELLO leaves the protocol as it is.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_hello_switches_protocol() {
+    tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            ..Config::default()
+        },
+        future::pending(),
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    request(&mut stream, &["GET", "missing"], b"$-1\r\n").await;
+    request(&mut stream, &["HELLO"], &hello_reply("*14", 2)).await;
+
+    request(&mut stream, &["HELLO", "3"], &hello_reply("%7", 3)).await;
+    request(&mut stream, &["GET", "missing"], b"_\r\n").await;
+    request(
+        &mut stream,
+        &["CONFIG", "GET", "maxmemory"],
+        b"%1\r\n$9\r\nmaxmemory\r\n$1\r\n0\r\n",
+    )
+    .await;
+    request(
+        &mut stream,
+        &["HELLO", "4"],
+        b"-NOPROTO unsupported protocol version\r\n",
+    )
+    .await;
+    request(&mut stream, &["GET", "missing"], b"_\r\n").await;
+
+    request(&mut stream, &["HELLO", "2"], &hello_reply("*14", 2)).await;
+    request(
+        &mut stream,
+        &["CONFIG", "GET", "maxmemory"],
+        b"*2\r\n$9\r\nmaxmemory\r\n$1\r\n0\r\n",
+    )
+    .await;
+}
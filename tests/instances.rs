@@ -0,0 +1,81 @@
+//! Tests running two rustdis servers in-process, with `support::instance`.
+//!
+//! There is no replication, REPLICAOF is not supported, so the servers are checked to keep
+//! keyspaces of their own, and to converge when one is seeded with the export of the other, the
+//! way a replica is after a full resynchronization.
+
+use std::time::Duration;
+
+use redis::Connection;
+
+use rustdis::dump;
+
+mod support;
+
+use support::instance::Instance;
+use support::Snapshot;
+
+const PRIMARY_PORT: u16 = 6364;
+const SECONDARY_PORT: u16 = 6365;
+
+/// Writes keys spread over the servers' keyspace, with and without a time to live, overwriting
+/// and deleting some of them, in a pseudo random order that only depends on `seed`.
+fn workload(connection: &mut Connection, seed: u64) {
+    let mut state = seed;
+    let mut next = move || {
+        // xorshift64, good enough to shuffle a workload.
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..500 {
+        let key = format!("key:{}", next() % 100);
+        match next() % 4 {
+            0 => redis::cmd("DEL").arg(&key).query::<()>(connection),
+            1 => redis::cmd("SET")
+                .arg(&key)
+                .arg(next())
+                .arg("EX")
+                .arg(1000)
+                .query::<()>(connection),
+            _ => redis::cmd("SET")
+                .arg(&key)
+                .arg(next())
+                .query::<()>(connection),
+        }
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_instances_converge() {
+    let mut primary = Instance::start(PRIMARY_PORT);
+    let secondary = Instance::start(SECONDARY_PORT);
+
+    let mut connection = primary.connection();
+    workload(&mut connection, 42);
+
+    // A restart disconnects every client, the keyspace is kept.
+    let before = Snapshot::capture(&mut connection, "*");
+    primary.restart();
+    assert!(redis::cmd("PING").query::<String>(&mut connection).is_err());
+    let mut connection = primary.connection();
+    workload(&mut connection, 7);
+
+    // The servers don't share anything until the secondary is seeded.
+    let mut replica = secondary.connection();
+    assert_eq!(Snapshot::capture(&mut replica, "*"), Snapshot::default());
+
+    let export = dump::export(primary.store());
+    dump::import(secondary.store(), &export).unwrap();
+
+    let primary_snapshot = Snapshot::capture(&mut connection, "*");
+    let secondary_snapshot = Snapshot::capture(&mut replica, "*");
+    let diff = primary_snapshot.diff(&secondary_snapshot, Duration::from_secs(1));
+    assert!(diff.is_empty(), "the servers diverged:\n{}", diff);
+    assert!(!before
+        .diff(&primary_snapshot, Duration::from_secs(1))
+        .is_empty());
+}
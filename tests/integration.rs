@@ -1,23 +1,129 @@
+use std::collections::HashSet;
+use std::sync::{Once, OnceLock};
+use std::thread;
+
 use redis::Connection;
 use redis::RedisError;
 use redis::Value;
+use rustdis::config::Config;
 use rustdis::server::run;
 
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
+
+mod support;
+
+use support::target::{self, Version};
+use support::Snapshot;
+
+const PORT: u16 = 6378;
+
+static SERVER: Once = Once::new();
+
+static THEIR_VERSION: OnceLock<Version> = OnceLock::new();
+
+/// Starts the server once for the whole test binary, in a runtime of its own so it outlives the
+/// runtime of the test that happened to start it.
+fn start_server() {
+    SERVER.call_once(|| {
+        thread::spawn(|| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(run(Config {
+                port: PORT,
+                ..Config::default()
+            }))
+        });
+        thread::sleep(Duration::from_millis(100));
+    });
+}
 
 async fn connect() -> Result<(Connection, Connection), RedisError> {
-    tokio::spawn(async { run(6378).await });
-    sleep(Duration::from_millis(100)).await;
+    start_server();
 
-    let our_client = redis::Client::open("redis://127.0.0.1:6378/")?;
+    let our_client = redis::Client::open(format!("redis://127.0.0.1:{}/", PORT))?;
     let our_connection = our_client.get_connection()?;
 
-    let thir_client = redis::Client::open("redis://127.0.0.1:6379/")?;
+    let thir_client = redis::Client::open(target::url())?;
     let their_connection = thir_client.get_connection()?;
 
     Ok((our_connection, their_connection))
 }
 
+/// The version of the Redis server rustdis is compared against.
+async fn their_version() -> Version {
+    if let Some(version) = THEIR_VERSION.get() {
+        return *version;
+    }
+
+    let (_, mut their_connection) = connect().await.unwrap();
+    *THEIR_VERSION.get_or_init(|| Version::of(&mut their_connection))
+}
+
+/// Whether the Redis server rustdis is compared against supports `feature`, added in `since`.
+/// Tests return early when it doesn't, there is nothing to compare.
+async fn supports(feature: &str, since: Version) -> bool {
+    let version = their_version().await;
+    if version < since {
+        eprintln!(
+            "skipped: {} requires Redis {}, comparing against {}",
+            feature, since, version
+        );
+        return false;
+    }
+    true
+}
+
+/// Runs `pipeline` against both servers and asserts they reply the same.
+async fn test_compare(pipeline: &redis::Pipeline) {
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    let our_response: Value = pipeline.query(&mut our_connection).unwrap();
+    let their_response: Value = pipeline.query(&mut their_connection).unwrap();
+
+    assert_eq!(our_response, their_response);
+}
+
+/// Runs each command against both servers, after `setup`, and asserts they reply with the same
+/// value or the same error. Unlike a pipeline, an error doesn't hide the replies to the others.
+async fn test_compare_err(setup: &redis::Pipeline, commands: &[redis::Cmd]) {
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    let _: () = setup.query(&mut our_connection).unwrap();
+    let _: () = setup.query(&mut their_connection).unwrap();
+
+    for cmd in commands {
+        let ours: Result<Value, String> = cmd.query(&mut our_connection).map_err(|e| e.to_string());
+        let theirs: Result<Value, String> =
+            cmd.query(&mut their_connection).map_err(|e| e.to_string());
+
+        let packed = cmd.get_packed_command();
+        assert_eq!(ours, theirs, "{}", String::from_utf8_lossy(&packed));
+    }
+}
+
+/// Runs `pipeline` against both servers and asserts they reply the same and end up with the same
+/// keys matching `pattern`, printing which keys differ otherwise.
+async fn test_compare_keyspace(pipeline: &redis::Pipeline, pattern: &str) {
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    let our_response: Value = pipeline.query(&mut our_connection).unwrap();
+    let their_response: Value = pipeline.query(&mut their_connection).unwrap();
+    assert_eq!(our_response, their_response);
+
+    let ours = Snapshot::capture(&mut our_connection, pattern);
+    let theirs = Snapshot::capture(&mut their_connection, pattern);
+    let diff = theirs.diff(&ours, Duration::from_secs(1));
+    assert!(diff.is_empty(), "keyspace differs from Redis':\n{}", diff);
+}
+
+/// rustdis reports the version it targets, and the suite compares it with one it can target.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_target_version() {
+    let (mut our_connection, _) = connect().await.unwrap();
+
+    assert_eq!(Version::of(&mut our_connection), target::TARGET);
+    assert!(their_version().await <= target::TARGET);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_set_and_get() {
     let (mut our_connection, mut their_connection) = connect().await.unwrap();
@@ -37,3 +143,498 @@ async fn test_set_and_get() {
 
     assert_eq!(our_response, their_response);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ping() {
+    let mut pipeline = redis::pipe();
+
+    pipeline.cmd("PING");
+    pipeline.cmd("PING").arg("hello");
+    pipeline.cmd("PING").arg("");
+
+    test_compare(&pipeline).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_echo() {
+    let mut pipeline = redis::pipe();
+
+    pipeline.cmd("ECHO").arg("hello");
+    pipeline.cmd("ECHO").arg("");
+
+    test_compare(&pipeline).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wait() {
+    let mut pipeline = redis::pipe();
+
+    pipeline.cmd("WAIT").arg(0).arg(0);
+
+    test_compare(&pipeline).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_options_keyspace() {
+    let mut pipeline = redis::pipe();
+
+    // Previous runs may have left the keys behind.
+    pipeline
+        .cmd("DEL")
+        .arg("options:1")
+        .arg("options:2")
+        .arg("options:3")
+        .ignore();
+    pipeline
+        .cmd("SET")
+        .arg("options:1")
+        .arg("a")
+        .arg("EX")
+        .arg(100);
+    pipeline
+        .cmd("SET")
+        .arg("options:2")
+        .arg("b")
+        .arg("PX")
+        .arg(50_000);
+    pipeline.cmd("SET").arg("options:2").arg("c").arg("KEEPTTL");
+    pipeline.cmd("SET").arg("options:3").arg("d").arg("NX");
+    pipeline.cmd("SET").arg("options:3").arg("e").arg("NX");
+    pipeline.cmd("GETEX").arg("options:1").arg("PERSIST");
+    pipeline.cmd("GETEX").arg("options:3").arg("EX").arg(10);
+
+    test_compare_keyspace(&pipeline, "options:*").await;
+}
+
+/// INCR, DECR, INCRBY, DECRBY and INCRBYFLOAT reject values and arguments that aren't numbers,
+/// and results that can't be stored, with the same errors.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_incr_errors() {
+    let mut setup = redis::pipe();
+    setup
+        .cmd("SET")
+        .arg("incr:text")
+        .arg("one")
+        .ignore()
+        .cmd("SET")
+        .arg("incr:padded")
+        .arg("007")
+        .ignore()
+        .cmd("SET")
+        .arg("incr:max")
+        .arg(i64::MAX)
+        .ignore()
+        .cmd("SET")
+        .arg("incr:min")
+        .arg(i64::MIN)
+        .ignore();
+
+    let mut commands = vec![];
+    for key in ["incr:text", "incr:padded", "incr:max", "incr:min"] {
+        commands.push(redis::cmd("INCR").arg(key).clone());
+        commands.push(redis::cmd("DECR").arg(key).clone());
+        commands.push(redis::cmd("INCRBY").arg(key).arg(1).clone());
+        commands.push(redis::cmd("DECRBY").arg(key).arg(1).clone());
+    }
+    for increment in ["ten", "1.5", "+1", "99999999999999999999"] {
+        commands.push(redis::cmd("INCRBY").arg("incr:new").arg(increment).clone());
+        commands.push(redis::cmd("DECRBY").arg("incr:new").arg(increment).clone());
+    }
+    // Before 7.0, Redis negates the decrement without checking it overflows.
+    if their_version().await >= Version::new(7, 0, 0) {
+        commands.push(redis::cmd("DECRBY").arg("incr:new").arg(i64::MIN).clone());
+    }
+    commands.push(redis::cmd("INCRBYFLOAT").arg("incr:text").arg(1).clone());
+    commands.push(redis::cmd("INCRBYFLOAT").arg("incr:new").arg("ten").clone());
+    commands.push(redis::cmd("INCRBYFLOAT").arg("incr:new").arg("inf").clone());
+    commands.push(redis::cmd("EXISTS").arg("incr:new").clone());
+
+    test_compare_err(&setup, &commands).await;
+}
+
+/// SELECT rejects indexes out of range and arguments that aren't integers with the same errors,
+/// and the writes around it, in a transaction too, still reach the database selected.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_select_errors() {
+    let mut setup = redis::pipe();
+    setup.cmd("SET").arg("select:key").arg(1).ignore();
+
+    let mut commands = vec![];
+    for index in ["16", "-1", "one", "1.5", "+1", "01", "99999999999999999999"] {
+        commands.push(redis::cmd("SELECT").arg(index).clone());
+        commands.push(redis::cmd("INCR").arg("select:key").clone());
+    }
+    commands.push(redis::cmd("MULTI").clone());
+    commands.push(redis::cmd("SELECT").arg(16).clone());
+    commands.push(redis::cmd("INCR").arg("select:key").clone());
+    commands.push(redis::cmd("EXEC").clone());
+    commands.push(redis::cmd("GET").arg("select:key").clone());
+
+    test_compare_err(&setup, &commands).await;
+}
+
+/// Pipelines interleaving SELECT with writes leave the same keyspace.
+///
+/// Only the database 0 is selected, rustdis has a single keyspace for every database.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_select_pipeline() {
+    let mut pipeline = redis::pipe();
+    pipeline
+        .cmd("SELECT")
+        .arg(0)
+        .cmd("SET")
+        .arg("select:pipeline")
+        .arg(1)
+        .cmd("SELECT")
+        .arg("0")
+        .cmd("INCR")
+        .arg("select:pipeline")
+        .cmd("SELECT")
+        .arg(0)
+        .cmd("SET")
+        .arg("select:other")
+        .arg("value")
+        .arg("EX")
+        .arg(100)
+        .cmd("GET")
+        .arg("select:pipeline");
+
+    test_compare_keyspace(&pipeline, "select:*").await;
+}
+
+/// Hash commands leave the same keyspace, and the keys they remove or expire are gone the same.
+///
+/// HGETALL, HKEYS and HVALS are left out, Redis replies with the fields in no particular order.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_hash_keyspace() {
+    let mut pipeline = redis::pipe();
+    pipeline
+        .cmd("DEL")
+        .arg(&["hash:user", "hash:gone", "hash:expiring"])
+        .cmd("HSET")
+        .arg("hash:user")
+        .arg(&["name", "ana", "age", "30"])
+        .cmd("HSET")
+        .arg("hash:user")
+        .arg(&["age", "31", "city", "rome"])
+        .cmd("HGET")
+        .arg("hash:user")
+        .arg("age")
+        .cmd("HGET")
+        .arg("hash:user")
+        .arg("missing")
+        .cmd("HEXISTS")
+        .arg("hash:user")
+        .arg("city")
+        .cmd("HLEN")
+        .arg("hash:user")
+        .cmd("HSETNX")
+        .arg("hash:user")
+        .arg(&["name", "bob"])
+        .cmd("HSETNX")
+        .arg("hash:user")
+        .arg(&["nick", "ani"])
+        .cmd("HMGET")
+        .arg("hash:user")
+        .arg(&["name", "missing", "nick"])
+        .cmd("HMGET")
+        .arg("hash:gone")
+        .arg(&["field"])
+        .cmd("HSTRLEN")
+        .arg("hash:user")
+        .arg("name")
+        .cmd("HDEL")
+        .arg("hash:user")
+        .arg(&["city", "missing"])
+        .cmd("HSET")
+        .arg("hash:gone")
+        .arg(&["field", "value"])
+        .cmd("HDEL")
+        .arg("hash:gone")
+        .arg("field")
+        .cmd("EXISTS")
+        .arg("hash:gone")
+        .cmd("HSET")
+        .arg("hash:expiring")
+        .arg(&["field", "value"])
+        .cmd("EXPIRE")
+        .arg("hash:expiring")
+        .arg(100)
+        .cmd("HSET")
+        .arg("hash:expiring")
+        .arg(&["other", "value"])
+        .cmd("TYPE")
+        .arg("hash:user");
+
+    test_compare_keyspace(&pipeline, "hash:*").await;
+}
+
+/// Commands on a key holding the other type reply with the same WRONGTYPE error.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_hash_wrong_type() {
+    let mut setup = redis::pipe();
+    setup
+        .cmd("DEL")
+        .arg(&["hash:string", "hash:hash"])
+        .ignore()
+        .cmd("SET")
+        .arg("hash:string")
+        .arg("value")
+        .ignore()
+        .cmd("HSET")
+        .arg("hash:hash")
+        .arg(&["field", "value"])
+        .ignore();
+
+    let commands = [
+        redis::cmd("HSET")
+            .arg("hash:string")
+            .arg(&["field", "value"])
+            .clone(),
+        redis::cmd("HGET").arg("hash:string").arg("field").clone(),
+        redis::cmd("HDEL").arg("hash:string").arg("field").clone(),
+        redis::cmd("HLEN").arg("hash:string").clone(),
+        redis::cmd("HGETALL").arg("hash:string").clone(),
+        redis::cmd("HSETNX")
+            .arg("hash:string")
+            .arg(&["field", "value"])
+            .clone(),
+        redis::cmd("HMGET").arg("hash:string").arg("field").clone(),
+        redis::cmd("HSTRLEN")
+            .arg("hash:string")
+            .arg("field")
+            .clone(),
+        redis::cmd("GET").arg("hash:hash").clone(),
+        redis::cmd("APPEND").arg("hash:hash").arg("value").clone(),
+        redis::cmd("INCR").arg("hash:hash").clone(),
+        redis::cmd("STRLEN").arg("hash:hash").clone(),
+        redis::cmd("HSET")
+            .arg("hash:hash")
+            .arg(&["field", "value", "other"])
+            .clone(),
+    ];
+
+    test_compare_err(&setup, &commands).await;
+}
+
+/// Iterates the whole keyspace with SCAN, until the server replies with the cursor 0, running
+/// `between_calls` before every call after the first one.
+fn scan_all(
+    connection: &mut Connection,
+    pattern: &str,
+    mut between_calls: impl FnMut(&mut Connection),
+) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    let mut cursor = 0;
+
+    loop {
+        let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(100)
+            .query(connection)
+            .unwrap();
+        keys.extend(batch);
+
+        if next_cursor == 0 {
+            return keys;
+        }
+        cursor = next_cursor;
+        between_calls(connection);
+    }
+}
+
+/// Sets `count` keys named `{prefix}{i}` in a single pipeline.
+fn populate(connection: &mut Connection, prefix: &str, count: usize) {
+    let mut pipeline = redis::pipe();
+    for i in 0..count {
+        pipeline
+            .cmd("SET")
+            .arg(format!("{}{}", prefix, i))
+            .arg(i)
+            .ignore();
+    }
+    let _: () = pipeline.query(connection).unwrap();
+}
+
+/// Glob patterns made of `*`, `?` and character ranges, generated from a fixed seed so failures
+/// can be reproduced.
+fn scan_patterns(prefix: &str, count: usize) -> Vec<String> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |bound: u64| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % bound
+    };
+
+    (0..count)
+        .map(|_| {
+            let (a, b) = (next(10), next(10));
+            let pattern = match next(6) {
+                0 => format!("{}*", a),
+                1 => format!("*{}", a),
+                2 => format!("*{}{}*", a, b),
+                3 => format!("?{}*", a),
+                4 => format!("[{}-{}]*", a.min(b), a.max(b)),
+                _ => format!("{}?", a),
+            };
+            format!("{}{}", prefix, pattern)
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scan_full_iteration() {
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    let prefix = "scan:full:";
+    populate(&mut our_connection, prefix, 20_000);
+    populate(&mut their_connection, prefix, 20_000);
+
+    let mut patterns = scan_patterns(prefix, 20);
+    patterns.push(format!("{}*", prefix));
+
+    for pattern in patterns {
+        let our_keys = scan_all(&mut our_connection, &pattern, |_| {});
+        let their_keys = scan_all(&mut their_connection, &pattern, |_| {});
+
+        assert_eq!(our_keys, their_keys, "SCAN MATCH {}", pattern);
+    }
+}
+
+/// Keys present during the whole iteration are returned even when other keys are added and
+/// removed between SCAN calls, and the iteration ends.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scan_with_interleaved_writes() {
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    let prefix = "scan:stress:";
+    let stable = format!("{}stable:", prefix);
+    let removed = format!("{}removed:", prefix);
+    let expected: HashSet<String> = (0..10_000).map(|i| format!("{}{}", stable, i)).collect();
+
+    for connection in [&mut our_connection, &mut their_connection] {
+        populate(connection, &stable, 10_000);
+        populate(connection, &removed, 10_000);
+
+        let mut call = 0;
+        let keys = scan_all(connection, &format!("{}*", prefix), |connection| {
+            let mut pipeline = redis::pipe();
+            for i in call * 100..(call + 1) * 100 {
+                pipeline
+                    .cmd("SET")
+                    .arg(format!("{}added:{}", prefix, i))
+                    .arg(i)
+                    .ignore();
+                pipeline
+                    .cmd("DEL")
+                    .arg(format!("{}{}", removed, i))
+                    .ignore();
+            }
+            let _: () = pipeline.query(connection).unwrap();
+            call += 1;
+        });
+
+        let stable_keys: HashSet<String> = keys
+            .into_iter()
+            .filter(|key| key.starts_with(&stable))
+            .collect();
+        assert_eq!(stable_keys, expected);
+    }
+}
+
+/// Every NX, XX, GT and LT combination behaves the same on EXPIRE, PEXPIRE, EXPIREAT and
+/// PEXPIREAT, against keys without and with a timeout.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expire_flags() {
+    if !supports("EXPIRE NX, XX, GT and LT", Version::new(7, 0, 0)).await {
+        return;
+    }
+
+    let unix_now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let commands = [
+        ("EXPIRE", 100),
+        ("PEXPIRE", 100_000),
+        ("EXPIREAT", unix_now + 100),
+        ("PEXPIREAT", (unix_now + 100) * 1000),
+    ];
+    let options: [&[&str]; 7] = [
+        &[],
+        &["NX"],
+        &["XX"],
+        &["GT"],
+        &["LT"],
+        &["XX", "GT"],
+        &["XX", "LT"],
+    ];
+
+    let mut pipeline = redis::pipe();
+    for (command, time) in commands {
+        for options in options {
+            // Without a timeout, with an earlier one and with a later one.
+            for current in [None, Some(50), Some(200)] {
+                pipeline.cmd("SET").arg("expire:key").arg(1);
+                if let Some(current) = current {
+                    pipeline.cmd("EXPIRE").arg("expire:key").arg(current);
+                }
+                pipeline
+                    .cmd(command)
+                    .arg("expire:key")
+                    .arg(time)
+                    .arg(options);
+            }
+        }
+
+        pipeline.cmd(command).arg("expire:missing").arg(time);
+        pipeline.cmd(command).arg("expire:key").arg(-1);
+        pipeline.cmd("EXISTS").arg("expire:key");
+    }
+
+    test_compare(&pipeline).await;
+}
+
+/// EXPIRE, PEXPIRE, EXPIREAT and PEXPIREAT reject invalid options and times with the same errors.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expire_errors() {
+    if !supports("EXPIRE NX, XX, GT and LT", Version::new(7, 0, 0)).await {
+        return;
+    }
+
+    let (mut our_connection, mut their_connection) = connect().await.unwrap();
+
+    for command in ["EXPIRE", "PEXPIRE", "EXPIREAT", "PEXPIREAT"] {
+        for connection in [&mut our_connection, &mut their_connection] {
+            let _: () = redis::cmd("SET")
+                .arg("expire:key")
+                .arg(1)
+                .query(connection)
+                .unwrap();
+        }
+
+        let cases: [&[&str]; 5] = [
+            &["10", "NX", "XX"],
+            &["10", "GT", "LT"],
+            &["10", "ZZ"],
+            &["soon"],
+            &["9223372036854775807"],
+        ];
+        for args in cases {
+            let mut cmd = redis::cmd(command);
+            cmd.arg("expire:key").arg(args);
+
+            let ours: Result<Value, String> =
+                cmd.query(&mut our_connection).map_err(|e| e.to_string());
+            let theirs: Result<Value, String> =
+                cmd.query(&mut their_connection).map_err(|e| e.to_string());
+
+            assert_eq!(ours, theirs, "{} {:?}", command, args);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::future;
+
+use bytes::Bytes;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+
+use rustdis::config::Config;
+use rustdis::server::serve;
+use rustdis::store::Store;
+
+const PORT: u16 = 6370;
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+/// The journal holds the commands executed by every connection, in order, but not the ones
+/// rejected before being executed. Each one keeps the id of the request it was received in, which
+/// counts every frame received on its connection.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_journal() {
+    let store = Store::with_config(Config {
+        port: PORT,
+        journal: true,
+        ..Config::default()
+    });
+    tokio::spawn(serve(store.clone(), future::pending()));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    let pipeline = [
+        command(&["SET", "user:1", "alice", "EX", "60"]),
+        command(&["GET", "user:1"]),
+        command(&["NOPE"]),
+        command(&["GET"]),
+        command(&["DEL", "user:1"]),
+    ]
+    .concat();
+    stream.write_all(&pipeline).await.unwrap();
+
+    // Read until the reply to DEL, the last command.
+    let mut replies = vec![];
+    while !replies.ends_with(b":1\r\n") {
+        let read = timeout(Duration::from_secs(5), stream.read_buf(&mut replies))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(read > 0, "connection closed");
+    }
+    let replies = String::from_utf8(replies).unwrap();
+    assert!(
+        replies.starts_with("+OK\r\n$5\r\nalice\r\n-ERR"),
+        "{}",
+        replies
+    );
+    assert_eq!(replies.matches("-ERR").count(), 2, "{}", replies);
+
+    let journal = store.journal().unwrap();
+    let commands: Vec<(&str, Vec<Bytes>)> = journal
+        .entries()
+        .into_iter()
+        .map(|entry| (entry.command, entry.args))
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            (
+                "set",
+                vec![
+                    Bytes::from("user:1"),
+                    Bytes::from("alice"),
+                    Bytes::from("EX"),
+                    Bytes::from("60"),
+                ]
+            ),
+            ("get", vec![Bytes::from("user:1")]),
+            ("del", vec![Bytes::from("user:1")]),
+        ]
+    );
+
+    let request_ids: Vec<u64> = journal
+        .entries()
+        .iter()
+        .map(|entry| entry.request_id)
+        .collect();
+    assert_eq!(request_ids, vec![1, 2, 5]);
+
+    let taken = journal.take();
+    assert!(taken
+        .windows(2)
+        .all(|pair| pair[0].connection_id == pair[1].connection_id));
+    assert!(journal.is_empty());
+}
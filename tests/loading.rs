@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::future;
+use std::process;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6371;
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+async fn request(stream: &mut TcpStream, args: &[&str]) -> String {
+    stream.write_all(&command(args)).await.unwrap();
+
+    let mut reply = vec![0; 4096];
+    let read = stream.read(&mut reply).await.unwrap();
+    String::from_utf8_lossy(&reply[..read]).into_owned()
+}
+
+/// The import is read from a named pipe, so the server keeps loading it until the test closes the
+/// pipe, and commands are sent while it is certainly loading.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_loading() {
+    let import = env::temp_dir().join(format!("rustdis-{}-loading.resp", process::id()));
+    let status = process::Command::new("mkfifo")
+        .arg(&import)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            import: Some(import.clone()),
+            ..Config::default()
+        },
+        future::pending(),
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    assert_eq!(
+        request(&mut stream, &["GET", "key"]).await,
+        "-LOADING Redis is loading the dataset in memory\r\n"
+    );
+    assert_eq!(
+        request(&mut stream, &["SET", "key", "value"]).await,
+        "-LOADING Redis is loading the dataset in memory\r\n"
+    );
+    assert_eq!(request(&mut stream, &["PING"]).await, "+PONG\r\n");
+    assert!(request(&mut stream, &["INFO"])
+        .await
+        .contains("loading:1\n"));
+
+    let path = import.clone();
+    tokio::task::spawn_blocking(move || fs::write(path, command(&["SET", "key", "value"])))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut reply = String::new();
+    for _ in 0..50 {
+        reply = request(&mut stream, &["GET", "key"]).await;
+        if !reply.starts_with("-LOADING") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(reply, "$5\r\nvalue\r\n");
+    assert!(request(&mut stream, &["INFO"])
+        .await
+        .contains("loading:0\n"));
+
+    fs::remove_file(&import).unwrap();
+}
@@ -0,0 +1,45 @@
+use std::future;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6375;
+
+async fn request(stream: &mut TcpStream, request: &[u8], reply_len: usize) -> Vec<u8> {
+    stream.write_all(request).await.unwrap();
+
+    let mut reply = vec![0; reply_len];
+    stream.read_exact(&mut reply).await.unwrap();
+    reply
+}
+
+/// A panicking command is reported to its client, which can keep using its connection, and the
+/// store stays usable by everyone even though the command panicked while holding its lock.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_panicking_command() {
+    tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            ..Config::default()
+        },
+        future::pending(),
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    let reply = request(&mut stream, b"*2\r\n$5\r\nDEBUG\r\n$5\r\nPANIC\r\n", 21).await;
+    assert_eq!(reply, b"-ERR internal error\r\n");
+
+    let reply = request(&mut stream, b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n", 5).await;
+    assert_eq!(reply, b"+OK\r\n");
+
+    let mut other = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    let reply = request(&mut other, b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n", 7).await;
+    assert_eq!(reply, b"$1\r\nv\r\n");
+}
@@ -0,0 +1,71 @@
+use std::thread;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run;
+
+const PORT: u16 = 6377;
+
+/// Mimics `redis-cli --pipe`: a huge stream of pipelined commands is written without waiting for
+/// replies, followed by an ECHO of a random sentinel. Once the sentinel is echoed back, every
+/// previous command is known to have been processed.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mass_insertion() {
+    thread::spawn(|| {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run(Config {
+            port: PORT,
+            ..Config::default()
+        }))
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let count = 100_000;
+    let sentinel = "a5a3b1a5f8b8b6c8b29d";
+
+    let mut data = Vec::new();
+    for i in 0..count {
+        let key = format!("key:{}", i);
+        let value = format!("value:{}", i);
+        data.extend_from_slice(
+            format!(
+                "*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
+                key.len(),
+                key,
+                value.len(),
+                value
+            )
+            .as_bytes(),
+        );
+    }
+    data.extend_from_slice(format!("*2\r\n$4\r\nECHO\r\n$20\r\n{}\r\n", sentinel).as_bytes());
+
+    let stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    let (mut reader, mut writer) = stream.into_split();
+
+    // Write and read concurrently, as the server stops reading once the socket buffers are full.
+    let write = tokio::spawn(async move {
+        writer.write_all(&data).await.unwrap();
+        writer
+    });
+
+    let expected_tail = format!("$20\r\n{}\r\n", sentinel);
+    let mut replies = Vec::new();
+    let mut buf = vec![0; 64 * 1024];
+    while !replies.ends_with(expected_tail.as_bytes()) {
+        let n = reader.read(&mut buf).await.unwrap();
+        assert!(n > 0, "connection closed before the sentinel was echoed");
+        replies.extend_from_slice(&buf[..n]);
+    }
+
+    write.await.unwrap();
+
+    let ok = b"+OK\r\n";
+    assert_eq!(replies.len(), count * ok.len() + expected_tail.len());
+    assert!(replies[..count * ok.len()]
+        .chunks(ok.len())
+        .all(|reply| reply == ok));
+}
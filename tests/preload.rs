@@ -0,0 +1,69 @@
+use std::env;
+use std::fs;
+use std::future;
+use std::process;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6366;
+
+/// The first command sent, right after the server is spawned, already sees the whole dataset.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_preload() {
+    let preload = env::temp_dir().join(format!("rustdis-{}-preload.txt", process::id()));
+    let mut fixture = String::from("# Fixture\n");
+    for i in 0..10_000 {
+        fixture.push_str(&format!("SET key:{} \"value {}\"\n", i, i));
+    }
+    fs::write(&preload, fixture).unwrap();
+
+    tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            preload: Some(preload.clone()),
+            ..Config::default()
+        },
+        future::pending(),
+    ));
+
+    let mut stream = loop {
+        match TcpStream::connect(("127.0.0.1", PORT)).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    };
+
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$8\r\nkey:9999\r\n")
+        .await
+        .unwrap();
+    let mut reply = vec![0; 64];
+    let read = stream.read(&mut reply).await.unwrap();
+    assert_eq!(&reply[..read], b"$10\r\nvalue 9999\r\n");
+
+    fs::remove_file(&preload).unwrap();
+}
+
+/// A fixture that fails to run stops the server from starting.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_preload_failure() {
+    let preload = env::temp_dir().join(format!("rustdis-{}-preload-failure.txt", process::id()));
+    fs::write(&preload, "SET key value\nNOPE\n").unwrap();
+
+    let res = run_until(
+        Config {
+            port: PORT + 100,
+            preload: Some(preload.clone()),
+            ..Config::default()
+        },
+        future::pending(),
+    )
+    .await;
+    assert!(res.is_err());
+
+    fs::remove_file(&preload).unwrap();
+}
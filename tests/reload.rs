@@ -0,0 +1,67 @@
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::future;
+use std::process;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6362;
+
+async fn config_get(stream: &mut TcpStream, name: &str) -> String {
+    let command = format!(
+        "*3\r\n$6\r\nCONFIG\r\n$3\r\nGET\r\n${}\r\n{}\r\n",
+        name.len(),
+        name
+    );
+    stream.write_all(command.as_bytes()).await.unwrap();
+
+    let mut reply = vec![0; 256];
+    let read = stream.read(&mut reply).await.unwrap();
+    String::from_utf8_lossy(&reply[..read]).into_owned()
+}
+
+/// The configuration file is loaded again on SIGHUP. Only the directives read each time they are
+/// used change, the server keeps listening on the port it started with.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_on_sighup() {
+    let path = env::temp_dir().join(format!("rustdis-{}-reload.conf", process::id()));
+    fs::write(&path, format!("port {}\nkeys-max-results 10\n", PORT)).unwrap();
+
+    let mut config = Config::default();
+    config.load_file(&path).unwrap();
+    config.config_file = Some(path.clone());
+    tokio::spawn(run_until(config, future::pending()));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    assert_eq!(
+        config_get(&mut stream, "keys-max-results").await,
+        "*2\r\n$16\r\nkeys-max-results\r\n$2\r\n10\r\n"
+    );
+
+    fs::write(&path, "port 6000\nkeys-max-results 20\n").unwrap();
+    let status = process::Command::new("kill")
+        .args(["-HUP", &process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        config_get(&mut stream, "keys-max-results").await,
+        "*2\r\n$16\r\nkeys-max-results\r\n$2\r\n20\r\n"
+    );
+    assert_eq!(
+        config_get(&mut stream, "port").await,
+        format!("*2\r\n$4\r\nport\r\n$4\r\n{}\r\n", PORT)
+    );
+
+    fs::remove_file(&path).unwrap();
+}
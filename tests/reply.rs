@@ -0,0 +1,59 @@
+use std::future;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6374;
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+/// Pipelined commands are all executed while their replies are turned off, and only the replies
+/// sent with CLIENT REPLY ON reach the client.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_reply() {
+    tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            ..Config::default()
+        },
+        future::pending(),
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+
+    let pipeline = [
+        command(&["CLIENT", "REPLY", "OFF"]),
+        command(&["SET", "counter", "1"]),
+        command(&["INCR", "counter"]),
+        command(&["CLIENT", "REPLY", "SKIP"]),
+        command(&["CLIENT", "REPLY", "ON"]),
+        command(&["GET", "counter"]),
+        command(&["CLIENT", "REPLY", "SKIP"]),
+        command(&["INCR", "counter"]),
+        command(&["GET", "counter"]),
+    ]
+    .concat();
+    stream.write_all(&pipeline).await.unwrap();
+
+    let expected = b"+OK\r\n$1\r\n2\r\n$1\r\n3\r\n";
+    let mut reply = vec![0; expected.len()];
+    stream.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply, expected);
+
+    // Nothing else was sent before the reply to the next command.
+    stream.write_all(&command(&["PING"])).await.unwrap();
+    let mut reply = vec![0; 7];
+    stream.read_exact(&mut reply).await.unwrap();
+    assert_eq!(reply, b"+PONG\r\n");
+}
@@ -0,0 +1,64 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6376;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_graceful_shutdown() {
+    let export = env::temp_dir().join(format!("rustdis-{}-shutdown.resp", process::id()));
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server = tokio::spawn(run_until(
+        Config {
+            port: PORT,
+            export: Some(export.clone()),
+            ..Config::default()
+        },
+        async {
+            let _ = shutdown_rx.await;
+        },
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", PORT)).await.unwrap();
+    stream
+        .write_all(b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n")
+        .await
+        .unwrap();
+
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).await.unwrap();
+    assert_eq!(&reply, b"+OK\r\n");
+
+    shutdown_tx.send(()).unwrap();
+
+    // The idle connection is closed by the server.
+    let mut rest = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut rest)).await;
+    assert!(matches!(read, Ok(Ok(0))));
+
+    timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    let exported = fs::read(&export).unwrap();
+    assert_eq!(
+        exported,
+        b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n".to_vec()
+    );
+    fs::remove_file(&export).unwrap();
+
+    // No connections are accepted anymore.
+    assert!(TcpStream::connect(("127.0.0.1", PORT)).await.is_err());
+}
@@ -0,0 +1,129 @@
+//! rustdis servers running in-process, each with a store and a port of its own, for tests that
+//! need more than one server or need to cut the connections of their clients.
+//!
+//! ```ignore
+//! let mut primary = Instance::start(6364);
+//! let mut connection = primary.connection();
+//! redis::cmd("SET").arg("key").arg("value").query::<()>(&mut connection).unwrap();
+//!
+//! // Every client is disconnected, the keyspace is kept.
+//! primary.restart();
+//! assert!(primary.store().lock().exists("key"));
+//! ```
+
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use redis::Connection;
+use tokio::runtime::Runtime;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use rustdis::config::Config;
+use rustdis::error::RustdisError;
+use rustdis::server::serve;
+use rustdis::store::Store;
+
+/// How long `Instance::start` waits for the server to listen.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A server running in a runtime of its own, so it outlives the runtime of the test that started
+/// it, if any. It is shut down when dropped.
+pub struct Instance {
+    runtime: Runtime,
+    store: Store,
+    port: u16,
+    running: Option<Running>,
+}
+
+struct Running {
+    shutdown: oneshot::Sender<()>,
+    server: JoinHandle<Result<(), RustdisError>>,
+}
+
+impl Instance {
+    /// Starts a server listening on `port`, with the default configuration otherwise.
+    pub fn start(port: u16) -> Instance {
+        Instance::with_config(Config {
+            port,
+            ..Config::default()
+        })
+    }
+
+    /// Starts a server with `config`, once it accepts connections.
+    pub fn with_config(config: Config) -> Instance {
+        let port = config.port;
+        let runtime = Runtime::new().unwrap();
+        // The store spawns the tasks removing expired keys on the runtime it is created in.
+        let store = runtime.block_on(async { Store::with_config(config) });
+        let mut instance = Instance {
+            runtime,
+            store,
+            port,
+            running: None,
+        };
+        instance.resume();
+        instance
+    }
+
+    /// The store the server executes commands against, to inspect or write keys directly.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    pub fn url(&self) -> String {
+        format!("redis://127.0.0.1:{}/", self.port)
+    }
+
+    /// A new connection to the server.
+    pub fn connection(&self) -> Connection {
+        redis::Client::open(self.url())
+            .unwrap()
+            .get_connection()
+            .unwrap()
+    }
+
+    /// Shuts the server down, which closes the connection of every client, and starts it again
+    /// over the same store, so the keyspace outlives the disconnection.
+    pub fn restart(&mut self) {
+        self.stop();
+        self.resume();
+    }
+
+    /// Shuts the server down. Its store is kept, `restart` serves it again.
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() {
+            let _ = running.shutdown.send(());
+            self.runtime.block_on(running.server).unwrap().unwrap();
+        }
+    }
+
+    fn resume(&mut self) {
+        let (shutdown, stopped) = oneshot::channel();
+        let store = self.store.clone();
+        let server = self.runtime.spawn(serve(store, async {
+            let _ = stopped.await;
+        }));
+        self.running = Some(Running { shutdown, server });
+
+        let deadline = Instant::now() + START_TIMEOUT;
+        while TcpStream::connect(("127.0.0.1", self.port)).is_err() {
+            assert!(
+                Instant::now() < deadline,
+                "the server on port {} didn't start",
+                self.port
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Dropping the runtime cancels the server, along with its connections, without waiting for them.
+impl Drop for Instance {
+    fn drop(&mut self) {
+        if let Some(running) = self.running.take() {
+            let _ = running.shutdown.send(());
+        }
+    }
+}
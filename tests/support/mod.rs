@@ -0,0 +1,303 @@
+//! Keyspace snapshots, to compare the data two servers hold after running the same commands.
+//!
+//! Snapshots are taken over a connection, with SCAN, TYPE, GET, HGETALL and PTTL, so they work
+//! the same against rustdis and against Redis. Comparing replies only shows that a pipeline
+//! diverged, the diff of the snapshots taken after it shows which keys ended up different.
+//!
+//! See `target` for the Redis server they are compared against, and `instance` to run several
+//! rustdis servers in-process.
+
+// Each test binary compiles this module and uses a part of it.
+#![allow(dead_code)]
+
+pub mod instance;
+pub mod target;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use redis::Connection;
+
+/// A key as seen by a client: its type, its value when it is a string, its fields when it is a
+/// hash and its time to live.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub kind: String,
+    pub value: Option<Vec<u8>>,
+    pub fields: Option<BTreeMap<String, Vec<u8>>>,
+    pub ttl: Option<Duration>,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.value, &self.fields) {
+            (Some(value), _) => write!(f, "{:?}", String::from_utf8_lossy(value))?,
+            (None, Some(fields)) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| {
+                        format!("{}: {:?}", field, String::from_utf8_lossy(value))
+                    })
+                    .collect();
+                write!(f, "{{{}}}", fields.join(", "))?
+            }
+            (None, None) => write!(f, "<{}>", self.kind)?,
+        }
+        match self.ttl {
+            Some(ttl) => write!(f, " (ttl {}ms)", ttl.as_millis()),
+            None => write!(f, " (no ttl)"),
+        }
+    }
+}
+
+/// The keys matching a pattern at a point in time.
+#[derive(Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub entries: BTreeMap<String, Entry>,
+}
+
+impl Snapshot {
+    /// Captures every key matching `pattern`. Keys removed while the snapshot is taken are left
+    /// out.
+    pub fn capture(connection: &mut Connection, pattern: &str) -> Snapshot {
+        let mut keys = vec![];
+        let mut cursor = 0;
+        loop {
+            let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(1000)
+                .query(connection)
+                .unwrap();
+            keys.extend(batch);
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+
+        let mut entries = BTreeMap::new();
+        for key in keys {
+            let (kind, pttl): (String, i64) = redis::pipe()
+                .cmd("TYPE")
+                .arg(&key)
+                .cmd("PTTL")
+                .arg(&key)
+                .query(connection)
+                .unwrap();
+            if kind == "none" {
+                continue;
+            }
+            let value = match kind.as_str() {
+                "string" => redis::cmd("GET").arg(&key).query(connection).unwrap(),
+                _ => None,
+            };
+            let fields = match kind.as_str() {
+                "hash" => redis::cmd("HGETALL").arg(&key).query(connection).unwrap(),
+                _ => None,
+            };
+            let ttl = u64::try_from(pttl).ok().map(Duration::from_millis);
+
+            entries.insert(
+                key,
+                Entry {
+                    kind,
+                    value,
+                    fields,
+                    ttl,
+                },
+            );
+        }
+
+        Snapshot { entries }
+    }
+
+    /// Compares this snapshot to `other`. Times to live differing by at most `ttl_tolerance` are
+    /// considered equal, since two snapshots are never taken at the same instant.
+    pub fn diff(&self, other: &Snapshot, ttl_tolerance: Duration) -> Diff {
+        let mut diff = Diff::default();
+
+        for (key, before) in &self.entries {
+            match other.entries.get(key) {
+                None => diff.removed.push((key.clone(), before.clone())),
+                Some(after) => {
+                    let ttl_delta = match (before.ttl, after.ttl) {
+                        (Some(before), Some(after)) => {
+                            let delta = after.as_millis() as i128 - before.as_millis() as i128;
+                            (delta.unsigned_abs() > ttl_tolerance.as_millis()).then_some(delta)
+                        }
+                        (None, None) => None,
+                        // A timeout set or removed is a change of any size.
+                        _ => Some(0),
+                    };
+                    if before.kind != after.kind
+                        || before.value != after.value
+                        || before.fields != after.fields
+                        || ttl_delta.is_some()
+                    {
+                        diff.changed.push(Change {
+                            key: key.clone(),
+                            before: before.clone(),
+                            after: after.clone(),
+                            ttl_delta_ms: ttl_delta.filter(|delta| *delta != 0),
+                        });
+                    }
+                }
+            }
+        }
+        for (key, after) in &other.entries {
+            if !self.entries.contains_key(key) {
+                diff.added.push((key.clone(), after.clone()));
+            }
+        }
+
+        diff
+    }
+}
+
+/// A key present in both snapshots but with a different type, value, fields or time to live.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub key: String,
+    pub before: Entry,
+    pub after: Entry,
+    /// How much longer the key lives in the second snapshot, when both have a time to live.
+    pub ttl_delta_ms: Option<i128>,
+}
+
+/// How the second snapshot of [`Snapshot::diff`] differs from the first, sorted by key.
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    pub added: Vec<(String, Entry)>,
+    pub removed: Vec<(String, Entry)>,
+    pub changed: Vec<Change>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// One line per difference, like `+ key: "value" (no ttl)`, readable in a failed assertion.
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, entry) in &self.removed {
+            writeln!(f, "- {}: {}", key, entry)?;
+        }
+        for (key, entry) in &self.added {
+            writeln!(f, "+ {}: {}", key, entry)?;
+        }
+        for change in &self.changed {
+            write!(f, "~ {}: {} => {}", change.key, change.before, change.after)?;
+            match change.ttl_delta_ms {
+                Some(delta) => writeln!(f, " (ttl {:+}ms)", delta)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(value: &str, ttl: Option<u64>) -> Entry {
+        Entry {
+            kind: "string".to_string(),
+            value: Some(value.as_bytes().to_vec()),
+            fields: None,
+            ttl: ttl.map(Duration::from_millis),
+        }
+    }
+
+    fn hash(fields: &[(&str, &str)]) -> Entry {
+        Entry {
+            kind: "hash".to_string(),
+            value: None,
+            fields: Some(
+                fields
+                    .iter()
+                    .map(|(field, value)| (field.to_string(), value.as_bytes().to_vec()))
+                    .collect(),
+            ),
+            ttl: None,
+        }
+    }
+
+    fn snapshot(entries: &[(&str, Entry)]) -> Snapshot {
+        Snapshot {
+            entries: entries
+                .iter()
+                .map(|(key, entry)| (key.to_string(), entry.clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let before = snapshot(&[
+            ("same", entry("1", None)),
+            ("removed", entry("2", None)),
+            ("value", entry("3", None)),
+            ("ttl", entry("4", Some(10_000))),
+            ("close_ttl", entry("5", Some(10_000))),
+            ("persisted", entry("6", Some(10_000))),
+        ]);
+        let after = snapshot(&[
+            ("same", entry("1", None)),
+            ("added", entry("7", Some(5_000))),
+            ("value", entry("8", None)),
+            ("ttl", entry("4", Some(4_000))),
+            ("close_ttl", entry("5", Some(9_990))),
+            ("persisted", entry("6", None)),
+        ]);
+
+        let diff = before.diff(&after, Duration::from_millis(100));
+
+        assert_eq!(
+            diff.added,
+            vec![("added".to_string(), entry("7", Some(5_000)))]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![("removed".to_string(), entry("2", None))]
+        );
+        let changed: Vec<(&str, Option<i128>)> = diff
+            .changed
+            .iter()
+            .map(|change| (change.key.as_str(), change.ttl_delta_ms))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![("persisted", None), ("ttl", Some(-6_000)), ("value", None)]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "- removed: \"2\" (no ttl)\n\
+             + added: \"7\" (ttl 5000ms)\n\
+             ~ persisted: \"6\" (ttl 10000ms) => \"6\" (no ttl)\n\
+             ~ ttl: \"4\" (ttl 10000ms) => \"4\" (ttl 4000ms) (ttl -6000ms)\n\
+             ~ value: \"3\" (no ttl) => \"8\" (no ttl)\n"
+        );
+
+        assert!(before.diff(&before, Duration::ZERO).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_diff_hashes() {
+        let before = snapshot(&[("user", hash(&[("name", "ana")]))]);
+        let after = snapshot(&[("user", hash(&[("age", "30"), ("name", "ana")]))]);
+
+        let diff = before.diff(&after, Duration::ZERO);
+
+        assert_eq!(
+            diff.to_string(),
+            "~ user: {name: \"ana\"} (no ttl) => {age: \"30\", name: \"ana\"} (no ttl)\n"
+        );
+    }
+}
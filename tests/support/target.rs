@@ -0,0 +1,87 @@
+//! The Redis server rustdis is compared against.
+//!
+//! It is the one listening on 127.0.0.1:6379 unless `RUSTDIS_COMPARE_URL` points to another, like
+//! a container running an older version:
+//!
+//! ```shell
+//! docker run --rm -p 6390:6379 redis:6.2
+//! RUSTDIS_COMPARE_URL=redis://127.0.0.1:6390/ cargo test --test integration
+//! ```
+//!
+//! rustdis targets `TARGET`, the version it reports in INFO, and the comparison suite runs against
+//! Redis 6.2 onward. Tests covering features added after 6.2 check the version of the server they
+//! compare against, and skip or leave out what it doesn't support.
+
+use std::env;
+use std::fmt;
+
+use redis::Connection;
+
+/// The environment variable holding the URL of the Redis server to compare against.
+pub const URL_VAR: &str = "RUSTDIS_COMPARE_URL";
+
+const DEFAULT_URL: &str = "redis://127.0.0.1:6379/";
+
+/// The Redis version rustdis behaves like.
+pub const TARGET: Version = Version::new(7, 2, 4);
+
+/// The URL of the Redis server to compare against.
+pub fn url() -> String {
+    env::var(URL_VAR).unwrap_or_else(|_| DEFAULT_URL.to_string())
+}
+
+/// A Redis version, ordered like releases are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses a `major.minor.patch` version, like the `redis_version` field of INFO.
+    pub fn parse(version: &str) -> Option<Version> {
+        let mut parts = version.trim().splitn(3, '.').map(|part| part.parse().ok());
+        Some(Version::new(parts.next()??, parts.next()??, parts.next()??))
+    }
+
+    /// The version the server behind `connection` reports in INFO.
+    pub fn of(connection: &mut Connection) -> Version {
+        let info: String = redis::cmd("INFO").arg("server").query(connection).unwrap();
+        info.lines()
+            .find_map(|line| line.strip_prefix("redis_version:"))
+            .and_then(Version::parse)
+            .unwrap_or_else(|| panic!("no redis_version in INFO:\n{}", info))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("7.2.4"), Some(Version::new(7, 2, 4)));
+        assert_eq!(Version::parse("6.2.14\r"), Some(Version::new(6, 2, 14)));
+        assert_eq!(Version::parse("7.2"), None);
+        assert_eq!(Version::parse("7.x.0"), None);
+
+        assert!(Version::new(6, 2, 14) < Version::new(7, 0, 0));
+        assert!(Version::new(7, 0, 15) < TARGET);
+        assert_eq!(TARGET.to_string(), "7.2.4");
+    }
+}
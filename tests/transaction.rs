@@ -0,0 +1,269 @@
+use std::future;
+use std::sync::Once;
+use std::thread;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Duration;
+
+use rustdis::config::Config;
+use rustdis::server::run_until;
+
+const PORT: u16 = 6369;
+
+static SERVER: Once = Once::new();
+
+fn command(args: &[&str]) -> Vec<u8> {
+    let mut data = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        data.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    data
+}
+
+async fn request(stream: &mut TcpStream, args: &[&str]) -> String {
+    stream.write_all(&command(args)).await.unwrap();
+
+    let mut reply = vec![0; 4096];
+    let read = stream.read(&mut reply).await.unwrap();
+    String::from_utf8_lossy(&reply[..read]).into_owned()
+}
+
+/// Starts the server once for both tests, in a runtime of its own so it outlives the runtime of
+/// the test that happened to start it.
+async fn connect() -> TcpStream {
+    SERVER.call_once(|| {
+        thread::spawn(|| {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(run_until(
+                Config {
+                    port: PORT,
+                    ..Config::default()
+                },
+                future::pending(),
+            ))
+        });
+        thread::sleep(Duration::from_millis(100));
+    });
+
+    TcpStream::connect(("127.0.0.1", PORT)).await.unwrap()
+}
+
+/// Commands failing once executed are replied to with their error in the reply of EXEC, while a
+/// command rejected when queued aborts the whole transaction.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_errors() {
+    let mut stream = connect().await;
+
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    for args in [
+        &["SET", "key", "value"][..],
+        &["INCR", "key"],
+        &["SET", "key", "value", "EX", "soon"],
+        &["GET", "key"],
+    ] {
+        assert_eq!(
+            request(&mut stream, args).await,
+            "+QUEUED\r\n",
+            "{:?}",
+            args
+        );
+    }
+    let reply = request(&mut stream, &["EXEC"]).await;
+    assert!(reply.starts_with("*4\r\n+OK\r\n-"), "{}", reply);
+    assert!(
+        reply.ends_with("\r\n-ERR value is not an integer or out of range\r\n$5\r\nvalue\r\n"),
+        "{}",
+        reply
+    );
+
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["SET", "other", "value"]).await,
+        "+QUEUED\r\n"
+    );
+    assert!(request(&mut stream, &["NOSUCHCOMMAND"])
+        .await
+        .starts_with("-ERR "));
+    assert!(request(&mut stream, &["GET"]).await.starts_with("-ERR "));
+    assert_eq!(
+        request(&mut stream, &["EXEC"]).await,
+        "-EXECABORT Transaction discarded because of previous errors.\r\n"
+    );
+    assert_eq!(request(&mut stream, &["GET", "other"]).await, "$-1\r\n");
+
+    assert_eq!(
+        request(&mut stream, &["EXEC"]).await,
+        "-ERR EXEC without MULTI\r\n"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_discard() {
+    let mut stream = connect().await;
+
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["MULTI"]).await,
+        "-ERR MULTI calls can not be nested\r\n"
+    );
+    assert_eq!(
+        request(&mut stream, &["SET", "discarded", "value"]).await,
+        "+QUEUED\r\n"
+    );
+    assert_eq!(request(&mut stream, &["DISCARD"]).await, "+OK\r\n");
+    assert_eq!(request(&mut stream, &["GET", "discarded"]).await, "$-1\r\n");
+    assert_eq!(
+        request(&mut stream, &["DISCARD"]).await,
+        "-ERR DISCARD without MULTI\r\n"
+    );
+}
+
+/// A transaction is discarded when a key its client watches is written by another client before
+/// EXEC, and executed otherwise.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_watch() {
+    let mut stream = connect().await;
+    let mut other = connect().await;
+
+    assert_eq!(
+        request(&mut stream, &["WATCH", "watched", "untouched"]).await,
+        "+OK\r\n"
+    );
+    assert_eq!(
+        request(&mut other, &["SET", "watched", "other"]).await,
+        "+OK\r\n"
+    );
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["SET", "watched", "mine"]).await,
+        "+QUEUED\r\n"
+    );
+    assert_eq!(request(&mut stream, &["EXEC"]).await, "*-1\r\n");
+    assert_eq!(
+        request(&mut stream, &["GET", "watched"]).await,
+        "$5\r\nother\r\n"
+    );
+
+    // EXEC unwatched the keys.
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["SET", "watched", "mine"]).await,
+        "+QUEUED\r\n"
+    );
+    assert_eq!(request(&mut stream, &["EXEC"]).await, "*1\r\n+OK\r\n");
+
+    assert_eq!(request(&mut stream, &["WATCH", "watched"]).await, "+OK\r\n");
+    assert_eq!(request(&mut stream, &["UNWATCH"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut other, &["SET", "watched", "other"]).await,
+        "+OK\r\n"
+    );
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["GET", "watched"]).await,
+        "+QUEUED\r\n"
+    );
+    assert_eq!(
+        request(&mut stream, &["EXEC"]).await,
+        "*1\r\n$5\r\nother\r\n"
+    );
+
+    // A missing key created and deleted again by another client was written too.
+    assert_eq!(
+        request(&mut stream, &["WATCH", "watched:missing"]).await,
+        "+OK\r\n"
+    );
+    assert_eq!(
+        request(&mut other, &["SET", "watched:missing", "other"]).await,
+        "+OK\r\n"
+    );
+    assert_eq!(
+        request(&mut other, &["DEL", "watched:missing"]).await,
+        ":1\r\n"
+    );
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["SET", "watched:missing", "mine"]).await,
+        "+QUEUED\r\n"
+    );
+    assert_eq!(request(&mut stream, &["EXEC"]).await, "*-1\r\n");
+
+    // WATCH can't be queued, and aborts the transaction.
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    assert_eq!(
+        request(&mut stream, &["WATCH", "watched"]).await,
+        "-ERR Command not allowed inside a transaction\r\n"
+    );
+    assert_eq!(
+        request(&mut stream, &["EXEC"]).await,
+        "-EXECABORT Transaction discarded because of previous errors.\r\n"
+    );
+}
+
+/// Clients incrementing a counter with WATCH, GET and a transaction setting it never lose an
+/// increment: a transaction is only executed when no other one wrote the counter since it was
+/// read, and no write happens between that check and its execution.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_check_and_set() {
+    const CLIENTS: usize = 8;
+    const INCREMENTS: usize = 50;
+
+    let mut clients = tokio::task::JoinSet::new();
+    for _ in 0..CLIENTS {
+        clients.spawn(async {
+            let mut stream = connect().await;
+            for _ in 0..INCREMENTS {
+                loop {
+                    request(&mut stream, &["WATCH", "cas:counter"]).await;
+                    let reply = request(&mut stream, &["GET", "cas:counter"]).await;
+                    let value: usize = match reply.split("\r\n").nth(1) {
+                        Some(value) if !reply.starts_with("$-1") => value.parse().unwrap(),
+                        _ => 0,
+                    };
+                    let value = (value + 1).to_string();
+
+                    request(&mut stream, &["MULTI"]).await;
+                    request(&mut stream, &["SET", "cas:counter", &value]).await;
+                    if request(&mut stream, &["EXEC"]).await == "*1\r\n+OK\r\n" {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    while let Some(res) = clients.join_next().await {
+        res.unwrap();
+    }
+
+    let mut stream = connect().await;
+    let total = (CLIENTS * INCREMENTS).to_string();
+    assert_eq!(
+        request(&mut stream, &["GET", "cas:counter"]).await,
+        format!("${}\r\n{}\r\n", total.len(), total)
+    );
+}
+
+/// DEBUG SLEEP calls queued in a transaction add up to however long they ask for, without
+/// overflowing: the connection sleeps instead of failing.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_long_sleeps() {
+    let mut stream = connect().await;
+
+    assert_eq!(request(&mut stream, &["MULTI"]).await, "+OK\r\n");
+    for _ in 0..2 {
+        assert_eq!(
+            request(&mut stream, &["DEBUG", "SLEEP", "1e19"]).await,
+            "+QUEUED\r\n"
+        );
+    }
+    stream.write_all(&command(&["EXEC"])).await.unwrap();
+
+    let mut reply = vec![0; 64];
+    let read = tokio::time::timeout(Duration::from_millis(200), stream.read(&mut reply)).await;
+    assert!(read.is_err(), "{:?}", read);
+
+    // The other connections are still served.
+    let mut other = connect().await;
+    assert_eq!(request(&mut other, &["PING"]).await, "+PONG\r\n");
+}